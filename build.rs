@@ -0,0 +1,11 @@
+//! Compile the protobuf definitions for the gRPC services
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+
+    tonic_build::compile_protos("proto/tasks.proto")?;
+
+    Ok(())
+}
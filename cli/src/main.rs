@@ -0,0 +1,53 @@
+//! # A demo project showing dependency injection approaches
+#![forbid(unsafe_code)]
+
+/// The `tasks` command, to manage Tasks from the terminal
+mod tasks;
+
+use rust_demo_dependency_injection::{
+    args::{Args, Command, DataStore},
+    commands,
+};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse()?;
+
+    let data_store: DataStore = args
+        .data_store
+        .map_or(Ok(DataStore::Postgres), |v| v.try_into())?;
+    let dynamo = commands::dynamo_config(args.timestamp_format)?;
+
+    match args.command {
+        Command::Help(help) => {
+            print!("{}", help);
+
+            Ok(())
+        }
+        Command::Serve(serve_args) => commands::serve::run(serve_args, data_store, dynamo).await,
+        Command::Migrate(command) => {
+            commands::migrate::run(&commands::connect(data_store, &dynamo).await?, command).await
+        }
+        Command::Provision(provision_args) => {
+            commands::provision::run(
+                &commands::connect(data_store, &dynamo).await?,
+                provision_args,
+            )
+            .await
+        }
+        Command::RewriteTimestamps => commands::rewrite_timestamps::run(data_store, &dynamo).await,
+        Command::Seed { count } => {
+            commands::seed::run(&commands::connect(data_store, &dynamo).await?, count).await
+        }
+        Command::Export { file } => {
+            commands::export::run(&commands::connect(data_store, &dynamo).await?, file).await
+        }
+        Command::Import { file } => {
+            commands::import::run(&commands::connect(data_store, &dynamo).await?, file).await
+        }
+        Command::CheckConfig(serve_args) => {
+            commands::check_config::run(serve_args, data_store, dynamo).await
+        }
+        Command::Tasks(tasks_args) => tasks::run(tasks_args, data_store, &dynamo).await,
+    }
+}
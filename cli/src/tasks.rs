@@ -0,0 +1,595 @@
+use chrono::DateTime;
+use rust_demo_dependency_injection::{
+    args::{DataStore, TasksArgs, TasksCommand},
+    attachments::storage,
+    commands,
+    comments::{self, model::Comment},
+    config,
+    tasks::{
+        inputs,
+        model::Task,
+        store::{Page, Store},
+        tree::Tree,
+        workflow::{Status, Workflow},
+    },
+    utils::Update,
+};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde_json::json;
+use tasks_client::{self as client, Client};
+
+/// How timestamps are shown in the table output
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S %:z";
+
+/// Where the `tasks` command reads and writes Tasks
+#[derive(Clone, Debug)]
+pub enum Target {
+    /// A running server, over HTTP
+    Server(Client),
+
+    /// The configured data store, directly
+    Store(Store),
+}
+
+/// The output format for the `tasks` command
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Output {
+    /// A human-readable table
+    #[default]
+    Table,
+
+    /// Pretty-printed JSON
+    Json,
+}
+
+impl TryFrom<String> for Output {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "table" => Ok(Output::Table),
+            "json" => Ok(Output::Json),
+            _ => Err(anyhow::anyhow!("Invalid output format: {}", value)),
+        }
+    }
+}
+
+/// Run a `tasks` subcommand against a running server or the data store, printing the output
+pub async fn run(
+    args: TasksArgs,
+    data_store: DataStore,
+    dynamo: &config::Dynamo,
+) -> anyhow::Result<()> {
+    let target = match args.url {
+        Some(url) => Target::Server(Client::new(url)),
+        None => Target::Store(commands::connect(data_store, dynamo).await?),
+    };
+
+    let output = args
+        .output
+        .map_or(Ok(Default::default()), |v| v.try_into())?;
+
+    print!("{}", execute(&target, args.command, output).await?);
+
+    Ok(())
+}
+
+/// Run a `tasks` subcommand against the given target, returning the formatted output
+pub async fn execute(
+    target: &Target,
+    command: TasksCommand,
+    output: Output,
+) -> anyhow::Result<String> {
+    match command {
+        TasksCommand::List {
+            after,
+            limit,
+            tag: Some(tag),
+            overdue,
+            due_within_days,
+            order,
+        } => {
+            if overdue || due_within_days.is_some() || order.is_some() {
+                return Err(anyhow::anyhow!(
+                    "--tag can't be combined with --overdue, --due-within-days or --order"
+                ));
+            }
+
+            let query = inputs::ListByTag { after, limit };
+
+            let page = match target {
+                Target::Server(client) => client.list_by_tag(&tag, &query).await?,
+                Target::Store(store) => store.page_by_tag(&tag, &query).await?,
+            };
+
+            render_page(&page, output)
+        }
+        TasksCommand::List {
+            after,
+            limit,
+            tag: None,
+            overdue,
+            due_within_days,
+            order,
+        } => {
+            let query = inputs::List {
+                after,
+                limit,
+                overdue,
+                due_within_days,
+                order: order.map(|order| order.parse()).transpose()?,
+            };
+
+            let page = match target {
+                Target::Server(client) => client.list(&query).await?,
+                Target::Store(store) => store.page(&query).await?,
+            };
+
+            render_page(&page, output)
+        }
+        TasksCommand::Get { id } => {
+            let task = match target {
+                Target::Server(client) => match client.get(&id).await {
+                    Ok(task) => Some(task),
+                    Err(client::Error::NotFound) => None,
+                    Err(err) => return Err(err.into()),
+                },
+                Target::Store(store) => store.get(&id).await?,
+            };
+
+            let task = task.ok_or_else(|| anyhow::anyhow!("Task not found: {}", id))?;
+
+            render_tasks(&[task], output)
+        }
+        TasksCommand::Create {
+            title,
+            description,
+            due_at,
+            priority,
+            parent,
+            project,
+        } => {
+            let input = inputs::Create {
+                title,
+                description,
+                due_at: due_at.as_deref().map(parse_timestamp).transpose()?,
+                priority,
+                parent_id: parent,
+                project_id: project,
+            };
+
+            let task = match target {
+                Target::Server(client) => client.create(&input).await?,
+                Target::Store(store) => store.create(&input).await?,
+            };
+
+            render_tasks(&[task], output)
+        }
+        TasksCommand::Update {
+            id,
+            title,
+            description,
+            description_clear,
+            due_at,
+            due_at_clear,
+            priority,
+            priority_clear,
+            project,
+            project_clear,
+        } => {
+            let due_at = due_at.as_deref().map(parse_timestamp).transpose()?;
+
+            let input = inputs::Update {
+                title: title.map_or(Update::Unchanged, Update::Value),
+                description: update(description, description_clear, "description")?,
+                due_at: update(due_at, due_at_clear, "due-at")?,
+                priority: update(priority, priority_clear, "priority")?,
+                project_id: update(project, project_clear, "project")?,
+            };
+
+            let task = match target {
+                Target::Server(client) => client.update(&id, &input).await?,
+                Target::Store(store) => store.update(&id, &input).await?,
+            };
+
+            render_tasks(&[task], output)
+        }
+        TasksCommand::Delete { id } => {
+            match target {
+                Target::Server(client) => client.delete(&id).await?,
+                Target::Store(store) => {
+                    // The attached files are kept where `serve` keeps them by default
+                    let storage = storage::connect(&config::Attachments::default().storage);
+
+                    store.delete(storage.as_ref(), &id).await?;
+                }
+            };
+
+            Ok(match output {
+                Output::Table => format!("Deleted Task {id}\n"),
+                Output::Json => format!("{:#}\n", json!({ "id": id, "deleted": true })),
+            })
+        }
+        TasksCommand::Transition {
+            id,
+            status,
+            workflow,
+        } => {
+            let status: Status = status.parse()?;
+
+            let task = match target {
+                Target::Server(client) => client.transition(&id, status).await?,
+                Target::Store(store) => {
+                    let workflow = match workflow {
+                        Some(path) => Workflow::from_file(&path)?,
+                        None => Workflow::default(),
+                    };
+
+                    store.transition(&workflow, &id, status).await?
+                }
+            };
+
+            render_tasks(&[task], output)
+        }
+        TasksCommand::Tag { id, name } => {
+            let tags = match target {
+                Target::Server(client) => client.add_tag(&id, &name).await?,
+                Target::Store(store) => store.add_tag(&id, &name).await?,
+            };
+
+            render_tags(&tags, output)
+        }
+        TasksCommand::Untag { id, name } => {
+            let tags = match target {
+                Target::Server(client) => client.remove_tag(&id, &name).await?,
+                Target::Store(store) => store.remove_tag(&id, &name).await?,
+            };
+
+            render_tags(&tags, output)
+        }
+        TasksCommand::Tags { id: Some(id) } => {
+            let tags = match target {
+                Target::Server(client) => client.tags(&id).await?,
+                Target::Store(store) => store.tags(&id).await?,
+            };
+
+            render_tags(&tags, output)
+        }
+        TasksCommand::Tags { id: None } => {
+            let counts = match target {
+                Target::Server(client) => client.tag_counts().await?,
+                Target::Store(store) => store.tag_counts().await?,
+            };
+
+            match output {
+                Output::Json => Ok(format!("{}\n", serde_json::to_string_pretty(&counts)?)),
+                Output::Table => {
+                    let width = counts
+                        .iter()
+                        .map(|count| count.name.chars().count())
+                        .max()
+                        .unwrap_or_default();
+
+                    Ok(counts
+                        .iter()
+                        .map(|count| format!("{:width$}  {}\n", count.name, count.tasks))
+                        .collect())
+                }
+            }
+        }
+        TasksCommand::Children { id } => {
+            let tasks = match target {
+                Target::Server(client) => client.children(&id).await?,
+                Target::Store(store) => store.children(&id).await?,
+            };
+
+            match output {
+                Output::Json => Ok(format!("{}\n", serde_json::to_string_pretty(&tasks)?)),
+                Output::Table => Ok(table(&tasks)),
+            }
+        }
+        TasksCommand::Tree { id } => {
+            let tree = match target {
+                Target::Server(client) => client.tree(&id).await?,
+                Target::Store(store) => store.subtree(&id).await?,
+            };
+
+            match output {
+                Output::Json => Ok(format!("{}\n", serde_json::to_string_pretty(&tree)?)),
+                Output::Table => Ok(outline(&tree, 0)),
+            }
+        }
+        TasksCommand::Move { id, parent, root } => {
+            let input = match (parent, root) {
+                (Some(_), true) => {
+                    return Err(anyhow::anyhow!(
+                        "--parent and --root can't be used together"
+                    ))
+                }
+                (None, false) => {
+                    return Err(anyhow::anyhow!("Either --parent or --root is required"))
+                }
+                (parent_id, _) => inputs::Move { parent_id },
+            };
+
+            let task = match target {
+                Target::Server(client) => client.move_task(&id, &input).await?,
+                Target::Store(store) => store.move_task(&id, &input).await?,
+            };
+
+            render_tasks(&[task], output)
+        }
+        TasksCommand::Comments { id, after, limit } => {
+            let input = comments::inputs::List { after, limit };
+
+            let page = match target {
+                Target::Server(client) => client.list_comments(&id, &input).await?,
+                Target::Store(store) => store.page_comments(&id, &input).await?,
+            };
+
+            match output {
+                Output::Json => Ok(format!("{}\n", serde_json::to_string_pretty(&page)?)),
+                Output::Table => {
+                    let mut rendered: String = page.comments.iter().map(render_comment).collect();
+
+                    if let Some(cursor) = &page.next_cursor {
+                        rendered.push_str(&format!(
+                            "More Comments are available with --after {cursor}\n"
+                        ));
+                    }
+
+                    Ok(rendered)
+                }
+            }
+        }
+        TasksCommand::Comment { id, author, body } => {
+            let input = comments::inputs::Create { author, body };
+
+            let comment = match target {
+                Target::Server(client) => client.create_comment(&id, &input).await?,
+                Target::Store(store) => store.create_comment(&id, &input).await?,
+            };
+
+            match output {
+                Output::Json => Ok(format!("{}\n", serde_json::to_string_pretty(&comment)?)),
+                Output::Table => Ok(render_comment(&comment)),
+            }
+        }
+    }
+}
+
+/// Render a Comment as a heading line followed by its indented body and a blank line
+fn render_comment(comment: &Comment) -> String {
+    let mut rendered = format!(
+        "{}  {}  {}\n",
+        comment.id,
+        comment.author,
+        comment.created_at.format(TIMESTAMP_FORMAT)
+    );
+
+    for line in comment.body.lines() {
+        rendered.push_str(&format!("    {line}\n"));
+    }
+
+    rendered.push('\n');
+
+    rendered
+}
+
+/// Render a Task tree as an outline, indenting each level of subtasks under its parent
+fn outline(tree: &Tree, depth: usize) -> String {
+    let mut rendered = format!(
+        "{}{}  {}  [{}]",
+        "  ".repeat(depth),
+        tree.task.id,
+        tree.task.title,
+        tree.task.status
+    );
+
+    if !tree.children.is_empty() {
+        rendered.push_str(&format!(
+            "  {}/{} done",
+            tree.progress.done, tree.progress.total
+        ));
+    }
+
+    rendered.push('\n');
+
+    for child in &tree.children {
+        rendered.push_str(&outline(child, depth + 1));
+    }
+
+    rendered
+}
+
+fn render_tags(tags: &[String], output: Output) -> anyhow::Result<String> {
+    match output {
+        Output::Json => Ok(format!("{}\n", serde_json::to_string_pretty(tags)?)),
+        Output::Table => Ok(tags.iter().map(|tag| format!("{tag}\n")).collect()),
+    }
+}
+
+/// Combine a `--<name>` value and its `--<name>-clear` flag into an update
+fn update<T>(value: Option<T>, clear: bool, name: &str) -> anyhow::Result<Update<T>> {
+    match (value, clear) {
+        (Some(_), true) => Err(anyhow::anyhow!(
+            "--{name} and --{name}-clear can't be used together"
+        )),
+        (Some(value), false) => Ok(Update::Value(value)),
+        (None, true) => Ok(Update::Empty),
+        (None, false) => Ok(Update::Unchanged),
+    }
+}
+
+fn parse_timestamp(value: &str) -> anyhow::Result<DateTimeWithTimeZone> {
+    DateTime::parse_from_rfc3339(value)
+        .map_err(|_err| anyhow::anyhow!("Invalid timestamp, expected RFC 3339: {}", value))
+}
+
+fn render_page(page: &Page, output: Output) -> anyhow::Result<String> {
+    match output {
+        Output::Json => Ok(format!("{}\n", serde_json::to_string_pretty(page)?)),
+        Output::Table => {
+            let mut rendered = table(&page.tasks);
+
+            if let Some(cursor) = &page.next_cursor {
+                rendered.push_str(&format!(
+                    "\nMore Tasks are available with --after {cursor}\n"
+                ));
+            }
+
+            Ok(rendered)
+        }
+    }
+}
+
+fn render_tasks(tasks: &[Task], output: Output) -> anyhow::Result<String> {
+    match output {
+        Output::Json => match tasks {
+            [task] => Ok(format!("{}\n", serde_json::to_string_pretty(task)?)),
+            _ => Ok(format!("{}\n", serde_json::to_string_pretty(tasks)?)),
+        },
+        Output::Table => Ok(table(tasks)),
+    }
+}
+
+/// Render Tasks as a table with a header row and columns padded to their widest value
+fn table(tasks: &[Task]) -> String {
+    let header = [
+        "ID",
+        "TITLE",
+        "STATUS",
+        "PRIORITY",
+        "DUE",
+        "PARENT",
+        "DESCRIPTION",
+        "CREATED",
+        "UPDATED",
+    ]
+    .map(String::from);
+
+    let rows: Vec<[String; 9]> = std::iter::once(header)
+        .chain(tasks.iter().map(|task| {
+            [
+                task.id.clone(),
+                task.title.clone(),
+                task.status.to_string(),
+                task.priority
+                    .map(|priority| priority.to_string())
+                    .unwrap_or_default(),
+                task.due_at
+                    .map(|due_at| due_at.format(TIMESTAMP_FORMAT).to_string())
+                    .unwrap_or_default(),
+                task.parent_id.clone().unwrap_or_default(),
+                task.description.clone().unwrap_or_default(),
+                task.created_at.format(TIMESTAMP_FORMAT).to_string(),
+                task.updated_at.format(TIMESTAMP_FORMAT).to_string(),
+            ]
+        }))
+        .collect();
+
+    let mut widths = [0; 9];
+
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect();
+
+            format!("{}\n", cells.join("  ").trim_end())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let timestamp =
+            DateTimeWithTimeZone::parse_from_rfc3339("2026-10-19T07:00:00+02:00").unwrap();
+
+        let task = Task {
+            id: "01HX".to_string(),
+            created_at: timestamp,
+            updated_at: timestamp,
+            title: "Write the CLI".to_string(),
+            description: None,
+            status: Status::InProgress,
+            due_at: None,
+            priority: Some(2),
+            parent_id: Some("01HW".to_string()),
+            project_id: None,
+        };
+
+        assert_eq!(
+            table(&[task]),
+            "\
+ID    TITLE          STATUS       PRIORITY  DUE  PARENT  DESCRIPTION  CREATED                     UPDATED
+01HX  Write the CLI  in_progress  2              01HW                 2026-10-19 07:00:00 +02:00  2026-10-19 07:00:00 +02:00
+"
+        );
+    }
+
+    #[test]
+    fn test_render_comment() {
+        let comment = Comment {
+            id: "01HX".to_string(),
+            task_id: "01HW".to_string(),
+            created_at: DateTimeWithTimeZone::parse_from_rfc3339("2026-10-19T07:00:00+02:00")
+                .unwrap(),
+            author: "ana".to_string(),
+            body: "Looks good.\n- ship it".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            render_comment(&comment),
+            "\
+01HX  ana  2026-10-19 07:00:00 +02:00
+    Looks good.
+    - ship it
+
+"
+        );
+    }
+
+    #[test]
+    fn test_outline() {
+        let task = |id: &str, title: &str, status: Status| Task {
+            id: id.to_string(),
+            title: title.to_string(),
+            status,
+            parent_id: (id != "01HA").then(|| "01HA".to_string()),
+            ..Default::default()
+        };
+
+        let tree = Tree::new(
+            task("01HA", "Release", Status::InProgress),
+            vec![
+                task("01HB", "Write notes", Status::Done),
+                task("01HC", "Tag the build", Status::Todo),
+            ],
+        );
+
+        assert_eq!(
+            outline(&tree, 0),
+            "\
+01HA  Release  [in_progress]  1/2 done
+  01HB  Write notes  [done]
+  01HC  Tag the build  [todo]
+"
+        );
+    }
+}
@@ -0,0 +1,666 @@
+//! A typed client for the Tasks REST API
+
+use std::time::Duration;
+
+use futures::{stream, Stream, TryStreamExt};
+use reqwest::{
+    multipart::{Form, Part},
+    Method, RequestBuilder, Response, StatusCode,
+};
+use serde::de::DeserializeOwned;
+
+use rust_demo_dependency_injection::{
+    attachments::model::Attachment,
+    comments::{self, model::Comment, store::CommentPage},
+    projects::{self, model::Project, store::ProjectPage},
+    recurrences::{self, model::Recurrence, store::RecurrencePage},
+    tasks::{
+        inputs,
+        model::Task,
+        store::Page,
+        tag::TagCount,
+        tree::Tree,
+        workflow::{Status, Transitions},
+    },
+};
+
+/// Errors returned by the Tasks API client
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The requested Task, Project, Comment or Attachment does not exist
+    #[error("Not found")]
+    NotFound,
+
+    /// The API responded with an unsuccessful status
+    #[error("Request failed with status {status}: {message}")]
+    Status {
+        /// The response status
+        status: StatusCode,
+
+        /// The response body
+        message: String,
+    },
+
+    /// The request could not be sent, or the response could not be decoded
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+}
+
+/// A `Result` with the client `Error`
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// How failed requests are retried. Connection failures are retried for every request, while
+/// throttling and unavailable responses are only retried for idempotent requests.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of retries after the first attempt
+    pub max_retries: u32,
+
+    /// The delay before the first retry, doubled for each retry after that
+    pub initial_backoff: Duration,
+
+    /// The maximum delay between retries
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry failed requests
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// A client for the Tasks REST API
+#[derive(Clone, Debug)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    retry_policy: RetryPolicy,
+}
+
+impl Client {
+    /// Create a client for the API at the given base URL, such as `http://localhost:3000`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Use the given retry policy
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Use the given `reqwest` client, to configure timeouts or default headers
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Get an individual `Task` by id
+    pub async fn get(&self, id: &str) -> Result<Task> {
+        let request = self.request(Method::GET, &format!("/tasks/{id}"));
+
+        self.send(request, true).await
+    }
+
+    /// Get a page of `Task`s
+    pub async fn list(&self, query: &inputs::List) -> Result<Page> {
+        let request = self.request(Method::GET, "/tasks").query(query);
+
+        self.send(request, true).await
+    }
+
+    /// Stream every `Task`, requesting pages of the given size as needed
+    pub fn stream(&self, limit: Option<usize>) -> impl Stream<Item = Result<Task>> + '_ {
+        let first = Some(inputs::List {
+            limit,
+            ..Default::default()
+        });
+
+        stream::try_unfold(first, move |query| async move {
+            let Some(query) = query else {
+                return Ok::<_, Error>(None);
+            };
+
+            let page = self.list(&query).await?;
+
+            let next = page.next_cursor.map(|after| inputs::List {
+                after: Some(after),
+                limit,
+                ..Default::default()
+            });
+
+            Ok(Some((
+                stream::iter(page.tasks.into_iter().map(Ok::<_, Error>)),
+                next,
+            )))
+        })
+        .try_flatten()
+    }
+
+    /// Collect every `Task`, requesting pages of the given size as needed
+    pub async fn list_all(&self, limit: Option<usize>) -> Result<Vec<Task>> {
+        self.stream(limit).try_collect().await
+    }
+
+    /// Create a `Task` with the given input
+    pub async fn create(&self, input: &inputs::Create) -> Result<Task> {
+        let request = self.request(Method::POST, "/tasks").json(input);
+
+        self.send(request, false).await
+    }
+
+    /// Update an existing `Task` by id, leaving `Unchanged` fields out of the request
+    pub async fn update(&self, id: &str, input: &inputs::Update) -> Result<Task> {
+        let request = self
+            .request(Method::PATCH, &format!("/tasks/{id}"))
+            .json(input);
+
+        self.send(request, true).await
+    }
+
+    /// Delete an existing `Task`
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        let request = self.request(Method::DELETE, &format!("/tasks/{id}"));
+
+        self.execute(request, true).await?;
+
+        Ok(())
+    }
+
+    /// Delete a Task by id, returning the deleted Task
+    pub async fn delete_returning(&self, id: &str) -> Result<Task> {
+        let request = self
+            .request(Method::DELETE, &format!("/tasks/{id}"))
+            .header("Prefer", "return=representation");
+
+        self.send(request, true).await
+    }
+
+    /// Get a Task's status and the statuses it can move to
+    pub async fn transitions(&self, id: &str) -> Result<Transitions> {
+        let request = self.request(Method::GET, &format!("/tasks/{id}/transitions"));
+
+        self.send(request, true).await
+    }
+
+    /// Move an existing `Task` to another status, if the server's workflow allows it
+    pub async fn transition(&self, id: &str, status: Status) -> Result<Task> {
+        let request = self
+            .request(Method::POST, &format!("/tasks/{id}/transitions"))
+            .json(&inputs::Transition { status });
+
+        self.send(request, false).await
+    }
+
+    /// List the names of a `Task`'s Tags
+    pub async fn tags(&self, id: &str) -> Result<Vec<String>> {
+        let request = self.request(Method::GET, &format!("/tasks/{id}/tags"));
+
+        self.send(request, true).await
+    }
+
+    /// Add a Tag to a `Task`, returning the Task's Tags
+    pub async fn add_tag(&self, id: &str, name: &str) -> Result<Vec<String>> {
+        let request = self.request(Method::PUT, &format!("/tasks/{id}/tags/{}", encode(name)));
+
+        self.send(request, true).await
+    }
+
+    /// Remove a Tag from a `Task`, returning the Task's remaining Tags
+    pub async fn remove_tag(&self, id: &str, name: &str) -> Result<Vec<String>> {
+        let request = self.request(
+            Method::DELETE,
+            &format!("/tasks/{id}/tags/{}", encode(name)),
+        );
+
+        self.send(request, true).await
+    }
+
+    /// Get a page of the `Task`s with a Tag
+    pub async fn list_by_tag(&self, name: &str, query: &inputs::ListByTag) -> Result<Page> {
+        let request = self
+            .request(Method::GET, &format!("/tags/{}/tasks", encode(name)))
+            .query(query);
+
+        self.send(request, true).await
+    }
+
+    /// List every Tag with the number of Tasks it labels
+    pub async fn tag_counts(&self) -> Result<Vec<TagCount>> {
+        let request = self.request(Method::GET, "/tags");
+
+        self.send(request, true).await
+    }
+
+    /// List a `Task`'s direct subtasks
+    pub async fn children(&self, id: &str) -> Result<Vec<Task>> {
+        let request = self.request(Method::GET, &format!("/tasks/{id}/children"));
+
+        self.send(request, true).await
+    }
+
+    /// Get a `Task` with every subtask below it
+    pub async fn tree(&self, id: &str) -> Result<Tree> {
+        let request = self.request(Method::GET, &format!("/tasks/{id}/subtree"));
+
+        self.send(request, true).await
+    }
+
+    /// Move an existing `Task` under another parent, or to the top level
+    pub async fn move_task(&self, id: &str, input: &inputs::Move) -> Result<Task> {
+        let request = self
+            .request(Method::PUT, &format!("/tasks/{id}/parent"))
+            .json(input);
+
+        self.send(request, true).await
+    }
+
+    /// List the `Task`s blocking a `Task`
+    pub async fn blockers(&self, id: &str) -> Result<Vec<Task>> {
+        let request = self.request(Method::GET, &format!("/tasks/{id}/blockers"));
+
+        self.send(request, true).await
+    }
+
+    /// Block a `Task` by another Task, returning the Task's blockers
+    pub async fn add_blocker(&self, id: &str, blocker_id: &str) -> Result<Vec<Task>> {
+        let request = self.request(Method::PUT, &format!("/tasks/{id}/blockers/{blocker_id}"));
+
+        self.send(request, true).await
+    }
+
+    /// Stop a Task from blocking a `Task`, returning the Task's remaining blockers
+    pub async fn remove_blocker(&self, id: &str, blocker_id: &str) -> Result<Vec<Task>> {
+        let request = self.request(
+            Method::DELETE,
+            &format!("/tasks/{id}/blockers/{blocker_id}"),
+        );
+
+        self.send(request, true).await
+    }
+
+    /// List the `Task`s blocked by a `Task`
+    pub async fn dependents(&self, id: &str) -> Result<Vec<Task>> {
+        let request = self.request(Method::GET, &format!("/tasks/{id}/dependents"));
+
+        self.send(request, true).await
+    }
+
+    /// Get a page of the `Comment`s on a `Task`, oldest first
+    pub async fn list_comments(
+        &self,
+        id: &str,
+        query: &comments::inputs::List,
+    ) -> Result<CommentPage> {
+        let request = self
+            .request(Method::GET, &format!("/tasks/{id}/comments"))
+            .query(query);
+
+        self.send(request, true).await
+    }
+
+    /// Post a `Comment` on a `Task`
+    pub async fn create_comment(
+        &self,
+        id: &str,
+        input: &comments::inputs::Create,
+    ) -> Result<Comment> {
+        let request = self
+            .request(Method::POST, &format!("/tasks/{id}/comments"))
+            .json(input);
+
+        self.send(request, false).await
+    }
+
+    /// Edit the body of a `Comment` on a `Task`
+    pub async fn update_comment(
+        &self,
+        id: &str,
+        comment_id: &str,
+        input: &comments::inputs::Update,
+    ) -> Result<Comment> {
+        let request = self
+            .request(Method::PATCH, &format!("/tasks/{id}/comments/{comment_id}"))
+            .json(input);
+
+        self.send(request, true).await
+    }
+
+    /// Delete a `Comment` on a `Task`
+    pub async fn delete_comment(&self, id: &str, comment_id: &str) -> Result<()> {
+        let request = self.request(
+            Method::DELETE,
+            &format!("/tasks/{id}/comments/{comment_id}"),
+        );
+
+        self.execute(request, true).await?;
+
+        Ok(())
+    }
+
+    /// List the files attached to a `Task`, oldest first
+    pub async fn list_attachments(&self, id: &str) -> Result<Vec<Attachment>> {
+        let request = self.request(Method::GET, &format!("/tasks/{id}/attachments"));
+
+        self.send(request, true).await
+    }
+
+    /// Attach a file to a `Task`
+    pub async fn upload_attachment(
+        &self,
+        id: &str,
+        name: &str,
+        content_type: &str,
+        bytes: Vec<u8>,
+    ) -> Result<Attachment> {
+        let part = Part::bytes(bytes)
+            .file_name(name.to_string())
+            .mime_str(content_type)?;
+
+        let request = self
+            .request(Method::POST, &format!("/tasks/{id}/attachments"))
+            .multipart(Form::new().part("file", part));
+
+        self.send(request, false).await
+    }
+
+    /// Download a file attached to a `Task`
+    pub async fn download_attachment(&self, id: &str, attachment_id: &str) -> Result<Vec<u8>> {
+        let request = self.request(
+            Method::GET,
+            &format!("/tasks/{id}/attachments/{attachment_id}"),
+        );
+
+        let response = self.execute(request, true).await?;
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Delete a file attached to a `Task`
+    pub async fn delete_attachment(&self, id: &str, attachment_id: &str) -> Result<()> {
+        let request = self.request(
+            Method::DELETE,
+            &format!("/tasks/{id}/attachments/{attachment_id}"),
+        );
+
+        self.execute(request, true).await?;
+
+        Ok(())
+    }
+
+    /// Get an individual `Project` by id
+    pub async fn get_project(&self, id: &str) -> Result<Project> {
+        let request = self.request(Method::GET, &format!("/projects/{id}"));
+
+        self.send(request, true).await
+    }
+
+    /// Get a page of `Project`s
+    pub async fn list_projects(&self, query: &projects::inputs::List) -> Result<ProjectPage> {
+        let request = self.request(Method::GET, "/projects").query(query);
+
+        self.send(request, true).await
+    }
+
+    /// Create a `Project` with the given input
+    pub async fn create_project(&self, input: &projects::inputs::Create) -> Result<Project> {
+        let request = self.request(Method::POST, "/projects").json(input);
+
+        self.send(request, false).await
+    }
+
+    /// Update an existing `Project` by id
+    pub async fn update_project(
+        &self,
+        id: &str,
+        input: &projects::inputs::Update,
+    ) -> Result<Project> {
+        let request = self
+            .request(Method::PATCH, &format!("/projects/{id}"))
+            .json(input);
+
+        self.send(request, true).await
+    }
+
+    /// Delete an existing `Project` with all of its Tasks
+    pub async fn delete_project(&self, id: &str) -> Result<()> {
+        let request = self.request(Method::DELETE, &format!("/projects/{id}"));
+
+        self.execute(request, true).await?;
+
+        Ok(())
+    }
+
+    /// Archive a `Project`, cancelling the Tasks the server's workflow allows to be cancelled
+    pub async fn archive_project(&self, id: &str) -> Result<Project> {
+        let request = self.request(Method::POST, &format!("/projects/{id}/archive"));
+
+        self.send(request, true).await
+    }
+
+    /// Restore an archived `Project`
+    pub async fn restore_project(&self, id: &str) -> Result<Project> {
+        let request = self.request(Method::POST, &format!("/projects/{id}/restore"));
+
+        self.send(request, true).await
+    }
+
+    /// Get a page of a `Project`'s Tasks
+    pub async fn list_project_tasks(
+        &self,
+        id: &str,
+        query: &projects::inputs::ListTasks,
+    ) -> Result<Page> {
+        let request = self
+            .request(Method::GET, &format!("/projects/{id}/tasks"))
+            .query(query);
+
+        self.send(request, true).await
+    }
+
+    /// Get an individual `Recurrence` by id
+    pub async fn get_recurrence(&self, id: &str) -> Result<Recurrence> {
+        let request = self.request(Method::GET, &format!("/recurrences/{id}"));
+
+        self.send(request, true).await
+    }
+
+    /// Get a page of `Recurrence`s
+    pub async fn list_recurrences(
+        &self,
+        query: &recurrences::inputs::List,
+    ) -> Result<RecurrencePage> {
+        let request = self.request(Method::GET, "/recurrences").query(query);
+
+        self.send(request, true).await
+    }
+
+    /// Create a `Recurrence` with the given input
+    pub async fn create_recurrence(
+        &self,
+        input: &recurrences::inputs::Create,
+    ) -> Result<Recurrence> {
+        let request = self.request(Method::POST, "/recurrences").json(input);
+
+        self.send(request, false).await
+    }
+
+    /// Edit the series of an existing `Recurrence` by id
+    pub async fn update_recurrence(
+        &self,
+        id: &str,
+        input: &recurrences::inputs::Update,
+    ) -> Result<Recurrence> {
+        let request = self
+            .request(Method::PATCH, &format!("/recurrences/{id}"))
+            .json(input);
+
+        self.send(request, true).await
+    }
+
+    /// Delete an existing `Recurrence`, keeping the Tasks it generated
+    pub async fn delete_recurrence(&self, id: &str) -> Result<()> {
+        let request = self.request(Method::DELETE, &format!("/recurrences/{id}"));
+
+        self.execute(request, true).await?;
+
+        Ok(())
+    }
+
+    /// Skip one occurrence of a `Recurrence`
+    pub async fn skip_occurrence(
+        &self,
+        id: &str,
+        input: &recurrences::inputs::Skip,
+    ) -> Result<Recurrence> {
+        let request = self
+            .request(Method::POST, &format!("/recurrences/{id}/skip"))
+            .json(input);
+
+        self.send(request, true).await
+    }
+
+    /// End a `Recurrence`, so no more Tasks are generated for it
+    pub async fn end_recurrence(
+        &self,
+        id: &str,
+        input: &recurrences::inputs::End,
+    ) -> Result<Recurrence> {
+        let request = self
+            .request(Method::POST, &format!("/recurrences/{id}/end"))
+            .json(input);
+
+        self.send(request, false).await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{path}", self.base_url))
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        idempotent: bool,
+    ) -> Result<T> {
+        let response = self.execute(request, idempotent).await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Execute the request with retries, and map unsuccessful responses to errors
+    async fn execute(&self, request: RequestBuilder, idempotent: bool) -> Result<Response> {
+        let mut attempt = 0;
+
+        let response = loop {
+            // Requests with streaming bodies can't be cloned, so they aren't retried
+            let Some(current) = request.try_clone() else {
+                break request.send().await?;
+            };
+
+            let retryable = match current.send().await {
+                Ok(response) => {
+                    if !(idempotent && is_retryable_status(response.status())) {
+                        break response;
+                    }
+
+                    Ok(response)
+                }
+                Err(err) if err.is_connect() || (idempotent && err.is_timeout()) => Err(err),
+                Err(err) => return Err(err.into()),
+            };
+
+            if attempt >= self.retry_policy.max_retries {
+                break retryable?;
+            }
+
+            tokio::time::sleep(self.retry_policy.backoff(attempt)).await;
+            attempt += 1;
+        };
+
+        let status = response.status();
+
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        if status == StatusCode::NOT_FOUND {
+            return Err(Error::NotFound);
+        }
+
+        Err(Error::Status {
+            status,
+            message: response.text().await.unwrap_or_default(),
+        })
+    }
+}
+
+/// Percent-encode a path segment, since Tag names can contain any character
+fn encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(10), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("needs review"), "needs%20review");
+        assert_eq!(encode("a/b"), "a%2Fb");
+        assert_eq!(encode("urgent"), "urgent");
+    }
+}
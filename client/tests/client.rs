@@ -0,0 +1,331 @@
+//! Tests for the Tasks API client against a server bound to an ephemeral local port
+
+use std::{sync::Arc, time::Duration};
+
+use pretty_assertions::assert_eq;
+use reqwest::StatusCode;
+use rust_demo_dependency_injection::{
+    attachments::{
+        model::Attachment,
+        storage::{self, LocalStorage, Storage},
+    },
+    config::{self, ConfigForDB},
+    dependencies,
+    recurrences::{self, model::Recurrence},
+    server::{self, DatabaseAppState},
+    tasks::{
+        model::Task,
+        tag::Tag,
+        workflow::{Status, Workflow},
+    },
+};
+use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+use tasks_client::{Client, Error, RetryPolicy};
+use tokio::net::TcpListener;
+
+fn task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        title: format!("Task {id}"),
+        ..Default::default()
+    }
+}
+
+async fn serve(db: MockDatabase) -> anyhow::Result<Client> {
+    let storage = LocalStorage::new(std::env::temp_dir().join("unused-attachments"));
+
+    serve_with_storage(db, Arc::new(storage), Default::default()).await
+}
+
+async fn serve_with_storage(
+    db: MockDatabase,
+    storage: Arc<dyn Storage>,
+    config: ConfigForDB,
+) -> anyhow::Result<Client> {
+    let state = DatabaseAppState {
+        config,
+        db: Arc::new(db.into_connection()),
+        storage,
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    tokio::spawn(async move { axum::serve(listener, server::database_router(state)).await });
+
+    Ok(Client::new(format!("http://{addr}")).with_retry_policy(RetryPolicy::none()))
+}
+
+#[tokio::test]
+async fn test_get() -> anyhow::Result<()> {
+    let task = task("1");
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![task.clone()], vec![]]);
+
+    let client = serve(db).await?;
+
+    assert_eq!(client.get(&task.id).await?, task);
+    assert!(matches!(client.get(&task.id).await, Err(Error::NotFound)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_all() -> anyhow::Result<()> {
+    let tasks = vec![task("1"), task("2"), task("3")];
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([tasks.clone(), tasks[2..].to_vec()]);
+
+    let client = serve(db).await?;
+
+    assert_eq!(client.list_all(Some(2)).await?, tasks);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_remove_tag() -> anyhow::Result<()> {
+    let task = task("1");
+
+    let tag = |name: &str| Tag {
+        id: format!("tag-{name}"),
+        name: name.to_string(),
+        created_at: task.created_at,
+    };
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![task.clone()]])
+        .append_query_results([vec![tag("needs review")]])
+        .append_exec_results([MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }])
+        .append_query_results([vec![tag("urgent")]]);
+
+    let client = serve(db).await?;
+
+    assert_eq!(
+        client.remove_tag(&task.id, "Needs Review").await?,
+        vec!["urgent".to_string()]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete() -> anyhow::Result<()> {
+    let task = task("1");
+
+    let deleted = MockExecResult {
+        last_insert_id: 0,
+        rows_affected: 1,
+    };
+
+    // Each delete finds the Task, then lists its Attachments before deleting it
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![task.clone()]])
+        .append_query_results([Vec::<Attachment>::new()])
+        .append_query_results([vec![task.clone()]])
+        .append_query_results([Vec::<Attachment>::new()])
+        .append_query_results([Vec::<Task>::new()])
+        .append_exec_results([deleted.clone(), deleted]);
+
+    let client = serve(db).await?;
+
+    client.delete(&task.id).await?;
+
+    assert_eq!(client.delete_returning(&task.id).await?, task);
+    assert!(matches!(
+        client.delete(&task.id).await,
+        Err(Error::NotFound)
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_attachments() -> anyhow::Result<()> {
+    let task = task("1");
+    let root = tempfile::tempdir()?;
+    let storage = Arc::new(LocalStorage::new(root.path().to_path_buf()));
+
+    let attachment = Attachment {
+        id: "01HY".to_string(),
+        task_id: task.id.clone(),
+        name: "notes.txt".to_string(),
+        size: 5,
+        content_type: "text/plain".to_string(),
+        checksum: "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string(),
+        ..Default::default()
+    };
+
+    storage
+        .put(
+            &storage::key(&task.id, &attachment.id),
+            "text/plain",
+            "hello".into(),
+        )
+        .await?;
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![task.clone()]])
+        .append_query_results([vec![attachment.clone()], vec![attachment.clone()]]);
+
+    let config = ConfigForDB {
+        attachments: config::Attachments {
+            max_size: 8,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let client = serve_with_storage(db, storage, config).await?;
+
+    let uploaded = client
+        .upload_attachment(&task.id, "notes.txt", "text/plain", b"hello".to_vec())
+        .await?;
+
+    assert_eq!(uploaded, attachment);
+    assert_eq!(
+        std::fs::read_dir(root.path().join("tasks").join(&task.id))?.count(),
+        2
+    );
+
+    assert_eq!(
+        client.download_attachment(&task.id, &attachment.id).await?,
+        b"hello"
+    );
+
+    assert!(matches!(
+        client
+            .upload_attachment(&task.id, "run.sh", "application/x-sh", b"ls".to_vec())
+            .await,
+        Err(Error::Status { status, .. }) if status == StatusCode::UNSUPPORTED_MEDIA_TYPE
+    ));
+    assert!(matches!(
+        client
+            .upload_attachment(&task.id, "notes.txt", "text/plain", b"too large".to_vec())
+            .await,
+        Err(Error::Status { status, .. }) if status == StatusCode::PAYLOAD_TOO_LARGE
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dependencies() -> anyhow::Result<()> {
+    let (task, blocker) = (task("1"), task("2"));
+
+    let edge = |task_id: &str, blocker_id: &str| dependencies::model::Model {
+        task_id: task_id.to_string(),
+        blocker_id: blocker_id.to_string(),
+        created_at: task.created_at,
+    };
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        // Additions take an advisory lock first
+        .append_exec_results([MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }])
+        // The blocker already depends on the Task
+        .append_query_results([vec![task.clone()], vec![blocker.clone()]])
+        .append_query_results([vec![edge(&blocker.id, &task.id)]])
+        // The Task's only blocker is still in progress
+        .append_query_results([vec![Task {
+            status: Status::InProgress,
+            ..blocker.clone()
+        }]]);
+
+    let config = ConfigForDB {
+        workflow: Workflow::default().require_unblocked(true),
+        ..Default::default()
+    };
+
+    let storage = LocalStorage::new(std::env::temp_dir().join("unused-attachments"));
+    let client = serve_with_storage(db, Arc::new(storage), config).await?;
+
+    assert!(matches!(
+        client.add_blocker(&task.id, &blocker.id).await,
+        Err(Error::Status { status, .. }) if status == StatusCode::CONFLICT
+    ));
+    assert!(matches!(
+        client.transition(&task.id, Status::Done).await,
+        Err(Error::Status { status, message }) if status == StatusCode::CONFLICT && message.contains(&blocker.id)
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recurrences() -> anyhow::Result<()> {
+    let weekly = Recurrence {
+        id: "1".to_string(),
+        title: "Ops checklist".to_string(),
+        rule: "FREQ=WEEKLY;BYDAY=MO".to_string(),
+        starts_at: chrono::DateTime::parse_from_rfc3339("2026-10-19T09:00:00Z")?,
+        ..Default::default()
+    };
+    let ended = Recurrence {
+        ended_at: Some(weekly.starts_at),
+        ..weekly.clone()
+    };
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![weekly.clone()], vec![ended.clone()]]);
+
+    let client = serve(db).await?;
+
+    assert!(matches!(
+        client
+            .create_recurrence(&recurrences::inputs::Create {
+                title: "Ops checklist".to_string(),
+                rule: "FREQ=FORTNIGHTLY".to_string(),
+                starts_at: weekly.starts_at,
+                ..Default::default()
+            })
+            .await,
+        Err(Error::Status { status, .. }) if status == StatusCode::BAD_REQUEST
+    ));
+
+    // A Tuesday isn't an occurrence of a rule on Mondays
+    assert!(matches!(
+        client
+            .skip_occurrence(
+                &weekly.id,
+                &recurrences::inputs::Skip {
+                    occurs_at: chrono::DateTime::parse_from_rfc3339("2026-10-20T09:00:00Z")?,
+                },
+            )
+            .await,
+        Err(Error::Status { status, .. }) if status == StatusCode::BAD_REQUEST
+    ));
+    assert!(matches!(
+        client
+            .end_recurrence(&ended.id, &Default::default())
+            .await,
+        Err(Error::Status { status, .. }) if status == StatusCode::CONFLICT
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retries_connection_failures() -> anyhow::Result<()> {
+    // Bind and drop a listener to find a port with nothing listening on it
+    let addr = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+
+    let client = Client::new(format!("http://{addr}")).with_retry_policy(RetryPolicy {
+        max_retries: 2,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(1),
+    });
+
+    let result = client.get("missing").await;
+
+    assert!(matches!(result, Err(Error::Http(err)) if err.is_connect()));
+
+    Ok(())
+}
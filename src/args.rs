@@ -1,44 +1,821 @@
+use std::{
+    convert::Infallible,
+    ffi::{OsStr, OsString},
+    path::PathBuf,
+};
+
 use pico_args::Arguments;
 
+/// The top-level `--help` output
 pub const HELP: &str = "\
-Usage: rust-demo-dependency-injection [OPTIONS]
+Usage: rust-demo-dependency-injection [COMMAND] [OPTIONS]
+
+Commands:
+  serve                Run the HTTP and gRPC servers (default)
+  migrate              Apply schema changes to the data store
+  provision            Create the DynamoDB table, or bring it up to date
+  rewrite-timestamps   Rewrite DynamoDB timestamps in the configured format
+  seed                 Seed the data store with fake Tasks
+  export               Export every Task as JSON lines
+  import               Import Tasks from JSON lines
+  check-config         Validate the configuration and data store connection
+  tasks                Manage Tasks from the terminal
+
+Options:
+  -h, --help           Print help for the command
+  -d, --data-store     The data store to use: 'postgres' or 'dynamodb', defaults to 'postgres'
+  -t, --timestamp-format
+                       The format DynamoDB timestamps are written in: 'rfc3339' or
+                       'epoch-millis', defaults to 'rfc3339'
+
+Run `rust-demo-dependency-injection <COMMAND> --help` for the options of each command.
+";
+
+/// The `serve --help` output
+pub const SERVE_HELP: &str = "\
+Usage: rust-demo-dependency-injection serve [OPTIONS]
+
+Run the HTTP and gRPC servers. This is the default when no command is given.
+
+Options:
+  -h, --help           Print help (this message)
+  -d, --data-store     The data store to use: 'postgres' or 'dynamodb', defaults to 'postgres'
+  -t, --timestamp-format
+                       The format DynamoDB timestamps are written in: 'rfc3339' or
+                       'epoch-millis', defaults to 'rfc3339'
+  -a, --address        The address to bind to, defaults to '127.0.0.1'
+  -p, --port           The port to bind to, defaults to '3000'
+  -g, --grpc-port      The port to bind the gRPC server to, defaults to '50051'
+      --auto-migrate   Apply pending Postgres migrations on startup
+  -w, --workflow       A JSON file mapping each Task status to the statuses it can move to
+      --require-unblocked
+                       Refuse to complete a Task until every Task blocking it is finished
+      --attachments-dir
+                       The directory to keep attached files in, defaults to 'attachments'
+      --attachments-bucket
+                       An S3 bucket to keep attached files in, instead of a directory
+      --s3-region      The region of the attachments bucket, defaults to 'us-east-1'
+      --s3-endpoint    The endpoint of an S3-compatible service to use instead of AWS
+      --max-attachment-size
+                       The largest file that can be attached, in bytes, defaults to 10 MiB
+      --attachment-types
+                       A comma-separated list of the content types that can be attached, where
+                       'image/*' allows every image type, defaults to images, PDFs and text
+      --recurrence-horizon
+                       How many days ahead to generate recurring Tasks, defaults to 14
+      --recurrence-interval
+                       How often to generate recurring Tasks, in seconds, defaults to 60
+      --job-concurrency
+                       How many background Jobs to run at once, defaults to 4
+";
+
+/// The `migrate --help` output
+pub const MIGRATE_HELP: &str = "\
+Usage: rust-demo-dependency-injection migrate [COMMAND] [OPTIONS]
+
+Manage the versioned schema migrations, recorded in the `seaql_migrations` table.
+
+Commands:
+  status               List every migration and whether it has been applied
+  up                   Apply pending migrations (default)
+  down                 Roll back applied migrations, one by default
+  fresh                Drop every table, then apply all migrations
+
+Options:
+  -h, --help           Print help (this message)
+  -d, --data-store     The data store to use, only 'postgres' is supported
+  -n, --steps          The number of migrations to apply or roll back
+";
+
+/// The `provision --help` output
+pub const PROVISION_HELP: &str = "\
+Usage: rust-demo-dependency-injection provision [OPTIONS]
+
+Create the DynamoDB Tasks table with its key schema, indexes and TTL, or add whatever an existing
+table is missing. Safe to run repeatedly.
+
+Options:
+  -h, --help           Print help (this message)
+  -d, --data-store     The data store to use, only 'dynamodb' is supported
+      --read-capacity  Provisioned read capacity units, instead of on-demand billing
+      --write-capacity Provisioned write capacity units, instead of on-demand billing
+      --ttl-attribute  The attribute holding each item's expiry time, to enable TTL on
+";
+
+/// The `rewrite-timestamps --help` output
+pub const REWRITE_TIMESTAMPS_HELP: &str = "\
+Usage: rust-demo-dependency-injection rewrite-timestamps [OPTIONS]
+
+Rewrite the timestamps of every DynamoDB item in the given format, including those written in a
+legacy format. Items already in the format are left untouched, so it is safe to run repeatedly.
+
+Options:
+  -h, --help           Print help (this message)
+  -d, --data-store     The data store to use, only 'dynamodb' is supported
+  -t, --timestamp-format
+                       The format to write timestamps in: 'rfc3339' or 'epoch-millis', defaults
+                       to 'rfc3339'
+";
+
+/// The `seed --help` output
+pub const SEED_HELP: &str = "\
+Usage: rust-demo-dependency-injection seed [OPTIONS]
+
+Create Tasks with fake titles, descriptions, priorities and due dates.
+
+Options:
+  -h, --help           Print help (this message)
+  -d, --data-store     The data store to use: 'postgres' or 'dynamodb', defaults to 'postgres'
+  -t, --timestamp-format
+                       The format DynamoDB timestamps are written in: 'rfc3339' or
+                       'epoch-millis', defaults to 'rfc3339'
+  -c, --count          The number of Tasks to create, defaults to '10'
+";
+
+/// The `export --help` output
+pub const EXPORT_HELP: &str = "\
+Usage: rust-demo-dependency-injection export [OPTIONS]
+
+Write every Task as one JSON object per line, to be read back with `import`.
 
 Options:
   -h, --help           Print help (this message)
   -d, --data-store     The data store to use: 'postgres' or 'dynamodb', defaults to 'postgres'
+  -t, --timestamp-format
+                       The format DynamoDB timestamps are written in: 'rfc3339' or
+                       'epoch-millis', defaults to 'rfc3339'
+  -f, --file           The file to write to, defaults to stdout
+";
+
+/// The `import --help` output
+pub const IMPORT_HELP: &str = "\
+Usage: rust-demo-dependency-injection import [OPTIONS]
+
+Read Tasks written by `export`, one JSON object per line, and save them with their original ids
+and timestamps. Tasks that already exist are replaced.
+
+Options:
+  -h, --help           Print help (this message)
+  -d, --data-store     The data store to use: 'postgres' or 'dynamodb', defaults to 'postgres'
+  -t, --timestamp-format
+                       The format DynamoDB timestamps are written in: 'rfc3339' or
+                       'epoch-millis', defaults to 'rfc3339'
+  -f, --file           The file to read from, defaults to stdin
+";
+
+/// The `check-config --help` output
+pub const CHECK_CONFIG_HELP: &str = "\
+Usage: rust-demo-dependency-injection check-config [OPTIONS]
+
+Print the resolved configuration and check that the data store can be reached.
+
+Options:
+  -h, --help           Print help (this message)
+  -d, --data-store     The data store to use: 'postgres' or 'dynamodb', defaults to 'postgres'
+  -t, --timestamp-format
+                       The format DynamoDB timestamps are written in: 'rfc3339' or
+                       'epoch-millis', defaults to 'rfc3339'
   -a, --address        The address to bind to, defaults to '127.0.0.1'
   -p, --port           The port to bind to, defaults to '3000'
+  -g, --grpc-port      The port to bind the gRPC server to, defaults to '50051'
+      --auto-migrate   Apply pending Postgres migrations on startup
+  -w, --workflow       A JSON file mapping each Task status to the statuses it can move to
+      --require-unblocked
+                       Refuse to complete a Task until every Task blocking it is finished
+      --attachments-dir
+                       The directory to keep attached files in, defaults to 'attachments'
+      --attachments-bucket
+                       An S3 bucket to keep attached files in, instead of a directory
+      --s3-region      The region of the attachments bucket, defaults to 'us-east-1'
+      --s3-endpoint    The endpoint of an S3-compatible service to use instead of AWS
+      --max-attachment-size
+                       The largest file that can be attached, in bytes, defaults to 10 MiB
+      --attachment-types
+                       A comma-separated list of the content types that can be attached, where
+                       'image/*' allows every image type, defaults to images, PDFs and text
+      --recurrence-horizon
+                       How many days ahead to generate recurring Tasks, defaults to 14
+      --recurrence-interval
+                       How often to generate recurring Tasks, in seconds, defaults to 60
+      --job-concurrency
+                       How many background Jobs to run at once, defaults to 4
 ";
 
+/// The `tasks --help` output
+pub const TASKS_HELP: &str = "\
+Usage: rust-demo-dependency-injection tasks <COMMAND> [OPTIONS]
+
+Commands:
+  list                 List a page of Tasks
+  get <ID>             Get a Task by id
+  create               Create a Task
+  update <ID>          Update a Task
+  delete <ID>          Delete a Task
+  transition <ID>      Move a Task to another status
+  tag <ID>             Add a Tag to a Task
+  untag <ID>           Remove a Tag from a Task
+  tags [ID]            List a Task's Tags, or every Tag with its Task count
+  children <ID>        List a Task's subtasks
+  tree <ID>            Show a Task with every subtask below it and their progress
+  move <ID>            Move a Task under another parent, or to the top level
+  comments <ID>        List the Comments on a Task, oldest first
+  comment <ID>         Post a Comment on a Task
+
+Options:
+  -h, --help           Print help (this message)
+  -u, --url            The URL of a running server to use, such as 'http://localhost:3000'
+  -d, --data-store     The data store to use when no URL is given: 'postgres' or 'dynamodb',
+                       defaults to 'postgres'
+  -t, --timestamp-format
+                       The format DynamoDB timestamps are written in: 'rfc3339' or
+                       'epoch-millis', defaults to 'rfc3339'
+  -o, --output         The output format: 'table' or 'json', defaults to 'table'
+
+List options:
+      --after          The cursor returned with the previous page
+      --limit          The maximum number of Tasks to return
+      --overdue        Only list Tasks past their due date that aren't done or cancelled
+      --due-within-days
+                       Only list Tasks due within this many days
+      --order          The order to list Tasks in: 'id' or 'priority', defaults to 'id'
+      --tag            Only list Tasks with this Tag, which can't be combined with the other
+                       filters or ordering
+
+Create and update options:
+      --title              The Task's title
+      --description        The Task's description
+      --description-clear  Clear the Task's description (update only)
+      --due-at             When the Task is due, as an RFC 3339 timestamp
+      --due-at-clear       Clear the Task's due date (update only)
+      --priority           The Task's priority, where higher numbers are more urgent
+      --priority-clear     Clear the Task's priority (update only)
+      --parent             The Task to create this as a subtask of (create only)
+      --project            The Project the Task belongs to
+      --project-clear      Remove the Task from its Project (update only)
+
+Transition options:
+      --status         The status to move to: 'todo', 'in_progress', 'blocked', 'done' or
+                       'cancelled'
+  -w, --workflow       A JSON file with the workflow to enforce when no URL is given
+
+Tag options:
+      --name           The Tag to add or remove
+
+Move options:
+      --parent         The Task to move the Task under
+      --root           Move the Task to the top level instead
+
+Comment options:
+      --author         Who is writing the Comment
+      --body           The Comment body, in Markdown
+      --after          The cursor returned with the previous page of Comments
+      --limit          The maximum number of Comments to return
+";
+
+/// Command line arguments
 #[derive(Debug)]
 pub struct Args {
+    /// The data store to use
     pub data_store: Option<String>,
+
+    /// The format DynamoDB timestamps are written in
+    pub timestamp_format: Option<String>,
+
+    /// The command to run
+    pub command: Command,
+}
+
+/// The command to run
+#[derive(Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Print the given help text
+    Help(&'static str),
+
+    /// Run the HTTP and gRPC servers
+    Serve(ServeArgs),
+
+    /// Apply schema changes to the data store
+    Migrate(MigrateCommand),
+
+    /// Create the DynamoDB table, or bring it up to date
+    Provision(ProvisionArgs),
+
+    /// Rewrite DynamoDB timestamps in the configured format
+    RewriteTimestamps,
+
+    /// Seed the data store with fake Tasks
+    Seed {
+        /// The number of Tasks to create
+        count: Option<usize>,
+    },
+
+    /// Export every Task as JSON lines
+    Export {
+        /// The file to write to, instead of stdout
+        file: Option<PathBuf>,
+    },
+
+    /// Import Tasks from JSON lines
+    Import {
+        /// The file to read from, instead of stdin
+        file: Option<PathBuf>,
+    },
+
+    /// Validate the configuration and data store connection
+    CheckConfig(ServeArgs),
+
+    /// Manage Tasks from the terminal
+    Tasks(TasksArgs),
+}
+
+/// Arguments for the `serve` and `check-config` commands
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ServeArgs {
+    /// The address to bind to
     pub address: Option<String>,
+
+    /// The HTTP port to bind to
     pub port: Option<u16>,
+
+    /// The gRPC port to bind to
+    pub grpc_port: Option<u16>,
+
+    /// Apply pending migrations on startup
+    pub auto_migrate: bool,
+
+    /// A JSON file with the Task status workflow
+    pub workflow: Option<PathBuf>,
+
+    /// Refuse to complete a Task until its blockers are finished
+    pub require_unblocked: bool,
+
+    /// The directory to keep attached files in
+    pub attachments_dir: Option<PathBuf>,
+
+    /// An S3 bucket to keep attached files in
+    pub attachments_bucket: Option<String>,
+
+    /// The region of the attachments bucket
+    pub s3_region: Option<String>,
+
+    /// The endpoint of an S3-compatible service
+    pub s3_endpoint: Option<String>,
+
+    /// The largest file that can be attached, in bytes
+    pub max_attachment_size: Option<usize>,
+
+    /// A comma-separated list of the content types that can be attached
+    pub attachment_types: Option<String>,
+
+    /// How many days ahead to generate recurring Tasks
+    pub recurrence_horizon: Option<u32>,
+
+    /// How often to generate recurring Tasks, in seconds
+    pub recurrence_interval: Option<u64>,
+
+    /// How many background Jobs to run at once
+    pub job_concurrency: Option<usize>,
+}
+
+/// A `migrate` subcommand
+#[derive(Debug, Eq, PartialEq)]
+pub enum MigrateCommand {
+    /// List every migration and whether it has been applied
+    Status,
+
+    /// Apply pending migrations
+    Up {
+        /// The number of migrations to apply, or all of them
+        steps: Option<u32>,
+    },
+
+    /// Roll back applied migrations
+    Down {
+        /// The number of migrations to roll back, or one
+        steps: Option<u32>,
+    },
+
+    /// Drop every table, then apply all migrations
+    Fresh,
+}
+
+/// Arguments for the `provision` command
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ProvisionArgs {
+    /// Provisioned read capacity units
+    pub read_capacity: Option<i64>,
+
+    /// Provisioned write capacity units
+    pub write_capacity: Option<i64>,
+
+    /// The attribute to enable TTL on
+    pub ttl_attribute: Option<String>,
+}
+
+/// Arguments for the `tasks` command
+#[derive(Debug, Eq, PartialEq)]
+pub struct TasksArgs {
+    /// The URL of a running server to use instead of the data store
+    pub url: Option<String>,
+
+    /// The output format
+    pub output: Option<String>,
+
+    /// The Task command to run
+    pub command: TasksCommand,
+}
+
+/// A `tasks` subcommand
+#[derive(Debug, Eq, PartialEq)]
+pub enum TasksCommand {
+    /// List a page of Tasks
+    List {
+        /// The cursor returned with the previous page
+        after: Option<String>,
+
+        /// The maximum number of Tasks to return
+        limit: Option<usize>,
+
+        /// Only list overdue Tasks
+        overdue: bool,
+
+        /// Only list Tasks due within this many days
+        due_within_days: Option<u32>,
+
+        /// The order to list Tasks in
+        order: Option<String>,
+
+        /// Only list Tasks with this Tag
+        tag: Option<String>,
+    },
+
+    /// Get a Task by id
+    Get {
+        /// The Task id
+        id: String,
+    },
+
+    /// Create a Task
+    Create {
+        /// The Task's title
+        title: String,
+
+        /// The Task's description
+        description: Option<String>,
+
+        /// When the Task is due, as an RFC 3339 timestamp
+        due_at: Option<String>,
+
+        /// The Task's priority
+        priority: Option<i32>,
+
+        /// The Task to create this as a subtask of
+        parent: Option<String>,
+
+        /// The Project to create the Task in
+        project: Option<String>,
+    },
+
+    /// Update a Task
+    Update {
+        /// The Task id
+        id: String,
+
+        /// The Task's new title
+        title: Option<String>,
+
+        /// The Task's new description
+        description: Option<String>,
+
+        /// Clear the Task's description
+        description_clear: bool,
+
+        /// When the Task is now due, as an RFC 3339 timestamp
+        due_at: Option<String>,
+
+        /// Clear the Task's due date
+        due_at_clear: bool,
+
+        /// The Task's new priority
+        priority: Option<i32>,
+
+        /// Clear the Task's priority
+        priority_clear: bool,
+
+        /// The Project to move the Task to
+        project: Option<String>,
+
+        /// Remove the Task from its Project
+        project_clear: bool,
+    },
+
+    /// Delete a Task
+    Delete {
+        /// The Task id
+        id: String,
+    },
+
+    /// Move a Task to another status
+    Transition {
+        /// The Task id
+        id: String,
+
+        /// The status to move to
+        status: String,
+
+        /// A JSON file with the workflow to enforce, instead of the default
+        workflow: Option<PathBuf>,
+    },
+
+    /// Add a Tag to a Task
+    Tag {
+        /// The Task id
+        id: String,
+
+        /// The Tag name
+        name: String,
+    },
+
+    /// Remove a Tag from a Task
+    Untag {
+        /// The Task id
+        id: String,
+
+        /// The Tag name
+        name: String,
+    },
+
+    /// List a Task's Tags, or every Tag with its Task count
+    Tags {
+        /// The Task id
+        id: Option<String>,
+    },
+
+    /// List a Task's subtasks
+    Children {
+        /// The Task id
+        id: String,
+    },
+
+    /// Show a Task with every subtask below it
+    Tree {
+        /// The Task id
+        id: String,
+    },
+
+    /// Move a Task under another parent, or to the top level
+    Move {
+        /// The Task id
+        id: String,
+
+        /// The new parent
+        parent: Option<String>,
+
+        /// Move the Task to the top level
+        root: bool,
+    },
+
+    /// List the Comments on a Task
+    Comments {
+        /// The Task id
+        id: String,
+
+        /// The cursor returned with the previous page
+        after: Option<String>,
+
+        /// The maximum number of Comments to return
+        limit: Option<usize>,
+    },
+
+    /// Post a Comment on a Task
+    Comment {
+        /// The Task id
+        id: String,
+
+        /// Who is writing the Comment
+        author: String,
+
+        /// The Comment body, in Markdown
+        body: String,
+    },
 }
 
 impl Args {
-    pub fn parse() -> anyhow::Result<Option<Self>> {
-        let mut pargs = Arguments::from_env();
+    /// Parse the arguments from the environment
+    pub fn parse() -> anyhow::Result<Self> {
+        Self::parse_from(Arguments::from_env())
+    }
+
+    /// Parse the arguments from the given list, excluding the binary name
+    pub fn parse_from_vec(args: Vec<OsString>) -> anyhow::Result<Self> {
+        Self::parse_from(Arguments::from_vec(args))
+    }
+
+    fn parse_from(mut pargs: Arguments) -> anyhow::Result<Self> {
+        // Subcommands must be taken before any options.
+        let subcommand = pargs.subcommand()?;
 
         // Help has a higher priority and should be handled separately.
         if pargs.contains(["-h", "--help"]) {
-            return Ok(None);
+            let help = match subcommand.as_deref() {
+                Some("serve") => SERVE_HELP,
+                Some("migrate") => MIGRATE_HELP,
+                Some("provision") => PROVISION_HELP,
+                Some("rewrite-timestamps") => REWRITE_TIMESTAMPS_HELP,
+                Some("seed") => SEED_HELP,
+                Some("export") => EXPORT_HELP,
+                Some("import") => IMPORT_HELP,
+                Some("check-config") => CHECK_CONFIG_HELP,
+                Some("tasks") => TASKS_HELP,
+                _ => HELP,
+            };
+
+            return Ok(Args {
+                data_store: None,
+                timestamp_format: None,
+                command: Command::Help(help),
+            });
+        }
+
+        let data_store = pargs.opt_value_from_str(["-d", "--data-store"])?;
+        let timestamp_format = pargs.opt_value_from_str(["-t", "--timestamp-format"])?;
+
+        let command = match subcommand.as_deref() {
+            None | Some("serve") => Command::Serve(ServeArgs::parse(&mut pargs)?),
+            Some("migrate") => Command::Migrate(MigrateCommand::parse(&mut pargs)?),
+            Some("provision") => Command::Provision(ProvisionArgs {
+                read_capacity: pargs.opt_value_from_str("--read-capacity")?,
+                write_capacity: pargs.opt_value_from_str("--write-capacity")?,
+                ttl_attribute: pargs.opt_value_from_str("--ttl-attribute")?,
+            }),
+            Some("rewrite-timestamps") => Command::RewriteTimestamps,
+            Some("seed") => Command::Seed {
+                count: pargs.opt_value_from_str(["-c", "--count"])?,
+            },
+            Some("export") => Command::Export {
+                file: pargs.opt_value_from_os_str(["-f", "--file"], parse_path)?,
+            },
+            Some("import") => Command::Import {
+                file: pargs.opt_value_from_os_str(["-f", "--file"], parse_path)?,
+            },
+            Some("check-config") => Command::CheckConfig(ServeArgs::parse(&mut pargs)?),
+            Some("tasks") => Command::Tasks(TasksArgs::parse(&mut pargs)?),
+            Some(other) => return Err(anyhow::anyhow!("Unknown command: {}", other)),
+        };
+
+        let remaining = pargs.finish();
+
+        if !remaining.is_empty() {
+            return Err(anyhow::anyhow!("Unexpected arguments: {:?}", remaining));
         }
 
-        let args = Args {
-            data_store: pargs.opt_value_from_str(["-d", "--data-store"])?,
+        Ok(Args {
+            data_store,
+            timestamp_format,
+            command,
+        })
+    }
+}
+
+impl ServeArgs {
+    fn parse(pargs: &mut Arguments) -> anyhow::Result<Self> {
+        Ok(Self {
             address: pargs.opt_value_from_str(["-a", "--address"])?,
             port: pargs.opt_value_from_str(["-p", "--port"])?,
+            grpc_port: pargs.opt_value_from_str(["-g", "--grpc-port"])?,
+            auto_migrate: pargs.contains("--auto-migrate"),
+            workflow: pargs.opt_value_from_os_str(["-w", "--workflow"], parse_path)?,
+            require_unblocked: pargs.contains("--require-unblocked"),
+            attachments_dir: pargs.opt_value_from_os_str("--attachments-dir", parse_path)?,
+            attachments_bucket: pargs.opt_value_from_str("--attachments-bucket")?,
+            s3_region: pargs.opt_value_from_str("--s3-region")?,
+            s3_endpoint: pargs.opt_value_from_str("--s3-endpoint")?,
+            max_attachment_size: pargs.opt_value_from_str("--max-attachment-size")?,
+            attachment_types: pargs.opt_value_from_str("--attachment-types")?,
+            recurrence_horizon: pargs.opt_value_from_str("--recurrence-horizon")?,
+            recurrence_interval: pargs.opt_value_from_str("--recurrence-interval")?,
+            job_concurrency: pargs.opt_value_from_str("--job-concurrency")?,
+        })
+    }
+}
+
+impl MigrateCommand {
+    fn parse(pargs: &mut Arguments) -> anyhow::Result<Self> {
+        let subcommand = pargs.subcommand()?;
+
+        match subcommand.as_deref() {
+            None | Some("up") => Ok(MigrateCommand::Up {
+                steps: pargs.opt_value_from_str(["-n", "--steps"])?,
+            }),
+            Some("down") => Ok(MigrateCommand::Down {
+                steps: pargs.opt_value_from_str(["-n", "--steps"])?,
+            }),
+            Some("status") => Ok(MigrateCommand::Status),
+            Some("fresh") => Ok(MigrateCommand::Fresh),
+            Some(other) => Err(anyhow::anyhow!("Unknown migrate command: {}", other)),
+        }
+    }
+}
+
+impl TasksArgs {
+    fn parse(pargs: &mut Arguments) -> anyhow::Result<Self> {
+        let subcommand = pargs.subcommand()?;
+
+        let url = pargs.opt_value_from_str(["-u", "--url"])?;
+        let output = pargs.opt_value_from_str(["-o", "--output"])?;
+
+        // Free arguments like the id must be taken after every option.
+        let command = match subcommand.as_deref() {
+            Some("list") => TasksCommand::List {
+                after: pargs.opt_value_from_str("--after")?,
+                limit: pargs.opt_value_from_str("--limit")?,
+                overdue: pargs.contains("--overdue"),
+                due_within_days: pargs.opt_value_from_str("--due-within-days")?,
+                order: pargs.opt_value_from_str("--order")?,
+                tag: pargs.opt_value_from_str("--tag")?,
+            },
+            Some("get") => TasksCommand::Get {
+                id: pargs.free_from_str()?,
+            },
+            Some("create") => TasksCommand::Create {
+                title: pargs.value_from_str("--title")?,
+                description: pargs.opt_value_from_str("--description")?,
+                due_at: pargs.opt_value_from_str("--due-at")?,
+                priority: pargs.opt_value_from_str("--priority")?,
+                parent: pargs.opt_value_from_str("--parent")?,
+                project: pargs.opt_value_from_str("--project")?,
+            },
+            Some("update") => TasksCommand::Update {
+                title: pargs.opt_value_from_str("--title")?,
+                description: pargs.opt_value_from_str("--description")?,
+                description_clear: pargs.contains("--description-clear"),
+                due_at: pargs.opt_value_from_str("--due-at")?,
+                due_at_clear: pargs.contains("--due-at-clear"),
+                priority: pargs.opt_value_from_str("--priority")?,
+                priority_clear: pargs.contains("--priority-clear"),
+                project: pargs.opt_value_from_str("--project")?,
+                project_clear: pargs.contains("--project-clear"),
+                id: pargs.free_from_str()?,
+            },
+            Some("delete") => TasksCommand::Delete {
+                id: pargs.free_from_str()?,
+            },
+            Some("transition") => TasksCommand::Transition {
+                status: pargs.value_from_str("--status")?,
+                workflow: pargs.opt_value_from_os_str(["-w", "--workflow"], parse_path)?,
+                id: pargs.free_from_str()?,
+            },
+            Some("tag") => TasksCommand::Tag {
+                name: pargs.value_from_str("--name")?,
+                id: pargs.free_from_str()?,
+            },
+            Some("untag") => TasksCommand::Untag {
+                name: pargs.value_from_str("--name")?,
+                id: pargs.free_from_str()?,
+            },
+            Some("tags") => TasksCommand::Tags {
+                id: pargs.opt_free_from_str()?,
+            },
+            Some("children") => TasksCommand::Children {
+                id: pargs.free_from_str()?,
+            },
+            Some("tree") => TasksCommand::Tree {
+                id: pargs.free_from_str()?,
+            },
+            Some("move") => TasksCommand::Move {
+                parent: pargs.opt_value_from_str("--parent")?,
+                root: pargs.contains("--root"),
+                id: pargs.free_from_str()?,
+            },
+            Some("comments") => TasksCommand::Comments {
+                after: pargs.opt_value_from_str("--after")?,
+                limit: pargs.opt_value_from_str("--limit")?,
+                id: pargs.free_from_str()?,
+            },
+            Some("comment") => TasksCommand::Comment {
+                author: pargs.value_from_str("--author")?,
+                body: pargs.value_from_str("--body")?,
+                id: pargs.free_from_str()?,
+            },
+            Some(other) => return Err(anyhow::anyhow!("Unknown tasks command: {}", other)),
+            None => return Err(anyhow::anyhow!("Missing tasks command, see `tasks --help`")),
         };
 
-        Ok(Some(args))
+        Ok(Self {
+            url,
+            output,
+            command,
+        })
     }
 }
 
-#[derive(Debug)]
+/// The data store used for persistence
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum DataStore {
+    /// Postgres, via SeaORM
     Postgres,
+
+    /// DynamoDB
     DynamoDB,
 }
 
@@ -62,3 +839,343 @@ impl From<DataStore> for String {
         }
     }
 }
+
+fn parse_path(value: &OsStr) -> Result<PathBuf, Infallible> {
+    Ok(value.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Args> {
+        Args::parse_from_vec(args.iter().map(OsString::from).collect())
+    }
+
+    #[test]
+    fn test_serve_is_the_default() -> anyhow::Result<()> {
+        let args = parse(&["--port", "4000"])?;
+
+        assert_eq!(
+            args.command,
+            Command::Serve(ServeArgs {
+                port: Some(4000),
+                ..Default::default()
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_serve_attachments() -> anyhow::Result<()> {
+        let args = parse(&[
+            "serve",
+            "--require-unblocked",
+            "--attachments-bucket",
+            "task-files",
+            "--s3-endpoint",
+            "http://localhost:9000",
+            "--max-attachment-size",
+            "1048576",
+            "--attachment-types",
+            "image/*,application/pdf",
+            "--recurrence-horizon",
+            "28",
+            "--job-concurrency",
+            "8",
+        ])?;
+
+        assert_eq!(
+            args.command,
+            Command::Serve(ServeArgs {
+                require_unblocked: true,
+                attachments_bucket: Some("task-files".to_string()),
+                s3_endpoint: Some("http://localhost:9000".to_string()),
+                max_attachment_size: Some(1_048_576),
+                attachment_types: Some("image/*,application/pdf".to_string()),
+                recurrence_horizon: Some(28),
+                job_concurrency: Some(8),
+                ..Default::default()
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_help() -> anyhow::Result<()> {
+        assert_eq!(parse(&["--help"])?.command, Command::Help(HELP));
+        assert_eq!(parse(&["tasks", "-h"])?.command, Command::Help(TASKS_HELP));
+        assert_eq!(
+            parse(&["check-config", "--help"])?.command,
+            Command::Help(CHECK_CONFIG_HELP)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tasks_update() -> anyhow::Result<()> {
+        let args = parse(&[
+            "tasks",
+            "update",
+            "-o",
+            "json",
+            "01HX",
+            "--description-clear",
+            "--priority",
+            "3",
+            "-d",
+            "dynamodb",
+        ])?;
+
+        assert_eq!(args.data_store, Some("dynamodb".to_string()));
+
+        assert_eq!(
+            args.command,
+            Command::Tasks(TasksArgs {
+                url: None,
+                output: Some("json".to_string()),
+                command: TasksCommand::Update {
+                    id: "01HX".to_string(),
+                    title: None,
+                    description: None,
+                    description_clear: true,
+                    due_at: None,
+                    due_at_clear: false,
+                    priority: Some(3),
+                    priority_clear: false,
+                    project: None,
+                    project_clear: false,
+                },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tasks_list() -> anyhow::Result<()> {
+        let args = parse(&[
+            "tasks",
+            "list",
+            "--overdue",
+            "--due-within-days",
+            "7",
+            "--order",
+            "priority",
+        ])?;
+
+        assert_eq!(
+            args.command,
+            Command::Tasks(TasksArgs {
+                url: None,
+                output: None,
+                command: TasksCommand::List {
+                    after: None,
+                    limit: None,
+                    overdue: true,
+                    due_within_days: Some(7),
+                    order: Some("priority".to_string()),
+                    tag: None,
+                },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tasks_tag() -> anyhow::Result<()> {
+        let args = parse(&["tasks", "tag", "01HX", "--name", "urgent"])?;
+
+        assert_eq!(
+            args.command,
+            Command::Tasks(TasksArgs {
+                url: None,
+                output: None,
+                command: TasksCommand::Tag {
+                    id: "01HX".to_string(),
+                    name: "urgent".to_string(),
+                },
+            })
+        );
+
+        let args = parse(&["tasks", "tags"])?;
+
+        assert_eq!(
+            args.command,
+            Command::Tasks(TasksArgs {
+                url: None,
+                output: None,
+                command: TasksCommand::Tags { id: None },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tasks_comment() -> anyhow::Result<()> {
+        let args = parse(&[
+            "tasks",
+            "comment",
+            "01HX",
+            "--author",
+            "ana",
+            "--body",
+            "Needs a *second* look",
+        ])?;
+
+        assert_eq!(
+            args.command,
+            Command::Tasks(TasksArgs {
+                url: None,
+                output: None,
+                command: TasksCommand::Comment {
+                    id: "01HX".to_string(),
+                    author: "ana".to_string(),
+                    body: "Needs a *second* look".to_string(),
+                },
+            })
+        );
+
+        let args = parse(&["tasks", "comments", "01HX", "--limit", "5"])?;
+
+        assert_eq!(
+            args.command,
+            Command::Tasks(TasksArgs {
+                url: None,
+                output: None,
+                command: TasksCommand::Comments {
+                    id: "01HX".to_string(),
+                    after: None,
+                    limit: Some(5),
+                },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tasks_move() -> anyhow::Result<()> {
+        let args = parse(&["tasks", "move", "01HX", "--parent", "01HY"])?;
+
+        assert_eq!(
+            args.command,
+            Command::Tasks(TasksArgs {
+                url: None,
+                output: None,
+                command: TasksCommand::Move {
+                    id: "01HX".to_string(),
+                    parent: Some("01HY".to_string()),
+                    root: false,
+                },
+            })
+        );
+
+        let args = parse(&["tasks", "move", "--root", "01HX"])?;
+
+        assert_eq!(
+            args.command,
+            Command::Tasks(TasksArgs {
+                url: None,
+                output: None,
+                command: TasksCommand::Move {
+                    id: "01HX".to_string(),
+                    parent: None,
+                    root: true,
+                },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tasks_transition() -> anyhow::Result<()> {
+        let args = parse(&[
+            "tasks",
+            "transition",
+            "01HX",
+            "--status",
+            "done",
+            "-w",
+            "workflow.json",
+        ])?;
+
+        assert_eq!(
+            args.command,
+            Command::Tasks(TasksArgs {
+                url: None,
+                output: None,
+                command: TasksCommand::Transition {
+                    id: "01HX".to_string(),
+                    status: "done".to_string(),
+                    workflow: Some(PathBuf::from("workflow.json")),
+                },
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export() -> anyhow::Result<()> {
+        let args = parse(&["export", "-f", "tasks.jsonl", "-d", "dynamodb"])?;
+
+        assert_eq!(args.data_store, Some("dynamodb".to_string()));
+        assert_eq!(
+            args.command,
+            Command::Export {
+                file: Some(PathBuf::from("tasks.jsonl"))
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_timestamp_format() -> anyhow::Result<()> {
+        let args = parse(&["tasks", "list", "-t", "epoch-millis", "-d", "dynamodb"])?;
+
+        assert_eq!(args.timestamp_format, Some("epoch-millis".to_string()));
+
+        let args = parse(&["seed", "--timestamp-format", "epoch-millis"])?;
+
+        assert_eq!(args.timestamp_format, Some("epoch-millis".to_string()));
+        assert_eq!(args.command, Command::Seed { count: None });
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate() -> anyhow::Result<()> {
+        assert_eq!(
+            parse(&["migrate"])?.command,
+            Command::Migrate(MigrateCommand::Up { steps: None })
+        );
+        assert_eq!(
+            parse(&["migrate", "down", "-n", "2"])?.command,
+            Command::Migrate(MigrateCommand::Down { steps: Some(2) })
+        );
+        assert_eq!(
+            parse(&["migrate", "status"])?.command,
+            Command::Migrate(MigrateCommand::Status)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unexpected_arguments() {
+        assert!(parse(&["migrate", "--port", "4000"]).is_err());
+        assert!(parse(&["migrate", "status", "--steps", "1"]).is_err());
+        assert!(parse(&["tasks", "get", "01HX", "extra"]).is_err());
+        assert!(parse(&["unknown"]).is_err());
+    }
+}
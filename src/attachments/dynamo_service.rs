@@ -0,0 +1,329 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, Put, ReturnValue, TransactWriteItem},
+    Client,
+};
+use chrono::SubsecRound;
+use ulid::Ulid;
+
+use crate::{
+    config::{self, TimestampFormat},
+    tasks::{
+        self,
+        dynamo_service::{failed_conditions, task_exists},
+        dynamo_table::{ATTACHMENT_ENTITY, ATTACHMENT_INDEX, ENTITY_ATTRIBUTE, TASK_ENTITY},
+    },
+    utils::dynamo::{self, Item},
+};
+
+use super::{
+    error::Error,
+    inputs,
+    model::Attachment,
+    storage::{self, Storage},
+};
+
+/// List the files attached to a `Task`, oldest first
+pub async fn list(
+    client: Arc<Client>,
+    table_name: &str,
+    task_id: &str,
+) -> anyhow::Result<Vec<Attachment>> {
+    tasks::dynamo_service::get(client.clone(), table_name, task_id)
+        .await?
+        .ok_or(tasks::error::Error::NotFound)?;
+
+    task_attachments(&client, table_name, task_id).await
+}
+
+/// Get the metadata of a file attached to a `Task`
+pub async fn get(
+    client: Arc<Client>,
+    table_name: &str,
+    task_id: &str,
+    id: &str,
+) -> anyhow::Result<Attachment> {
+    let result = client
+        .get_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(attachment_key(task_id, id)))
+        .send()
+        .await?;
+
+    from_attachment_item(result.item.ok_or(Error::NotFound)?)
+}
+
+/// Attach a file to a `Task`. The bytes are stored first, then the metadata is written on the
+/// condition that the Task exists, and the bytes are removed again if it can't be.
+pub async fn create(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    storage: &dyn Storage,
+    config: &config::Attachments,
+    task_id: &str,
+    upload: inputs::Upload,
+) -> anyhow::Result<Attachment> {
+    upload.validate(config)?;
+
+    let attachment = Attachment {
+        id: Ulid::new().to_string(),
+        task_id: task_id.to_string(),
+        // Truncated so the returned Attachment matches what reads return in every format
+        created_at: chrono::Utc::now().fixed_offset().trunc_subsecs(3),
+        name: upload.name.clone(),
+        size: upload.size(),
+        content_type: upload.content_type.clone(),
+        checksum: upload.checksum(),
+    };
+
+    let key = storage::key(task_id, &attachment.id);
+
+    storage
+        .put(&key, &upload.content_type, upload.bytes)
+        .await?;
+
+    if let Err(err) = put_attachment(&client, table_name, format, &attachment).await {
+        if let Err(cleanup) = storage.delete(&key).await {
+            log::warn!("Unable to remove the bytes of an unsaved Attachment {key}: {cleanup}");
+        }
+
+        return Err(err);
+    }
+
+    Ok(attachment)
+}
+
+/// Detach a file from a `Task`, returning its metadata. The metadata is deleted before the bytes,
+/// so a listed Attachment can always be downloaded.
+pub async fn delete(
+    client: Arc<Client>,
+    table_name: &str,
+    storage: &dyn Storage,
+    task_id: &str,
+    id: &str,
+) -> anyhow::Result<Attachment> {
+    let result = client
+        .delete_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(attachment_key(task_id, id)))
+        .return_values(ReturnValue::AllOld)
+        .send()
+        .await?;
+
+    // DynamoDB only returns the old attributes when an item was actually deleted
+    let item = result.attributes.ok_or(Error::NotFound)?;
+
+    storage.delete(&storage::key(task_id, id)).await?;
+
+    from_attachment_item(item)
+}
+
+/// Delete every file attached to a Task, once the Task itself has been deleted. The metadata is
+/// deleted before the bytes are purged.
+pub async fn delete_all(
+    client: &Client,
+    table_name: &str,
+    storage: &dyn Storage,
+    task_id: &str,
+) -> anyhow::Result<()> {
+    let attachments = task_attachments(client, table_name, task_id).await?;
+
+    for attachment in &attachments {
+        client
+            .delete_item()
+            .table_name(table_name)
+            .key(
+                "id",
+                AttributeValue::S(attachment_key(task_id, &attachment.id)),
+            )
+            .send()
+            .await?;
+    }
+
+    storage::purge(storage, &attachments).await;
+
+    Ok(())
+}
+
+/// Write the metadata of an Attachment in a transaction with a check that its Task exists
+async fn put_attachment(
+    client: &Client,
+    table_name: &str,
+    format: TimestampFormat,
+    attachment: &Attachment,
+) -> anyhow::Result<()> {
+    let task_exists = task_exists(table_name, &attachment.task_id)?;
+
+    let put = Put::builder()
+        .table_name(table_name)
+        .set_item(Some(attachment_item(attachment, format)?))
+        .condition_expression("attribute_not_exists(#id)")
+        .expression_attribute_names("#id", "id")
+        .build()?;
+
+    let result = client
+        .transact_write_items()
+        .transact_items(
+            TransactWriteItem::builder()
+                .condition_check(task_exists)
+                .build(),
+        )
+        .transact_items(TransactWriteItem::builder().put(put).build())
+        .send()
+        .await;
+
+    if let Err(err) = result {
+        return match failed_conditions(&err).as_deref() {
+            Some([true, ..]) => Err(tasks::error::Error::NotFound.into()),
+            _ => Err(err.into()),
+        };
+    }
+
+    Ok(())
+}
+
+/// Every file attached to a Task from the Attachment index, oldest first
+async fn task_attachments(
+    client: &Client,
+    table_name: &str,
+    task_id: &str,
+) -> anyhow::Result<Vec<Attachment>> {
+    let mut attachments = vec![];
+    let mut start_key = None;
+
+    loop {
+        let results = client
+            .query()
+            .table_name(table_name)
+            .index_name(ATTACHMENT_INDEX)
+            .key_condition_expression("#task_id = :task_id")
+            .expression_attribute_names("#task_id", "task_id")
+            .expression_attribute_values(":task_id", AttributeValue::S(task_id.to_string()))
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        for item in results.items.unwrap_or_default() {
+            attachments.push(from_attachment_item(item)?);
+        }
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(attachments),
+        }
+    }
+}
+
+/// The id of an Attachment's item, which sits alongside the other items keyed by its Task
+fn attachment_key(task_id: &str, id: &str) -> String {
+    format!("{TASK_ENTITY}#{task_id}#{ATTACHMENT_ENTITY}#{id}")
+}
+
+/// The item for an Attachment, keyed by its Task and with the keys of the Attachment index
+fn attachment_item(attachment: &Attachment, format: TimestampFormat) -> anyhow::Result<Item> {
+    let mut item = dynamo::to_item(attachment, format)?;
+
+    item.insert(
+        "id".to_string(),
+        AttributeValue::S(attachment_key(&attachment.task_id, &attachment.id)),
+    );
+    item.insert(
+        "attachment_id".to_string(),
+        AttributeValue::S(attachment.id.clone()),
+    );
+    item.insert(
+        ENTITY_ATTRIBUTE.to_string(),
+        AttributeValue::S(ATTACHMENT_ENTITY.to_string()),
+    );
+
+    Ok(item)
+}
+
+/// Read an Attachment from its item, where the Attachment id is kept apart from the item key
+fn from_attachment_item(mut item: Item) -> anyhow::Result<Attachment> {
+    let id = item
+        .remove("attachment_id")
+        .ok_or_else(|| anyhow!("Found an Attachment without an attachment_id: {item:?}"))?;
+
+    item.insert("id".to_string(), id);
+
+    Ok(dynamo::from_item(item)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Bytes;
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use crate::{attachments::storage::LocalStorage, utils::fake_dynamo::FakeDynamo};
+
+    use super::*;
+
+    #[test]
+    fn test_attachment_item() -> anyhow::Result<()> {
+        let attachment = Attachment {
+            created_at: chrono::Utc::now().fixed_offset().trunc_subsecs(3),
+            ..Faker.fake()
+        };
+
+        let item = attachment_item(&attachment, TimestampFormat::Rfc3339)?;
+
+        assert_eq!(
+            item.get("id"),
+            Some(&AttributeValue::S(format!(
+                "task#{}#attachment#{}",
+                attachment.task_id, attachment.id
+            )))
+        );
+        assert_eq!(
+            item.get("size"),
+            Some(&AttributeValue::N(attachment.size.to_string()))
+        );
+        assert_eq!(from_attachment_item(item)?, attachment);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_on_a_project() -> anyhow::Result<()> {
+        let table = FakeDynamo::serve(vec![json!({
+            "id": { "S": "01HXPROJECT" },
+            "entity": { "S": "project" },
+            "name": { "S": "Launch" },
+        })])
+        .await?;
+
+        let root = tempfile::tempdir()?;
+        let storage = LocalStorage::new(root.path().to_path_buf());
+
+        let result = create(
+            table.client.clone(),
+            "tasks",
+            TimestampFormat::Rfc3339,
+            &storage,
+            &config::Attachments::default(),
+            "01HXPROJECT",
+            inputs::Upload::new("notes.txt", "text/plain", Bytes::from("Notes")),
+        )
+        .await;
+
+        assert!(matches!(result, Err(err) if tasks::error::Error::is_not_found(&err)));
+
+        // Nothing is left behind, in the table or in storage
+        assert_eq!(
+            table.get("01HXPROJECT").map(|item| item["entity"].clone()),
+            Some(json!({ "S": "project" }))
+        );
+        assert_eq!(
+            std::fs::read_dir(root.path().join("tasks/01HXPROJECT"))?.count(),
+            0
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+/// Attachment errors that callers respond to differently, carried inside `anyhow::Error`
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The Attachment does not exist on the Task, or its bytes are missing from storage
+    #[error("Attachment not found")]
+    NotFound,
+
+    /// The file is larger than the configured limit
+    #[error("Attachments can't be larger than {0} bytes")]
+    TooLarge(usize),
+
+    /// The file's content type isn't on the configured allowlist
+    #[error("Unsupported content type: {0}")]
+    UnsupportedContentType(String),
+
+    /// The upload is missing its file or the file has no name
+    #[error("Invalid Attachment: {0}")]
+    Invalid(String),
+}
+
+impl Error {
+    /// Check whether the error is, or wraps, a `NotFound` error
+    pub fn is_not_found(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::NotFound))
+    }
+
+    /// Check whether the error is, or wraps, a `TooLarge` error
+    pub fn is_too_large(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::TooLarge(_)))
+    }
+
+    /// Check whether the error is, or wraps, an `UnsupportedContentType` error
+    pub fn is_unsupported_content_type(err: &anyhow::Error) -> bool {
+        matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::UnsupportedContentType(_))
+        )
+    }
+
+    /// Check whether the error is, or wraps, an `Invalid` error
+    pub fn is_invalid(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::Invalid(_)))
+    }
+}
@@ -0,0 +1,136 @@
+use axum::body::Bytes;
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+
+use crate::config;
+
+use super::error::Error;
+
+/// A file uploaded to attach to a Task
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Upload {
+    /// The file name, without any directories
+    pub name: String,
+
+    /// The file's content type, such as "image/png"
+    pub content_type: String,
+
+    /// The file contents
+    pub bytes: Bytes,
+}
+
+impl Upload {
+    /// Create an upload, keeping only the last component of the file name since some clients send
+    /// the full path
+    pub fn new(name: &str, content_type: &str, bytes: Bytes) -> Self {
+        let name = name
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect::<String>();
+
+        Self {
+            name: name.trim().to_string(),
+            content_type: content_type.trim().to_string(),
+            bytes,
+        }
+    }
+
+    /// Check that the file has a name, isn't larger than the limit and has an allowed content type
+    pub fn validate(&self, config: &config::Attachments) -> Result<(), Error> {
+        if self.name.is_empty() || self.name == "." || self.name == ".." {
+            return Err(Error::Invalid("the file name cannot be blank".to_string()));
+        }
+
+        if self.bytes.len() > config.max_size {
+            return Err(Error::TooLarge(config.max_size));
+        }
+
+        if !config.allows(&self.content_type) {
+            return Err(Error::UnsupportedContentType(self.content_type.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// The file size, in bytes
+    pub fn size(&self) -> i64 {
+        self.bytes.len() as i64
+    }
+
+    /// The hex-encoded SHA-256 checksum of the file
+    pub fn checksum(&self) -> String {
+        hex::encode(Sha256::digest(&self.bytes))
+    }
+}
+
+/// The `multipart/form-data` body that uploads an Attachment, as described in the OpenAPI document
+#[derive(ToSchema)]
+#[schema(as = UploadAttachmentForm)]
+pub struct UploadForm {
+    /// The file, sent with its name and content type
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let upload = Upload::new(
+            "C:\\Users\\ana\\screenshot.png",
+            " image/png ",
+            Bytes::new(),
+        );
+
+        assert_eq!(upload.name, "screenshot.png");
+        assert_eq!(upload.content_type, "image/png");
+        assert_eq!(
+            Upload::new("../notes.txt", "", Bytes::new()).name,
+            "notes.txt"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let config = config::Attachments {
+            max_size: 4,
+            ..Default::default()
+        };
+
+        let upload = Upload::new("notes.txt", "text/plain", Bytes::from_static(b"todo"));
+
+        assert!(upload.validate(&config).is_ok());
+
+        assert!(matches!(
+            Upload::new("docs/", "text/plain", Bytes::new()).validate(&config),
+            Err(Error::Invalid(_))
+        ));
+
+        assert!(matches!(
+            Upload::new("notes.txt", "text/plain", Bytes::from_static(b"todos")).validate(&config),
+            Err(Error::TooLarge(4))
+        ));
+
+        assert!(matches!(
+            Upload::new("run.sh", "application/x-sh", Bytes::new()).validate(&config),
+            Err(Error::UnsupportedContentType(_))
+        ));
+    }
+
+    #[test]
+    fn test_checksum() {
+        let upload = Upload::new("notes.txt", "text/plain", Bytes::from_static(b"hello"));
+
+        assert_eq!(
+            upload.checksum(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+}
@@ -0,0 +1,20 @@
+/// The Attachment Model
+pub mod model;
+
+/// The Attachment entity general-purpose service
+pub mod service;
+
+/// The Attachment entity DynamoDB service
+pub mod dynamo_service;
+
+/// The Attachment error types
+pub mod error;
+
+/// The Attachment entity input types
+pub mod inputs;
+
+/// Where the bytes of Attachments are kept
+pub mod storage;
+
+/// Attachment operations on the Task store for the configured `DataStore`
+pub mod store;
@@ -0,0 +1,123 @@
+use async_graphql::SimpleObject;
+use chrono::Utc;
+use sea_orm::{entity::prelude::*, Set};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use utoipa::ToSchema;
+
+use crate::utils::{
+    dynamo::DynamoItem,
+    timezone::{Timezone, WithTimestamps},
+};
+
+#[cfg(test)]
+use fake::Dummy;
+
+/// The Attachment Model, which holds the metadata of a file attached to a Task. The bytes are kept
+/// in the configured `Storage`.
+#[derive(
+    Clone, Debug, Eq, PartialEq, DeriveEntityModel, Deserialize, Serialize, SimpleObject, ToSchema,
+)]
+#[cfg_attr(test, derive(Dummy))]
+#[sea_orm(table_name = "attachments")]
+#[graphql(name = "Attachment")]
+#[schema(as = Attachment)]
+pub struct Model {
+    /// The Attachment id
+    #[sea_orm(primary_key, column_type = "Text")]
+    pub id: String,
+
+    /// The Task the file is attached to
+    #[sea_orm(column_type = "Text")]
+    pub task_id: String,
+
+    /// The date the file was uploaded
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTimeWithTimeZone,
+
+    /// The file name
+    #[sea_orm(column_type = "Text")]
+    pub name: String,
+
+    /// The file size, in bytes
+    pub size: i64,
+
+    /// The file's content type, such as "image/png"
+    #[sea_orm(column_type = "Text")]
+    pub content_type: String,
+
+    /// The hex-encoded SHA-256 checksum of the file
+    #[sea_orm(column_type = "Text")]
+    pub checksum: String,
+}
+
+/// The name for a Sea ORM model must be "Model", so this provides a convenient alias
+pub type Attachment = Model;
+
+/// Attachment entity relationships
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// The Task the file is attached to
+    #[sea_orm(
+        belongs_to = "crate::tasks::model::Entity",
+        from = "Column::TaskId",
+        to = "crate::tasks::model::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Task,
+}
+
+impl Related<crate::tasks::model::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Task.def()
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    /// Generate a ULID and timestamp for new Attachments, which are never edited
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            if self.id.is_not_set() {
+                self.id = Set(Ulid::new().to_string());
+            }
+
+            if self.created_at.is_not_set() {
+                self.created_at = Set(Utc::now().fixed_offset());
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+impl WithTimestamps for Model {
+    /// Render the timestamps in the given timezone
+    fn in_timezone(self, timezone: &Timezone) -> Self {
+        Self {
+            created_at: timezone.convert(self.created_at),
+            ..self
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            id: String::default(),
+            task_id: String::default(),
+            created_at: Utc::now().fixed_offset(),
+            name: String::default(),
+            size: 0,
+            content_type: String::default(),
+            checksum: String::default(),
+        }
+    }
+}
+
+impl DynamoItem for Model {
+    const TIMESTAMPS: &'static [&'static str] = &["created_at"];
+}
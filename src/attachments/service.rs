@@ -0,0 +1,213 @@
+use std::sync::Arc;
+
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use ulid::Ulid;
+
+use crate::{config, tasks};
+
+use super::{
+    error::Error,
+    inputs,
+    model::{self, Attachment},
+    storage::{self, Storage},
+};
+
+/// List the files attached to a `Task`, oldest first
+pub async fn list(db: Arc<DatabaseConnection>, task_id: &str) -> anyhow::Result<Vec<Attachment>> {
+    let task = tasks::model::Entity::find_by_id(task_id.to_string())
+        .one(&*db)
+        .await?
+        .ok_or(tasks::error::Error::NotFound)?;
+
+    let attachments = task
+        .find_related(model::Entity)
+        .order_by_asc(model::Column::Id)
+        .all(&*db)
+        .await?;
+
+    Ok(attachments)
+}
+
+/// Get the metadata of a file attached to a `Task`
+pub async fn get(
+    db: Arc<DatabaseConnection>,
+    task_id: &str,
+    id: &str,
+) -> anyhow::Result<Attachment> {
+    find(&db, task_id, id).await
+}
+
+/// Attach a file to a `Task`. The bytes are stored before the metadata is saved, and removed
+/// again if it can't be.
+pub async fn create(
+    db: Arc<DatabaseConnection>,
+    storage: &dyn Storage,
+    config: &config::Attachments,
+    task_id: &str,
+    upload: inputs::Upload,
+) -> anyhow::Result<Attachment> {
+    upload.validate(config)?;
+
+    tasks::model::Entity::find_by_id(task_id.to_string())
+        .one(&*db)
+        .await?
+        .ok_or(tasks::error::Error::NotFound)?;
+
+    let id = Ulid::new().to_string();
+    let key = storage::key(task_id, &id);
+
+    let attachment = model::ActiveModel {
+        id: Set(id),
+        task_id: Set(task_id.to_string()),
+        name: Set(upload.name.clone()),
+        size: Set(upload.size()),
+        content_type: Set(upload.content_type.clone()),
+        checksum: Set(upload.checksum()),
+        ..Default::default()
+    };
+
+    storage
+        .put(&key, &upload.content_type, upload.bytes)
+        .await?;
+
+    match attachment.insert(&*db).await {
+        Ok(attachment) => Ok(attachment),
+        Err(err) => {
+            if let Err(cleanup) = storage.delete(&key).await {
+                log::warn!("Unable to remove the bytes of an unsaved Attachment {key}: {cleanup}");
+            }
+
+            Err(err.into())
+        }
+    }
+}
+
+/// Detach a file from a `Task`, returning its metadata. The metadata is deleted before the bytes,
+/// so a listed Attachment can always be downloaded.
+pub async fn delete(
+    db: Arc<DatabaseConnection>,
+    storage: &dyn Storage,
+    task_id: &str,
+    id: &str,
+) -> anyhow::Result<Attachment> {
+    let attachment = find(&db, task_id, id).await?;
+
+    let result = attachment.clone().delete(&*db).await?;
+
+    // Another request deleted the Attachment after it was read
+    if result.rows_affected == 0 {
+        return Err(Error::NotFound.into());
+    }
+
+    storage.delete(&storage::key(task_id, id)).await?;
+
+    Ok(attachment)
+}
+
+/// Find an Attachment by id, only if it's on the given Task
+async fn find(db: &DatabaseConnection, task_id: &str, id: &str) -> anyhow::Result<Attachment> {
+    let attachment = model::Entity::find_by_id(id.to_string())
+        .filter(model::Column::TaskId.eq(task_id))
+        .one(db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(attachment)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Bytes;
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, DbErr, MockDatabase, Transaction};
+
+    use crate::{attachments::storage::LocalStorage, tasks::model::Task};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list() -> anyhow::Result<()> {
+        let task: Task = Faker.fake();
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![task.clone()]])
+                .append_query_results([Vec::<Attachment>::new()])
+                .into_connection(),
+        );
+
+        list(db.clone(), &task.id).await?;
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        assert_eq!(
+            log[1],
+            Transaction::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "attachments"."id", "attachments"."task_id", "attachments"."created_at", "attachments"."name", "attachments"."size", "attachments"."content_type", "attachments"."checksum" FROM "attachments" INNER JOIN "tasks" ON "tasks"."id" = "attachments"."task_id" WHERE "tasks"."id" = $1 ORDER BY "attachments"."id" ASC"#,
+                [task.id.into()],
+            )
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_removes_bytes_on_failure() -> anyhow::Result<()> {
+        let task: Task = Faker.fake();
+        let root = tempfile::tempdir()?;
+        let storage = LocalStorage::new(root.path().to_path_buf());
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![task.clone()]])
+                .append_query_errors([DbErr::Custom("Connection lost".to_string())])
+                .into_connection(),
+        );
+
+        let upload = inputs::Upload::new("notes.txt", "text/plain", Bytes::from_static(b"todo"));
+
+        let result = create(
+            db,
+            &storage,
+            &config::Attachments::default(),
+            &task.id,
+            upload,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            std::fs::read_dir(root.path().join("tasks").join(&task.id))?.count(),
+            0
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_validates() {
+        let db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let storage = LocalStorage::new("attachments".into());
+
+        let upload = inputs::Upload::new("run.sh", "application/x-sh", Bytes::new());
+
+        let err = create(
+            db,
+            &storage,
+            &config::Attachments::default(),
+            "01HX",
+            upload,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(Error::is_unsupported_content_type(&err));
+    }
+}
@@ -0,0 +1,387 @@
+use std::{
+    fmt::Debug,
+    io::ErrorKind,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use aws_sdk_s3::{
+    config::{
+        BehaviorVersion, Credentials, Region, RequestChecksumCalculation,
+        ResponseChecksumValidation,
+    },
+    primitives::ByteStream,
+    Client,
+};
+use axum::{async_trait, body::Bytes};
+use ulid::Ulid;
+
+use crate::config;
+
+use super::{error::Error, model::Attachment};
+
+/// Keeps the bytes of Attachments by key, apart from their metadata in the data store
+#[async_trait]
+pub trait Storage: Debug + Send + Sync {
+    /// Write the bytes under the key, replacing anything already there
+    async fn put(&self, key: &str, content_type: &str, bytes: Bytes) -> anyhow::Result<()>;
+
+    /// Read the bytes under the key, failing with `NotFound` if there aren't any
+    async fn get(&self, key: &str) -> anyhow::Result<Bytes>;
+
+    /// Delete the bytes under the key, succeeding if there weren't any
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+}
+
+/// The key the bytes of an Attachment are stored under, grouped by Task
+pub fn key(task_id: &str, id: &str) -> String {
+    format!("tasks/{task_id}/{id}")
+}
+
+/// Delete the bytes of Attachments whose metadata is already gone, such as when their Task is
+/// deleted. Failures are logged rather than returned, since there's nothing left to retry from.
+pub async fn purge(storage: &dyn Storage, attachments: &[Attachment]) {
+    for attachment in attachments {
+        let key = key(&attachment.task_id, &attachment.id);
+
+        if let Err(err) = storage.delete(&key).await {
+            log::warn!("Unable to remove the bytes of a deleted Attachment {key}: {err}");
+        }
+    }
+}
+
+/// Create the configured `Storage`
+pub fn connect(config: &config::Storage) -> Arc<dyn Storage> {
+    match config {
+        config::Storage::Local { root } => Arc::new(LocalStorage::new(root.clone())),
+        config::Storage::S3 {
+            bucket,
+            region,
+            endpoint_url,
+        } => Arc::new(S3Storage::connect(bucket, region, endpoint_url.as_deref())),
+    }
+}
+
+/// Keeps each file at its key under a directory on the local filesystem
+#[derive(Clone, Debug)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    /// Keep files under the given directory, which is created when the first file is written
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The path of the file for a key, which must stay inside the root directory
+    fn path(&self, key: &str) -> anyhow::Result<PathBuf> {
+        let relative = Path::new(key);
+
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(anyhow!("Invalid storage key: {key}"));
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, _content_type: &str, bytes: Bytes) -> anyhow::Result<()> {
+        let path = self.path(key)?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Written to a temporary file first, so readers never see a partial file
+        let partial = path.with_file_name(format!(".{}.partial", Ulid::new()));
+
+        tokio::fs::write(&partial, &bytes).await?;
+
+        if let Err(err) = tokio::fs::rename(&partial, &path).await {
+            let _ = tokio::fs::remove_file(&partial).await;
+
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Bytes> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(bytes) => Ok(bytes.into()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Err(Error::NotFound.into()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Keeps each file as an object in an S3 bucket, or a bucket on an S3-compatible service
+#[derive(Clone, Debug)]
+pub struct S3Storage {
+    client: Client,
+    bucket: String,
+}
+
+impl S3Storage {
+    /// Connect to a bucket, signing requests with the keys in `AWS_ACCESS_KEY_ID`,
+    /// `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN` when they're set
+    pub fn connect(bucket: &str, region: &str, endpoint_url: Option<&str>) -> Self {
+        let credentials = match (
+            std::env::var("AWS_ACCESS_KEY_ID"),
+            std::env::var("AWS_SECRET_ACCESS_KEY"),
+        ) {
+            (Ok(access_key_id), Ok(secret_access_key)) => Some(Credentials::new(
+                access_key_id,
+                secret_access_key,
+                std::env::var("AWS_SESSION_TOKEN").ok(),
+                None,
+                "environment",
+            )),
+            _ => None,
+        };
+
+        Self::new(bucket, region, endpoint_url, credentials)
+    }
+
+    /// Connect to a bucket with the given credentials. Objects are addressed by path rather than
+    /// by subdomain, and checksums are only sent when an operation requires them, since
+    /// S3-compatible services often support neither.
+    pub fn new(
+        bucket: &str,
+        region: &str,
+        endpoint_url: Option<&str>,
+        credentials: Option<Credentials>,
+    ) -> Self {
+        let mut config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(region.to_string()))
+            .force_path_style(true)
+            .request_checksum_calculation(RequestChecksumCalculation::WhenRequired)
+            .response_checksum_validation(ResponseChecksumValidation::WhenRequired);
+
+        if let Some(endpoint_url) = endpoint_url {
+            config = config.endpoint_url(endpoint_url);
+        }
+
+        if let Some(credentials) = credentials {
+            config = config.credentials_provider(credentials);
+        }
+
+        Self {
+            client: Client::from_conf(config.build()),
+            bucket: bucket.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, content_type: &str, bytes: Bytes) -> anyhow::Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .body(ByteStream::from(bytes))
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Bytes> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|err| {
+                if err
+                    .as_service_error()
+                    .is_some_and(|err| err.is_no_such_key())
+                {
+                    Error::NotFound.into()
+                } else {
+                    anyhow::Error::from(err)
+                }
+            })?;
+
+        Ok(object.body.collect().await?.into_bytes())
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        // S3 succeeds whether or not the object exists
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use axum::{
+        extract::{Path as UrlPath, State},
+        http::{header, StatusCode},
+        response::IntoResponse,
+        routing::get,
+        Router,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    type Objects = Arc<Mutex<HashMap<String, Bytes>>>;
+
+    /// Serve the object operations of an S3-compatible service from memory, with path-style URLs
+    async fn s3_stand_in() -> anyhow::Result<(String, Objects)> {
+        async fn put_object(
+            UrlPath((bucket, key)): UrlPath<(String, String)>,
+            State(objects): State<Objects>,
+            body: Bytes,
+        ) -> impl IntoResponse {
+            objects
+                .lock()
+                .expect("Poisoned lock")
+                .insert(format!("{bucket}/{key}"), body);
+
+            [(header::ETAG, "\"etag\"")]
+        }
+
+        async fn get_object(
+            UrlPath((bucket, key)): UrlPath<(String, String)>,
+            State(objects): State<Objects>,
+        ) -> impl IntoResponse {
+            match objects
+                .lock()
+                .expect("Poisoned lock")
+                .get(&format!("{bucket}/{key}"))
+            {
+                Some(body) => (StatusCode::OK, body.clone()).into_response(),
+                None => (
+                    StatusCode::NOT_FOUND,
+                    [(header::CONTENT_TYPE, "application/xml")],
+                    "<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>",
+                )
+                    .into_response(),
+            }
+        }
+
+        async fn delete_object(
+            UrlPath((bucket, key)): UrlPath<(String, String)>,
+            State(objects): State<Objects>,
+        ) -> impl IntoResponse {
+            objects
+                .lock()
+                .expect("Poisoned lock")
+                .remove(&format!("{bucket}/{key}"));
+
+            StatusCode::NO_CONTENT
+        }
+
+        let objects = Objects::default();
+
+        let app = Router::new()
+            .route(
+                "/:bucket/*key",
+                get(get_object).put(put_object).delete(delete_object),
+            )
+            .with_state(objects.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        Ok((url, objects))
+    }
+
+    #[tokio::test]
+    async fn test_local_storage() -> anyhow::Result<()> {
+        let root = tempfile::tempdir()?;
+        let storage = LocalStorage::new(root.path().to_path_buf());
+
+        storage
+            .put(
+                "tasks/01HX/01HY",
+                "text/plain",
+                Bytes::from_static(b"hello"),
+            )
+            .await?;
+
+        assert_eq!(storage.get("tasks/01HX/01HY").await?, "hello");
+        assert_eq!(
+            std::fs::read_dir(root.path().join("tasks/01HX"))?.count(),
+            1
+        );
+
+        storage.delete("tasks/01HX/01HY").await?;
+        storage.delete("tasks/01HX/01HY").await?;
+
+        let err = storage.get("tasks/01HX/01HY").await.unwrap_err();
+
+        assert!(Error::is_not_found(&err));
+        assert!(storage.get("tasks/../../etc/passwd").await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_s3_storage() -> anyhow::Result<()> {
+        let (url, objects) = s3_stand_in().await?;
+
+        let storage = S3Storage::new(
+            "attachments",
+            "us-east-1",
+            Some(&url),
+            Some(Credentials::new("test", "test", None, None, "test")),
+        );
+
+        storage
+            .put(
+                "tasks/01HX/01HY",
+                "text/plain",
+                Bytes::from_static(b"hello"),
+            )
+            .await?;
+
+        assert_eq!(
+            objects
+                .lock()
+                .expect("Poisoned lock")
+                .get("attachments/tasks/01HX/01HY"),
+            Some(&Bytes::from_static(b"hello"))
+        );
+        assert_eq!(storage.get("tasks/01HX/01HY").await?, "hello");
+
+        storage.delete("tasks/01HX/01HY").await?;
+
+        let err = storage.get("tasks/01HX/01HY").await.unwrap_err();
+
+        assert!(Error::is_not_found(&err));
+
+        Ok(())
+    }
+}
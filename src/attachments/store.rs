@@ -0,0 +1,17 @@
+use crate::tasks::store::Store;
+
+use super::{dynamo_service, model::Attachment, service};
+
+/// Attachment metadata lives in the same data store as its Tasks, so the Task store serves it too.
+/// The bytes are kept in the configured `Storage` and served over REST.
+impl Store {
+    /// List the files attached to a `Task`, oldest first
+    pub async fn attachments(&self, task_id: &str) -> anyhow::Result<Vec<Attachment>> {
+        match self {
+            Store::Database(db) => service::list(db.clone(), task_id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::list(client.clone(), table_name, task_id).await,
+        }
+    }
+}
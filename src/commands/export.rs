@@ -0,0 +1,94 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use crate::tasks::{
+    inputs,
+    store::{Store, MAX_PAGE_SIZE},
+};
+
+/// Write every Task as JSON lines to the given file, or stdout
+pub async fn run(store: &Store, file: Option<PathBuf>) -> anyhow::Result<()> {
+    let count = match file {
+        Some(path) => {
+            let mut writer = BufWriter::new(File::create(path)?);
+            let count = export(store, &mut writer).await?;
+            writer.flush()?;
+
+            count
+        }
+        None => export(store, &mut io::stdout().lock()).await?,
+    };
+
+    eprintln!("Exported {count} Tasks");
+
+    Ok(())
+}
+
+/// Write every Task to the writer as one JSON object per line, returning the number written
+pub async fn export(store: &Store, writer: &mut impl Write) -> anyhow::Result<usize> {
+    let mut count = 0;
+    let mut after = None;
+
+    loop {
+        let query = inputs::List {
+            after: after.take(),
+            limit: Some(MAX_PAGE_SIZE),
+            ..Default::default()
+        };
+
+        let page = store.page(&query).await?;
+
+        for task in &page.tasks {
+            serde_json::to_writer(&mut *writer, task)?;
+            writeln!(writer)?;
+        }
+
+        count += page.tasks.len();
+
+        match page.next_cursor {
+            Some(cursor) => after = Some(cursor),
+            None => return Ok(count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    use crate::tasks::model::Task;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_export() -> anyhow::Result<()> {
+        let tasks: Vec<Task> = (0..2).map(|_| Faker.fake()).collect();
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([tasks.clone()])
+            .into_connection();
+
+        let mut output = Vec::new();
+
+        let count = export(&Store::Database(Arc::new(db)), &mut output).await?;
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            String::from_utf8(output)?,
+            format!(
+                "{}\n{}\n",
+                serde_json::to_string(&tasks[0])?,
+                serde_json::to_string(&tasks[1])?
+            )
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+
+use crate::tasks::{model::Task, store::Store};
+
+/// Read Tasks as JSON lines from the given file, or stdin, and save them
+pub async fn run(store: &Store, file: Option<PathBuf>) -> anyhow::Result<()> {
+    let count = match file {
+        Some(path) => import(store, BufReader::new(File::open(path)?)).await?,
+        None => import(store, io::stdin().lock()).await?,
+    };
+
+    eprintln!("Imported {count} Tasks");
+
+    Ok(())
+}
+
+/// Save each Task read from the reader, one JSON object per line, returning the number saved
+pub async fn import(store: &Store, reader: impl BufRead) -> anyhow::Result<usize> {
+    let mut count = 0;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let task: Task = serde_json::from_str(&line)
+            .with_context(|| format!("Unable to parse the Task on line {}", index + 1))?;
+
+        store
+            .upsert(&task)
+            .await
+            .with_context(|| format!("Unable to import the Task on line {}", index + 1))?;
+
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_import() -> anyhow::Result<()> {
+        let task: Task = Faker.fake();
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            .into_connection();
+
+        let input = format!("{}\n\n", serde_json::to_string(&task)?);
+
+        let count = import(&Store::Database(Arc::new(db)), input.as_bytes()).await?;
+
+        assert_eq!(count, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_invalid_line() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+        let result = import(&Store::Database(Arc::new(db)), &b"{}\n"[..]).await;
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unable to parse the Task on line 1"
+        );
+    }
+}
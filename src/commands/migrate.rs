@@ -0,0 +1,48 @@
+use sea_orm_migration::MigratorTrait;
+
+use crate::{args::MigrateCommand, migrations::Migrator, tasks::store::Store};
+
+/// Run a `migrate` subcommand against the Postgres data store
+pub async fn run(store: &Store, command: MigrateCommand) -> anyhow::Result<()> {
+    let Store::Database(db) = store else {
+        return Err(anyhow::anyhow!(
+            "Schema migrations are only supported for the 'postgres' data store, use `provision` \
+             for DynamoDB"
+        ));
+    };
+
+    let db = db.as_ref();
+
+    match command {
+        MigrateCommand::Status => {
+            for migration in Migrator::get_migration_with_status(db).await? {
+                println!("{:<8} {}", migration.status(), migration.name());
+            }
+        }
+        MigrateCommand::Up { steps } => {
+            let pending = Migrator::get_pending_migrations(db).await?.len();
+
+            Migrator::up(db, steps).await?;
+
+            let applied = steps.map_or(pending, |steps| pending.min(steps as usize));
+
+            println!("Applied {applied} migrations");
+        }
+        MigrateCommand::Down { steps } => {
+            let applied = Migrator::get_applied_migrations(db).await?.len();
+
+            let steps = steps.unwrap_or(1);
+
+            Migrator::down(db, Some(steps)).await?;
+
+            println!("Rolled back {} migrations", applied.min(steps as usize));
+        }
+        MigrateCommand::Fresh => {
+            Migrator::fresh(db).await?;
+
+            println!("Dropped every table and applied all migrations");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use crate::{args::DataStore, config, tasks::store::Store};
+
+/// The `serve` command, to run the HTTP and gRPC servers
+pub mod serve;
+
+/// The `migrate` command, to apply schema changes
+pub mod migrate;
+
+/// The `provision` command, to create the DynamoDB table
+pub mod provision;
+
+/// The `rewrite-timestamps` command, to migrate DynamoDB timestamps between formats
+pub mod rewrite_timestamps;
+
+/// The `seed` command, to create fake Tasks
+pub mod seed;
+
+/// The `export` command, to write every Task as JSON lines
+pub mod export;
+
+/// The `import` command, to read Tasks from JSON lines
+pub mod import;
+
+/// The `check-config` command, to validate the configuration
+pub mod check_config;
+
+/// Resolve the DynamoDB config shared by every command, applying the timestamp format argument
+/// to the defaults
+pub fn dynamo_config(timestamp_format: Option<String>) -> anyhow::Result<config::Dynamo> {
+    let mut dynamo = config::Dynamo::default();
+
+    if let Some(timestamp_format) = timestamp_format {
+        dynamo.timestamp_format = timestamp_format.try_into()?;
+    }
+
+    Ok(dynamo)
+}
+
+/// Connect to the Task store for the given `DataStore` with the default Database config
+pub async fn connect(data_store: DataStore, dynamo: &config::Dynamo) -> anyhow::Result<Store> {
+    Store::connect(data_store, &config::Database::default(), dynamo).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{args::Args, config::TimestampFormat};
+
+    use super::*;
+
+    #[test]
+    fn test_dynamo_config() -> anyhow::Result<()> {
+        // Every command resolves its DynamoDB config the same way, not just `serve`
+        for command in [&["seed"][..], &["import"], &["tasks", "list"]] {
+            let args = Args::parse_from_vec(
+                [command, &["-t", "epoch-millis"]]
+                    .concat()
+                    .into_iter()
+                    .map(OsString::from)
+                    .collect(),
+            )?;
+
+            assert_eq!(
+                dynamo_config(args.timestamp_format)?.timestamp_format,
+                TimestampFormat::EpochMillis
+            );
+        }
+
+        assert_eq!(
+            dynamo_config(None)?.timestamp_format,
+            TimestampFormat::Rfc3339
+        );
+        assert!(dynamo_config(Some("iso8601".to_string())).is_err());
+
+        Ok(())
+    }
+}
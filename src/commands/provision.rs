@@ -0,0 +1,49 @@
+use crate::{
+    args::ProvisionArgs,
+    config::{self, BillingMode},
+    tasks::{dynamo_table, store::Store},
+};
+
+/// Create the DynamoDB Tasks table, or add whatever an existing table is missing
+pub async fn run(store: &Store, args: ProvisionArgs) -> anyhow::Result<()> {
+    let Store::Dynamo { client, .. } = store else {
+        return Err(anyhow::anyhow!(
+            "Provisioning is only supported for the 'dynamodb' data store, use `migrate` for \
+             Postgres"
+        ));
+    };
+
+    let billing_mode = match (args.read_capacity, args.write_capacity) {
+        (None, None) => BillingMode::PayPerRequest,
+        (Some(read_capacity_units), Some(write_capacity_units)) => BillingMode::Provisioned {
+            read_capacity_units,
+            write_capacity_units,
+        },
+        _ => {
+            return Err(anyhow::anyhow!(
+                "--read-capacity and --write-capacity must be used together"
+            ))
+        }
+    };
+
+    let dynamo = config::Dynamo {
+        billing_mode,
+        ttl_attribute: args.ttl_attribute,
+        ..Default::default()
+    };
+
+    let changes = dynamo_table::provision(client, &dynamo).await?;
+
+    if changes.is_empty() {
+        println!(
+            "The DynamoDB table '{}' is up to date",
+            dynamo.tasks_table_name
+        );
+    }
+
+    for change in changes {
+        println!("{change}");
+    }
+
+    Ok(())
+}
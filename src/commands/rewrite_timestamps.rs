@@ -0,0 +1,40 @@
+use crate::{
+    args::DataStore,
+    config,
+    tasks::{dynamo_service, store::Store},
+};
+
+/// Rewrite the timestamps of every DynamoDB item in the given format
+pub async fn run(data_store: DataStore, dynamo: &config::Dynamo) -> anyhow::Result<()> {
+    let store = Store::connect(data_store, &config::Database::default(), dynamo).await?;
+
+    let Store::Dynamo {
+        client,
+        table_name,
+        timestamp_format,
+    } = store
+    else {
+        return Err(anyhow::anyhow!(
+            "Rewriting timestamps is only supported for the 'dynamodb' data store"
+        ));
+    };
+
+    let outcome = dynamo_service::rewrite_timestamps(client, &table_name, timestamp_format).await?;
+
+    println!(
+        "Rewrote {} of {} items as {}",
+        outcome.rewritten,
+        outcome.scanned,
+        String::from(timestamp_format)
+    );
+
+    if outcome.skipped > 0 {
+        println!(
+            "Skipped {} items that were updated while being rewritten, run the command again to \
+             check them",
+            outcome.skipped
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use chrono::{Duration, Utc};
+use fake::{
+    faker::lorem::en::{Paragraph, Sentence},
+    Fake, Faker,
+};
+
+use crate::tasks::{inputs, store::Store};
+
+/// The number of Tasks created when no count is given
+const DEFAULT_COUNT: usize = 10;
+
+/// Create Tasks with fake titles, descriptions, priorities and due dates within two weeks either
+/// side of now
+pub async fn run(store: &Store, count: Option<usize>) -> anyhow::Result<()> {
+    let count = count.unwrap_or(DEFAULT_COUNT);
+
+    for _ in 0..count {
+        let input = inputs::Create {
+            title: Sentence(2..6).fake(),
+            description: Faker.fake::<bool>().then(|| Paragraph(1..3).fake()),
+            due_at: Faker
+                .fake::<bool>()
+                .then(|| Utc::now().fixed_offset() + Duration::hours((-14 * 24..14 * 24).fake())),
+            priority: Faker.fake::<bool>().then(|| (1..=5).fake()),
+            parent_id: None,
+            project_id: None,
+        };
+
+        store.create(&input).await?;
+    }
+
+    println!("Created {count} Tasks");
+
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use sea_orm_migration::MigratorTrait;
+use tokio::sync::watch;
+
+use crate::{
+    args::{DataStore, ServeArgs},
+    attachments::storage,
+    config::{self, ConfigForDB, ConfigForDynamo},
+    grpc,
+    jobs::{queue, runner::Runner},
+    migrations::Migrator,
+    recurrences::job::ExpandRecurrences,
+    server::{self, DatabaseAppState, DynamoAppState},
+    tasks::{dynamo_table, store::Store, workflow::Workflow},
+};
+
+/// Resolve the HTTP config, applying the command line arguments to the defaults
+pub fn http_config(args: &ServeArgs) -> config::Http {
+    let mut http = config::Http::default();
+
+    if let Some(address) = &args.address {
+        http.address.clone_from(address);
+    }
+
+    if let Some(port) = args.port {
+        http.port = port;
+    }
+
+    http
+}
+
+/// Resolve the gRPC config, applying the command line arguments to the defaults
+pub fn grpc_config(args: &ServeArgs) -> config::Grpc {
+    let mut grpc = config::Grpc::default();
+
+    if let Some(port) = args.grpc_port {
+        grpc.port = port;
+    }
+
+    grpc
+}
+
+/// Resolve the Database config, applying the command line arguments to the defaults
+pub fn database_config(args: &ServeArgs) -> config::Database {
+    let mut db = config::Database::default();
+
+    if args.auto_migrate {
+        db.auto_migrate = true;
+    }
+
+    db
+}
+
+/// Resolve the Attachment config, applying the command line arguments to the defaults
+pub fn attachments_config(args: &ServeArgs) -> anyhow::Result<config::Attachments> {
+    let mut attachments = config::Attachments::default();
+
+    if let Some(max_size) = args.max_attachment_size {
+        attachments.max_size = max_size;
+    }
+
+    if let Some(types) = &args.attachment_types {
+        attachments.allowed_content_types = types
+            .split(',')
+            .map(str::trim)
+            .filter(|content_type| !content_type.is_empty())
+            .map(String::from)
+            .collect();
+    }
+
+    match (&args.attachments_dir, &args.attachments_bucket) {
+        (Some(_), Some(_)) => {
+            return Err(anyhow::anyhow!(
+                "Attachments can be kept in a directory or a bucket, but not both"
+            ))
+        }
+        (Some(root), None) => {
+            attachments.storage = config::Storage::Local { root: root.clone() };
+        }
+        (None, Some(bucket)) => {
+            attachments.storage = config::Storage::S3 {
+                bucket: bucket.clone(),
+                region: args
+                    .s3_region
+                    .clone()
+                    .unwrap_or_else(|| "us-east-1".to_string()),
+                endpoint_url: args.s3_endpoint.clone(),
+            };
+        }
+        (None, None) => {}
+    }
+
+    Ok(attachments)
+}
+
+/// Resolve the recurring Task config, applying the command line arguments to the defaults
+pub fn recurrences_config(args: &ServeArgs) -> anyhow::Result<config::Recurrences> {
+    let mut recurrences = config::Recurrences::default();
+
+    if let Some(days) = args.recurrence_horizon {
+        recurrences.horizon = chrono::Duration::days(days.into());
+    }
+
+    if let Some(seconds) = args.recurrence_interval {
+        if seconds == 0 {
+            return Err(anyhow::anyhow!(
+                "--recurrence-interval must be at least one second"
+            ));
+        }
+
+        recurrences.interval = std::time::Duration::from_secs(seconds);
+    }
+
+    Ok(recurrences)
+}
+
+/// Resolve the background Job config, applying the command line arguments to the defaults
+pub fn jobs_config(args: &ServeArgs) -> anyhow::Result<config::Jobs> {
+    let mut jobs = config::Jobs::default();
+
+    if let Some(concurrency) = args.job_concurrency {
+        if concurrency == 0 {
+            return Err(anyhow::anyhow!("--job-concurrency must be at least 1"));
+        }
+
+        jobs.concurrency = concurrency;
+    }
+
+    Ok(jobs)
+}
+
+/// Resolve the Task status workflow, reading it from the given file or using the default, and
+/// whether it requires Tasks to be unblocked before they're done
+pub fn workflow_config(args: &ServeArgs) -> anyhow::Result<Workflow> {
+    let workflow = match &args.workflow {
+        Some(path) => Workflow::from_file(path)?,
+        None => Workflow::default(),
+    };
+
+    Ok(workflow.require_unblocked(args.require_unblocked))
+}
+
+/// Wait for Ctrl+C, or for SIGTERM on Unix, which is how container runtimes ask a process to stop
+async fn shutdown_signal() -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result?,
+            _ = terminate.recv() => (),
+        }
+    }
+
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await?;
+
+    Ok(())
+}
+
+/// Run the HTTP and gRPC servers and the background Job runner, shutting them all down
+/// gracefully on Ctrl+C or SIGTERM
+pub async fn run(
+    args: ServeArgs,
+    data_store: DataStore,
+    dynamo: config::Dynamo,
+) -> anyhow::Result<()> {
+    let http = http_config(&args);
+    let grpc = grpc_config(&args);
+
+    let db = database_config(&args);
+    let workflow = workflow_config(&args)?;
+    let attachments = attachments_config(&args)?;
+    let storage = storage::connect(&attachments.storage);
+    let recurrences = recurrences_config(&args)?;
+    let jobs = jobs_config(&args)?;
+
+    let store = Store::connect(data_store, &db, &dynamo).await?;
+
+    match &store {
+        Store::Database(conn) => {
+            if db.auto_migrate {
+                Migrator::up(conn.as_ref(), None).await?;
+            }
+        }
+        Store::Dynamo { client, .. } => dynamo_table::check(client, &dynamo).await?,
+    }
+
+    let app = match &store {
+        Store::Database(conn) => {
+            let config = ConfigForDB {
+                http: http.clone(),
+                grpc: grpc.clone(),
+                db,
+                workflow: workflow.clone(),
+                attachments,
+                recurrences: recurrences.clone(),
+                jobs: jobs.clone(),
+            };
+
+            let state = DatabaseAppState {
+                db: conn.clone(),
+                storage: storage.clone(),
+                config,
+            };
+
+            server::database_router(state)
+        }
+        Store::Dynamo {
+            client, table_name, ..
+        } => {
+            let config = ConfigForDynamo {
+                http: http.clone(),
+                grpc: grpc.clone(),
+                dynamo,
+                workflow: workflow.clone(),
+                attachments,
+                recurrences: recurrences.clone(),
+                jobs: jobs.clone(),
+            };
+
+            let state = DynamoAppState {
+                client: client.clone(),
+                tasks_table_name: table_name.clone(),
+                storage: storage.clone(),
+                config,
+            };
+
+            server::dynamo_router(state)
+        }
+    };
+
+    // run it
+    let listener = tokio::net::TcpListener::bind(format!("{}:{}", http.address, http.port)).await?;
+
+    let grpc_listener =
+        tokio::net::TcpListener::bind(format!("{}:{}", http.address, grpc.port)).await?;
+
+    println!("listening on {}", listener.local_addr()?);
+    println!("gRPC listening on {}", grpc_listener.local_addr()?);
+
+    let runner = Runner::new(store.clone(), queue::connect(&store), jobs).every(
+        recurrences.interval,
+        ExpandRecurrences {
+            horizon: recurrences.horizon,
+        },
+        &(),
+    )?;
+
+    let (shutdown, shutting_down) = watch::channel(false);
+
+    let stopped = || {
+        let mut shutting_down = shutting_down.clone();
+
+        async move {
+            // An error means the sender is gone, which only happens once everything has stopped
+            let _ = shutting_down.wait_for(|stopping| *stopping).await;
+        }
+    };
+
+    tokio::try_join!(
+        async {
+            shutdown_signal().await?;
+
+            println!("shutting down");
+
+            shutdown.send_replace(true);
+
+            Ok(())
+        },
+        async {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(stopped())
+                .await
+                .map_err(anyhow::Error::from)
+        },
+        grpc::serve(grpc_listener, store.clone(), workflow, storage, stopped()),
+        runner.run(stopped()),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::anyhow;
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, Put, ReturnValue, TransactWriteItem},
+    Client,
+};
+use chrono::SubsecRound;
+use ulid::Ulid;
+
+use crate::{
+    config::TimestampFormat,
+    tasks::{
+        self,
+        dynamo_service::{failed_conditions, task_exists},
+        dynamo_table::{COMMENT_ENTITY, COMMENT_INDEX, ENTITY_ATTRIBUTE, TASK_ENTITY},
+    },
+    utils::{
+        dynamo::{self, Item},
+        timestamp,
+    },
+};
+
+use super::{error::Error, inputs, model::Comment};
+
+/// List up to `limit` of a `Task`'s Comments, oldest first and starting after the given cursor
+pub async fn list(
+    client: Arc<Client>,
+    table_name: &str,
+    task_id: &str,
+    after: Option<&str>,
+    limit: u64,
+) -> anyhow::Result<Vec<Comment>> {
+    tasks::dynamo_service::get(client.clone(), table_name, task_id)
+        .await?
+        .ok_or(tasks::error::Error::NotFound)?;
+
+    task_comments(&client, table_name, task_id, after, Some(limit)).await
+}
+
+/// Post a `Comment` on a `Task`. The Comment is written on the condition that the Task exists, in
+/// the same transaction.
+pub async fn create(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    task_id: &str,
+    input: &inputs::Create,
+) -> anyhow::Result<Comment> {
+    input.validate()?;
+
+    // Truncated so the returned Comment matches what reads return in every format
+    let now = chrono::Utc::now().fixed_offset().trunc_subsecs(3);
+
+    let comment = Comment {
+        id: Ulid::new().to_string(),
+        task_id: task_id.to_string(),
+        created_at: now,
+        updated_at: now,
+        author: input.author.clone(),
+        body: input.body.clone(),
+    };
+
+    let task_exists = task_exists(table_name, task_id)?;
+
+    let put = Put::builder()
+        .table_name(table_name)
+        .set_item(Some(comment_item(&comment, format)?))
+        .condition_expression("attribute_not_exists(#id)")
+        .expression_attribute_names("#id", "id")
+        .build()?;
+
+    let result = client
+        .transact_write_items()
+        .transact_items(
+            TransactWriteItem::builder()
+                .condition_check(task_exists)
+                .build(),
+        )
+        .transact_items(TransactWriteItem::builder().put(put).build())
+        .send()
+        .await;
+
+    if let Err(err) = result {
+        return match failed_conditions(&err).as_deref() {
+            Some([true, ..]) => Err(tasks::error::Error::NotFound.into()),
+            _ => Err(err.into()),
+        };
+    }
+
+    Ok(comment)
+}
+
+/// Edit the body of an existing `Comment` on a `Task` with a single conditional `UpdateItem`
+pub async fn update(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    task_id: &str,
+    id: &str,
+    input: &inputs::Update,
+) -> anyhow::Result<Comment> {
+    input.validate()?;
+
+    let updated_at = chrono::Utc::now().fixed_offset().trunc_subsecs(3);
+
+    let result = client
+        .update_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(comment_key(task_id, id)))
+        .update_expression("SET #body = :body, #updated_at = :updated_at")
+        .condition_expression("#entity = :entity")
+        .expression_attribute_names("#body", "body")
+        .expression_attribute_names("#updated_at", "updated_at")
+        .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+        .expression_attribute_values(":body", AttributeValue::S(input.body.clone()))
+        .expression_attribute_values(":updated_at", timestamp::to_attribute(updated_at, format))
+        .expression_attribute_values(":entity", AttributeValue::S(COMMENT_ENTITY.to_string()))
+        .return_values(ReturnValue::AllNew)
+        .send()
+        .await
+        .map_err(|err| {
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception())
+            {
+                Error::NotFound.into()
+            } else {
+                anyhow::Error::from(err)
+            }
+        })?;
+
+    from_comment_item(result.attributes.ok_or(anyhow!("No attributes returned"))?)
+}
+
+/// Delete an existing `Comment` on a `Task`, returning it
+pub async fn delete(
+    client: Arc<Client>,
+    table_name: &str,
+    task_id: &str,
+    id: &str,
+) -> anyhow::Result<Comment> {
+    let result = client
+        .delete_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(comment_key(task_id, id)))
+        .return_values(ReturnValue::AllOld)
+        .send()
+        .await?;
+
+    // DynamoDB only returns the old attributes when an item was actually deleted
+    let item = result.attributes.ok_or(Error::NotFound)?;
+
+    from_comment_item(item)
+}
+
+/// Delete every Comment on a Task, once the Task itself has been deleted
+pub async fn delete_all(client: &Client, table_name: &str, task_id: &str) -> anyhow::Result<()> {
+    for comment in task_comments(client, table_name, task_id, None, None).await? {
+        client
+            .delete_item()
+            .table_name(table_name)
+            .key("id", AttributeValue::S(comment_key(task_id, &comment.id)))
+            .send()
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// A Task's Comments from the Comment index, oldest first, starting after the given cursor and
+/// stopping at the limit if there is one
+async fn task_comments(
+    client: &Client,
+    table_name: &str,
+    task_id: &str,
+    after: Option<&str>,
+    limit: Option<u64>,
+) -> anyhow::Result<Vec<Comment>> {
+    let mut key_condition = "#task_id = :task_id".to_string();
+    let mut names = HashMap::from([("#task_id".to_string(), "task_id".to_string())]);
+    let mut values = HashMap::from([(
+        ":task_id".to_string(),
+        AttributeValue::S(task_id.to_string()),
+    )]);
+
+    if let Some(after) = after {
+        key_condition.push_str(" AND #comment_id > :after");
+        names.insert("#comment_id".to_string(), "comment_id".to_string());
+        values.insert(":after".to_string(), AttributeValue::S(after.to_string()));
+    }
+
+    let mut comments = vec![];
+    let mut start_key = None;
+
+    loop {
+        let results = client
+            .query()
+            .table_name(table_name)
+            .index_name(COMMENT_INDEX)
+            .key_condition_expression(&key_condition)
+            .set_expression_attribute_names(Some(names.clone()))
+            .set_expression_attribute_values(Some(values.clone()))
+            .set_limit(limit.map(i32::try_from).transpose()?)
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        for item in results.items.unwrap_or_default() {
+            comments.push(from_comment_item(item)?);
+
+            if limit.is_some_and(|limit| comments.len() as u64 >= limit) {
+                return Ok(comments);
+            }
+        }
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(comments),
+        }
+    }
+}
+
+/// The id of a Comment's item, which sits alongside the other items keyed by its Task
+fn comment_key(task_id: &str, id: &str) -> String {
+    format!("{TASK_ENTITY}#{task_id}#{COMMENT_ENTITY}#{id}")
+}
+
+/// The item for a Comment, keyed by its Task and with the keys of the Comment index
+fn comment_item(comment: &Comment, format: TimestampFormat) -> anyhow::Result<Item> {
+    let mut item = dynamo::to_item(comment, format)?;
+
+    item.insert(
+        "id".to_string(),
+        AttributeValue::S(comment_key(&comment.task_id, &comment.id)),
+    );
+    item.insert(
+        "comment_id".to_string(),
+        AttributeValue::S(comment.id.clone()),
+    );
+    item.insert(
+        ENTITY_ATTRIBUTE.to_string(),
+        AttributeValue::S(COMMENT_ENTITY.to_string()),
+    );
+
+    Ok(item)
+}
+
+/// Read a Comment from its item, where the Comment id is kept apart from the item key
+fn from_comment_item(mut item: Item) -> anyhow::Result<Comment> {
+    let id = item
+        .remove("comment_id")
+        .ok_or_else(|| anyhow!("Found a Comment without a comment_id: {item:?}"))?;
+
+    item.insert("id".to_string(), id);
+
+    Ok(dynamo::from_item(item)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_comment_item() -> anyhow::Result<()> {
+        let comment = Comment {
+            created_at: chrono::Utc::now().fixed_offset().trunc_subsecs(3),
+            updated_at: chrono::Utc::now().fixed_offset().trunc_subsecs(3),
+            ..Faker.fake()
+        };
+
+        let item = comment_item(&comment, TimestampFormat::EpochMillis)?;
+
+        assert_eq!(
+            item.get("id"),
+            Some(&AttributeValue::S(format!(
+                "task#{}#comment#{}",
+                comment.task_id, comment.id
+            )))
+        );
+        assert_eq!(
+            item.get("comment_id"),
+            Some(&AttributeValue::S(comment.id.clone()))
+        );
+        assert_eq!(from_comment_item(item)?, comment);
+
+        Ok(())
+    }
+}
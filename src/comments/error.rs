@@ -0,0 +1,23 @@
+/// Comment errors that callers respond to differently, carried inside `anyhow::Error`
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The Comment does not exist on the Task
+    #[error("Comment not found")]
+    NotFound,
+
+    /// The Comment is missing its author or body, or its body is too long
+    #[error("Invalid Comment: {0}")]
+    Invalid(String),
+}
+
+impl Error {
+    /// Check whether the error is, or wraps, a `NotFound` error
+    pub fn is_not_found(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::NotFound))
+    }
+
+    /// Check whether the error is, or wraps, an `Invalid` error
+    pub fn is_invalid(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::Invalid(_)))
+    }
+}
@@ -0,0 +1,119 @@
+use async_graphql::InputObject;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::tasks::inputs::page_size;
+
+use super::error::Error;
+
+/// The longest Comment body allowed, in characters
+const MAX_BODY_LENGTH: usize = 10_000;
+
+/// The `CreateCommentInput` input type
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, InputObject, ToSchema)]
+#[graphql(name = "CreateCommentInput")]
+#[schema(as = CreateCommentInput)]
+pub struct Create {
+    /// Who wrote the Comment
+    pub author: String,
+
+    /// The Comment body, in Markdown
+    pub body: String,
+}
+
+impl Create {
+    /// Check that the author and body aren't blank and the body isn't too long
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.author.trim().is_empty() {
+            return Err(Error::Invalid("the author cannot be blank".to_string()));
+        }
+
+        validate_body(&self.body)
+    }
+}
+
+/// The `UpdateCommentInput` input type. The author of a Comment cannot be changed.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, InputObject, ToSchema)]
+#[graphql(name = "UpdateCommentInput")]
+#[schema(as = UpdateCommentInput)]
+pub struct Update {
+    /// The Comment's new body, in Markdown
+    pub body: String,
+}
+
+impl Update {
+    /// Check that the body isn't blank or too long
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_body(&self.body)
+    }
+}
+
+/// The `ListCommentsInput` query parameters for a page of a Task's Comments, oldest first
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct List {
+    /// The cursor returned with the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+
+    /// The maximum number of Comments to return, defaults to 20 and is capped at 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl List {
+    /// The requested page size, with the default and maximum applied
+    pub fn page_size(&self) -> usize {
+        page_size(self.limit)
+    }
+}
+
+fn validate_body(body: &str) -> Result<(), Error> {
+    if body.trim().is_empty() {
+        return Err(Error::Invalid("the body cannot be blank".to_string()));
+    }
+
+    if body.chars().count() > MAX_BODY_LENGTH {
+        return Err(Error::Invalid(format!(
+            "the body cannot be longer than {MAX_BODY_LENGTH} characters"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let input = Create {
+            author: "ana".to_string(),
+            body: "Looks **good**".to_string(),
+        };
+
+        assert!(input.validate().is_ok());
+
+        assert!(matches!(
+            Create {
+                author: " ".to_string(),
+                ..input.clone()
+            }
+            .validate(),
+            Err(Error::Invalid(_))
+        ));
+
+        assert!(Update {
+            body: "\n".to_string()
+        }
+        .validate()
+        .is_err());
+
+        assert!(Update {
+            body: "x".repeat(MAX_BODY_LENGTH + 1)
+        }
+        .validate()
+        .is_err());
+    }
+}
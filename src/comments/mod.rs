@@ -0,0 +1,20 @@
+/// The Comment Model
+pub mod model;
+
+/// The Comment entity general-purpose service
+pub mod service;
+
+/// The Comment entity DynamoDB service
+pub mod dynamo_service;
+
+/// The Comment error types
+pub mod error;
+
+/// The Comment entity input types
+pub mod inputs;
+
+/// Comment operations on the Task store for the configured `DataStore`
+pub mod store;
+
+/// The Comment GraphQL resolvers
+pub mod resolvers;
@@ -0,0 +1,127 @@
+use async_graphql::SimpleObject;
+use chrono::Utc;
+use sea_orm::{entity::prelude::*, Set};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use utoipa::ToSchema;
+
+use crate::utils::{
+    dynamo::DynamoItem,
+    timezone::{Timezone, WithTimestamps},
+};
+
+#[cfg(test)]
+use fake::Dummy;
+
+/// The Comment Model
+#[derive(
+    Clone, Debug, Eq, PartialEq, DeriveEntityModel, Deserialize, Serialize, SimpleObject, ToSchema,
+)]
+#[cfg_attr(test, derive(Dummy))]
+#[sea_orm(table_name = "comments")]
+#[graphql(name = "Comment")]
+#[schema(as = Comment)]
+pub struct Model {
+    /// The Comment id
+    #[sea_orm(primary_key, column_type = "Text")]
+    pub id: String,
+
+    /// The Task the Comment is on
+    #[sea_orm(column_type = "Text")]
+    pub task_id: String,
+
+    /// The date the Comment was posted
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTimeWithTimeZone,
+
+    /// The date the Comment was last edited
+    #[schema(value_type = String, format = DateTime)]
+    pub updated_at: DateTimeWithTimeZone,
+
+    /// Who wrote the Comment
+    #[sea_orm(column_type = "Text")]
+    pub author: String,
+
+    /// The Comment body, in Markdown
+    #[sea_orm(column_type = "Text")]
+    pub body: String,
+}
+
+/// The name for a Sea ORM model must be "Model", so this provides a convenient alias
+pub type Comment = Model;
+
+/// Comment entity relationships
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// The Task the Comment is on
+    #[sea_orm(
+        belongs_to = "crate::tasks::model::Entity",
+        from = "Column::TaskId",
+        to = "crate::tasks::model::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Task,
+}
+
+impl Related<crate::tasks::model::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Task.def()
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    /// Generate a ULID and timestamps for new Comments, and bump `updated_at` on every edit
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let now = Utc::now().fixed_offset();
+
+        if insert {
+            if self.id.is_not_set() {
+                self.id = Set(Ulid::new().to_string());
+            }
+
+            if self.created_at.is_not_set() {
+                self.created_at = Set(now);
+            }
+
+            if self.updated_at.is_not_set() {
+                self.updated_at = Set(now);
+            }
+        } else {
+            self.updated_at = Set(now);
+        }
+
+        Ok(self)
+    }
+}
+
+impl WithTimestamps for Model {
+    /// Render the timestamps in the given timezone
+    fn in_timezone(self, timezone: &Timezone) -> Self {
+        Self {
+            created_at: timezone.convert(self.created_at),
+            updated_at: timezone.convert(self.updated_at),
+            ..self
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            id: String::default(),
+            task_id: String::default(),
+            created_at: Utc::now().fixed_offset(),
+            updated_at: Utc::now().fixed_offset(),
+            author: String::default(),
+            body: String::default(),
+        }
+    }
+}
+
+impl DynamoItem for Model {
+    const TIMESTAMPS: &'static [&'static str] = &["created_at", "updated_at"];
+}
@@ -0,0 +1,47 @@
+use async_graphql::{Context, Object, Result};
+
+use crate::tasks::store::Store;
+
+use super::{inputs, model::Comment};
+
+/// The Mutation segment for Comments
+#[derive(Default)]
+pub struct CommentsMutation;
+
+/// Mutations for the `Comment` model
+#[Object]
+impl CommentsMutation {
+    /// Post a Comment on a Task
+    async fn add_comment(
+        &self,
+        ctx: &Context<'_>,
+        task_id: String,
+        input: inputs::Create,
+    ) -> Result<Comment> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.create_comment(&task_id, &input).await?)
+    }
+
+    /// Edit the body of a Comment on a Task
+    async fn update_comment(
+        &self,
+        ctx: &Context<'_>,
+        task_id: String,
+        id: String,
+        input: inputs::Update,
+    ) -> Result<Comment> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.update_comment(&task_id, &id, &input).await?)
+    }
+
+    /// Remove a Comment from a Task
+    async fn delete_comment(&self, ctx: &Context<'_>, task_id: String, id: String) -> Result<bool> {
+        let store = ctx.data::<Store>()?;
+
+        store.delete_comment(&task_id, &id).await?;
+
+        Ok(true)
+    }
+}
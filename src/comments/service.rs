@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set,
+};
+
+use crate::tasks;
+
+use super::{
+    error::Error,
+    inputs,
+    model::{self, Comment},
+};
+
+/// List up to `limit` of a `Task`'s Comments, oldest first and starting after the given cursor
+pub async fn list(
+    db: Arc<DatabaseConnection>,
+    task_id: &str,
+    after: Option<&str>,
+    limit: u64,
+) -> anyhow::Result<Vec<Comment>> {
+    let task = tasks::model::Entity::find_by_id(task_id.to_string())
+        .one(&*db)
+        .await?
+        .ok_or(tasks::error::Error::NotFound)?;
+
+    let mut query = task
+        .find_related(model::Entity)
+        .order_by_asc(model::Column::Id);
+
+    if let Some(after) = after {
+        query = query.filter(model::Column::Id.gt(after));
+    }
+
+    let comments = query.limit(limit).all(&*db).await?;
+
+    Ok(comments)
+}
+
+/// Post a `Comment` on a `Task`
+pub async fn create(
+    db: Arc<DatabaseConnection>,
+    task_id: &str,
+    input: &inputs::Create,
+) -> anyhow::Result<Comment> {
+    input.validate()?;
+
+    tasks::model::Entity::find_by_id(task_id.to_string())
+        .one(&*db)
+        .await?
+        .ok_or(tasks::error::Error::NotFound)?;
+
+    let comment = model::ActiveModel {
+        task_id: Set(task_id.to_string()),
+        author: Set(input.author.clone()),
+        body: Set(input.body.clone()),
+        ..Default::default()
+    }
+    .insert(&*db)
+    .await?;
+
+    Ok(comment)
+}
+
+/// Edit the body of an existing `Comment` on a `Task`
+pub async fn update(
+    db: Arc<DatabaseConnection>,
+    task_id: &str,
+    id: &str,
+    input: &inputs::Update,
+) -> anyhow::Result<Comment> {
+    input.validate()?;
+
+    let comment = find(&db, task_id, id).await?;
+
+    let mut comment: model::ActiveModel = comment.into();
+    comment.body = Set(input.body.clone());
+
+    let updated = comment.update(&*db).await?;
+
+    Ok(updated)
+}
+
+/// Delete an existing `Comment` on a `Task`, returning it
+pub async fn delete(
+    db: Arc<DatabaseConnection>,
+    task_id: &str,
+    id: &str,
+) -> anyhow::Result<Comment> {
+    let comment = find(&db, task_id, id).await?;
+
+    let result = comment.clone().delete(&*db).await?;
+
+    // Another request deleted the Comment after it was read
+    if result.rows_affected == 0 {
+        return Err(Error::NotFound.into());
+    }
+
+    Ok(comment)
+}
+
+/// Find a Comment by id, only if it's on the given Task
+async fn find(db: &DatabaseConnection, task_id: &str, id: &str) -> anyhow::Result<Comment> {
+    let comment = model::Entity::find_by_id(id.to_string())
+        .filter(model::Column::TaskId.eq(task_id))
+        .one(db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(comment)
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase, Transaction};
+
+    use crate::tasks::model::Task;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list() -> anyhow::Result<()> {
+        let task: Task = Faker.fake();
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![task.clone()]])
+                .append_query_results([Vec::<Comment>::new()])
+                .into_connection(),
+        );
+
+        list(db.clone(), &task.id, Some("01HX"), 21).await?;
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        assert_eq!(
+            log[1],
+            Transaction::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "comments"."id", "comments"."task_id", "comments"."created_at", "comments"."updated_at", "comments"."author", "comments"."body" FROM "comments" INNER JOIN "tasks" ON "tasks"."id" = "comments"."task_id" WHERE "tasks"."id" = $1 AND "comments"."id" > $2 ORDER BY "comments"."id" ASC LIMIT $3"#,
+                [task.id.into(), "01HX".into(), 21u64.into()],
+            )
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_on_another_task() {
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<Comment>::new()])
+                .into_connection(),
+        );
+
+        let input = inputs::Update {
+            body: "Edited".to_string(),
+        };
+
+        let err = update(db, "01HX", "01HY", &input).await.unwrap_err();
+
+        assert!(Error::is_not_found(&err));
+    }
+
+    #[tokio::test]
+    async fn test_create_validates() {
+        let db = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+
+        let input = inputs::Create {
+            author: "ana".to_string(),
+            body: " ".to_string(),
+        };
+
+        let err = create(db, "01HX", &input).await.unwrap_err();
+
+        assert!(Error::is_invalid(&err));
+    }
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::tasks::store::Store;
+
+use super::{dynamo_service, inputs, model::Comment, service};
+
+/// A page of a Task's `Comment`s, oldest first
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[schema(as = CommentPage)]
+pub struct CommentPage {
+    /// The Comments on this page
+    pub comments: Vec<Comment>,
+
+    /// The cursor to request the next page with, if there is one
+    pub next_cursor: Option<String>,
+}
+
+impl CommentPage {
+    /// Build a page from up to `limit + 1` Comments in id order, where the extra Comment signals a
+    /// next page
+    pub fn new(mut comments: Vec<Comment>, limit: usize) -> Self {
+        let has_next_page = comments.len() > limit;
+        comments.truncate(limit);
+
+        let next_cursor = comments
+            .last()
+            .filter(|_| has_next_page)
+            .map(|comment| comment.id.clone());
+
+        Self {
+            comments,
+            next_cursor,
+        }
+    }
+}
+
+/// Comments live in the same data store as their Tasks, so the Task store serves them too
+impl Store {
+    /// Get a page of a `Task`'s Comments, starting after the given cursor
+    pub async fn page_comments(
+        &self,
+        task_id: &str,
+        input: &inputs::List,
+    ) -> anyhow::Result<CommentPage> {
+        let limit = input.page_size();
+        let after = input.after.as_deref();
+
+        // Fetch one extra Comment to find out if there is another page
+        let comments = match self {
+            Store::Database(db) => {
+                service::list(db.clone(), task_id, after, limit as u64 + 1).await?
+            }
+            Store::Dynamo {
+                client, table_name, ..
+            } => {
+                dynamo_service::list(client.clone(), table_name, task_id, after, limit as u64 + 1)
+                    .await?
+            }
+        };
+
+        Ok(CommentPage::new(comments, limit))
+    }
+
+    /// Post a `Comment` on a `Task`
+    pub async fn create_comment(
+        &self,
+        task_id: &str,
+        input: &inputs::Create,
+    ) -> anyhow::Result<Comment> {
+        match self {
+            Store::Database(db) => service::create(db.clone(), task_id, input).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => {
+                dynamo_service::create(
+                    client.clone(),
+                    table_name,
+                    *timestamp_format,
+                    task_id,
+                    input,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Edit an existing `Comment` on a `Task`
+    pub async fn update_comment(
+        &self,
+        task_id: &str,
+        id: &str,
+        input: &inputs::Update,
+    ) -> anyhow::Result<Comment> {
+        match self {
+            Store::Database(db) => service::update(db.clone(), task_id, id, input).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => {
+                dynamo_service::update(
+                    client.clone(),
+                    table_name,
+                    *timestamp_format,
+                    task_id,
+                    id,
+                    input,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Delete an existing `Comment` on a `Task`, returning it
+    pub async fn delete_comment(&self, task_id: &str, id: &str) -> anyhow::Result<Comment> {
+        match self {
+            Store::Database(db) => service::delete(db.clone(), task_id, id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::delete(client.clone(), table_name, task_id, id).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_comment_page() {
+        let comments: Vec<Comment> = ["01HA", "01HB", "01HC"]
+            .iter()
+            .map(|id| Comment {
+                id: id.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        let page = CommentPage::new(comments.clone(), 2);
+
+        assert_eq!(page.comments, comments[..2]);
+        assert_eq!(page.next_cursor, Some("01HB".to_string()));
+
+        let page = CommentPage::new(comments, 3);
+
+        assert_eq!(page.next_cursor, None);
+    }
+}
@@ -1,23 +1,60 @@
+use std::path::PathBuf;
+
+use crate::tasks::workflow::Workflow;
+
+/// Config for the Postgres data store
 #[allow(dead_code)]
 #[derive(Clone, Debug, Default)]
 pub struct ConfigForDB {
     /// HTTP config
     pub http: Http,
 
+    /// gRPC config
+    pub grpc: Grpc,
+
     /// Optional Database config
     pub db: Database,
+
+    /// The Task status workflow
+    pub workflow: Workflow,
+
+    /// Attachment config
+    pub attachments: Attachments,
+
+    /// Recurring Task config
+    pub recurrences: Recurrences,
+
+    /// Background Job config
+    pub jobs: Jobs,
 }
 
+/// Config for the DynamoDB data store
 #[allow(dead_code)]
 #[derive(Clone, Debug, Default)]
 pub struct ConfigForDynamo {
     /// HTTP config
     pub http: Http,
 
+    /// gRPC config
+    pub grpc: Grpc,
+
     /// Optional Dynamo config
     pub dynamo: Dynamo,
+
+    /// The Task status workflow
+    pub workflow: Workflow,
+
+    /// Attachment config
+    pub attachments: Attachments,
+
+    /// Recurring Task config
+    pub recurrences: Recurrences,
+
+    /// Background Job config
+    pub jobs: Jobs,
 }
 
+/// HTTP server config
 #[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct Http {
@@ -37,32 +74,274 @@ impl Default for Http {
     }
 }
 
+/// gRPC server config
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct Grpc {
+    /// The port to bind the gRPC server to, on the same address as the HTTP server
+    pub port: u16,
+}
+
+impl Default for Grpc {
+    fn default() -> Self {
+        Self { port: 50051 }
+    }
+}
+
+/// Postgres config
 #[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct Database {
     /// The database URL to use with Postgres
     pub url: String,
+
+    /// Apply pending migrations when the server starts
+    pub auto_migrate: bool,
 }
 
 impl Default for Database {
     fn default() -> Self {
         Self {
             url: "postgres://localhost:5432/rust_demo".to_string(),
+            auto_migrate: false,
         }
     }
 }
 
+/// DynamoDB config
 #[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct Dynamo {
     /// The table name to use for Tasks with DynamoDB
     pub tasks_table_name: String,
+
+    /// The billing mode used when the Tasks table is created
+    pub billing_mode: BillingMode,
+
+    /// The attribute holding each item's expiry time, to enable TTL on
+    pub ttl_attribute: Option<String>,
+
+    /// The format timestamps are written in
+    pub timestamp_format: TimestampFormat,
 }
 
 impl Default for Dynamo {
     fn default() -> Self {
         Self {
             tasks_table_name: "tasks".to_string(),
+            billing_mode: BillingMode::default(),
+            ttl_attribute: None,
+            timestamp_format: TimestampFormat::default(),
+        }
+    }
+}
+
+/// Recurring Task config
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Recurrences {
+    /// How far ahead Tasks are generated for each Recurrence's occurrences
+    pub horizon: chrono::Duration,
+
+    /// How often occurrences are checked for Tasks to generate
+    pub interval: std::time::Duration,
+}
+
+impl Default for Recurrences {
+    fn default() -> Self {
+        Self {
+            horizon: chrono::Duration::days(14),
+            interval: std::time::Duration::from_secs(60),
+        }
+    }
+}
+
+/// Background Job config
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Jobs {
+    /// How many Jobs run at once on each server
+    pub concurrency: usize,
+
+    /// How long an idle worker waits before checking the queue again
+    pub poll_interval: std::time::Duration,
+
+    /// How long a claimed Job may run before it's given up on and due again
+    pub lease: chrono::Duration,
+
+    /// The delay before the first retry of a failed Job, doubled for each retry after it
+    pub retry_delay: chrono::Duration,
+
+    /// The longest delay between retries
+    pub max_retry_delay: chrono::Duration,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            poll_interval: std::time::Duration::from_secs(1),
+            lease: chrono::Duration::minutes(5),
+            retry_delay: chrono::Duration::seconds(10),
+            max_retry_delay: chrono::Duration::hours(1),
         }
     }
 }
+
+/// Attachment config
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attachments {
+    /// The largest file accepted, in bytes
+    pub max_size: usize,
+
+    /// The content types accepted, where "image/*" accepts every image type and "*/*" accepts
+    /// anything
+    pub allowed_content_types: Vec<String>,
+
+    /// Where the bytes of Attachments are kept
+    pub storage: Storage,
+}
+
+impl Attachments {
+    /// Check whether a content type is on the allowlist, ignoring parameters like the charset
+    pub fn allows(&self, content_type: &str) -> bool {
+        let essence = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        let Some((kind, _)) = essence.split_once('/') else {
+            return false;
+        };
+
+        self.allowed_content_types.iter().any(|allowed| {
+            let allowed = allowed.trim().to_ascii_lowercase();
+
+            allowed == "*/*"
+                || allowed == essence
+                || allowed
+                    .strip_suffix("/*")
+                    .is_some_and(|allowed_kind| allowed_kind == kind)
+        })
+    }
+}
+
+impl Default for Attachments {
+    fn default() -> Self {
+        Self {
+            max_size: 10 * 1024 * 1024,
+            allowed_content_types: [
+                "image/*",
+                "application/pdf",
+                "text/plain",
+                "text/markdown",
+                "text/csv",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            storage: Storage::default(),
+        }
+    }
+}
+
+/// Where the bytes of Attachments are kept
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Storage {
+    /// A directory on the local filesystem
+    Local {
+        /// The directory files are written under
+        root: PathBuf,
+    },
+
+    /// An S3 bucket, or a bucket on an S3-compatible service
+    S3 {
+        /// The bucket name
+        bucket: String,
+
+        /// The region of the bucket
+        region: String,
+
+        /// The endpoint of an S3-compatible service, instead of AWS
+        endpoint_url: Option<String>,
+    },
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Storage::Local {
+            root: PathBuf::from("attachments"),
+        }
+    }
+}
+
+/// DynamoDB billing mode
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BillingMode {
+    /// On-demand capacity
+    #[default]
+    PayPerRequest,
+
+    /// Provisioned capacity, applied to the table and each of its indexes
+    Provisioned {
+        /// Read capacity units
+        read_capacity_units: i64,
+
+        /// Write capacity units
+        write_capacity_units: i64,
+    },
+}
+
+/// The format timestamps are written in with DynamoDB
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TimestampFormat {
+    /// An RFC 3339 string in UTC, such as "2026-10-18T10:00:00.123Z"
+    #[default]
+    Rfc3339,
+
+    /// A number of milliseconds since the Unix epoch
+    EpochMillis,
+}
+
+impl TryFrom<String> for TimestampFormat {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "rfc3339" => Ok(TimestampFormat::Rfc3339),
+            "epoch-millis" => Ok(TimestampFormat::EpochMillis),
+            _ => Err(anyhow::anyhow!("Invalid timestamp format: {}", value)),
+        }
+    }
+}
+
+impl From<TimestampFormat> for String {
+    fn from(format: TimestampFormat) -> Self {
+        match format {
+            TimestampFormat::Rfc3339 => "rfc3339".to_string(),
+            TimestampFormat::EpochMillis => "epoch-millis".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachments_allows() {
+        let attachments = Attachments::default();
+
+        assert!(attachments.allows("image/png"));
+        assert!(attachments.allows("Text/Plain; charset=utf-8"));
+        assert!(!attachments.allows("application/zip"));
+        assert!(!attachments.allows("image"));
+
+        let attachments = Attachments {
+            allowed_content_types: vec!["*/*".to_string()],
+            ..Default::default()
+        };
+
+        assert!(attachments.allows("application/zip"));
+    }
+}
@@ -0,0 +1,417 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, ConditionCheck, Put, TransactWriteItem},
+    Client,
+};
+use chrono::SubsecRound;
+
+use crate::{
+    config::TimestampFormat,
+    tasks::{
+        self,
+        dynamo_service::{batch_get, failed_conditions, task_exists},
+        dynamo_table::{
+            BLOCKER_INDEX, DEPENDENCY_ENTITY, DEPENDENT_INDEX, ENTITY_ATTRIBUTE, TASK_ENTITY,
+        },
+        model::Task,
+        workflow::Status,
+    },
+    utils::{dynamo::Item, timestamp},
+};
+
+use super::error::Error;
+
+/// The timestamps held by a Dependency's edge item
+pub(crate) const TIMESTAMPS: &[&str] = &["created_at"];
+
+/// List the Tasks blocking a `Task`, in id order
+pub async fn blockers(
+    client: Arc<Client>,
+    table_name: &str,
+    id: &str,
+) -> anyhow::Result<Vec<Task>> {
+    find_task(&client, table_name, id).await?;
+
+    let ids = linked_ids(
+        &client,
+        table_name,
+        BLOCKER_INDEX,
+        "task_id",
+        "blocker_id",
+        id,
+    )
+    .await?;
+
+    batch_get(&client, table_name, &ids).await
+}
+
+/// List the Tasks blocked by a `Task`, in id order
+pub async fn dependents(
+    client: Arc<Client>,
+    table_name: &str,
+    id: &str,
+) -> anyhow::Result<Vec<Task>> {
+    find_task(&client, table_name, id).await?;
+
+    let ids = linked_ids(
+        &client,
+        table_name,
+        DEPENDENT_INDEX,
+        "blocker_id",
+        "task_id",
+        id,
+    )
+    .await?;
+
+    batch_get(&client, table_name, &ids).await
+}
+
+/// Block a `Task` by another Task and return the Task's blockers. The edge is written on the
+/// condition that both Tasks exist and the blocker isn't blocked by the Task. A Task can't be
+/// blocked by itself or by a Task that already depends on it, and adding an existing blocker
+/// changes nothing.
+pub async fn add(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    id: &str,
+    blocker_id: &str,
+) -> anyhow::Result<Vec<Task>> {
+    find_task(&client, table_name, id).await?;
+
+    tasks::dynamo_service::get(client.clone(), table_name, blocker_id)
+        .await?
+        .ok_or_else(|| Error::BlockerNotFound(blocker_id.to_string()))?;
+
+    // Walk everything the blocker depends on, one Task at a time, looking for the Task
+    let mut visited = HashSet::from([blocker_id.to_string()]);
+    let mut pending = vec![blocker_id.to_string()];
+
+    while let Some(current) = pending.pop() {
+        if current == id {
+            return Err(Error::Cycle {
+                id: id.to_string(),
+                blocker_id: blocker_id.to_string(),
+            }
+            .into());
+        }
+
+        let next = linked_ids(
+            &client,
+            table_name,
+            BLOCKER_INDEX,
+            "task_id",
+            "blocker_id",
+            &current,
+        )
+        .await?;
+
+        pending.extend(next.into_iter().filter(|next| visited.insert(next.clone())));
+    }
+
+    // The walk is read before the transaction, so a concurrent addition of the reverse edge is
+    // caught here instead. Longer cycles closed by concurrent additions can still slip through.
+    let no_reverse_edge = ConditionCheck::builder()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(dependency_key(blocker_id, id)))
+        .condition_expression("attribute_not_exists(#id)")
+        .expression_attribute_names("#id", "id")
+        .build()?;
+
+    let edge = Put::builder()
+        .table_name(table_name)
+        .set_item(Some(dependency_item(id, blocker_id, format)))
+        .condition_expression("attribute_not_exists(#id)")
+        .expression_attribute_names("#id", "id")
+        .build()?;
+
+    let result = client
+        .transact_write_items()
+        .transact_items(
+            TransactWriteItem::builder()
+                .condition_check(task_exists(table_name, id)?)
+                .build(),
+        )
+        .transact_items(
+            TransactWriteItem::builder()
+                .condition_check(task_exists(table_name, blocker_id)?)
+                .build(),
+        )
+        .transact_items(TransactWriteItem::builder().put(edge).build())
+        .transact_items(
+            TransactWriteItem::builder()
+                .condition_check(no_reverse_edge)
+                .build(),
+        )
+        .send()
+        .await;
+
+    if let Err(err) = result {
+        match failed_conditions(&err).as_deref() {
+            Some([true, ..]) => return Err(tasks::error::Error::NotFound.into()),
+            Some([false, true, ..]) => {
+                return Err(Error::BlockerNotFound(blocker_id.to_string()).into())
+            }
+            Some([false, false, _, true]) => {
+                return Err(Error::Cycle {
+                    id: id.to_string(),
+                    blocker_id: blocker_id.to_string(),
+                }
+                .into())
+            }
+            // The edge already exists, so the Task is already blocked by the blocker
+            Some([false, false, true, ..]) => (),
+            _ => return Err(err.into()),
+        }
+    }
+
+    blockers(client, table_name, id).await
+}
+
+/// Stop a Task from blocking a `Task` and return the Task's remaining blockers. Removing a Task
+/// that isn't a blocker changes nothing.
+pub async fn remove(
+    client: Arc<Client>,
+    table_name: &str,
+    id: &str,
+    blocker_id: &str,
+) -> anyhow::Result<Vec<Task>> {
+    client
+        .delete_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(dependency_key(id, blocker_id)))
+        .send()
+        .await?;
+
+    blockers(client, table_name, id).await
+}
+
+/// Check that every Task blocking a Task is done or cancelled, so the Task can be completed
+pub async fn check_unblocked(client: &Client, table_name: &str, id: &str) -> anyhow::Result<()> {
+    let blockers = open_blockers(client, table_name, id).await?;
+
+    if !blockers.is_empty() {
+        return Err(Error::OpenBlockers {
+            id: id.to_string(),
+            blockers,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// The ids of a Task's blockers that are neither done nor cancelled, in id order
+async fn open_blockers(client: &Client, table_name: &str, id: &str) -> anyhow::Result<Vec<String>> {
+    let ids = linked_ids(
+        client,
+        table_name,
+        BLOCKER_INDEX,
+        "task_id",
+        "blocker_id",
+        id,
+    )
+    .await?;
+
+    Ok(batch_get(client, table_name, &ids)
+        .await?
+        .into_iter()
+        .filter(|blocker| !matches!(blocker.status, Status::Done | Status::Cancelled))
+        .map(|blocker| blocker.id)
+        .collect())
+}
+
+/// Delete the edges to and from a Task, once the Task itself has been deleted
+pub async fn delete_all(client: &Client, table_name: &str, id: &str) -> anyhow::Result<()> {
+    let blocker_ids = linked_ids(
+        client,
+        table_name,
+        BLOCKER_INDEX,
+        "task_id",
+        "blocker_id",
+        id,
+    )
+    .await?;
+    let dependent_ids = linked_ids(
+        client,
+        table_name,
+        DEPENDENT_INDEX,
+        "blocker_id",
+        "task_id",
+        id,
+    )
+    .await?;
+
+    let keys = blocker_ids
+        .iter()
+        .map(|blocker_id| dependency_key(id, blocker_id))
+        .chain(
+            dependent_ids
+                .iter()
+                .map(|dependent_id| dependency_key(dependent_id, id)),
+        );
+
+    for key in keys {
+        client
+            .delete_item()
+            .table_name(table_name)
+            .key("id", AttributeValue::S(key))
+            .send()
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Check that a Task exists, failing with the Task `NotFound` error
+async fn find_task(client: &Arc<Client>, table_name: &str, id: &str) -> anyhow::Result<Task> {
+    let task = tasks::dynamo_service::get(client.clone(), table_name, id)
+        .await?
+        .ok_or(tasks::error::Error::NotFound)?;
+
+    Ok(task)
+}
+
+/// The Task ids at the other end of a Task's edges, read from one of the Dependency indexes in id
+/// order
+async fn linked_ids(
+    client: &Client,
+    table_name: &str,
+    index_name: &str,
+    from: &str,
+    to: &str,
+    id: &str,
+) -> anyhow::Result<Vec<String>> {
+    let mut ids = vec![];
+    let mut start_key = None;
+
+    loop {
+        let results = client
+            .query()
+            .table_name(table_name)
+            .index_name(index_name)
+            .key_condition_expression("#from = :id")
+            .expression_attribute_names("#from", from)
+            .expression_attribute_values(":id", AttributeValue::S(id.to_string()))
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        ids.extend(
+            results
+                .items
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|item| item.get(to)?.as_s().ok().cloned()),
+        );
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(ids),
+        }
+    }
+}
+
+/// The id of the edge item from a Task to a Task blocking it
+fn dependency_key(id: &str, blocker_id: &str) -> String {
+    format!("{TASK_ENTITY}#{id}#{DEPENDENCY_ENTITY}#{blocker_id}")
+}
+
+/// The edge item from a Task to a Task blocking it, with the keys of both Dependency indexes
+fn dependency_item(id: &str, blocker_id: &str, format: TimestampFormat) -> Item {
+    let created_at = chrono::Utc::now().fixed_offset().trunc_subsecs(3);
+
+    HashMap::from([
+        (
+            "id".to_string(),
+            AttributeValue::S(dependency_key(id, blocker_id)),
+        ),
+        (
+            ENTITY_ATTRIBUTE.to_string(),
+            AttributeValue::S(DEPENDENCY_ENTITY.to_string()),
+        ),
+        ("task_id".to_string(), AttributeValue::S(id.to_string())),
+        (
+            "blocker_id".to_string(),
+            AttributeValue::S(blocker_id.to_string()),
+        ),
+        (
+            "created_at".to_string(),
+            timestamp::to_attribute(created_at, format),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    use crate::utils::fake_dynamo::FakeDynamo;
+
+    use super::*;
+
+    fn task_item(id: &str) -> Value {
+        json!({
+            "id": { "S": id },
+            "entity": { "S": "task" },
+            "created_at": { "S": "2026-10-19T12:00:00.000Z" },
+            "updated_at": { "S": "2026-10-19T12:00:00.000Z" },
+            "title": { "S": id },
+        })
+    }
+
+    #[test]
+    fn test_dependency_item() {
+        let item = dependency_item("01HA", "01HB", TimestampFormat::EpochMillis);
+
+        assert_eq!(
+            item.get("id"),
+            Some(&AttributeValue::S("task#01HA#dependency#01HB".to_string()))
+        );
+        assert_eq!(
+            item.get("task_id"),
+            Some(&AttributeValue::S("01HA".to_string()))
+        );
+        assert_eq!(
+            item.get("blocker_id"),
+            Some(&AttributeValue::S("01HB".to_string()))
+        );
+        assert!(matches!(item.get("created_at"), Some(AttributeValue::N(_))));
+    }
+
+    #[tokio::test]
+    async fn test_add_rejects_a_concurrent_reverse_edge() -> anyhow::Result<()> {
+        // The other edge was just written, so the blocker index doesn't show it yet
+        let table = FakeDynamo::serve(vec![
+            task_item("01HA"),
+            task_item("01HB"),
+            json!({
+                "id": { "S": "task#01HB#dependency#01HA" },
+                "entity": { "S": "dependency" },
+                "task_id": { "S": "01HB" },
+                "blocker_id": { "S": "01HA" },
+            }),
+        ])
+        .await?;
+
+        let err = add(
+            table.client.clone(),
+            "tasks",
+            TimestampFormat::Rfc3339,
+            "01HA",
+            "01HB",
+        )
+        .await
+        .unwrap_err();
+
+        assert!(Error::is_cycle(&err), "{err:#}");
+        assert_eq!(table.get("task#01HA#dependency#01HB"), None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+/// Dependency errors that callers respond to differently, carried inside `anyhow::Error`
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The Task given as a blocker doesn't exist
+    #[error("Blocker Task not found: {0}")]
+    BlockerNotFound(String),
+
+    /// The blocker already depends on the Task, directly or through other Tasks
+    #[error("Task {id} can't be blocked by {blocker_id}, which already depends on it")]
+    Cycle {
+        /// The Task being blocked
+        id: String,
+
+        /// The requested blocker
+        blocker_id: String,
+    },
+
+    /// The Task can't be completed while some of its blockers are unfinished
+    #[error("Task {id} is blocked by unfinished Tasks: {}", blockers.join(", "))]
+    OpenBlockers {
+        /// The Task being completed
+        id: String,
+
+        /// The ids of the blockers that aren't done or cancelled
+        blockers: Vec<String>,
+    },
+}
+
+impl Error {
+    /// Check whether the error is, or wraps, a `BlockerNotFound` error
+    pub fn is_blocker_not_found(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::BlockerNotFound(_)))
+    }
+
+    /// Check whether the error is, or wraps, a `Cycle` error
+    pub fn is_cycle(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::Cycle { .. }))
+    }
+
+    /// Check whether the error is, or wraps, an `OpenBlockers` error
+    pub fn is_open_blockers(err: &anyhow::Error) -> bool {
+        matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::OpenBlockers { .. })
+        )
+    }
+}
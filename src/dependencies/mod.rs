@@ -0,0 +1,17 @@
+/// The Dependency Model
+pub mod model;
+
+/// The Dependency general-purpose service
+pub mod service;
+
+/// The Dependency DynamoDB service
+pub mod dynamo_service;
+
+/// The Dependency error types
+pub mod error;
+
+/// Dependency operations on the Task store for the configured `DataStore`
+pub mod store;
+
+/// The Dependency GraphQL resolvers
+pub mod resolvers;
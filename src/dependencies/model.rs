@@ -0,0 +1,44 @@
+use sea_orm::entity::prelude::*;
+
+/// The edge recording that a Task is blocked by another Task, which must be finished first
+#[derive(Clone, Debug, Eq, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "task_dependencies")]
+pub struct Model {
+    /// The blocked Task
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub task_id: String,
+
+    /// The Task blocking it
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub blocker_id: String,
+
+    /// The date the dependency was added
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// The name for a Sea ORM model must be "Model", so this provides a convenient alias
+pub type Dependency = Model;
+
+/// Dependency entity relationships
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// The blocked Task
+    #[sea_orm(
+        belongs_to = "crate::tasks::model::Entity",
+        from = "Column::TaskId",
+        to = "crate::tasks::model::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Task,
+
+    /// The Task blocking it
+    #[sea_orm(
+        belongs_to = "crate::tasks::model::Entity",
+        from = "Column::BlockerId",
+        to = "crate::tasks::model::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Blocker,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,35 @@
+use async_graphql::{Context, Object, Result};
+
+use crate::tasks::{model::Task, store::Store};
+
+/// The Mutation segment for Dependencies
+#[derive(Default)]
+pub struct DependenciesMutation;
+
+/// Mutations for the edges between Tasks and the Tasks blocking them
+#[Object]
+impl DependenciesMutation {
+    /// Block a Task by another Task, returning the Task's blockers
+    async fn add_task_blocker(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        blocker_id: String,
+    ) -> Result<Vec<Task>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.add_blocker(&id, &blocker_id).await?)
+    }
+
+    /// Stop a Task from blocking another Task, returning the remaining blockers
+    async fn remove_task_blocker(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        blocker_id: String,
+    ) -> Result<Vec<Task>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.remove_blocker(&id, &blocker_id).await?)
+    }
+}
@@ -0,0 +1,298 @@
+use std::{collections::HashSet, sync::Arc};
+
+use chrono::Utc;
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set, Statement, TransactionTrait,
+};
+
+use crate::tasks::{self, model::Task, workflow::Status};
+
+use super::{error::Error, model};
+
+/// The key of the transaction-level advisory lock taken while adding a Dependency. Two additions
+/// that each pass the cycle check could otherwise close a cycle between them, however long it is.
+const ADD_LOCK: i64 = 0x7461_736b_6465_7073;
+
+/// List the Tasks blocking a `Task`, in id order
+pub async fn blockers(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Vec<Task>> {
+    find_task(&*db, id).await?;
+
+    blockers_of(&*db, id).await
+}
+
+/// List the Tasks blocked by a `Task`, in id order
+pub async fn dependents(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Vec<Task>> {
+    find_task(&*db, id).await?;
+
+    let subquery = model::Entity::find()
+        .select_only()
+        .column(model::Column::TaskId)
+        .filter(model::Column::BlockerId.eq(id))
+        .into_query();
+
+    let dependents = tasks::model::Entity::find()
+        .filter(tasks::model::Column::Id.in_subquery(subquery))
+        .order_by_asc(tasks::model::Column::Id)
+        .all(&*db)
+        .await?;
+
+    Ok(dependents)
+}
+
+/// Block a `Task` by another Task and return the Task's blockers. A Task can't be blocked by
+/// itself or by a Task that already depends on it, and adding an existing blocker changes nothing.
+/// Additions are serialized, so concurrent ones can't close a cycle between them.
+pub async fn add(
+    db: Arc<DatabaseConnection>,
+    id: &str,
+    blocker_id: &str,
+) -> anyhow::Result<Vec<Task>> {
+    let txn = db.begin().await?;
+
+    // Held until the transaction ends, so the cycle check sees every edge committed before it
+    txn.execute(Statement::from_sql_and_values(
+        txn.get_database_backend(),
+        "SELECT pg_advisory_xact_lock($1)",
+        [ADD_LOCK.into()],
+    ))
+    .await?;
+
+    find_task(&txn, id).await?;
+
+    tasks::model::Entity::find_by_id(blocker_id.to_string())
+        .one(&txn)
+        .await?
+        .ok_or_else(|| Error::BlockerNotFound(blocker_id.to_string()))?;
+
+    // Walk everything the blocker depends on, one level at a time, looking for the Task
+    let mut visited = HashSet::from([blocker_id.to_string()]);
+    let mut level = vec![blocker_id.to_string()];
+
+    while !level.is_empty() {
+        if level.iter().any(|current| current == id) {
+            return Err(Error::Cycle {
+                id: id.to_string(),
+                blocker_id: blocker_id.to_string(),
+            }
+            .into());
+        }
+
+        let edges = model::Entity::find()
+            .filter(model::Column::TaskId.is_in(level))
+            .all(&txn)
+            .await?;
+
+        level = edges
+            .into_iter()
+            .map(|edge| edge.blocker_id)
+            .filter(|next| visited.insert(next.clone()))
+            .collect();
+    }
+
+    model::Entity::insert(model::ActiveModel {
+        task_id: Set(id.to_string()),
+        blocker_id: Set(blocker_id.to_string()),
+        created_at: Set(Utc::now().fixed_offset()),
+    })
+    .on_conflict(
+        OnConflict::columns([model::Column::TaskId, model::Column::BlockerId])
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(&txn)
+    .await?;
+
+    let blockers = blockers_of(&txn, id).await?;
+
+    txn.commit().await?;
+
+    Ok(blockers)
+}
+
+/// Stop a Task from blocking a `Task` and return the Task's remaining blockers. Removing a Task
+/// that isn't a blocker changes nothing.
+pub async fn remove(
+    db: Arc<DatabaseConnection>,
+    id: &str,
+    blocker_id: &str,
+) -> anyhow::Result<Vec<Task>> {
+    find_task(&*db, id).await?;
+
+    model::Entity::delete_by_id((id.to_string(), blocker_id.to_string()))
+        .exec(&*db)
+        .await?;
+
+    blockers_of(&*db, id).await
+}
+
+/// Check that every Task blocking a Task is done or cancelled, so the Task can be completed
+pub async fn check_unblocked<C: ConnectionTrait>(db: &C, id: &str) -> anyhow::Result<()> {
+    let blockers = open_blockers(db, id).await?;
+
+    if !blockers.is_empty() {
+        return Err(Error::OpenBlockers {
+            id: id.to_string(),
+            blockers,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// The ids of a Task's blockers that are neither done nor cancelled, in id order
+async fn open_blockers<C: ConnectionTrait>(db: &C, id: &str) -> anyhow::Result<Vec<String>> {
+    let blockers = blockers_of(db, id).await?;
+
+    Ok(blockers
+        .into_iter()
+        .filter(|blocker| !matches!(blocker.status, Status::Done | Status::Cancelled))
+        .map(|blocker| blocker.id)
+        .collect())
+}
+
+/// Check that a Task exists, failing with the Task `NotFound` error
+async fn find_task<C: ConnectionTrait>(db: &C, id: &str) -> anyhow::Result<Task> {
+    let task = tasks::model::Entity::find_by_id(id.to_string())
+        .one(db)
+        .await?
+        .ok_or(tasks::error::Error::NotFound)?;
+
+    Ok(task)
+}
+
+async fn blockers_of<C: ConnectionTrait>(db: &C, id: &str) -> anyhow::Result<Vec<Task>> {
+    let subquery = model::Entity::find()
+        .select_only()
+        .column(model::Column::BlockerId)
+        .filter(model::Column::TaskId.eq(id))
+        .into_query();
+
+    let blockers = tasks::model::Entity::find()
+        .filter(tasks::model::Column::Id.in_subquery(subquery))
+        .order_by_asc(tasks::model::Column::Id)
+        .all(db)
+        .await?;
+
+    Ok(blockers)
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction};
+
+    use super::*;
+
+    /// The result of taking the advisory lock
+    fn locked() -> MockExecResult {
+        MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }
+    }
+
+    fn edge(task_id: &str, blocker_id: &str) -> model::Model {
+        model::Model {
+            task_id: task_id.to_string(),
+            blocker_id: blocker_id.to_string(),
+            created_at: Utc::now().fixed_offset(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blockers() -> anyhow::Result<()> {
+        let task: Task = Faker.fake();
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![task.clone()]])
+                .append_query_results([Vec::<Task>::new()])
+                .into_connection(),
+        );
+
+        blockers(db.clone(), &task.id).await?;
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        assert_eq!(
+            log[1],
+            Transaction::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "tasks"."id", "tasks"."created_at", "tasks"."updated_at", "tasks"."title", "tasks"."description", "tasks"."status", "tasks"."due_at", "tasks"."priority", "tasks"."parent_id", "tasks"."project_id" FROM "tasks" WHERE "tasks"."id" IN (SELECT "task_dependencies"."blocker_id" FROM "task_dependencies" WHERE "task_dependencies"."task_id" = $1) ORDER BY "tasks"."id" ASC"#,
+                [task.id.into()],
+            )
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_add_rejects_cycles() -> anyhow::Result<()> {
+        let task: Task = Faker.fake();
+        let blocker: Task = Faker.fake();
+
+        // The blocker already depends on the Task through another Task
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([locked()])
+                .append_query_results([vec![task.clone()]])
+                .append_query_results([vec![blocker.clone()]])
+                .append_query_results([vec![edge(&blocker.id, "01HMIDDLE")]])
+                .append_query_results([vec![edge("01HMIDDLE", &task.id)]])
+                .into_connection(),
+        );
+
+        let err = add(db.clone(), &task.id, &blocker.id).await.unwrap_err();
+
+        assert!(Error::is_cycle(&err));
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        // The lock is taken before anything is read
+        let sql = format!("{:?}", log[0]);
+
+        let lock = sql
+            .find("pg_advisory_xact_lock")
+            .ok_or(anyhow::anyhow!("No lock was taken: {sql}"))?;
+
+        assert!(sql.find("FROM").is_some_and(|read| lock < read), "{sql}");
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([locked()])
+                .append_query_results([vec![task.clone()]])
+                .append_query_results([vec![task.clone()]])
+                .into_connection(),
+        );
+
+        let err = add(db, &task.id, &task.id).await.unwrap_err();
+
+        assert!(Error::is_cycle(&err));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_blockers() -> anyhow::Result<()> {
+        let [done, cancelled, open] =
+            [Status::Done, Status::Cancelled, Status::InProgress].map(|status| Task {
+                status,
+                ..Faker.fake()
+            });
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![done, cancelled, open.clone()]])
+            .into_connection();
+
+        assert_eq!(open_blockers(&db, "01HX").await?, vec![open.id]);
+
+        Ok(())
+    }
+}
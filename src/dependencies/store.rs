@@ -0,0 +1,57 @@
+use crate::tasks::{model::Task, store::Store};
+
+use super::{dynamo_service, service};
+
+/// Dependencies live in the same data store as their Tasks, so the Task store serves them too
+impl Store {
+    /// List the Tasks blocking a `Task`, in id order
+    pub async fn blockers(&self, id: &str) -> anyhow::Result<Vec<Task>> {
+        match self {
+            Store::Database(db) => service::blockers(db.clone(), id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::blockers(client.clone(), table_name, id).await,
+        }
+    }
+
+    /// List the Tasks blocked by a `Task`, in id order
+    pub async fn dependents(&self, id: &str) -> anyhow::Result<Vec<Task>> {
+        match self {
+            Store::Database(db) => service::dependents(db.clone(), id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::dependents(client.clone(), table_name, id).await,
+        }
+    }
+
+    /// Block a `Task` by another Task and return the Task's blockers
+    pub async fn add_blocker(&self, id: &str, blocker_id: &str) -> anyhow::Result<Vec<Task>> {
+        match self {
+            Store::Database(db) => service::add(db.clone(), id, blocker_id).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => {
+                dynamo_service::add(
+                    client.clone(),
+                    table_name,
+                    *timestamp_format,
+                    id,
+                    blocker_id,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Stop a Task from blocking a `Task` and return the Task's remaining blockers
+    pub async fn remove_blocker(&self, id: &str, blocker_id: &str) -> anyhow::Result<Vec<Task>> {
+        match self {
+            Store::Database(db) => service::remove(db.clone(), id, blocker_id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::remove(client.clone(), table_name, id, blocker_id).await,
+        }
+    }
+}
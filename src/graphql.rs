@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use async_graphql::{http::GraphiQLSource, EmptySubscription, MergedObject, Schema};
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    routing::get,
+    Json, Router,
+};
+
+use crate::{
+    attachments::storage::Storage,
+    comments::resolvers::CommentsMutation,
+    dependencies::resolvers::DependenciesMutation,
+    projects::resolvers::{ProjectsMutation, ProjectsQuery},
+    tasks::{
+        resolvers::{TasksMutation, TasksQuery},
+        store::Store,
+        workflow::Workflow,
+    },
+};
+
+/// The top-level Query type
+#[derive(MergedObject, Default)]
+pub struct Query(TasksQuery, ProjectsQuery);
+
+/// The top-level Mutation type
+#[derive(MergedObject, Default)]
+pub struct Mutation(
+    TasksMutation,
+    ProjectsMutation,
+    CommentsMutation,
+    DependenciesMutation,
+);
+
+/// The application's top-level merged GraphQL schema
+pub type GraphQLSchema = Schema<Query, Mutation, EmptySubscription>;
+
+/// Build the GraphQL schema for the given Task store, status workflow and Attachment storage
+pub fn schema(store: Store, workflow: Workflow, storage: Arc<dyn Storage>) -> GraphQLSchema {
+    Schema::build(Query::default(), Mutation::default(), EmptySubscription)
+        .data(store)
+        .data(workflow)
+        .data(storage)
+        .finish()
+}
+
+/// Serve the GraphQL endpoint and the GraphiQL page at `/graphql`
+pub fn router(store: Store, workflow: Workflow, storage: Arc<dyn Storage>) -> Router {
+    Router::new()
+        .route("/graphql", get(graphiql).post(graphql_handler))
+        .with_state(schema(store, workflow, storage))
+}
+
+async fn graphql_handler(
+    State(schema): State<GraphQLSchema>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema.execute(request).await)
+}
+
+async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
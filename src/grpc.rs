@@ -0,0 +1,27 @@
+use std::{future::Future, sync::Arc};
+
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+
+use crate::{
+    attachments::storage::Storage,
+    tasks::{grpc::TasksService, store::Store, workflow::Workflow},
+};
+
+/// Serve the gRPC services on the given listener until the shutdown future resolves, letting
+/// in-flight requests finish
+pub async fn serve(
+    listener: TcpListener,
+    store: Store,
+    workflow: Workflow,
+    storage: Arc<dyn Storage>,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()> {
+    Server::builder()
+        .add_service(TasksService::new(store, workflow, storage).into_server())
+        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,395 @@
+use std::sync::Arc;
+
+use aws_sdk_dynamodb::{error::SdkError, types::AttributeValue, types::ReturnValue, Client};
+use chrono::{Duration, SecondsFormat, SubsecRound, Utc};
+use sea_orm::prelude::DateTimeUtc;
+
+use crate::{
+    config::TimestampFormat,
+    tasks::dynamo_table::{ENTITY_ATTRIBUTE, JOB_ENTITY, JOB_INDEX},
+    utils::{
+        dynamo::{self, Item},
+        timestamp,
+    },
+};
+
+use super::{
+    error::Error,
+    model::{Job, Status},
+    queue::NewJob,
+};
+
+/// The attribute holding the kind of a pending or running Job, which puts it in the Job index.
+/// Failed Jobs don't have it, so they drop out of the index.
+const QUEUED_KIND_ATTRIBUTE: &str = "queued_kind";
+
+/// How many due Jobs of each kind to read from the index when claiming one
+const CANDIDATES: i32 = 10;
+
+/// The timestamps written in the configured format, which rewriting them may change. `run_at` is
+/// left out, since the Job index needs it as a fixed-width string.
+pub(crate) const REWRITABLE_TIMESTAMPS: &[&str] = &["created_at", "updated_at"];
+
+/// Add a `Job` to the queue, returning `None` if one with the same id is already queued
+pub async fn push(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    job: NewJob,
+) -> anyhow::Result<Option<Job>> {
+    let job = job.into_job();
+
+    let result = client
+        .put_item()
+        .table_name(table_name)
+        .set_item(Some(job_item(&job, format)?))
+        .condition_expression("attribute_not_exists(id)")
+        .send()
+        .await;
+
+    match result {
+        Ok(_) => Ok(Some(job)),
+        Err(err) if is_conditional_check_failed(&err) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Claim the earliest due `Job` of the given kinds. The index is only eventually consistent, so
+/// each candidate is claimed on the condition that it hasn't changed since it was read, and the
+/// next one is tried if another worker got there first.
+pub async fn claim(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    kinds: &[&str],
+    lease: Duration,
+) -> anyhow::Result<Option<Job>> {
+    let now = Utc::now().fixed_offset().trunc_subsecs(3);
+    let mut candidates = vec![];
+
+    for kind in kinds {
+        let results = client
+            .query()
+            .table_name(table_name)
+            .index_name(JOB_INDEX)
+            .key_condition_expression("#queued_kind = :kind AND #run_at <= :now")
+            .expression_attribute_names("#queued_kind", QUEUED_KIND_ATTRIBUTE)
+            .expression_attribute_names("#run_at", "run_at")
+            .expression_attribute_values(":kind", AttributeValue::S(kind.to_string()))
+            .expression_attribute_values(":now", AttributeValue::S(run_at_attribute(now.to_utc())))
+            .limit(CANDIDATES)
+            .send()
+            .await?;
+
+        candidates.extend(results.items.unwrap_or_default());
+    }
+
+    candidates.sort_by_key(|item| {
+        item.get("run_at")
+            .and_then(|run_at| run_at.as_s().ok())
+            .cloned()
+    });
+
+    for candidate in candidates {
+        let (Some(key), Some(seen)) = (candidate.get("id"), candidate.get("run_at")) else {
+            continue;
+        };
+
+        let result = client
+            .update_item()
+            .table_name(table_name)
+            .key("id", key.clone())
+            .update_expression(
+                "SET #status = :running, #run_at = :lease, #updated_at = :now ADD #attempts :one",
+            )
+            .condition_expression("#run_at = :seen AND attribute_exists(#queued_kind)")
+            .expression_attribute_names("#status", "status")
+            .expression_attribute_names("#run_at", "run_at")
+            .expression_attribute_names("#updated_at", "updated_at")
+            .expression_attribute_names("#attempts", "attempts")
+            .expression_attribute_names("#queued_kind", QUEUED_KIND_ATTRIBUTE)
+            .expression_attribute_values(
+                ":running",
+                AttributeValue::S(Status::Running.as_str().to_string()),
+            )
+            .expression_attribute_values(
+                ":lease",
+                AttributeValue::S(run_at_attribute((now + lease).to_utc())),
+            )
+            .expression_attribute_values(":now", timestamp::to_attribute(now, format))
+            .expression_attribute_values(":one", AttributeValue::N("1".to_string()))
+            .expression_attribute_values(":seen", seen.clone())
+            .return_values(ReturnValue::AllNew)
+            .send()
+            .await;
+
+        match result {
+            Ok(output) => {
+                if let Some(item) = output.attributes {
+                    return Ok(Some(from_job_item(item)?));
+                }
+            }
+            // Claimed, finished or moved by another worker since the index was read, so try the
+            // next candidate
+            Err(err) if is_conditional_check_failed(&err) => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Delete a claimed `Job` that succeeded
+pub async fn complete(client: Arc<Client>, table_name: &str, job: &Job) -> anyhow::Result<()> {
+    let result = client
+        .delete_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(job_key(&job.id)))
+        .condition_expression("#status = :running AND #attempts = :attempts")
+        .expression_attribute_names("#status", "status")
+        .expression_attribute_names("#attempts", "attempts")
+        .expression_attribute_values(
+            ":running",
+            AttributeValue::S(Status::Running.as_str().to_string()),
+        )
+        .expression_attribute_values(":attempts", AttributeValue::N(job.attempts.to_string()))
+        .send()
+        .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(err) if is_conditional_check_failed(&err) => Err(Error::Lost(job.id.clone()).into()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Put a claimed `Job` back in the queue to run again at `run_at`
+pub async fn retry(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    job: &Job,
+    run_at: DateTimeUtc,
+    error: Option<&str>,
+    reset_attempts: bool,
+) -> anyhow::Result<()> {
+    let mut set = vec![
+        "#status = :pending",
+        "#run_at = :run_at",
+        "#updated_at = :now",
+    ];
+
+    if reset_attempts {
+        set.push("#attempts = :zero");
+    }
+
+    if error.is_some() {
+        set.push("#last_error = :error");
+    }
+
+    let mut expression = format!("SET {}", set.join(", "));
+
+    if error.is_none() {
+        expression.push_str(" REMOVE #last_error");
+    }
+
+    let mut request = client
+        .update_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(job_key(&job.id)))
+        .update_expression(expression)
+        .condition_expression("#status = :running AND #attempts = :attempts")
+        .expression_attribute_names("#status", "status")
+        .expression_attribute_names("#run_at", "run_at")
+        .expression_attribute_names("#updated_at", "updated_at")
+        .expression_attribute_names("#attempts", "attempts")
+        .expression_attribute_names("#last_error", "last_error")
+        .expression_attribute_values(
+            ":pending",
+            AttributeValue::S(Status::Pending.as_str().to_string()),
+        )
+        .expression_attribute_values(
+            ":running",
+            AttributeValue::S(Status::Running.as_str().to_string()),
+        )
+        .expression_attribute_values(":run_at", AttributeValue::S(run_at_attribute(run_at)))
+        .expression_attribute_values(
+            ":now",
+            timestamp::to_attribute(Utc::now().fixed_offset(), format),
+        )
+        .expression_attribute_values(":attempts", AttributeValue::N(job.attempts.to_string()));
+
+    if reset_attempts {
+        request = request.expression_attribute_values(":zero", AttributeValue::N("0".to_string()));
+    }
+
+    if let Some(error) = error {
+        request =
+            request.expression_attribute_values(":error", AttributeValue::S(error.to_string()));
+    }
+
+    match request.send().await {
+        Ok(_) => Ok(()),
+        Err(err) if is_conditional_check_failed(&err) => Err(Error::Lost(job.id.clone()).into()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Mark a claimed `Job` as failed for good, taking it out of the Job index
+pub async fn fail(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    job: &Job,
+    error: &str,
+) -> anyhow::Result<()> {
+    let result = client
+        .update_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(job_key(&job.id)))
+        .update_expression(
+            "SET #status = :failed, #last_error = :error, #updated_at = :now REMOVE #queued_kind",
+        )
+        .condition_expression("#status = :running AND #attempts = :attempts")
+        .expression_attribute_names("#status", "status")
+        .expression_attribute_names("#last_error", "last_error")
+        .expression_attribute_names("#updated_at", "updated_at")
+        .expression_attribute_names("#queued_kind", QUEUED_KIND_ATTRIBUTE)
+        .expression_attribute_names("#attempts", "attempts")
+        .expression_attribute_values(
+            ":failed",
+            AttributeValue::S(Status::Failed.as_str().to_string()),
+        )
+        .expression_attribute_values(
+            ":running",
+            AttributeValue::S(Status::Running.as_str().to_string()),
+        )
+        .expression_attribute_values(":error", AttributeValue::S(error.to_string()))
+        .expression_attribute_values(
+            ":now",
+            timestamp::to_attribute(Utc::now().fixed_offset(), format),
+        )
+        .expression_attribute_values(":attempts", AttributeValue::N(job.attempts.to_string()))
+        .send()
+        .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(err) if is_conditional_check_failed(&err) => Err(Error::Lost(job.id.clone()).into()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Whether a request failed on its condition expression
+fn is_conditional_check_failed<E, R>(err: &SdkError<E, R>) -> bool
+where
+    E: aws_sdk_dynamodb::error::ProvideErrorMetadata,
+{
+    err.as_service_error()
+        .and_then(|err| err.code())
+        .is_some_and(|code| code == "ConditionalCheckFailedException")
+}
+
+/// The id of the item holding a Job, kept apart from the ids of other items in the table
+fn job_key(id: &str) -> String {
+    format!("{JOB_ENTITY}#{id}")
+}
+
+/// A `run_at` as a UTC RFC 3339 string with fixed precision, so Jobs sort by when they're due in
+/// the Job index whatever format other timestamps are written in
+fn run_at_attribute(run_at: DateTimeUtc) -> String {
+    run_at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Convert a `Job` into an item, with its payload as a JSON string
+fn job_item(job: &Job, format: TimestampFormat) -> anyhow::Result<Item> {
+    let mut item = dynamo::to_item(job, format)?;
+
+    item.insert("id".to_string(), AttributeValue::S(job_key(&job.id)));
+    item.insert(
+        ENTITY_ATTRIBUTE.to_string(),
+        AttributeValue::S(JOB_ENTITY.to_string()),
+    );
+    item.insert(
+        "payload".to_string(),
+        AttributeValue::S(job.payload.to_string()),
+    );
+    item.insert(
+        "run_at".to_string(),
+        AttributeValue::S(run_at_attribute(job.run_at.to_utc())),
+    );
+
+    if job.status != Status::Failed {
+        item.insert(
+            QUEUED_KIND_ATTRIBUTE.to_string(),
+            AttributeValue::S(job.kind.clone()),
+        );
+    }
+
+    Ok(item)
+}
+
+/// Convert an item into a `Job`
+fn from_job_item(mut item: Item) -> anyhow::Result<Job> {
+    let payload = match item.remove("payload") {
+        Some(AttributeValue::S(payload)) => serde_json::from_str(&payload)?,
+        _ => serde_json::Value::Null,
+    };
+
+    item.insert("payload".to_string(), AttributeValue::Null(true));
+
+    let job: Job = dynamo::from_item(item)?;
+
+    Ok(Job {
+        id: job
+            .id
+            .strip_prefix(&format!("{JOB_ENTITY}#"))
+            .unwrap_or(&job.id)
+            .to_string(),
+        payload,
+        ..job
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_job_item() -> anyhow::Result<()> {
+        let job = NewJob {
+            id: "01HJ".to_string(),
+            kind: "deliver_webhook".to_string(),
+            payload: serde_json::json!({"url": "https://example.com/hook", "attempt": 1}),
+            run_at: chrono::DateTime::parse_from_rfc3339("2026-10-19T11:00:00+02:00")?.to_utc(),
+        }
+        .into_job();
+
+        let item = job_item(&job, TimestampFormat::EpochMillis)?;
+
+        assert_eq!(
+            item.get("id"),
+            Some(&AttributeValue::S("job#01HJ".to_string()))
+        );
+        assert_eq!(
+            item.get("run_at"),
+            Some(&AttributeValue::S("2026-10-19T09:00:00.000Z".to_string()))
+        );
+        assert_eq!(
+            item.get(QUEUED_KIND_ATTRIBUTE),
+            Some(&AttributeValue::S("deliver_webhook".to_string()))
+        );
+        assert_eq!(from_job_item(item)?, job);
+
+        let failed = Job {
+            status: Status::Failed,
+            ..job
+        };
+
+        assert!(!job_item(&failed, TimestampFormat::Rfc3339)?.contains_key(QUEUED_KIND_ATTRIBUTE));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+/// Job errors that callers respond to differently, carried inside `anyhow::Error`
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The Job's lease expired and another worker claimed it, or it was removed
+    #[error("Job {0} is no longer held by this worker")]
+    Lost(String),
+
+    /// No handler is registered for the Job's kind
+    #[error("No handler is registered for jobs of kind '{0}'")]
+    UnknownKind(String),
+}
+
+impl Error {
+    /// Check whether the error is, or wraps, a `Lost` error
+    pub fn is_lost(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::Lost(_)))
+    }
+}
@@ -0,0 +1,59 @@
+use std::{fmt::Debug, sync::Arc};
+
+use axum::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::tasks::store::Store;
+
+use super::queue::Queue;
+
+/// What a handler can reach while it runs a Job
+#[derive(Clone, Debug)]
+pub struct Context {
+    /// The Task store for the configured data store
+    pub store: Store,
+
+    /// The queue the Job came from, for queueing follow-up Jobs
+    pub queue: Arc<dyn Queue>,
+}
+
+/// Runs the Jobs of one kind, each carrying a typed payload. A failed Job is retried with
+/// backoff until it runs out of attempts, so handlers should be safe to run more than once.
+#[async_trait]
+pub trait Handler: Send + Sync + 'static {
+    /// The payload each Job carries
+    type Job: Serialize + DeserializeOwned + Send + Sync;
+
+    /// The kind Jobs are queued under, which must stay the same across releases so queued Jobs
+    /// still find their handler
+    const KIND: &'static str;
+
+    /// How many times a Job is attempted before it's marked failed
+    const MAX_ATTEMPTS: u32 = 5;
+
+    /// Run a Job
+    async fn handle(&self, job: Self::Job, context: &Context) -> anyhow::Result<()>;
+}
+
+/// A `Handler` with its payload type erased, so handlers of every kind can be kept together
+#[async_trait]
+pub(crate) trait ErasedHandler: Send + Sync {
+    /// How many times a Job is attempted before it's marked failed
+    fn max_attempts(&self) -> u32;
+
+    /// Decode the payload and run the Job
+    async fn handle(&self, payload: serde_json::Value, context: &Context) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl<H: Handler> ErasedHandler for H {
+    fn max_attempts(&self) -> u32 {
+        H::MAX_ATTEMPTS
+    }
+
+    async fn handle(&self, payload: serde_json::Value, context: &Context) -> anyhow::Result<()> {
+        let job = serde_json::from_value(payload)?;
+
+        Handler::handle(self, job, context).await
+    }
+}
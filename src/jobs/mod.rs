@@ -0,0 +1,20 @@
+/// The Job Model
+pub mod model;
+
+/// The Job queue general-purpose service
+pub mod service;
+
+/// The Job queue DynamoDB service
+pub mod dynamo_service;
+
+/// The Job error types
+pub mod error;
+
+/// The persistent queue Jobs wait in, for the configured `DataStore`
+pub mod queue;
+
+/// Typed handlers that run each kind of Job
+pub mod handler;
+
+/// The runner that claims and runs Jobs in the background
+pub mod runner;
@@ -0,0 +1,97 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::dynamo::DynamoItem;
+
+/// The Job Model, a unit of background work waiting in the queue. Jobs are deleted once they
+/// succeed, so the queue only holds pending, running and failed jobs.
+#[derive(Clone, Debug, Eq, PartialEq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "jobs")]
+pub struct Model {
+    /// The Job id, which is its unique key if it was queued with one
+    #[sea_orm(primary_key, column_type = "Text")]
+    pub id: String,
+
+    /// The date the Job was queued
+    pub created_at: DateTimeWithTimeZone,
+
+    /// The date the Job was last updated
+    pub updated_at: DateTimeWithTimeZone,
+
+    /// The kind of Job, naming the handler that runs it
+    #[sea_orm(column_type = "Text")]
+    pub kind: String,
+
+    /// The handler's input
+    #[sea_orm(column_type = "JsonBinary")]
+    pub payload: Json,
+
+    /// Whether the Job is waiting, running or has failed for good
+    pub status: Status,
+
+    /// How many times the Job has been started
+    pub attempts: i32,
+
+    /// When a pending Job is due to run, or when a running Job's lease expires and it can be
+    /// claimed again
+    pub run_at: DateTimeWithTimeZone,
+
+    /// The error from the last failed attempt
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_error: Option<String>,
+}
+
+/// The name for a Sea ORM model must be "Model", so this provides a convenient alias
+pub type Job = Model;
+
+/// Job entity relationships
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl DynamoItem for Model {
+    const TIMESTAMPS: &'static [&'static str] = &["created_at", "updated_at", "run_at"];
+}
+
+/// The status of a Job
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Hash,
+    EnumIter,
+    DeriveActiveEnum,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// Waiting for `run_at`
+    #[default]
+    #[sea_orm(string_value = "pending")]
+    Pending,
+
+    /// Claimed by a worker until `run_at`
+    #[sea_orm(string_value = "running")]
+    Running,
+
+    /// Out of attempts, and kept to be looked into
+    #[sea_orm(string_value = "failed")]
+    Failed,
+}
+
+impl Status {
+    /// The status as it's stored
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Pending => "pending",
+            Status::Running => "running",
+            Status::Failed => "failed",
+        }
+    }
+}
@@ -0,0 +1,428 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use aws_sdk_dynamodb::Client;
+use axum::async_trait;
+use chrono::{Duration, SubsecRound, Utc};
+use sea_orm::{prelude::DateTimeUtc, DatabaseConnection};
+use ulid::Ulid;
+
+use crate::{config::TimestampFormat, tasks::store::Store};
+
+use super::{
+    dynamo_service,
+    error::Error,
+    handler::Handler,
+    model::{Job, Status},
+    service,
+};
+
+/// Holds the Jobs waiting to run, so they survive restarts and are shared between servers. Every
+/// change to a claimed Job is checked against its attempt count, so a worker whose lease expired
+/// can't overwrite the outcome of the worker that claimed the Job after it.
+#[async_trait]
+pub trait Queue: Debug + Send + Sync {
+    /// Add a Job, returning `None` if one with the same id is already queued
+    async fn push(&self, job: NewJob) -> anyhow::Result<Option<Job>>;
+
+    /// Claim the earliest due Job of the given kinds for the length of the lease, counting an
+    /// attempt. A running Job whose lease has expired is due again.
+    async fn claim(&self, kinds: &[&str], lease: Duration) -> anyhow::Result<Option<Job>>;
+
+    /// Remove a claimed Job that succeeded
+    async fn complete(&self, job: &Job) -> anyhow::Result<()>;
+
+    /// Put a claimed Job back in the queue to run again at `run_at`, recording the error if the
+    /// attempt failed. Repeating Jobs start over with no attempts.
+    async fn retry(
+        &self,
+        job: &Job,
+        run_at: DateTimeUtc,
+        error: Option<&str>,
+        reset_attempts: bool,
+    ) -> anyhow::Result<()>;
+
+    /// Mark a claimed Job as failed for good, keeping it to be looked into
+    async fn fail(&self, job: &Job, error: &str) -> anyhow::Result<()>;
+}
+
+/// A Job to add to the queue
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NewJob {
+    /// The Job id, a new ULID unless the Job is queued with a unique key
+    pub id: String,
+
+    /// The kind of Job, naming the handler that runs it
+    pub kind: String,
+
+    /// The handler's input
+    pub payload: serde_json::Value,
+
+    /// When the Job is due to run
+    pub run_at: DateTimeUtc,
+}
+
+impl NewJob {
+    /// A Job for the given handler, due to run straight away
+    pub fn new<H: Handler>(job: &H::Job) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: Ulid::new().to_string(),
+            kind: H::KIND.to_string(),
+            payload: serde_json::to_value(job)?,
+            run_at: Utc::now(),
+        })
+    }
+
+    /// Schedule the Job to run at a later time
+    pub fn at(self, run_at: DateTimeUtc) -> Self {
+        Self { run_at, ..self }
+    }
+
+    /// Queue the Job under a unique key, so it isn't added again while a Job with the same key is
+    /// pending, running or failed
+    pub fn unique(self, key: &str) -> Self {
+        Self {
+            id: key.to_string(),
+            ..self
+        }
+    }
+
+    /// The pending Job as it's first stored
+    pub fn into_job(self) -> Job {
+        // Truncated so the returned Job matches what reads return in every format
+        let now = Utc::now().fixed_offset().trunc_subsecs(3);
+
+        Job {
+            id: self.id,
+            created_at: now,
+            updated_at: now,
+            kind: self.kind,
+            payload: self.payload,
+            status: Status::Pending,
+            attempts: 0,
+            run_at: self.run_at.fixed_offset().trunc_subsecs(3),
+            last_error: None,
+        }
+    }
+}
+
+/// Create the `Queue` for the configured data store, so Jobs are kept alongside the Tasks
+pub fn connect(store: &Store) -> Arc<dyn Queue> {
+    match store {
+        Store::Database(db) => Arc::new(DatabaseQueue { db: db.clone() }),
+        Store::Dynamo {
+            client,
+            table_name,
+            timestamp_format,
+        } => Arc::new(DynamoQueue {
+            client: client.clone(),
+            table_name: table_name.clone(),
+            timestamp_format: *timestamp_format,
+        }),
+    }
+}
+
+/// Keeps Jobs in the `jobs` table in Postgres
+#[derive(Clone, Debug)]
+pub struct DatabaseQueue {
+    db: Arc<DatabaseConnection>,
+}
+
+#[async_trait]
+impl Queue for DatabaseQueue {
+    async fn push(&self, job: NewJob) -> anyhow::Result<Option<Job>> {
+        service::push(self.db.clone(), job).await
+    }
+
+    async fn claim(&self, kinds: &[&str], lease: Duration) -> anyhow::Result<Option<Job>> {
+        service::claim(self.db.clone(), kinds, lease).await
+    }
+
+    async fn complete(&self, job: &Job) -> anyhow::Result<()> {
+        service::complete(self.db.clone(), job).await
+    }
+
+    async fn retry(
+        &self,
+        job: &Job,
+        run_at: DateTimeUtc,
+        error: Option<&str>,
+        reset_attempts: bool,
+    ) -> anyhow::Result<()> {
+        service::retry(self.db.clone(), job, run_at, error, reset_attempts).await
+    }
+
+    async fn fail(&self, job: &Job, error: &str) -> anyhow::Result<()> {
+        service::fail(self.db.clone(), job, error).await
+    }
+}
+
+/// Keeps Jobs as items in the DynamoDB Tasks table
+#[derive(Clone, Debug)]
+pub struct DynamoQueue {
+    client: Arc<Client>,
+    table_name: String,
+    timestamp_format: TimestampFormat,
+}
+
+#[async_trait]
+impl Queue for DynamoQueue {
+    async fn push(&self, job: NewJob) -> anyhow::Result<Option<Job>> {
+        dynamo_service::push(
+            self.client.clone(),
+            &self.table_name,
+            self.timestamp_format,
+            job,
+        )
+        .await
+    }
+
+    async fn claim(&self, kinds: &[&str], lease: Duration) -> anyhow::Result<Option<Job>> {
+        dynamo_service::claim(
+            self.client.clone(),
+            &self.table_name,
+            self.timestamp_format,
+            kinds,
+            lease,
+        )
+        .await
+    }
+
+    async fn complete(&self, job: &Job) -> anyhow::Result<()> {
+        dynamo_service::complete(self.client.clone(), &self.table_name, job).await
+    }
+
+    async fn retry(
+        &self,
+        job: &Job,
+        run_at: DateTimeUtc,
+        error: Option<&str>,
+        reset_attempts: bool,
+    ) -> anyhow::Result<()> {
+        dynamo_service::retry(
+            self.client.clone(),
+            &self.table_name,
+            self.timestamp_format,
+            job,
+            run_at,
+            error,
+            reset_attempts,
+        )
+        .await
+    }
+
+    async fn fail(&self, job: &Job, error: &str) -> anyhow::Result<()> {
+        dynamo_service::fail(
+            self.client.clone(),
+            &self.table_name,
+            self.timestamp_format,
+            job,
+            error,
+        )
+        .await
+    }
+}
+
+/// Keeps Jobs in memory, for tests and for running without a shared data store. Jobs are lost
+/// when the process exits.
+#[derive(Debug, Default)]
+pub struct MemoryQueue {
+    jobs: Mutex<HashMap<String, Job>>,
+}
+
+impl MemoryQueue {
+    /// An empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a queued Job by id
+    pub fn get(&self, id: &str) -> Option<Job> {
+        self.lock().get(id).cloned()
+    }
+
+    /// Every queued Job, in no particular order
+    pub fn jobs(&self) -> Vec<Job> {
+        self.lock().values().cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Job>> {
+        // A panic while the lock was held can't leave a half-updated Job, so carry on
+        self.jobs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Apply a change to a claimed Job if this worker still holds it
+    fn update(
+        &self,
+        job: &Job,
+        change: impl FnOnce(&mut HashMap<String, Job>),
+    ) -> anyhow::Result<()> {
+        let mut jobs = self.lock();
+
+        match jobs.get(&job.id) {
+            Some(held) if held.status == Status::Running && held.attempts == job.attempts => {
+                change(&mut jobs);
+
+                Ok(())
+            }
+            _ => Err(Error::Lost(job.id.clone()).into()),
+        }
+    }
+}
+
+#[async_trait]
+impl Queue for MemoryQueue {
+    async fn push(&self, job: NewJob) -> anyhow::Result<Option<Job>> {
+        let mut jobs = self.lock();
+
+        if jobs.contains_key(&job.id) {
+            return Ok(None);
+        }
+
+        let job = job.into_job();
+
+        jobs.insert(job.id.clone(), job.clone());
+
+        Ok(Some(job))
+    }
+
+    async fn claim(&self, kinds: &[&str], lease: Duration) -> anyhow::Result<Option<Job>> {
+        let now = Utc::now().fixed_offset();
+        let mut jobs = self.lock();
+
+        let Some(job) = jobs
+            .values_mut()
+            .filter(|job| {
+                job.status != Status::Failed
+                    && job.run_at <= now
+                    && kinds.contains(&job.kind.as_str())
+            })
+            .min_by_key(|job| job.run_at)
+        else {
+            return Ok(None);
+        };
+
+        job.status = Status::Running;
+        job.attempts += 1;
+        job.run_at = now + lease;
+        job.updated_at = now;
+
+        Ok(Some(job.clone()))
+    }
+
+    async fn complete(&self, job: &Job) -> anyhow::Result<()> {
+        self.update(job, |jobs| {
+            jobs.remove(&job.id);
+        })
+    }
+
+    async fn retry(
+        &self,
+        job: &Job,
+        run_at: DateTimeUtc,
+        error: Option<&str>,
+        reset_attempts: bool,
+    ) -> anyhow::Result<()> {
+        self.update(job, |jobs| {
+            if let Some(held) = jobs.get_mut(&job.id) {
+                held.status = Status::Pending;
+                held.run_at = run_at.fixed_offset();
+                held.last_error = error.map(str::to_string);
+                held.updated_at = Utc::now().fixed_offset();
+
+                if reset_attempts {
+                    held.attempts = 0;
+                }
+            }
+        })
+    }
+
+    async fn fail(&self, job: &Job, error: &str) -> anyhow::Result<()> {
+        self.update(job, |jobs| {
+            if let Some(held) = jobs.get_mut(&job.id) {
+                held.status = Status::Failed;
+                held.last_error = Some(error.to_string());
+                held.updated_at = Utc::now().fixed_offset();
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn new_job(id: &str, kind: &str, run_at: DateTimeUtc) -> NewJob {
+        NewJob {
+            id: id.to_string(),
+            kind: kind.to_string(),
+            payload: serde_json::Value::Null,
+            run_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_queue() -> anyhow::Result<()> {
+        let queue = MemoryQueue::new();
+        let now = Utc::now();
+
+        queue
+            .push(new_job("later", "email", now + Duration::hours(1)))
+            .await?;
+        queue
+            .push(new_job("first", "email", now - Duration::minutes(2)))
+            .await?;
+        queue
+            .push(new_job("second", "email", now - Duration::minutes(1)))
+            .await?;
+        queue
+            .push(new_job("other", "purge", now - Duration::hours(1)))
+            .await?;
+
+        // A unique key isn't queued twice
+        assert_eq!(queue.push(new_job("first", "email", now)).await?, None);
+
+        let claimed = queue.claim(&["email"], Duration::minutes(5)).await?;
+
+        assert_eq!(claimed.as_ref().map(|job| job.id.as_str()), Some("first"));
+
+        let Some(first) = claimed else {
+            return Err(anyhow::anyhow!("Expected a Job"));
+        };
+
+        assert_eq!(first.attempts, 1);
+        assert_eq!(first.status, Status::Running);
+
+        // The running Job isn't claimed again until its lease expires
+        let next = queue.claim(&["email"], Duration::minutes(5)).await?;
+
+        assert_eq!(next.map(|job| job.id), Some("second".to_string()));
+
+        queue.retry(&first, now, Some("timed out"), false).await?;
+
+        let retried = queue.get("first");
+
+        assert_eq!(
+            retried.as_ref().map(|job| job.status),
+            Some(Status::Pending)
+        );
+        assert_eq!(
+            retried.and_then(|job| job.last_error),
+            Some("timed out".to_string())
+        );
+
+        // The earlier claim no longer holds the Job
+        let err = queue.complete(&first).await.unwrap_err();
+
+        assert!(Error::is_lost(&err));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,394 @@
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use anyhow::anyhow;
+use chrono::{Duration, Utc};
+use tokio::sync::watch;
+
+use crate::{config, tasks::store::Store};
+
+use super::{
+    error::Error,
+    handler::{Context, ErasedHandler, Handler},
+    model::Job,
+    queue::{NewJob, Queue},
+};
+
+/// Claims Jobs from the queue and runs them with the handler registered for their kind, on a
+/// fixed number of workers
+pub struct Runner {
+    context: Context,
+    config: config::Jobs,
+    handlers: HashMap<&'static str, Arc<dyn ErasedHandler>>,
+    repeating: HashMap<&'static str, (Duration, NewJob)>,
+}
+
+impl Runner {
+    /// A runner with no handlers, taking Jobs from the given queue
+    pub fn new(store: Store, queue: Arc<dyn Queue>, config: config::Jobs) -> Self {
+        Self {
+            context: Context { store, queue },
+            config,
+            handlers: HashMap::new(),
+            repeating: HashMap::new(),
+        }
+    }
+
+    /// Register the handler for a kind of Job, replacing any handler already registered for it
+    pub fn register<H: Handler>(mut self, handler: H) -> Self {
+        self.handlers.insert(H::KIND, Arc::new(handler));
+
+        self
+    }
+
+    /// Register a handler and run a Job for it on a fixed interval. Only one of these Jobs is
+    /// queued at a time, however many servers share the queue, and it's queued again for the
+    /// next interval each time it finishes, whether it succeeded or ran out of attempts.
+    pub fn every<H: Handler>(
+        mut self,
+        interval: std::time::Duration,
+        handler: H,
+        job: &H::Job,
+    ) -> anyhow::Result<Self> {
+        let job = NewJob::new::<H>(job)?.unique(H::KIND);
+
+        self.repeating
+            .insert(H::KIND, (Duration::from_std(interval)?, job));
+
+        Ok(self.register(handler))
+    }
+
+    /// Run Jobs until the shutdown future resolves, then stop claiming Jobs and wait for the
+    /// running ones to finish. A Job cut short by the process exiting anyway is claimed again
+    /// once its lease expires.
+    pub async fn run(self, shutdown: impl Future<Output = ()>) -> anyhow::Result<()> {
+        for (_, job) in self.repeating.values() {
+            self.context.queue.push(job.clone()).await?;
+        }
+
+        if self.handlers.is_empty() {
+            shutdown.await;
+
+            return Ok(());
+        }
+
+        let runner = Arc::new(self);
+        let (stop, stopped) = watch::channel(false);
+
+        let workers: Vec<_> = (0..runner.config.concurrency.max(1))
+            .map(|_| tokio::spawn(runner.clone().work(stopped.clone())))
+            .collect();
+
+        shutdown.await;
+        stop.send_replace(true);
+
+        log::info!("Waiting for running Jobs to finish");
+
+        for worker in workers {
+            worker.await?;
+        }
+
+        Ok(())
+    }
+
+    /// Claim and run Jobs one at a time until told to stop, polling while the queue is empty
+    async fn work(self: Arc<Self>, mut stopped: watch::Receiver<bool>) {
+        let kinds: Vec<&str> = self.handlers.keys().copied().collect();
+
+        while !*stopped.borrow() {
+            match self.context.queue.claim(&kinds, self.config.lease).await {
+                Ok(Some(job)) => {
+                    self.execute(job).await;
+
+                    continue;
+                }
+                Ok(None) => (),
+                Err(err) => log::warn!("Unable to claim a Job: {err:#}"),
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(self.config.poll_interval) => (),
+                _ = stopped.changed() => (),
+            }
+        }
+    }
+
+    /// Run a claimed Job and record the outcome, retrying it with backoff if it failed and has
+    /// attempts left
+    async fn execute(&self, job: Job) {
+        let Some(handler) = self.handlers.get(job.kind.as_str()).cloned() else {
+            let err = Error::UnknownKind(job.kind.clone());
+
+            if let Err(err) = self.context.queue.fail(&job, &err.to_string()).await {
+                log::warn!("Unable to record the outcome of Job {}: {err:#}", job.id);
+            }
+
+            return;
+        };
+
+        let attempts = u32::try_from(job.attempts).unwrap_or_default();
+        let max_attempts = handler.max_attempts();
+
+        let result = if attempts > max_attempts {
+            // The lease expired on the last attempt, most likely because its worker stopped
+            Err(anyhow!("abandoned after {max_attempts} attempts"))
+        } else {
+            self.attempt(handler, &job).await
+        };
+
+        let now = Utc::now();
+        let queue = &self.context.queue;
+        let repeating = self.repeating.get(job.kind.as_str());
+
+        let recorded = match (result, repeating) {
+            (Ok(()), Some((interval, _))) => queue.retry(&job, now + *interval, None, true).await,
+            (Ok(()), None) => queue.complete(&job).await,
+            (Err(err), _) if attempts < max_attempts => {
+                let delay = backoff(&self.config, attempts);
+
+                log::warn!(
+                    "Job {} ({}) failed on attempt {attempts}, retrying in {}s: {err:#}",
+                    job.id,
+                    job.kind,
+                    delay.num_seconds()
+                );
+
+                queue
+                    .retry(&job, now + delay, Some(&format!("{err:#}")), false)
+                    .await
+            }
+            (Err(err), Some((interval, _))) => {
+                log::warn!(
+                    "Job {} ({}) failed after {attempts} attempts, running again in {}s: {err:#}",
+                    job.id,
+                    job.kind,
+                    interval.num_seconds()
+                );
+
+                queue
+                    .retry(&job, now + *interval, Some(&format!("{err:#}")), true)
+                    .await
+            }
+            (Err(err), None) => {
+                log::warn!(
+                    "Job {} ({}) failed after {attempts} attempts: {err:#}",
+                    job.id,
+                    job.kind
+                );
+
+                queue.fail(&job, &format!("{err:#}")).await
+            }
+        };
+
+        if let Err(err) = recorded {
+            log::warn!("Unable to record the outcome of Job {}: {err:#}", job.id);
+        }
+    }
+
+    /// Run the handler on its own task, so a panic fails the attempt rather than the worker, and
+    /// give up on it when its lease runs out so another worker doesn't run it at the same time
+    async fn attempt(&self, handler: Arc<dyn ErasedHandler>, job: &Job) -> anyhow::Result<()> {
+        let context = self.context.clone();
+        let payload = job.payload.clone();
+
+        let mut task = tokio::spawn(async move { handler.handle(payload, &context).await });
+
+        let lease = self
+            .config
+            .lease
+            .to_std()
+            .unwrap_or(std::time::Duration::ZERO);
+
+        match tokio::time::timeout(lease, &mut task).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(err)) => Err(anyhow!("the handler panicked: {err}")),
+            Err(_) => {
+                task.abort();
+
+                Err(anyhow!(
+                    "timed out after {}s",
+                    self.config.lease.num_seconds()
+                ))
+            }
+        }
+    }
+}
+
+/// The delay before retrying a Job that has failed `attempts` times, doubling from the
+/// configured delay with each attempt up to the configured maximum
+pub fn backoff(config: &config::Jobs, attempts: u32) -> Duration {
+    let factor = 2_i32
+        .checked_pow(attempts.saturating_sub(1))
+        .unwrap_or(i32::MAX);
+
+    config
+        .retry_delay
+        .checked_mul(factor)
+        .map_or(config.max_retry_delay, |delay| {
+            delay.min(config.max_retry_delay)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::async_trait;
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    use crate::jobs::{model::Status, queue::MemoryQueue};
+
+    use super::*;
+
+    /// Fails until it has been called `failures` times
+    struct Flaky {
+        calls: Arc<AtomicUsize>,
+        failures: usize,
+    }
+
+    #[async_trait]
+    impl Handler for Flaky {
+        type Job = String;
+
+        const KIND: &'static str = "flaky";
+
+        const MAX_ATTEMPTS: u32 = 3;
+
+        async fn handle(&self, job: String, _context: &Context) -> anyhow::Result<()> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(anyhow!("{job} failed"));
+            }
+
+            Ok(())
+        }
+    }
+
+    fn config() -> config::Jobs {
+        config::Jobs {
+            concurrency: 2,
+            poll_interval: std::time::Duration::from_millis(5),
+            retry_delay: Duration::zero(),
+            ..Default::default()
+        }
+    }
+
+    /// Run the runner until the queue has no Jobs left to claim
+    async fn run_until_idle(runner: Runner, queue: Arc<MemoryQueue>) -> anyhow::Result<()> {
+        let idle = async move {
+            while queue.jobs().iter().any(|job| job.status != Status::Failed) {
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            }
+        };
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), runner.run(idle)).await?
+    }
+
+    fn store() -> Store {
+        Store::Database(Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres).into_connection(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() -> anyhow::Result<()> {
+        let queue = Arc::new(MemoryQueue::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        queue
+            .push(NewJob::new::<Flaky>(&"Delivery".to_string())?)
+            .await?;
+
+        let runner = Runner::new(store(), queue.clone(), config()).register(Flaky {
+            calls: calls.clone(),
+            failures: 2,
+        });
+
+        run_until_idle(runner, queue.clone()).await?;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(queue.jobs(), vec![]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fails_when_out_of_attempts() -> anyhow::Result<()> {
+        let queue = Arc::new(MemoryQueue::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let job = queue
+            .push(NewJob::new::<Flaky>(&"Delivery".to_string())?)
+            .await?
+            .ok_or(anyhow!("Expected a Job"))?;
+
+        let runner = Runner::new(store(), queue.clone(), config()).register(Flaky {
+            calls: calls.clone(),
+            failures: usize::MAX,
+        });
+
+        run_until_idle(runner, queue.clone()).await?;
+
+        let failed = queue.get(&job.id).ok_or(anyhow!("Expected a Job"))?;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(failed.status, Status::Failed);
+        assert_eq!(failed.attempts, 3);
+        assert_eq!(failed.last_error, Some("Delivery failed".to_string()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_every_queues_one_job() -> anyhow::Result<()> {
+        let queue = Arc::new(MemoryQueue::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let runner = Runner::new(store(), queue.clone(), config()).every(
+            std::time::Duration::from_secs(3600),
+            Flaky {
+                calls: calls.clone(),
+                failures: 0,
+            },
+            &"Purge".to_string(),
+        )?;
+
+        // Another server queued it already
+        queue
+            .push(NewJob::new::<Flaky>(&"Purge".to_string())?.unique(Flaky::KIND))
+            .await?;
+
+        let waiting = queue.clone();
+
+        runner
+            .run(async move {
+                while waiting.get(Flaky::KIND).map(|job| job.run_at) <= Some(Utc::now().into()) {
+                    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                }
+            })
+            .await?;
+
+        let next = queue.get(Flaky::KIND).ok_or(anyhow!("Expected a Job"))?;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(next.status, Status::Pending);
+        assert_eq!(next.attempts, 0);
+        assert!(next.run_at > Utc::now() + Duration::minutes(59));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_backoff() {
+        let config = config::Jobs {
+            retry_delay: Duration::seconds(10),
+            max_retry_delay: Duration::minutes(1),
+            ..Default::default()
+        };
+
+        assert_eq!(backoff(&config, 1), Duration::seconds(10));
+        assert_eq!(backoff(&config, 2), Duration::seconds(20));
+        assert_eq!(backoff(&config, 3), Duration::seconds(40));
+        assert_eq!(backoff(&config, 4), Duration::minutes(1));
+        assert_eq!(backoff(&config, 40), Duration::minutes(1));
+    }
+}
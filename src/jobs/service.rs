@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use sea_orm::{
+    prelude::DateTimeUtc,
+    sea_query::{Expr, LockBehavior, LockType, OnConflict},
+    ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder,
+    QuerySelect, QueryTrait,
+};
+
+use super::{
+    error::Error,
+    model::{self, Job, Status},
+    queue::NewJob,
+};
+
+/// Get an individual `Job` by id
+pub async fn get(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Option<Job>> {
+    let job = model::Entity::find_by_id(id.to_string()).one(&*db).await?;
+
+    Ok(job)
+}
+
+/// Add a `Job` to the queue, returning `None` if one with the same id is already queued
+pub async fn push(db: Arc<DatabaseConnection>, job: NewJob) -> anyhow::Result<Option<Job>> {
+    let job = job.into_job();
+
+    let inserted = model::Entity::insert(job.clone().into_active_model())
+        .on_conflict(
+            OnConflict::column(model::Column::Id)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(&*db)
+        .await?;
+
+    Ok((inserted > 0).then_some(job))
+}
+
+/// Claim the earliest due `Job` of the given kinds. Rows other workers have locked are skipped
+/// rather than waited on, so any number of workers can claim from the table at once.
+pub async fn claim(
+    db: Arc<DatabaseConnection>,
+    kinds: &[&str],
+    lease: Duration,
+) -> anyhow::Result<Option<Job>> {
+    let now = Utc::now().fixed_offset();
+
+    let next = model::Entity::find()
+        .select_only()
+        .column(model::Column::Id)
+        .filter(model::Column::Kind.is_in(kinds.iter().copied()))
+        .filter(model::Column::Status.is_in([Status::Pending, Status::Running]))
+        .filter(model::Column::RunAt.lte(now))
+        .order_by_asc(model::Column::RunAt)
+        .limit(1)
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .into_query();
+
+    let claimed = model::Entity::update_many()
+        .col_expr(model::Column::Status, Expr::value(Status::Running))
+        .col_expr(
+            model::Column::Attempts,
+            Expr::col(model::Column::Attempts).add(1),
+        )
+        .col_expr(model::Column::RunAt, Expr::value(now + lease))
+        .col_expr(model::Column::UpdatedAt, Expr::value(now))
+        .filter(model::Column::Id.in_subquery(next))
+        .exec_with_returning(&*db)
+        .await?;
+
+    Ok(claimed.into_iter().next())
+}
+
+/// Delete a claimed `Job` that succeeded
+pub async fn complete(db: Arc<DatabaseConnection>, job: &Job) -> anyhow::Result<()> {
+    let result = model::Entity::delete_many()
+        .filter(model::Column::Id.eq(&job.id))
+        .filter(model::Column::Status.eq(Status::Running))
+        .filter(model::Column::Attempts.eq(job.attempts))
+        .exec(&*db)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(Error::Lost(job.id.clone()).into());
+    }
+
+    Ok(())
+}
+
+/// Put a claimed `Job` back in the queue to run again at `run_at`
+pub async fn retry(
+    db: Arc<DatabaseConnection>,
+    job: &Job,
+    run_at: DateTimeUtc,
+    error: Option<&str>,
+    reset_attempts: bool,
+) -> anyhow::Result<()> {
+    let mut update = model::Entity::update_many()
+        .col_expr(model::Column::Status, Expr::value(Status::Pending))
+        .col_expr(model::Column::RunAt, Expr::value(run_at.fixed_offset()))
+        .col_expr(
+            model::Column::LastError,
+            Expr::value(error.map(str::to_string)),
+        )
+        .col_expr(
+            model::Column::UpdatedAt,
+            Expr::value(Utc::now().fixed_offset()),
+        );
+
+    if reset_attempts {
+        update = update.col_expr(model::Column::Attempts, Expr::value(0));
+    }
+
+    let result = update
+        .filter(model::Column::Id.eq(&job.id))
+        .filter(model::Column::Status.eq(Status::Running))
+        .filter(model::Column::Attempts.eq(job.attempts))
+        .exec(&*db)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(Error::Lost(job.id.clone()).into());
+    }
+
+    Ok(())
+}
+
+/// Mark a claimed `Job` as failed for good
+pub async fn fail(db: Arc<DatabaseConnection>, job: &Job, error: &str) -> anyhow::Result<()> {
+    let result = model::Entity::update_many()
+        .col_expr(model::Column::Status, Expr::value(Status::Failed))
+        .col_expr(model::Column::LastError, Expr::value(error.to_string()))
+        .col_expr(
+            model::Column::UpdatedAt,
+            Expr::value(Utc::now().fixed_offset()),
+        )
+        .filter(model::Column::Id.eq(&job.id))
+        .filter(model::Column::Status.eq(Status::Running))
+        .filter(model::Column::Attempts.eq(job.attempts))
+        .exec(&*db)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(Error::Lost(job.id.clone()).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction};
+
+    use super::*;
+
+    fn job() -> Job {
+        NewJob {
+            id: "01HJ".to_string(),
+            kind: "expand_recurrences".to_string(),
+            payload: serde_json::Value::Null,
+            run_at: Utc::now(),
+        }
+        .into_job()
+    }
+
+    #[tokio::test]
+    async fn test_claim_skips_locked_rows() -> anyhow::Result<()> {
+        let job = Job {
+            status: Status::Running,
+            attempts: 1,
+            ..job()
+        };
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![job.clone()]])
+                .into_connection(),
+        );
+
+        let claimed = claim(db.clone(), &["expand_recurrences"], Duration::minutes(5)).await?;
+
+        assert_eq!(claimed, Some(job));
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        let sql = format!("{log:?}");
+
+        assert!(sql.contains(r#"\"attempts\" = \"attempts\" + $2"#), "{sql}");
+        assert!(
+            sql.contains(r#"ORDER BY \"jobs\".\"run_at\" ASC LIMIT $"#),
+            "{sql}"
+        );
+        assert!(sql.contains("FOR UPDATE SKIP LOCKED"), "{sql}");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_complete_checks_the_claim() -> anyhow::Result<()> {
+        let job = Job {
+            status: Status::Running,
+            attempts: 2,
+            ..job()
+        };
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 0,
+                }])
+                .into_connection(),
+        );
+
+        let err = complete(db.clone(), &job).await.unwrap_err();
+
+        assert!(Error::is_lost(&err));
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        assert_eq!(
+            log,
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"DELETE FROM "jobs" WHERE "jobs"."id" = $1 AND "jobs"."status" = $2 AND "jobs"."attempts" = $3"#,
+                [job.id.into(), "running".into(), 2.into()],
+            )]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+//! # A demo project showing dependency injection approaches
+#![forbid(unsafe_code)]
+
+/// Command line arguments
+pub mod args;
+
+/// Files attached to Tasks
+pub mod attachments;
+
+/// Command line subcommands
+pub mod commands;
+
+/// Comments on Tasks
+pub mod comments;
+
+/// Application config
+pub mod config;
+
+/// Dependencies between Tasks
+pub mod dependencies;
+
+/// The GraphQL API
+pub mod graphql;
+
+/// The gRPC API
+pub mod grpc;
+
+/// Background Jobs with a persistent queue
+pub mod jobs;
+
+/// Versioned schema migrations for Postgres
+pub mod migrations;
+
+/// The OpenAPI document for the REST API
+pub mod openapi;
+
+/// The Project entity, which groups Tasks
+pub mod projects;
+
+/// Recurring Tasks generated from iCalendar rules
+pub mod recurrences;
+
+/// The HTTP server
+pub mod server;
+
+/// The Task entity
+pub mod tasks;
+
+/// General-purpose utilities
+pub mod utils;
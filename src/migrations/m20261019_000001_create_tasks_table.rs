@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+/// Create the `tasks` table
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tasks::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Tasks::Id).text().not_null().primary_key())
+                    .col(ColumnDef::new(Tasks::CreatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(Tasks::UpdatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(Tasks::Title).text().not_null())
+                    .col(ColumnDef::new(Tasks::Description).text())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Tasks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Tasks {
+    Table,
+    Id,
+    CreatedAt,
+    UpdatedAt,
+    Title,
+    Description,
+}
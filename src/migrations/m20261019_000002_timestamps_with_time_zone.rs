@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+/// Store the `tasks` timestamps as `timestamptz`, reading the existing values as UTC
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The query builder can't express the `USING` clause the conversion needs
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE tasks
+                    ALTER COLUMN created_at TYPE timestamptz USING created_at AT TIME ZONE 'UTC',
+                    ALTER COLUMN updated_at TYPE timestamptz USING updated_at AT TIME ZONE 'UTC'",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE tasks
+                    ALTER COLUMN created_at TYPE timestamp USING created_at AT TIME ZONE 'UTC',
+                    ALTER COLUMN updated_at TYPE timestamp USING updated_at AT TIME ZONE 'UTC'",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+/// Add a `status` column to `tasks`, with existing Tasks starting as `todo`
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tasks::Table)
+                    .add_column(
+                        ColumnDef::new(Tasks::Status)
+                            .text()
+                            .not_null()
+                            .default("todo"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tasks::Table)
+                    .drop_column(Tasks::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Tasks {
+    Table,
+    Status,
+}
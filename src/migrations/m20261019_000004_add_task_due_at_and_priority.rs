@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+/// Add the `due_at` and `priority` columns to `tasks`, indexed for the list filters and ordering
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tasks::Table)
+                    .add_column(ColumnDef::new(Tasks::DueAt).timestamp_with_time_zone())
+                    .add_column(ColumnDef::new(Tasks::Priority).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tasks_due_at")
+                    .table(Tasks::Table)
+                    .col(Tasks::DueAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tasks_priority_id")
+                    .table(Tasks::Table)
+                    .col((Tasks::Priority, IndexOrder::Desc))
+                    .col(Tasks::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tasks::Table)
+                    .drop_column(Tasks::DueAt)
+                    .drop_column(Tasks::Priority)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Tasks {
+    Table,
+    Id,
+    DueAt,
+    Priority,
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+/// Create the `tags` table and the `task_tags` join table between Tasks and tags
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tags::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Tags::Id).text().not_null().primary_key())
+                    .col(ColumnDef::new(Tags::Name).text().not_null().unique_key())
+                    .col(
+                        ColumnDef::new(Tags::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskTags::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(TaskTags::TaskId).text().not_null())
+                    .col(ColumnDef::new(TaskTags::TagId).text().not_null())
+                    .primary_key(Index::create().col(TaskTags::TaskId).col(TaskTags::TagId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_task_tags_task_id")
+                            .from(TaskTags::Table, TaskTags::TaskId)
+                            .to(Tasks::Table, Tasks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_task_tags_tag_id")
+                            .from(TaskTags::Table, TaskTags::TagId)
+                            .to(Tags::Table, Tags::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The primary key covers lookups by Task, so listing a tag's Tasks needs its own index
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_task_tags_tag_id_task_id")
+                    .table(TaskTags::Table)
+                    .col(TaskTags::TagId)
+                    .col(TaskTags::TaskId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TaskTags::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Tags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Tasks {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Tags {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum TaskTags {
+    Table,
+    TaskId,
+    TagId,
+}
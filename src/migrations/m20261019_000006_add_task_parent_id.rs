@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+/// Add a `parent_id` column to `tasks`, so Tasks can be broken down into subtasks. Deleting a
+/// parent leaves its children at the top level.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tasks::Table)
+                    .add_column(ColumnDef::new(Tasks::ParentId).text())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_tasks_parent_id")
+                            .from_tbl(Tasks::Table)
+                            .from_col(Tasks::ParentId)
+                            .to_tbl(Tasks::Table)
+                            .to_col(Tasks::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tasks_parent_id_id")
+                    .table(Tasks::Table)
+                    .col(Tasks::ParentId)
+                    .col(Tasks::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tasks::Table)
+                    .drop_column(Tasks::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Tasks {
+    Table,
+    Id,
+    ParentId,
+}
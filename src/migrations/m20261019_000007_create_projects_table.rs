@@ -0,0 +1,98 @@
+use sea_orm_migration::prelude::*;
+
+/// Create the `projects` table and add a `project_id` column to `tasks`. Deleting a Project
+/// deletes its Tasks.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Projects::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Projects::Id).text().not_null().primary_key())
+                    .col(
+                        ColumnDef::new(Projects::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Projects::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Projects::Name).text().not_null())
+                    .col(ColumnDef::new(Projects::Description).text())
+                    .col(ColumnDef::new(Projects::ArchivedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tasks::Table)
+                    .add_column(ColumnDef::new(Tasks::ProjectId).text())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_tasks_project_id")
+                            .from_tbl(Tasks::Table)
+                            .from_col(Tasks::ProjectId)
+                            .to_tbl(Projects::Table)
+                            .to_col(Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tasks_project_id_id")
+                    .table(Tasks::Table)
+                    .col(Tasks::ProjectId)
+                    .col(Tasks::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tasks::Table)
+                    .drop_column(Tasks::ProjectId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Projects::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+    CreatedAt,
+    UpdatedAt,
+    Name,
+    Description,
+    ArchivedAt,
+}
+
+#[derive(DeriveIden)]
+enum Tasks {
+    Table,
+    Id,
+    ProjectId,
+}
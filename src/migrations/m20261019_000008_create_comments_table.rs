@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+/// Create the `comments` table for discussion on Tasks. Deleting a Task deletes its Comments.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Comments::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Comments::Id).text().not_null().primary_key())
+                    .col(ColumnDef::new(Comments::TaskId).text().not_null())
+                    .col(
+                        ColumnDef::new(Comments::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Comments::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Comments::Author).text().not_null())
+                    .col(ColumnDef::new(Comments::Body).text().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_comments_task_id")
+                            .from(Comments::Table, Comments::TaskId)
+                            .to(Tasks::Table, Tasks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_comments_task_id_id")
+                    .table(Comments::Table)
+                    .col(Comments::TaskId)
+                    .col(Comments::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Comments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Comments {
+    Table,
+    Id,
+    TaskId,
+    CreatedAt,
+    UpdatedAt,
+    Author,
+    Body,
+}
+
+#[derive(DeriveIden)]
+enum Tasks {
+    Table,
+    Id,
+}
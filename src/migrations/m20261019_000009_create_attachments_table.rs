@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+/// Create the `attachments` table for the metadata of files attached to Tasks. The bytes are kept
+/// in the configured storage. Deleting a Task deletes its Attachments.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Attachments::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Attachments::Id)
+                            .text()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Attachments::TaskId).text().not_null())
+                    .col(
+                        ColumnDef::new(Attachments::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Attachments::Name).text().not_null())
+                    .col(ColumnDef::new(Attachments::Size).big_integer().not_null())
+                    .col(ColumnDef::new(Attachments::ContentType).text().not_null())
+                    .col(ColumnDef::new(Attachments::Checksum).text().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_attachments_task_id")
+                            .from(Attachments::Table, Attachments::TaskId)
+                            .to(Tasks::Table, Tasks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_attachments_task_id_id")
+                    .table(Attachments::Table)
+                    .col(Attachments::TaskId)
+                    .col(Attachments::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Attachments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Attachments {
+    Table,
+    Id,
+    TaskId,
+    CreatedAt,
+    Name,
+    Size,
+    ContentType,
+    Checksum,
+}
+
+#[derive(DeriveIden)]
+enum Tasks {
+    Table,
+    Id,
+}
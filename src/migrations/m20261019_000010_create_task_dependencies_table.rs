@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+/// Create the `task_dependencies` table of edges from each Task to the Tasks blocking it. Deleting
+/// either Task deletes the edge.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskDependencies::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(TaskDependencies::TaskId).text().not_null())
+                    .col(
+                        ColumnDef::new(TaskDependencies::BlockerId)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TaskDependencies::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(TaskDependencies::TaskId)
+                            .col(TaskDependencies::BlockerId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_task_dependencies_task_id")
+                            .from(TaskDependencies::Table, TaskDependencies::TaskId)
+                            .to(Tasks::Table, Tasks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_task_dependencies_blocker_id")
+                            .from(TaskDependencies::Table, TaskDependencies::BlockerId)
+                            .to(Tasks::Table, Tasks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The primary key covers lookups by blocked Task, so listing dependents needs its own index
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_task_dependencies_blocker_id_task_id")
+                    .table(TaskDependencies::Table)
+                    .col(TaskDependencies::BlockerId)
+                    .col(TaskDependencies::TaskId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TaskDependencies::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TaskDependencies {
+    Table,
+    TaskId,
+    BlockerId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Tasks {
+    Table,
+    Id,
+}
@@ -0,0 +1,164 @@
+use sea_orm_migration::prelude::*;
+
+/// Create the `recurrences` table of repeating Task templates, and the `recurrence_occurrences`
+/// table recording each occurrence a Task has been generated or skipped for. Deleting a Project
+/// deletes its Recurrences, while deleting a generated Task keeps its occurrence.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Recurrences::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Recurrences::Id)
+                            .text()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Recurrences::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Recurrences::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Recurrences::Title).text().not_null())
+                    .col(ColumnDef::new(Recurrences::Description).text())
+                    .col(ColumnDef::new(Recurrences::Priority).integer())
+                    .col(ColumnDef::new(Recurrences::ProjectId).text())
+                    .col(ColumnDef::new(Recurrences::Rule).text().not_null())
+                    .col(
+                        ColumnDef::new(Recurrences::StartsAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Recurrences::Timezone)
+                            .text()
+                            .not_null()
+                            .default("UTC"),
+                    )
+                    .col(ColumnDef::new(Recurrences::EndedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Recurrences::GeneratedUntil).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_recurrences_project_id")
+                            .from(Recurrences::Table, Recurrences::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RecurrenceOccurrences::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RecurrenceOccurrences::RecurrenceId)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RecurrenceOccurrences::OccursAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RecurrenceOccurrences::TaskId).text())
+                    .primary_key(
+                        Index::create()
+                            .col(RecurrenceOccurrences::RecurrenceId)
+                            .col(RecurrenceOccurrences::OccursAt),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_recurrence_occurrences_recurrence_id")
+                            .from(
+                                RecurrenceOccurrences::Table,
+                                RecurrenceOccurrences::RecurrenceId,
+                            )
+                            .to(Recurrences::Table, Recurrences::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_recurrence_occurrences_task_id")
+                            .from(RecurrenceOccurrences::Table, RecurrenceOccurrences::TaskId)
+                            .to(Tasks::Table, Tasks::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Deleting a Task looks up the occurrence pointing at it to clear the link
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_recurrence_occurrences_task_id")
+                    .table(RecurrenceOccurrences::Table)
+                    .col(RecurrenceOccurrences::TaskId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RecurrenceOccurrences::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Recurrences::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Recurrences {
+    Table,
+    Id,
+    CreatedAt,
+    UpdatedAt,
+    Title,
+    Description,
+    Priority,
+    ProjectId,
+    Rule,
+    StartsAt,
+    Timezone,
+    EndedAt,
+    GeneratedUntil,
+}
+
+#[derive(DeriveIden)]
+enum RecurrenceOccurrences {
+    Table,
+    RecurrenceId,
+    OccursAt,
+    TaskId,
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Tasks {
+    Table,
+    Id,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+/// Create the `jobs` table holding the background job queue
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// The trait's signature elides the `SchemaManager` lifetime, so the impl must too.
+#[allow(elided_lifetimes_in_paths)]
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Jobs::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Jobs::Id).text().not_null().primary_key())
+                    .col(
+                        ColumnDef::new(Jobs::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Jobs::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Jobs::Kind).text().not_null())
+                    .col(ColumnDef::new(Jobs::Payload).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(Jobs::Status)
+                            .text()
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(Jobs::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Jobs::RunAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Jobs::LastError).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Workers claim the earliest due Job of the kinds they handle
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_jobs_kind_run_at")
+                    .table(Jobs::Table)
+                    .col(Jobs::Kind)
+                    .col(Jobs::RunAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Jobs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Jobs {
+    Table,
+    Id,
+    CreatedAt,
+    UpdatedAt,
+    Kind,
+    Payload,
+    Status,
+    Attempts,
+    RunAt,
+    LastError,
+}
@@ -0,0 +1,147 @@
+use sea_orm_migration::prelude::*;
+
+/// Create the `tasks` table
+pub mod m20261019_000001_create_tasks_table;
+
+/// Convert the `tasks` timestamps to `timestamptz`
+pub mod m20261019_000002_timestamps_with_time_zone;
+
+/// Add a `status` column to `tasks`
+pub mod m20261019_000003_add_task_status;
+
+/// Add the `due_at` and `priority` columns to `tasks`
+pub mod m20261019_000004_add_task_due_at_and_priority;
+
+/// Create the `tags` and `task_tags` tables
+pub mod m20261019_000005_create_tags_tables;
+
+/// Add a `parent_id` column to `tasks`
+pub mod m20261019_000006_add_task_parent_id;
+
+/// Create the `projects` table and add a `project_id` column to `tasks`
+pub mod m20261019_000007_create_projects_table;
+
+/// Create the `comments` table
+pub mod m20261019_000008_create_comments_table;
+
+/// Create the `attachments` table
+pub mod m20261019_000009_create_attachments_table;
+
+/// Create the `task_dependencies` table
+pub mod m20261019_000010_create_task_dependencies_table;
+
+/// Create the `recurrences` and `recurrence_occurrences` tables
+pub mod m20261019_000011_create_recurrences_tables;
+
+/// Create the `jobs` table
+pub mod m20261019_000012_create_jobs_table;
+
+/// Applies the migrations in order, recording each one in the `seaql_migrations` history table
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20261019_000001_create_tasks_table::Migration),
+            Box::new(m20261019_000002_timestamps_with_time_zone::Migration),
+            Box::new(m20261019_000003_add_task_status::Migration),
+            Box::new(m20261019_000004_add_task_due_at_and_priority::Migration),
+            Box::new(m20261019_000005_create_tags_tables::Migration),
+            Box::new(m20261019_000006_add_task_parent_id::Migration),
+            Box::new(m20261019_000007_create_projects_table::Migration),
+            Box::new(m20261019_000008_create_comments_table::Migration),
+            Box::new(m20261019_000009_create_attachments_table::Migration),
+            Box::new(m20261019_000010_create_task_dependencies_table::Migration),
+            Box::new(m20261019_000011_create_recurrences_tables::Migration),
+            Box::new(m20261019_000012_create_jobs_table::Migration),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+    use sea_orm_migration::{seaql_migrations, MigrationStatus};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_status() -> anyhow::Result<()> {
+        let installed = MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 0,
+        };
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([installed.clone(), installed])
+            .append_query_results([vec![seaql_migrations::Model {
+                version: "m20261019_000001_create_tasks_table".to_string(),
+                applied_at: 0,
+            }]])
+            .into_connection();
+
+        let statuses: Vec<(String, MigrationStatus)> = Migrator::get_migration_with_status(&db)
+            .await?
+            .iter()
+            .map(|migration| (migration.name().to_string(), migration.status()))
+            .collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                (
+                    "m20261019_000001_create_tasks_table".to_string(),
+                    MigrationStatus::Applied
+                ),
+                (
+                    "m20261019_000002_timestamps_with_time_zone".to_string(),
+                    MigrationStatus::Pending
+                ),
+                (
+                    "m20261019_000003_add_task_status".to_string(),
+                    MigrationStatus::Pending
+                ),
+                (
+                    "m20261019_000004_add_task_due_at_and_priority".to_string(),
+                    MigrationStatus::Pending
+                ),
+                (
+                    "m20261019_000005_create_tags_tables".to_string(),
+                    MigrationStatus::Pending
+                ),
+                (
+                    "m20261019_000006_add_task_parent_id".to_string(),
+                    MigrationStatus::Pending
+                ),
+                (
+                    "m20261019_000007_create_projects_table".to_string(),
+                    MigrationStatus::Pending
+                ),
+                (
+                    "m20261019_000008_create_comments_table".to_string(),
+                    MigrationStatus::Pending
+                ),
+                (
+                    "m20261019_000009_create_attachments_table".to_string(),
+                    MigrationStatus::Pending
+                ),
+                (
+                    "m20261019_000010_create_task_dependencies_table".to_string(),
+                    MigrationStatus::Pending
+                ),
+                (
+                    "m20261019_000011_create_recurrences_tables".to_string(),
+                    MigrationStatus::Pending
+                ),
+                (
+                    "m20261019_000012_create_jobs_table".to_string(),
+                    MigrationStatus::Pending
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+}
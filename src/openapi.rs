@@ -0,0 +1,122 @@
+use axum::{response::Html, routing::get, Json, Router};
+use utoipa::OpenApi;
+use utoipa_rapidoc::RapiDoc;
+
+/// The OpenAPI document for the REST routes. The Postgres and DynamoDB handlers share the same
+/// contract, so the paths are generated from the Postgres handlers.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Tasks API"),
+    paths(
+        crate::server::tasks_list_from_db,
+        crate::server::tasks_get_from_db,
+        crate::server::tasks_create_in_db,
+        crate::server::tasks_update_in_db,
+        crate::server::tasks_delete_in_db,
+        crate::server::tasks_transitions_from_db,
+        crate::server::tasks_transition_in_db,
+        crate::server::tasks_tags_from_db,
+        crate::server::tasks_tag_in_db,
+        crate::server::tasks_untag_in_db,
+        crate::server::tags_list_from_db,
+        crate::server::tags_tasks_from_db,
+        crate::server::tasks_children_from_db,
+        crate::server::tasks_subtree_from_db,
+        crate::server::tasks_move_in_db,
+        crate::server::tasks_blockers_from_db,
+        crate::server::tasks_block_in_db,
+        crate::server::tasks_unblock_in_db,
+        crate::server::tasks_dependents_from_db,
+        crate::server::comments_list_from_db,
+        crate::server::comments_create_in_db,
+        crate::server::comments_update_in_db,
+        crate::server::comments_delete_in_db,
+        crate::server::attachments_list_from_db,
+        crate::server::attachments_create_in_db,
+        crate::server::attachments_download_from_db,
+        crate::server::attachments_delete_in_db,
+        crate::server::projects_list_from_db,
+        crate::server::projects_get_from_db,
+        crate::server::projects_create_in_db,
+        crate::server::projects_update_in_db,
+        crate::server::projects_delete_in_db,
+        crate::server::projects_archive_in_db,
+        crate::server::projects_restore_in_db,
+        crate::server::projects_tasks_from_db,
+        crate::server::recurrences_list_from_db,
+        crate::server::recurrences_get_from_db,
+        crate::server::recurrences_create_in_db,
+        crate::server::recurrences_update_in_db,
+        crate::server::recurrences_delete_in_db,
+        crate::server::recurrences_skip_in_db,
+        crate::server::recurrences_end_in_db,
+    ),
+    tags(
+        (name = "tasks", description = "Task management"),
+        (name = "tags", description = "Labelling Tasks with Tags"),
+        (name = "dependencies", description = "Blocking Tasks on other Tasks"),
+        (name = "comments", description = "Discussing Tasks in Comments"),
+        (name = "attachments", description = "Attaching files to Tasks"),
+        (name = "projects", description = "Grouping Tasks into Projects"),
+        (name = "recurrences", description = "Generating Tasks on a recurring schedule")
+    )
+)]
+pub struct ApiDoc;
+
+/// Serve the OpenAPI document at `/openapi.json` and interactive docs at `/docs`
+pub fn router() -> Router {
+    let docs = RapiDoc::new("/openapi.json").to_html();
+
+    Router::new()
+        .route("/openapi.json", get(|| async { Json(ApiDoc::openapi()) }))
+        .route("/docs", get(|| async { Html(docs) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_api_doc() -> anyhow::Result<()> {
+        let doc = serde_json::to_value(ApiDoc::openapi())?;
+
+        assert!(doc["paths"]["/tasks"]["get"].is_object());
+        assert!(doc["paths"]["/tasks"]["post"].is_object());
+        assert!(doc["paths"]["/tasks/{id}"]["patch"].is_object());
+        assert!(doc["paths"]["/tasks/{id}/transitions"]["post"]["responses"]["409"].is_object());
+        assert!(doc["paths"]["/tasks/{id}/tags/{name}"]["put"].is_object());
+        assert!(doc["paths"]["/tags"]["get"].is_object());
+        assert!(doc["components"]["schemas"]["TagCount"].is_object());
+        assert!(doc["paths"]["/tasks/{id}/parent"]["put"]["responses"]["409"].is_object());
+        assert!(doc["components"]["schemas"]["TaskTree"].is_object());
+        assert!(doc["components"]["schemas"]["MoveTaskInput"].is_object());
+        assert!(
+            doc["paths"]["/tasks/{id}/blockers/{blocker_id}"]["put"]["responses"]["409"]
+                .is_object()
+        );
+        assert!(doc["paths"]["/tasks/{id}/dependents"]["get"].is_object());
+        assert!(doc["paths"]["/projects/{id}/archive"]["post"].is_object());
+        assert!(doc["paths"]["/projects/{id}/tasks"]["get"].is_object());
+        assert!(doc["components"]["schemas"]["ProjectPage"].is_object());
+        assert!(doc["paths"]["/tasks/{id}/comments/{comment_id}"]["patch"].is_object());
+        assert!(doc["components"]["schemas"]["CommentPage"].is_object());
+        assert!(doc["paths"]["/tasks/{id}/attachments"]["post"]["responses"]["413"].is_object());
+        assert!(doc["components"]["schemas"]["UploadAttachmentForm"].is_object());
+        assert!(doc["paths"]["/recurrences/{id}/skip"]["post"]["responses"]["409"].is_object());
+        assert!(doc["paths"]["/recurrences/{id}/end"]["post"].is_object());
+        assert!(doc["components"]["schemas"]["RecurrencePage"].is_object());
+        assert!(doc["components"]["schemas"]["SkipOccurrenceInput"].is_object());
+
+        let update = &doc["components"]["schemas"]["UpdateTaskInput"];
+
+        assert_eq!(update["required"], serde_json::Value::Null);
+        assert_eq!(
+            update["properties"]["description"]["type"],
+            serde_json::json!(["string", "null"])
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,474 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::anyhow;
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, ReturnValue},
+    Client,
+};
+use chrono::SubsecRound;
+use ulid::Ulid;
+
+use crate::{
+    attachments::storage::Storage,
+    config::TimestampFormat,
+    tasks::{
+        self,
+        dynamo_table::{ENTITY_ATTRIBUTE, PROJECT_ENTITY, PROJECT_INDEX},
+        model::Task,
+        workflow::{Status, Workflow},
+    },
+    utils::{
+        dynamo::{self, Item},
+        timestamp,
+        update::Update::{Empty, Unchanged, Value},
+    },
+};
+
+use super::{error::Error, inputs, model::Project};
+
+/// Get an individual `Project` by id
+pub async fn get(
+    client: Arc<Client>,
+    table_name: &str,
+    id: &str,
+) -> anyhow::Result<Option<Project>> {
+    let results = client
+        .get_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(id.to_string()))
+        .send()
+        .await?;
+
+    match results.item {
+        Some(item) if is_project(&item) => Ok(Some(dynamo::from_item(item)?)),
+        _ => Ok(None),
+    }
+}
+
+/// List up to `limit` `Project`s in table scan order, starting after the given cursor. Projects
+/// share the table with Tasks, so this scans it.
+pub async fn list(
+    client: Arc<Client>,
+    table_name: &str,
+    input: &inputs::List,
+    limit: u64,
+) -> anyhow::Result<Vec<Project>> {
+    let mut filter = "#entity = :entity".to_string();
+    let mut names = HashMap::from([("#entity".to_string(), ENTITY_ATTRIBUTE.to_string())]);
+
+    if !input.include_archived {
+        filter.push_str(" AND attribute_not_exists(#archived_at)");
+        names.insert("#archived_at".to_string(), "archived_at".to_string());
+    }
+
+    let mut start_key = input
+        .after
+        .as_ref()
+        .map(|after| HashMap::from([("id".to_string(), AttributeValue::S(after.clone()))]));
+
+    let mut projects = vec![];
+
+    // Filters are applied after each request's limit, so keep reading until the page is full
+    loop {
+        let results = client
+            .scan()
+            .table_name(table_name)
+            .limit(i32::try_from(limit)?)
+            .filter_expression(&filter)
+            .set_expression_attribute_names(Some(names.clone()))
+            .expression_attribute_values(":entity", AttributeValue::S(PROJECT_ENTITY.to_string()))
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        for item in results.items.unwrap_or_default() {
+            projects.push(dynamo::from_item(item)?);
+
+            if projects.len() as u64 >= limit {
+                return Ok(projects);
+            }
+        }
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(projects),
+        }
+    }
+}
+
+/// Create a `Project` with the given input
+pub async fn create(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    input: &inputs::Create,
+) -> anyhow::Result<Project> {
+    // Truncated so the returned Project matches what reads return in every format
+    let now = chrono::Utc::now().fixed_offset().trunc_subsecs(3);
+
+    let project = Project {
+        id: Ulid::new().to_string(),
+        created_at: now,
+        updated_at: now,
+        name: input.name.clone(),
+        description: input.description.clone(),
+        archived_at: None,
+    };
+
+    client
+        .put_item()
+        .table_name(table_name)
+        .set_item(Some(project_item(&project, format)?))
+        .condition_expression("attribute_not_exists(id)")
+        .send()
+        .await?;
+
+    Ok(project)
+}
+
+/// Update an existing `Project` by id with a single conditional `UpdateItem`
+pub async fn update(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    id: &str,
+    input: &inputs::Update,
+) -> anyhow::Result<Project> {
+    let updated_at = chrono::Utc::now().fixed_offset().trunc_subsecs(3);
+
+    let mut set = vec!["#updated_at = :updated_at"];
+    let mut remove = vec![];
+
+    let mut names = HashMap::from([
+        ("#entity".to_string(), ENTITY_ATTRIBUTE.to_string()),
+        ("#updated_at".to_string(), "updated_at".to_string()),
+    ]);
+
+    let mut values = HashMap::from([(
+        ":updated_at".to_string(),
+        timestamp::to_attribute(updated_at, format),
+    )]);
+
+    // The name is required, so clearing it leaves it unchanged
+    if let Value(name) = &input.name {
+        set.push("#name = :name");
+        names.insert("#name".to_string(), "name".to_string());
+        values.insert(":name".to_string(), AttributeValue::S(name.clone()));
+    }
+
+    match &input.description {
+        Unchanged => (),
+        Empty => {
+            remove.push("#description");
+            names.insert("#description".to_string(), "description".to_string());
+        }
+        Value(description) => {
+            set.push("#description = :description");
+            names.insert("#description".to_string(), "description".to_string());
+            values.insert(
+                ":description".to_string(),
+                AttributeValue::S(description.clone()),
+            );
+        }
+    }
+
+    let mut expression = format!("SET {}", set.join(", "));
+
+    if !remove.is_empty() {
+        expression.push_str(&format!(" REMOVE {}", remove.join(", ")));
+    }
+
+    update_project(&client, table_name, id, expression, names, values).await
+}
+
+/// Archive a `Project`, then cancel each of its Tasks the workflow allows to be cancelled.
+/// Archiving an archived Project keeps its original archive date.
+pub async fn archive(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    workflow: &Workflow,
+    id: &str,
+) -> anyhow::Result<Project> {
+    let now = timestamp::to_attribute(chrono::Utc::now().fixed_offset().trunc_subsecs(3), format);
+
+    let project = update_project(
+        &client,
+        table_name,
+        id,
+        "SET #updated_at = :now, #archived_at = if_not_exists(#archived_at, :now)".to_string(),
+        HashMap::from([
+            ("#entity".to_string(), ENTITY_ATTRIBUTE.to_string()),
+            ("#updated_at".to_string(), "updated_at".to_string()),
+            ("#archived_at".to_string(), "archived_at".to_string()),
+        ]),
+        HashMap::from([(":now".to_string(), now)]),
+    )
+    .await?;
+
+    let sources = workflow.sources(Status::Cancelled);
+
+    for task in project_tasks(&client, table_name, id, None, None).await? {
+        if !sources.contains(&task.status) {
+            continue;
+        }
+
+        let result = tasks::dynamo_service::transition(
+            client.clone(),
+            table_name,
+            format,
+            workflow,
+            &task.id,
+            Status::Cancelled,
+        )
+        .await;
+
+        match result {
+            Ok(_) => (),
+            // The Task moved on or was deleted in the meantime
+            Err(err)
+                if tasks::error::Error::is_invalid_transition(&err)
+                    || tasks::error::Error::is_not_found(&err) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(project)
+}
+
+/// Restore an archived `Project`, so it accepts new Tasks again. Cancelled Tasks stay cancelled.
+pub async fn restore(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    id: &str,
+) -> anyhow::Result<Project> {
+    let now = timestamp::to_attribute(chrono::Utc::now().fixed_offset().trunc_subsecs(3), format);
+
+    update_project(
+        &client,
+        table_name,
+        id,
+        "SET #updated_at = :now REMOVE #archived_at".to_string(),
+        HashMap::from([
+            ("#entity".to_string(), ENTITY_ATTRIBUTE.to_string()),
+            ("#updated_at".to_string(), "updated_at".to_string()),
+            ("#archived_at".to_string(), "archived_at".to_string()),
+        ]),
+        HashMap::from([(":now".to_string(), now)]),
+    )
+    .await
+}
+
+/// Delete an existing `Project` with all of its Tasks, returning it. Each Task is deleted the way
+/// deleting it directly would, purging its Attachments, unlinking its Tags and moving its subtasks
+/// to the top level.
+pub async fn delete(
+    client: Arc<Client>,
+    table_name: &str,
+    storage: &dyn Storage,
+    id: &str,
+) -> anyhow::Result<Project> {
+    get(client.clone(), table_name, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    for task in project_tasks(&client, table_name, id, None, None).await? {
+        match tasks::dynamo_service::delete(client.clone(), table_name, storage, &task.id).await {
+            Ok(_) => (),
+            Err(err) if tasks::error::Error::is_not_found(&err) => (),
+            Err(err) => return Err(err),
+        }
+    }
+
+    let result = client
+        .delete_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(id.to_string()))
+        .condition_expression("#entity = :entity")
+        .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+        .expression_attribute_values(":entity", AttributeValue::S(PROJECT_ENTITY.to_string()))
+        .return_values(ReturnValue::AllOld)
+        .send()
+        .await
+        .map_err(|err| {
+            // Another request deleted the Project after it was read
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception())
+            {
+                Error::NotFound.into()
+            } else {
+                anyhow::Error::from(err)
+            }
+        })?;
+
+    let item = result.attributes.ok_or(Error::NotFound)?;
+
+    Ok(dynamo::from_item(item)?)
+}
+
+/// List up to `limit` of a `Project`'s Tasks in id order, starting after the given cursor
+pub async fn list_tasks(
+    client: Arc<Client>,
+    table_name: &str,
+    id: &str,
+    after: Option<&str>,
+    limit: u64,
+) -> anyhow::Result<Vec<Task>> {
+    get(client.clone(), table_name, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    project_tasks(&client, table_name, id, after, Some(limit)).await
+}
+
+/// Check that a Project exists and isn't archived, so Tasks can be added to it
+pub async fn check_active(client: Arc<Client>, table_name: &str, id: &str) -> anyhow::Result<()> {
+    let project = get(client, table_name, id)
+        .await?
+        .ok_or_else(|| tasks::error::Error::ProjectNotFound(id.to_string()))?;
+
+    if project.is_archived() {
+        return Err(Error::Archived(project.id).into());
+    }
+
+    Ok(())
+}
+
+/// A Project's Tasks from the Project index in id order, starting after the given cursor and
+/// stopping at the limit if there is one
+async fn project_tasks(
+    client: &Client,
+    table_name: &str,
+    id: &str,
+    after: Option<&str>,
+    limit: Option<u64>,
+) -> anyhow::Result<Vec<Task>> {
+    let mut key_condition = "#project_id = :project_id".to_string();
+    let mut names = HashMap::from([("#project_id".to_string(), "project_id".to_string())]);
+    let mut values =
+        HashMap::from([(":project_id".to_string(), AttributeValue::S(id.to_string()))]);
+
+    if let Some(after) = after {
+        key_condition.push_str(" AND #id > :after");
+        names.insert("#id".to_string(), "id".to_string());
+        values.insert(":after".to_string(), AttributeValue::S(after.to_string()));
+    }
+
+    let mut tasks = vec![];
+    let mut start_key = None;
+
+    loop {
+        let results = client
+            .query()
+            .table_name(table_name)
+            .index_name(PROJECT_INDEX)
+            .key_condition_expression(&key_condition)
+            .set_expression_attribute_names(Some(names.clone()))
+            .set_expression_attribute_values(Some(values.clone()))
+            .set_limit(limit.map(i32::try_from).transpose()?)
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        for item in results.items.unwrap_or_default() {
+            tasks.push(dynamo::from_item(item)?);
+
+            if limit.is_some_and(|limit| tasks.len() as u64 >= limit) {
+                return Ok(tasks);
+            }
+        }
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(tasks),
+        }
+    }
+}
+
+/// Apply an update expression to a Project, on the condition that the item is a Project
+async fn update_project(
+    client: &Client,
+    table_name: &str,
+    id: &str,
+    expression: String,
+    names: HashMap<String, String>,
+    mut values: HashMap<String, AttributeValue>,
+) -> anyhow::Result<Project> {
+    values.insert(
+        ":entity".to_string(),
+        AttributeValue::S(PROJECT_ENTITY.to_string()),
+    );
+
+    let result = client
+        .update_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(id.to_string()))
+        .update_expression(expression)
+        .condition_expression("#entity = :entity")
+        .set_expression_attribute_names(Some(names))
+        .set_expression_attribute_values(Some(values))
+        .return_values(ReturnValue::AllNew)
+        .send()
+        .await
+        .map_err(|err| {
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception())
+            {
+                Error::NotFound.into()
+            } else {
+                anyhow::Error::from(err)
+            }
+        })?;
+
+    let project = dynamo::from_item(result.attributes.ok_or(anyhow!("No attributes returned"))?)?;
+
+    Ok(project)
+}
+
+/// Whether an item is a Project
+fn is_project(item: &Item) -> bool {
+    item.get(ENTITY_ATTRIBUTE) == Some(&AttributeValue::S(PROJECT_ENTITY.to_string()))
+}
+
+/// Convert a `Project` into an item, marked with its entity so it's told apart from Tasks
+fn project_item(project: &Project, format: TimestampFormat) -> anyhow::Result<Item> {
+    let mut item = dynamo::to_item(project, format)?;
+
+    item.insert(
+        ENTITY_ATTRIBUTE.to_string(),
+        AttributeValue::S(PROJECT_ENTITY.to_string()),
+    );
+
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_project_item() -> anyhow::Result<()> {
+        let now = chrono::Utc::now().fixed_offset().trunc_subsecs(3);
+
+        let project = Project {
+            id: "01HX".to_string(),
+            created_at: now,
+            updated_at: now,
+            name: "Launch".to_string(),
+            ..Default::default()
+        };
+
+        let item = project_item(&project, TimestampFormat::Rfc3339)?;
+
+        assert!(is_project(&item));
+        assert!(!item.contains_key("archived_at"));
+        assert_eq!(dynamo::from_item::<Project>(item)?, project);
+
+        Ok(())
+    }
+}
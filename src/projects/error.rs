@@ -0,0 +1,23 @@
+/// Project errors that callers respond to differently, carried inside `anyhow::Error`
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The Project does not exist
+    #[error("Project not found")]
+    NotFound,
+
+    /// The Project is archived, so it doesn't accept new Tasks
+    #[error("Project {0} is archived")]
+    Archived(String),
+}
+
+impl Error {
+    /// Check whether the error is, or wraps, a `NotFound` error
+    pub fn is_not_found(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::NotFound))
+    }
+
+    /// Check whether the error is, or wraps, an `Archived` error
+    pub fn is_archived(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::Archived(_)))
+    }
+}
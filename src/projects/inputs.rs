@@ -0,0 +1,100 @@
+use async_graphql::InputObject;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{tasks::inputs::page_size, utils};
+
+/// The `CreateProjectInput` input type
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, InputObject, ToSchema)]
+#[graphql(name = "CreateProjectInput")]
+#[schema(as = CreateProjectInput)]
+pub struct Create {
+    /// The Project's name
+    pub name: String,
+
+    /// The Project's description
+    pub description: Option<String>,
+}
+
+/// The `UpdateProjectInput` input type. An omitted field is left unchanged, an explicit `null`
+/// clears it, and any other value replaces it.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default)]
+#[schema(as = UpdateProjectInput)]
+pub struct Update {
+    /// The Project's name, which cannot be cleared
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub name: utils::Update<String>,
+
+    /// The Project's description
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub description: utils::Update<String>,
+}
+
+/// The `ListProjectsInput` query parameters for a page of Projects, in id order
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct List {
+    /// The cursor returned with the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+
+    /// The maximum number of Projects to return, defaults to 20 and is capped at 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+
+    /// Include archived Projects, which are left out by default
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_archived: bool,
+}
+
+impl List {
+    /// The requested page size, with the default and maximum applied
+    pub fn page_size(&self) -> usize {
+        page_size(self.limit)
+    }
+}
+
+/// The `ListProjectTasksInput` query parameters for a page of a Project's Tasks, in id order
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListTasks {
+    /// The cursor returned with the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+
+    /// The maximum number of Tasks to return, defaults to 20 and is capped at 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl ListTasks {
+    /// The requested page size, with the default and maximum applied
+    pub fn page_size(&self) -> usize {
+        page_size(self.limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_update_omitted_and_null_fields() -> anyhow::Result<()> {
+        let input: Update = serde_json::from_str(r#"{"description": null}"#)?;
+
+        assert_eq!(
+            input,
+            Update {
+                name: utils::Update::Unchanged,
+                description: utils::Update::Empty,
+            }
+        );
+
+        Ok(())
+    }
+}
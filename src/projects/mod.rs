@@ -0,0 +1,20 @@
+/// The Project Model
+pub mod model;
+
+/// The Project entity general-purpose service
+pub mod service;
+
+/// The Project entity DynamoDB service
+pub mod dynamo_service;
+
+/// The Project error types
+pub mod error;
+
+/// The Project entity input types
+pub mod inputs;
+
+/// Project operations on the Task store for the configured `DataStore`
+pub mod store;
+
+/// The Project GraphQL resolvers
+pub mod resolvers;
@@ -0,0 +1,162 @@
+use async_graphql::SimpleObject;
+use chrono::Utc;
+use sea_orm::{entity::prelude::*, Set};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use utoipa::ToSchema;
+
+use crate::utils::{
+    dynamo::DynamoItem,
+    timezone::{Timezone, WithTimestamps},
+};
+
+#[cfg(test)]
+use fake::Dummy;
+
+/// The Project Model
+#[derive(
+    Clone, Debug, Eq, PartialEq, DeriveEntityModel, Deserialize, Serialize, SimpleObject, ToSchema,
+)]
+#[cfg_attr(test, derive(Dummy))]
+#[sea_orm(table_name = "projects")]
+#[graphql(name = "Project", complex)]
+#[schema(as = Project)]
+pub struct Model {
+    /// The Project id
+    #[sea_orm(primary_key, column_type = "Text")]
+    pub id: String,
+
+    /// The date the Project was created
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTimeWithTimeZone,
+
+    /// The date the Project was last updated
+    #[schema(value_type = String, format = DateTime)]
+    pub updated_at: DateTimeWithTimeZone,
+
+    /// The Project name
+    #[sea_orm(column_type = "Text")]
+    pub name: String,
+
+    /// An optional Project description
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+
+    /// When the Project was archived, if it has been. Archived Projects don't accept new Tasks.
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub archived_at: Option<DateTimeWithTimeZone>,
+}
+
+/// The name for a Sea ORM model must be "Model", so this provides a convenient alias
+pub type Project = Model;
+
+/// Project entity relationships
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// The Tasks in the Project
+    #[sea_orm(has_many = "crate::tasks::model::Entity")]
+    Tasks,
+}
+
+impl Related<crate::tasks::model::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tasks.def()
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    /// Generate a ULID and timestamps for new Projects, and bump `updated_at` on every update
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let now = Utc::now().fixed_offset();
+
+        if insert {
+            if self.id.is_not_set() {
+                self.id = Set(Ulid::new().to_string());
+            }
+
+            if self.created_at.is_not_set() {
+                self.created_at = Set(now);
+            }
+
+            if self.updated_at.is_not_set() {
+                self.updated_at = Set(now);
+            }
+        } else {
+            self.updated_at = Set(now);
+        }
+
+        Ok(self)
+    }
+}
+
+impl Model {
+    /// Whether the Project has been archived
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+}
+
+impl WithTimestamps for Model {
+    /// Render the timestamps in the given timezone
+    fn in_timezone(self, timezone: &Timezone) -> Self {
+        Self {
+            created_at: timezone.convert(self.created_at),
+            updated_at: timezone.convert(self.updated_at),
+            archived_at: self
+                .archived_at
+                .map(|archived_at| timezone.convert(archived_at)),
+            ..self
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            id: String::default(),
+            created_at: Utc::now().fixed_offset(),
+            updated_at: Utc::now().fixed_offset(),
+            name: String::default(),
+            description: None,
+            archived_at: None,
+        }
+    }
+}
+
+impl DynamoItem for Model {
+    const TIMESTAMPS: &'static [&'static str] = &["created_at", "updated_at", "archived_at"];
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::SubsecRound;
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+
+    use crate::{config::TimestampFormat, utils::dynamo};
+
+    use super::*;
+
+    #[test]
+    fn test_item_round_trip() -> anyhow::Result<()> {
+        let project: Project = Faker.fake();
+
+        let mut expected = project.clone();
+        expected.created_at = expected.created_at.trunc_subsecs(3);
+        expected.updated_at = expected.updated_at.trunc_subsecs(3);
+        expected.archived_at = expected
+            .archived_at
+            .map(|archived_at| archived_at.trunc_subsecs(3));
+
+        assert_eq!(
+            dynamo::from_item::<Project>(dynamo::to_item(&project, TimestampFormat::EpochMillis)?)?,
+            expected
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    connection::{query, Connection, Edge},
+    ComplexObject, Context, Error, InputObject, MaybeUndefined, Object, Result,
+};
+
+use crate::{
+    attachments::storage::Storage,
+    tasks::{
+        model::Task,
+        store::{Store, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+        workflow::Workflow,
+    },
+};
+
+use super::{inputs, model::Project};
+
+/// The `UpdateProjectInput` input type, where an omitted field is left unchanged and an explicit
+/// `null` clears it
+#[derive(Clone, Default, Eq, PartialEq, InputObject)]
+pub struct UpdateProjectInput {
+    /// The Project's name
+    pub name: MaybeUndefined<String>,
+
+    /// The Project's description
+    pub description: MaybeUndefined<String>,
+}
+
+impl From<UpdateProjectInput> for inputs::Update {
+    fn from(input: UpdateProjectInput) -> Self {
+        Self {
+            name: input.name.into(),
+            description: input.description.into(),
+        }
+    }
+}
+
+/// Fields resolved from other entities
+#[ComplexObject]
+impl Project {
+    /// Page through the Project's Tasks using a cursor-based connection
+    async fn tasks(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<String, Task>> {
+        let store = ctx.data::<Store>()?;
+
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _before: Option<String>, first, _last| async move {
+                let input = inputs::ListTasks {
+                    after: after.clone(),
+                    limit: Some(first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)),
+                };
+
+                let page = store.page_project_tasks(&self.id, &input).await?;
+
+                let mut connection = Connection::new(after.is_some(), page.next_cursor.is_some());
+                connection.edges.extend(
+                    page.tasks
+                        .into_iter()
+                        .map(|task| Edge::new(task.id.clone(), task)),
+                );
+
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+}
+
+/// The Query segment for Projects
+#[derive(Default)]
+pub struct ProjectsQuery;
+
+/// Queries for the `Project` model
+#[Object]
+impl ProjectsQuery {
+    /// Get a single Project by id
+    async fn project(&self, ctx: &Context<'_>, id: String) -> Result<Option<Project>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.get_project(&id).await?)
+    }
+
+    /// Page through Projects using a cursor-based connection, leaving out archived Projects
+    /// unless they're included
+    async fn projects(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+        #[graphql(default)] include_archived: bool,
+    ) -> Result<Connection<String, Project>> {
+        let store = ctx.data::<Store>()?;
+
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _before: Option<String>, first, _last| async move {
+                let input = inputs::List {
+                    after: after.clone(),
+                    limit: Some(first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)),
+                    include_archived,
+                };
+
+                let page = store.page_projects(&input).await?;
+
+                let mut connection = Connection::new(after.is_some(), page.next_cursor.is_some());
+                connection.edges.extend(
+                    page.projects
+                        .into_iter()
+                        .map(|project| Edge::new(project.id.clone(), project)),
+                );
+
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+}
+
+/// The Mutation segment for Projects
+#[derive(Default)]
+pub struct ProjectsMutation;
+
+/// Mutations for the `Project` model
+#[Object]
+impl ProjectsMutation {
+    /// Create a new Project
+    async fn create_project(&self, ctx: &Context<'_>, input: inputs::Create) -> Result<Project> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.create_project(&input).await?)
+    }
+
+    /// Update an existing Project
+    async fn update_project(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        input: UpdateProjectInput,
+    ) -> Result<Project> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.update_project(&id, &input.into()).await?)
+    }
+
+    /// Archive a Project, cancelling the Tasks the workflow allows to be cancelled
+    async fn archive_project(&self, ctx: &Context<'_>, id: String) -> Result<Project> {
+        let store = ctx.data::<Store>()?;
+        let workflow = ctx.data::<Workflow>()?;
+
+        Ok(store.archive_project(workflow, &id).await?)
+    }
+
+    /// Restore an archived Project
+    async fn restore_project(&self, ctx: &Context<'_>, id: String) -> Result<Project> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.restore_project(&id).await?)
+    }
+
+    /// Remove an existing Project with all of its Tasks and the files attached to them
+    async fn delete_project(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let store = ctx.data::<Store>()?;
+        let storage = ctx.data::<Arc<dyn Storage>>()?;
+
+        store.delete_project(storage.as_ref(), &id).await?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,314 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    EntityTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set,
+    TransactionTrait,
+};
+
+use crate::{
+    attachments::{self, storage::Storage},
+    tasks::{
+        self,
+        model::Task,
+        workflow::{Status, Workflow},
+    },
+    utils::Update::{Empty, Unchanged, Value},
+};
+
+use super::{
+    error::Error,
+    inputs,
+    model::{self, Project},
+};
+
+/// Get an individual `Project` by id
+pub async fn get(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Option<Project>> {
+    let project = model::Entity::find_by_id(id.to_string()).one(&*db).await?;
+
+    Ok(project)
+}
+
+/// List up to `limit` `Project`s in id order, starting after the given cursor
+pub async fn list(
+    db: Arc<DatabaseConnection>,
+    input: &inputs::List,
+    limit: u64,
+) -> anyhow::Result<Vec<Project>> {
+    let mut query = model::Entity::find().order_by_asc(model::Column::Id);
+
+    if let Some(after) = &input.after {
+        query = query.filter(model::Column::Id.gt(after));
+    }
+
+    if !input.include_archived {
+        query = query.filter(model::Column::ArchivedAt.is_null());
+    }
+
+    let projects = query.limit(limit).all(&*db).await?;
+
+    Ok(projects)
+}
+
+/// Create a `Project` with the given input
+pub async fn create(
+    db: Arc<DatabaseConnection>,
+    input: &inputs::Create,
+) -> anyhow::Result<Project> {
+    let project = model::ActiveModel {
+        name: Set(input.name.clone()),
+        description: Set(input.description.clone()),
+        ..Default::default()
+    }
+    .insert(&*db)
+    .await?;
+
+    Ok(project)
+}
+
+/// Update an existing `Project` by id
+pub async fn update(
+    db: Arc<DatabaseConnection>,
+    id: &str,
+    input: &inputs::Update,
+) -> anyhow::Result<Project> {
+    let project = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    let mut project: model::ActiveModel = project.into();
+
+    match &input.name {
+        Unchanged | Empty => (),
+        Value(value) => project.name = Set(value.clone()),
+    }
+
+    match &input.description {
+        Unchanged => (),
+        Empty => project.description = Set(None),
+        Value(value) => project.description = Set(Some(value.clone())),
+    }
+
+    let updated = project.update(&*db).await?;
+
+    Ok(updated)
+}
+
+/// Archive a `Project`, cancelling each of its Tasks the workflow allows to be cancelled.
+/// Archiving an archived Project keeps its original archive date.
+pub async fn archive(
+    db: Arc<DatabaseConnection>,
+    workflow: &Workflow,
+    id: &str,
+) -> anyhow::Result<Project> {
+    let txn = db.begin().await?;
+
+    let project = model::Entity::find_by_id(id.to_string())
+        .one(&txn)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let now = Utc::now().fixed_offset();
+
+    let project = if project.is_archived() {
+        project
+    } else {
+        let mut project: model::ActiveModel = project.into();
+        project.archived_at = Set(Some(now));
+        project.update(&txn).await?
+    };
+
+    tasks::model::Entity::update_many()
+        .col_expr(tasks::model::Column::Status, Expr::value(Status::Cancelled))
+        .col_expr(tasks::model::Column::UpdatedAt, Expr::value(now))
+        .filter(tasks::model::Column::ProjectId.eq(id))
+        .filter(tasks::model::Column::Status.is_in(workflow.sources(Status::Cancelled)))
+        .exec(&txn)
+        .await?;
+
+    txn.commit().await?;
+
+    Ok(project)
+}
+
+/// Restore an archived `Project`, so it accepts new Tasks again. Cancelled Tasks stay cancelled.
+pub async fn restore(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Project> {
+    let project = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    if !project.is_archived() {
+        return Ok(project);
+    }
+
+    let mut project: model::ActiveModel = project.into();
+    project.archived_at = Set(None);
+
+    let restored = project.update(&*db).await?;
+
+    Ok(restored)
+}
+
+/// Delete an existing `Project` with all of its Tasks and the bytes of their Attachments,
+/// returning it
+pub async fn delete(
+    db: Arc<DatabaseConnection>,
+    storage: &dyn Storage,
+    id: &str,
+) -> anyhow::Result<Project> {
+    let project = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    // Listed first, since the foreign keys delete the Attachments along with the Tasks
+    let task_ids = tasks::model::Entity::find()
+        .select_only()
+        .column(tasks::model::Column::Id)
+        .filter(tasks::model::Column::ProjectId.eq(id))
+        .into_query();
+
+    let attachments = attachments::model::Entity::find()
+        .filter(attachments::model::Column::TaskId.in_subquery(task_ids))
+        .all(&*db)
+        .await?;
+
+    // The foreign key deletes the Project's Tasks
+    let result = project.clone().delete(&*db).await?;
+
+    // Another request deleted the Project after it was read
+    if result.rows_affected == 0 {
+        return Err(Error::NotFound.into());
+    }
+
+    attachments::storage::purge(storage, &attachments).await;
+
+    Ok(project)
+}
+
+/// List up to `limit` of a `Project`'s Tasks in id order, starting after the given cursor
+pub async fn list_tasks(
+    db: Arc<DatabaseConnection>,
+    id: &str,
+    after: Option<&str>,
+    limit: u64,
+) -> anyhow::Result<Vec<Task>> {
+    let project = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    let mut query = project
+        .find_related(tasks::model::Entity)
+        .order_by_asc(tasks::model::Column::Id);
+
+    if let Some(after) = after {
+        query = query.filter(tasks::model::Column::Id.gt(after));
+    }
+
+    let tasks = query.limit(limit).all(&*db).await?;
+
+    Ok(tasks)
+}
+
+/// Check that a Project exists and isn't archived, so Tasks can be added to it
+pub async fn check_active<C: ConnectionTrait>(db: &C, id: &str) -> anyhow::Result<()> {
+    let project = model::Entity::find_by_id(id.to_string())
+        .one(db)
+        .await?
+        .ok_or_else(|| tasks::error::Error::ProjectNotFound(id.to_string()))?;
+
+    if project.is_archived() {
+        return Err(Error::Archived(project.id).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list() -> anyhow::Result<()> {
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<Project>::new()])
+                .into_connection(),
+        );
+
+        let input = inputs::List {
+            after: Some("01HX".to_string()),
+            ..Default::default()
+        };
+
+        list(db.clone(), &input, 21).await?;
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        assert_eq!(
+            log,
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "projects"."id", "projects"."created_at", "projects"."updated_at", "projects"."name", "projects"."description", "projects"."archived_at" FROM "projects" WHERE "projects"."id" > $1 AND "projects"."archived_at" IS NULL ORDER BY "projects"."id" ASC LIMIT $2"#,
+                ["01HX".into(), 21u64.into()],
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_archive_cancels_tasks() -> anyhow::Result<()> {
+        let project = Project {
+            archived_at: None,
+            ..Faker.fake()
+        };
+
+        let archived = Project {
+            archived_at: Some(Utc::now().fixed_offset()),
+            ..project.clone()
+        };
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![project.clone()], vec![archived.clone()]])
+                .append_exec_results([MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 2,
+                }])
+                .into_connection(),
+        );
+
+        let result = archive(db.clone(), &Workflow::default(), &project.id).await?;
+
+        assert_eq!(result, archived);
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        // The Project and its Tasks are updated in one transaction
+        assert_eq!(log.len(), 1);
+        assert!(format!("{log:?}").contains("cancelled"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_active() {
+        let archived = Project {
+            archived_at: Some(Utc::now().fixed_offset()),
+            ..Faker.fake()
+        };
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![archived.clone()], vec![]])
+            .into_connection();
+
+        let err = check_active(&db, &archived.id).await.unwrap_err();
+
+        assert!(Error::is_archived(&err));
+
+        let err = check_active(&db, "missing").await.unwrap_err();
+
+        assert!(tasks::error::Error::is_project_not_found(&err));
+    }
+}
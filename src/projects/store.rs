@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    attachments::storage::Storage,
+    tasks::{
+        inputs::Order,
+        store::{Page, Store},
+        workflow::Workflow,
+    },
+};
+
+use super::{dynamo_service, inputs, model::Project, service};
+
+/// A page of `Project`s
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[schema(as = ProjectPage)]
+pub struct ProjectPage {
+    /// The Projects on this page
+    pub projects: Vec<Project>,
+
+    /// The cursor to request the next page with, if there is one
+    pub next_cursor: Option<String>,
+}
+
+impl ProjectPage {
+    /// Build a page from up to `limit + 1` Projects in id order, where the extra Project signals a
+    /// next page
+    pub fn new(mut projects: Vec<Project>, limit: usize) -> Self {
+        let has_next_page = projects.len() > limit;
+        projects.truncate(limit);
+
+        let next_cursor = projects
+            .last()
+            .filter(|_| has_next_page)
+            .map(|project| project.id.clone());
+
+        Self {
+            projects,
+            next_cursor,
+        }
+    }
+}
+
+/// Projects live in the same data store as their Tasks, so the Task store serves them too
+impl Store {
+    /// Get an individual `Project` by id
+    pub async fn get_project(&self, id: &str) -> anyhow::Result<Option<Project>> {
+        match self {
+            Store::Database(db) => service::get(db.clone(), id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::get(client.clone(), table_name, id).await,
+        }
+    }
+
+    /// Get a page of `Project`s, starting after the given cursor
+    pub async fn page_projects(&self, input: &inputs::List) -> anyhow::Result<ProjectPage> {
+        let limit = input.page_size();
+
+        // Fetch one extra Project to find out if there is another page
+        let projects = match self {
+            Store::Database(db) => service::list(db.clone(), input, limit as u64 + 1).await?,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::list(client.clone(), table_name, input, limit as u64 + 1).await?,
+        };
+
+        Ok(ProjectPage::new(projects, limit))
+    }
+
+    /// Create a `Project` with the given input
+    pub async fn create_project(&self, input: &inputs::Create) -> anyhow::Result<Project> {
+        match self {
+            Store::Database(db) => service::create(db.clone(), input).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => dynamo_service::create(client.clone(), table_name, *timestamp_format, input).await,
+        }
+    }
+
+    /// Update an existing `Project` by id
+    pub async fn update_project(
+        &self,
+        id: &str,
+        input: &inputs::Update,
+    ) -> anyhow::Result<Project> {
+        match self {
+            Store::Database(db) => service::update(db.clone(), id, input).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => {
+                dynamo_service::update(client.clone(), table_name, *timestamp_format, id, input)
+                    .await
+            }
+        }
+    }
+
+    /// Archive a `Project`, cancelling the Tasks the workflow allows to be cancelled
+    pub async fn archive_project(&self, workflow: &Workflow, id: &str) -> anyhow::Result<Project> {
+        match self {
+            Store::Database(db) => service::archive(db.clone(), workflow, id).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => {
+                dynamo_service::archive(client.clone(), table_name, *timestamp_format, workflow, id)
+                    .await
+            }
+        }
+    }
+
+    /// Restore an archived `Project`
+    pub async fn restore_project(&self, id: &str) -> anyhow::Result<Project> {
+        match self {
+            Store::Database(db) => service::restore(db.clone(), id).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => dynamo_service::restore(client.clone(), table_name, *timestamp_format, id).await,
+        }
+    }
+
+    /// Delete an existing `Project` with all of its Tasks and the bytes of their Attachments,
+    /// returning it
+    pub async fn delete_project(&self, storage: &dyn Storage, id: &str) -> anyhow::Result<Project> {
+        match self {
+            Store::Database(db) => service::delete(db.clone(), storage, id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::delete(client.clone(), table_name, storage, id).await,
+        }
+    }
+
+    /// Get a page of a `Project`'s Tasks, starting after the given cursor
+    pub async fn page_project_tasks(
+        &self,
+        id: &str,
+        input: &inputs::ListTasks,
+    ) -> anyhow::Result<Page> {
+        let limit = input.page_size();
+        let after = input.after.as_deref();
+
+        // Fetch one extra Task to find out if there is another page
+        let tasks = match self {
+            Store::Database(db) => {
+                service::list_tasks(db.clone(), id, after, limit as u64 + 1).await?
+            }
+            Store::Dynamo {
+                client, table_name, ..
+            } => {
+                dynamo_service::list_tasks(client.clone(), table_name, id, after, limit as u64 + 1)
+                    .await?
+            }
+        };
+
+        Ok(Page::new(tasks, limit, Order::Id))
+    }
+}
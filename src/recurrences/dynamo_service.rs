@@ -0,0 +1,780 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::anyhow;
+use aws_sdk_dynamodb::{
+    types::{AttributeValue, Put, ReturnValue, TransactWriteItem},
+    Client,
+};
+use chrono::{Duration, SecondsFormat, SubsecRound, Utc};
+use sea_orm::prelude::{DateTimeUtc, DateTimeWithTimeZone};
+use ulid::Ulid;
+
+use crate::{
+    attachments::storage::Storage,
+    config::TimestampFormat,
+    projects,
+    tasks::{
+        self,
+        dynamo_service::{failed_conditions, task_item},
+        dynamo_table::{ENTITY_ATTRIBUTE, OCCURRENCE_ENTITY, OCCURRENCE_INDEX, RECURRENCE_ENTITY},
+        model::Task,
+        workflow::Status,
+    },
+    utils::{
+        dynamo::{self, Item},
+        timestamp,
+    },
+};
+
+use super::{error::Error, inputs, model::Recurrence};
+
+/// The attribute a Recurrence's Project is kept in. It isn't called `project_id`, since that
+/// would list the Recurrence among the Project's Tasks in the Project index.
+const PROJECT_ATTRIBUTE: &str = "recurrence_project_id";
+
+/// Get an individual `Recurrence` by id
+pub async fn get(
+    client: Arc<Client>,
+    table_name: &str,
+    id: &str,
+) -> anyhow::Result<Option<Recurrence>> {
+    let results = client
+        .get_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(id.to_string()))
+        .send()
+        .await?;
+
+    match results.item {
+        Some(item) if is_recurrence(&item) => Ok(Some(from_recurrence_item(item)?)),
+        _ => Ok(None),
+    }
+}
+
+/// List up to `limit` `Recurrence`s in table scan order, starting after the given cursor.
+/// Recurrences share the table with Tasks, so this scans it.
+pub async fn list(
+    client: Arc<Client>,
+    table_name: &str,
+    input: &inputs::List,
+    limit: u64,
+) -> anyhow::Result<Vec<Recurrence>> {
+    let start_key = input
+        .after
+        .as_ref()
+        .map(|after| HashMap::from([("id".to_string(), AttributeValue::S(after.clone()))]));
+
+    scan(&client, table_name, None, start_key, Some(limit)).await
+}
+
+/// Create a `Recurrence` with the given input, then generate its Tasks up to the horizon
+pub async fn create(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    horizon: Duration,
+    input: &inputs::Create,
+) -> anyhow::Result<Recurrence> {
+    // Truncated so the returned Recurrence matches what reads return in every format
+    let now = Utc::now().fixed_offset().trunc_subsecs(3);
+
+    let recurrence = Recurrence {
+        id: Ulid::new().to_string(),
+        created_at: now,
+        updated_at: now,
+        title: input.title.clone(),
+        description: input.description.clone(),
+        priority: input.priority,
+        project_id: input.project_id.clone(),
+        rule: input.rule.trim().to_string(),
+        starts_at: input.starts_at.trunc_subsecs(0),
+        timezone: input.timezone.clone().unwrap_or_else(|| "UTC".to_string()),
+        ended_at: None,
+        generated_until: None,
+    };
+
+    recurrence.validate()?;
+
+    if let Some(project_id) = &recurrence.project_id {
+        projects::dynamo_service::check_active(client.clone(), table_name, project_id).await?;
+    }
+
+    client
+        .put_item()
+        .table_name(table_name)
+        .set_item(Some(recurrence_item(&recurrence, format)?))
+        .condition_expression("attribute_not_exists(id)")
+        .send()
+        .await?;
+
+    let (recurrence, _) =
+        expand(client, table_name, format, recurrence, Utc::now() + horizon).await?;
+
+    Ok(recurrence)
+}
+
+/// Update an existing `Recurrence` by id. Upcoming Tasks that haven't been started are deleted
+/// and generated again to match, while skipped occurrences stay skipped.
+pub async fn update(
+    client: Arc<Client>,
+    table_name: &str,
+    storage: &dyn Storage,
+    format: TimestampFormat,
+    horizon: Duration,
+    id: &str,
+    input: &inputs::Update,
+) -> anyhow::Result<Recurrence> {
+    let existing = get(client.clone(), table_name, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if existing.is_ended() {
+        return Err(Error::Ended(existing.id).into());
+    }
+
+    let updated = input.apply(existing.clone());
+
+    updated.validate()?;
+
+    if let Some(project_id) = &updated.project_id {
+        if existing.project_id.as_ref() != Some(project_id) {
+            projects::dynamo_service::check_active(client.clone(), table_name, project_id).await?;
+        }
+    }
+
+    let now = Utc::now().fixed_offset().trunc_subsecs(3);
+
+    prune(&client, table_name, storage, id, now).await?;
+
+    let recurrence = Recurrence {
+        updated_at: now,
+        rule: updated.rule.trim().to_string(),
+        starts_at: updated.starts_at.trunc_subsecs(0),
+        generated_until: existing
+            .generated_until
+            .map(|generated_until| generated_until.min(now)),
+        ..updated
+    };
+
+    client
+        .put_item()
+        .table_name(table_name)
+        .set_item(Some(recurrence_item(&recurrence, format)?))
+        .condition_expression("#entity = :entity AND attribute_not_exists(#ended_at)")
+        .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+        .expression_attribute_names("#ended_at", "ended_at")
+        .expression_attribute_values(":entity", AttributeValue::S(RECURRENCE_ENTITY.to_string()))
+        .send()
+        .await
+        .map_err(|err| {
+            // Another request deleted or ended the Recurrence after it was read
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception())
+            {
+                Error::Ended(id.to_string()).into()
+            } else {
+                anyhow::Error::from(err)
+            }
+        })?;
+
+    let (recurrence, _) =
+        expand(client, table_name, format, recurrence, Utc::now() + horizon).await?;
+
+    Ok(recurrence)
+}
+
+/// Delete an existing `Recurrence` with its occurrences, returning it. The Tasks it generated
+/// are kept.
+pub async fn delete(client: Arc<Client>, table_name: &str, id: &str) -> anyhow::Result<Recurrence> {
+    let result = client
+        .delete_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(id.to_string()))
+        .condition_expression("#entity = :entity")
+        .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+        .expression_attribute_values(":entity", AttributeValue::S(RECURRENCE_ENTITY.to_string()))
+        .return_values(ReturnValue::AllOld)
+        .send()
+        .await
+        .map_err(|err| {
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception())
+            {
+                Error::NotFound.into()
+            } else {
+                anyhow::Error::from(err)
+            }
+        })?;
+
+    let item = result.attributes.ok_or(Error::NotFound)?;
+
+    for occurrence in occurrences_after(&client, table_name, id, None).await? {
+        if let Some(key) = occurrence.get("id") {
+            client
+                .delete_item()
+                .table_name(table_name)
+                .key("id", key.clone())
+                .send()
+                .await?;
+        }
+    }
+
+    from_recurrence_item(item)
+}
+
+/// Skip an occurrence of a `Recurrence`, deleting its Task if one was generated and hasn't been
+/// started. A skipped occurrence is never generated again.
+pub async fn skip(
+    client: Arc<Client>,
+    table_name: &str,
+    storage: &dyn Storage,
+    id: &str,
+    input: &inputs::Skip,
+) -> anyhow::Result<Recurrence> {
+    let recurrence = get(client.clone(), table_name, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if recurrence.is_ended() {
+        return Err(Error::Ended(recurrence.id).into());
+    }
+
+    recurrence.check_occurrence(input.occurs_at)?;
+
+    let key = occurrence_key(id, input.occurs_at.to_utc());
+
+    loop {
+        let existing = client
+            .get_item()
+            .table_name(table_name)
+            .key("id", AttributeValue::S(key.clone()))
+            .send()
+            .await?
+            .item;
+
+        if let Some(existing) = existing {
+            let Some(task_id) = existing
+                .get("task_id")
+                .and_then(|task_id| task_id.as_s().ok())
+            else {
+                return Ok(recurrence);
+            };
+
+            if let Some(task) =
+                tasks::dynamo_service::get(client.clone(), table_name, task_id).await?
+            {
+                if task.status != Status::Todo {
+                    return Err(Error::AlreadyStarted(task.id).into());
+                }
+
+                match tasks::dynamo_service::delete(client.clone(), table_name, storage, &task.id)
+                    .await
+                {
+                    Ok(_) => (),
+                    Err(err) if tasks::error::Error::is_not_found(&err) => (),
+                    Err(err) => return Err(err),
+                }
+            }
+
+            client
+                .update_item()
+                .table_name(table_name)
+                .key("id", AttributeValue::S(key))
+                .update_expression("REMOVE #task_id")
+                .expression_attribute_names("#task_id", "task_id")
+                .send()
+                .await?;
+
+            return Ok(recurrence);
+        }
+
+        let result = client
+            .put_item()
+            .table_name(table_name)
+            .set_item(Some(occurrence_item(id, input.occurs_at.to_utc(), None)))
+            .condition_expression("attribute_not_exists(id)")
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => return Ok(recurrence),
+            // The occurrence was generated in the meantime, so its Task is deleted instead
+            Err(err)
+                if err
+                    .as_service_error()
+                    .is_some_and(|err| err.is_conditional_check_failed_exception()) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// End a `Recurrence`, deleting the Tasks generated after it ends that haven't been started
+pub async fn end(
+    client: Arc<Client>,
+    table_name: &str,
+    storage: &dyn Storage,
+    format: TimestampFormat,
+    id: &str,
+    input: &inputs::End,
+) -> anyhow::Result<Recurrence> {
+    let recurrence = get(client.clone(), table_name, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if recurrence.is_ended() {
+        return Err(Error::Ended(recurrence.id).into());
+    }
+
+    let now = Utc::now().fixed_offset().trunc_subsecs(3);
+    let ended_at = input.ends_at.unwrap_or(now).trunc_subsecs(0);
+
+    prune(&client, table_name, storage, id, ended_at).await?;
+
+    let result = client
+        .update_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(id.to_string()))
+        .update_expression("SET #ended_at = :ended_at, #updated_at = :updated_at")
+        .condition_expression("#entity = :entity AND attribute_not_exists(#ended_at)")
+        .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+        .expression_attribute_names("#ended_at", "ended_at")
+        .expression_attribute_names("#updated_at", "updated_at")
+        .expression_attribute_values(":entity", AttributeValue::S(RECURRENCE_ENTITY.to_string()))
+        .expression_attribute_values(":ended_at", timestamp::to_attribute(ended_at, format))
+        .expression_attribute_values(":updated_at", timestamp::to_attribute(now, format))
+        .return_values(ReturnValue::AllNew)
+        .send()
+        .await
+        .map_err(|err| {
+            // Another request deleted or ended the Recurrence after it was read
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception())
+            {
+                Error::Ended(id.to_string()).into()
+            } else {
+                anyhow::Error::from(err)
+            }
+        })?;
+
+    from_recurrence_item(result.attributes.ok_or(anyhow!("No attributes returned"))?)
+}
+
+/// The Recurrences that may have occurrences left to generate, in table scan order
+pub async fn due(client: Arc<Client>, table_name: &str) -> anyhow::Result<Vec<Recurrence>> {
+    let filter = "attribute_not_exists(#ended_at) OR attribute_not_exists(#generated_until) \
+                  OR #generated_until < #ended_at";
+
+    scan(&client, table_name, Some(filter), None, None).await
+}
+
+/// Generate a Task for each of a `Recurrence`'s occurrences after the ones already generated, up
+/// to `until`, returning the Recurrence with its new watermark and the number of Tasks generated.
+/// Each Task is written in a transaction with its occurrence on the condition that the
+/// occurrence is new, so repeating this, or running it from more than one server at once, never
+/// generates an occurrence twice.
+pub async fn expand(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    recurrence: Recurrence,
+    until: DateTimeUtc,
+) -> anyhow::Result<(Recurrence, usize)> {
+    if let Some(project_id) = &recurrence.project_id {
+        projects::dynamo_service::check_active(client.clone(), table_name, project_id).await?;
+    }
+
+    let after = recurrence.generated_until;
+
+    let pending: Vec<_> = recurrence
+        .occurrences()?
+        .skip_while(|occurrence| after.is_some_and(|after| *occurrence <= after))
+        .take_while(|occurrence| *occurrence <= until)
+        .collect();
+
+    let mut generated = 0;
+
+    for occurs_at in pending {
+        let now = Utc::now().fixed_offset().trunc_subsecs(3);
+
+        let task = Task {
+            id: Ulid::new().to_string(),
+            created_at: now,
+            updated_at: now,
+            title: recurrence.title.clone(),
+            description: recurrence.description.clone(),
+            status: Status::default(),
+            due_at: Some(occurs_at.fixed_offset()),
+            priority: recurrence.priority,
+            parent_id: None,
+            project_id: recurrence.project_id.clone(),
+        };
+
+        let new = |item: Item| {
+            Put::builder()
+                .table_name(table_name)
+                .set_item(Some(item))
+                .condition_expression("attribute_not_exists(#id)")
+                .expression_attribute_names("#id", "id")
+                .build()
+        };
+
+        let result = client
+            .transact_write_items()
+            .transact_items(
+                TransactWriteItem::builder()
+                    .put(new(occurrence_item(
+                        &recurrence.id,
+                        occurs_at,
+                        Some(&task.id),
+                    ))?)
+                    .build(),
+            )
+            .transact_items(
+                TransactWriteItem::builder()
+                    .put(new(task_item(&task, format)?)?)
+                    .build(),
+            )
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => generated += 1,
+            // The occurrence was generated or skipped before
+            Err(err) if matches!(failed_conditions(&err).as_deref(), Some([true, ..])) => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let until = until.fixed_offset().trunc_subsecs(3);
+
+    if after.is_some_and(|after| after >= until) {
+        return Ok((recurrence, generated));
+    }
+
+    // Only move the watermark on from where it was read, so a concurrent edit resetting it wins
+    let mut request = client
+        .update_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(recurrence.id.clone()))
+        .update_expression("SET #generated_until = :until")
+        .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+        .expression_attribute_names("#generated_until", "generated_until")
+        .expression_attribute_values(":entity", AttributeValue::S(RECURRENCE_ENTITY.to_string()))
+        .expression_attribute_values(":until", timestamp::to_attribute(until, format));
+
+    request = match after {
+        // The watermark may have been written before the timestamp format was changed
+        Some(after) => request
+            .condition_expression(
+                "#entity = :entity AND #generated_until IN (:after, :after_millis)",
+            )
+            .expression_attribute_values(
+                ":after",
+                timestamp::to_attribute(after, TimestampFormat::Rfc3339),
+            )
+            .expression_attribute_values(
+                ":after_millis",
+                timestamp::to_attribute(after, TimestampFormat::EpochMillis),
+            ),
+        None => request
+            .condition_expression("#entity = :entity AND attribute_not_exists(#generated_until)"),
+    };
+
+    match request.send().await {
+        Ok(_) => Ok((
+            Recurrence {
+                generated_until: Some(until),
+                ..recurrence
+            },
+            generated,
+        )),
+        // The Recurrence was edited or deleted in the meantime, and its occurrences are recorded
+        // so the next time round picks up where this left off
+        Err(err)
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception()) =>
+        {
+            Ok((recurrence, generated))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Delete the Tasks generated for a Recurrence's occurrences after a point that haven't been
+/// started, along with the occurrences so they can be generated again
+async fn prune(
+    client: &Arc<Client>,
+    table_name: &str,
+    storage: &dyn Storage,
+    id: &str,
+    after: DateTimeWithTimeZone,
+) -> anyhow::Result<()> {
+    for occurrence in occurrences_after(client, table_name, id, Some(after.to_utc())).await? {
+        let (Some(key), Some(task_id)) = (
+            occurrence.get("id"),
+            occurrence
+                .get("task_id")
+                .and_then(|task_id| task_id.as_s().ok()),
+        ) else {
+            continue;
+        };
+
+        let Some(task) = tasks::dynamo_service::get(client.clone(), table_name, task_id).await?
+        else {
+            continue;
+        };
+
+        if task.status != Status::Todo {
+            continue;
+        }
+
+        client
+            .delete_item()
+            .table_name(table_name)
+            .key("id", key.clone())
+            .send()
+            .await?;
+
+        match tasks::dynamo_service::delete(client.clone(), table_name, storage, &task.id).await {
+            Ok(_) => (),
+            Err(err) if tasks::error::Error::is_not_found(&err) => (),
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// A Recurrence's recorded occurrences from the occurrence index in time order, after a point
+/// if one is given
+async fn occurrences_after(
+    client: &Client,
+    table_name: &str,
+    id: &str,
+    after: Option<DateTimeUtc>,
+) -> anyhow::Result<Vec<Item>> {
+    let mut key_condition = "#recurrence_id = :recurrence_id".to_string();
+    let mut names = HashMap::from([("#recurrence_id".to_string(), "recurrence_id".to_string())]);
+    let mut values = HashMap::from([(
+        ":recurrence_id".to_string(),
+        AttributeValue::S(id.to_string()),
+    )]);
+
+    if let Some(after) = after {
+        key_condition.push_str(" AND #occurrence > :after");
+        names.insert("#occurrence".to_string(), "occurrence".to_string());
+        values.insert(":after".to_string(), AttributeValue::S(occurrence(after)));
+    }
+
+    let mut items = vec![];
+    let mut start_key = None;
+
+    loop {
+        let results = client
+            .query()
+            .table_name(table_name)
+            .index_name(OCCURRENCE_INDEX)
+            .key_condition_expression(&key_condition)
+            .set_expression_attribute_names(Some(names.clone()))
+            .set_expression_attribute_values(Some(values.clone()))
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        items.extend(results.items.unwrap_or_default());
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(items),
+        }
+    }
+}
+
+/// Scan the table for Recurrences matching an optional extra filter, starting after a key and
+/// stopping at the limit if there is one
+async fn scan(
+    client: &Client,
+    table_name: &str,
+    filter: Option<&str>,
+    mut start_key: Option<Item>,
+    limit: Option<u64>,
+) -> anyhow::Result<Vec<Recurrence>> {
+    let mut expression = "#entity = :entity".to_string();
+    let mut names = HashMap::from([("#entity".to_string(), ENTITY_ATTRIBUTE.to_string())]);
+
+    if let Some(filter) = filter {
+        expression = format!("{expression} AND ({filter})");
+        names.insert("#ended_at".to_string(), "ended_at".to_string());
+        names.insert(
+            "#generated_until".to_string(),
+            "generated_until".to_string(),
+        );
+    }
+
+    let mut recurrences = vec![];
+
+    // Filters are applied after each request's limit, so keep reading until the page is full
+    loop {
+        let results = client
+            .scan()
+            .table_name(table_name)
+            .set_limit(limit.map(i32::try_from).transpose()?)
+            .filter_expression(&expression)
+            .set_expression_attribute_names(Some(names.clone()))
+            .expression_attribute_values(
+                ":entity",
+                AttributeValue::S(RECURRENCE_ENTITY.to_string()),
+            )
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        for item in results.items.unwrap_or_default() {
+            recurrences.push(from_recurrence_item(item)?);
+
+            if limit.is_some_and(|limit| recurrences.len() as u64 >= limit) {
+                return Ok(recurrences);
+            }
+        }
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(recurrences),
+        }
+    }
+}
+
+/// Whether an item is a Recurrence
+fn is_recurrence(item: &Item) -> bool {
+    item.get(ENTITY_ATTRIBUTE) == Some(&AttributeValue::S(RECURRENCE_ENTITY.to_string()))
+}
+
+/// Convert a `Recurrence` into an item, marked with its entity so it's told apart from Tasks
+fn recurrence_item(recurrence: &Recurrence, format: TimestampFormat) -> anyhow::Result<Item> {
+    let mut item = dynamo::to_item(recurrence, format)?;
+
+    if let Some(project_id) = item.remove("project_id") {
+        item.insert(PROJECT_ATTRIBUTE.to_string(), project_id);
+    }
+
+    item.insert(
+        ENTITY_ATTRIBUTE.to_string(),
+        AttributeValue::S(RECURRENCE_ENTITY.to_string()),
+    );
+
+    Ok(item)
+}
+
+/// Convert an item into a `Recurrence`
+fn from_recurrence_item(mut item: Item) -> anyhow::Result<Recurrence> {
+    if let Some(project_id) = item.remove(PROJECT_ATTRIBUTE) {
+        item.insert("project_id".to_string(), project_id);
+    }
+
+    Ok(dynamo::from_item(item)?)
+}
+
+/// An occurrence as a UTC RFC 3339 string with fixed precision, so occurrences sort in time order
+/// whatever format other timestamps are written in
+fn occurrence(occurs_at: DateTimeUtc) -> String {
+    occurs_at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// The id of the item recording an occurrence of a Recurrence
+fn occurrence_key(id: &str, occurs_at: DateTimeUtc) -> String {
+    format!(
+        "{RECURRENCE_ENTITY}#{id}#{OCCURRENCE_ENTITY}#{}",
+        occurrence(occurs_at)
+    )
+}
+
+/// The item recording an occurrence of a Recurrence, with the Task generated for it if there is
+/// one
+fn occurrence_item(id: &str, occurs_at: DateTimeUtc, task_id: Option<&str>) -> Item {
+    let mut item = HashMap::from([
+        (
+            "id".to_string(),
+            AttributeValue::S(occurrence_key(id, occurs_at)),
+        ),
+        (
+            ENTITY_ATTRIBUTE.to_string(),
+            AttributeValue::S(OCCURRENCE_ENTITY.to_string()),
+        ),
+        (
+            "recurrence_id".to_string(),
+            AttributeValue::S(id.to_string()),
+        ),
+        (
+            "occurrence".to_string(),
+            AttributeValue::S(occurrence(occurs_at)),
+        ),
+    ]);
+
+    if let Some(task_id) = task_id {
+        item.insert(
+            "task_id".to_string(),
+            AttributeValue::S(task_id.to_string()),
+        );
+    }
+
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_recurrence_item() -> anyhow::Result<()> {
+        let recurrence = Recurrence {
+            id: "01HR".to_string(),
+            title: "Ops checklist".to_string(),
+            project_id: Some("01HP".to_string()),
+            rule: "FREQ=WEEKLY".to_string(),
+            created_at: DateTime::parse_from_rfc3339("2026-10-19T09:00:00Z")?,
+            updated_at: DateTime::parse_from_rfc3339("2026-10-19T09:00:00Z")?,
+            starts_at: DateTime::parse_from_rfc3339("2026-10-19T09:00:00Z")?,
+            ..Default::default()
+        };
+
+        let item = recurrence_item(&recurrence, TimestampFormat::EpochMillis)?;
+
+        assert!(is_recurrence(&item));
+        assert!(!item.contains_key("project_id"));
+        assert_eq!(from_recurrence_item(item)?, recurrence);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_occurrence_item() -> anyhow::Result<()> {
+        let occurs_at = DateTime::parse_from_rfc3339("2026-10-19T11:00:00+02:00")?.to_utc();
+
+        let item = occurrence_item("01HR", occurs_at, Some("01HT"));
+
+        assert_eq!(
+            item.get("id"),
+            Some(&AttributeValue::S(
+                "recurrence#01HR#occurrence#2026-10-19T09:00:00.000Z".to_string()
+            ))
+        );
+        assert_eq!(
+            item.get("occurrence"),
+            Some(&AttributeValue::S("2026-10-19T09:00:00.000Z".to_string()))
+        );
+        assert_eq!(
+            item.get("task_id"),
+            Some(&AttributeValue::S("01HT".to_string()))
+        );
+        assert!(!occurrence_item("01HR", occurs_at, None).contains_key("task_id"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+/// Recurrence errors that callers respond to differently, carried inside `anyhow::Error`
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The Recurrence does not exist
+    #[error("Recurrence not found")]
+    NotFound,
+
+    /// The recurrence rule can't be parsed, or uses a part that isn't supported
+    #[error("Invalid recurrence rule: {0}")]
+    InvalidRule(String),
+
+    /// The Recurrence can't be saved as given
+    #[error("Invalid Recurrence: {0}")]
+    Invalid(String),
+
+    /// The time given isn't one of the Recurrence's occurrences
+    #[error("{0} is not an occurrence of the Recurrence")]
+    NotAnOccurrence(String),
+
+    /// The Recurrence has ended, so it can't be changed any more
+    #[error("Recurrence {0} has ended")]
+    Ended(String),
+
+    /// The Task generated for an occurrence has already been started, so it isn't skipped
+    #[error("The Task {0} for the occurrence has already been started")]
+    AlreadyStarted(String),
+}
+
+impl Error {
+    /// Check whether the error is, or wraps, a `NotFound` error
+    pub fn is_not_found(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::NotFound))
+    }
+
+    /// Check whether the error is, or wraps, an `InvalidRule` or `Invalid` error
+    pub fn is_invalid(err: &anyhow::Error) -> bool {
+        matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::InvalidRule(_) | Error::Invalid(_))
+        )
+    }
+
+    /// Check whether the error is, or wraps, a `NotAnOccurrence` error
+    pub fn is_not_an_occurrence(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::NotAnOccurrence(_)))
+    }
+
+    /// Check whether the error is, or wraps, an `Ended` error
+    pub fn is_ended(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::Ended(_)))
+    }
+
+    /// Check whether the error is, or wraps, an `AlreadyStarted` error
+    pub fn is_already_started(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::AlreadyStarted(_)))
+    }
+}
@@ -0,0 +1,185 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{tasks::inputs::page_size, utils};
+
+use super::model::Recurrence;
+
+/// The `CreateRecurrenceInput` input type
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[schema(as = CreateRecurrenceInput)]
+pub struct Create {
+    /// The title of each generated Task
+    pub title: String,
+
+    /// The description of each generated Task
+    pub description: Option<String>,
+
+    /// The priority of each generated Task
+    pub priority: Option<i32>,
+
+    /// The Project to create each generated Task in
+    pub project_id: Option<String>,
+
+    /// The iCalendar recurrence rule, such as `FREQ=WEEKLY;BYDAY=MO`
+    pub rule: String,
+
+    /// The first occurrence, whose time of day every occurrence keeps
+    #[schema(value_type = String, format = DateTime)]
+    pub starts_at: DateTimeWithTimeZone,
+
+    /// The IANA timezone or UTC offset to follow the rule in, defaults to UTC
+    pub timezone: Option<String>,
+}
+
+/// The `UpdateRecurrenceInput` input type. An omitted field is left unchanged, an explicit `null`
+/// clears it, and any other value replaces it. Upcoming Tasks that haven't been started are
+/// generated again to match.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default)]
+#[schema(as = UpdateRecurrenceInput)]
+pub struct Update {
+    /// The title of each generated Task, which cannot be cleared
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub title: utils::Update<String>,
+
+    /// The description of each generated Task
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub description: utils::Update<String>,
+
+    /// The priority of each generated Task
+    #[schema(value_type = Option<i32>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub priority: utils::Update<i32>,
+
+    /// The Project to create each generated Task in
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub project_id: utils::Update<String>,
+
+    /// The iCalendar recurrence rule, which cannot be cleared
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub rule: utils::Update<String>,
+
+    /// The first occurrence, which cannot be cleared
+    #[schema(value_type = Option<String>, format = DateTime)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub starts_at: utils::Update<DateTimeWithTimeZone>,
+
+    /// The timezone to follow the rule in, where clearing it goes back to UTC
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub timezone: utils::Update<String>,
+}
+
+impl Update {
+    /// Apply the changes to a Recurrence, leaving the fields that can't be cleared as they are
+    pub fn apply(&self, mut recurrence: Recurrence) -> Recurrence {
+        if let utils::Update::Value(title) = &self.title {
+            recurrence.title.clone_from(title);
+        }
+
+        if let utils::Update::Value(rule) = &self.rule {
+            recurrence.rule.clone_from(rule);
+        }
+
+        if let utils::Update::Value(starts_at) = self.starts_at {
+            recurrence.starts_at = starts_at;
+        }
+
+        match &self.timezone {
+            utils::Update::Unchanged => (),
+            utils::Update::Empty => recurrence.timezone = "UTC".to_string(),
+            utils::Update::Value(timezone) => recurrence.timezone.clone_from(timezone),
+        }
+
+        self.description
+            .clone()
+            .update_to(&mut recurrence.description);
+        self.priority.update_to(&mut recurrence.priority);
+        self.project_id
+            .clone()
+            .update_to(&mut recurrence.project_id);
+
+        recurrence
+    }
+}
+
+/// The `ListRecurrencesInput` query parameters for a page of Recurrences, in id order
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct List {
+    /// The cursor returned with the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+
+    /// The maximum number of Recurrences to return, defaults to 20 and is capped at 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl List {
+    /// The requested page size, with the default and maximum applied
+    pub fn page_size(&self) -> usize {
+        page_size(self.limit)
+    }
+}
+
+/// The `SkipOccurrenceInput` input type
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[schema(as = SkipOccurrenceInput)]
+pub struct Skip {
+    /// The occurrence to skip
+    #[schema(value_type = String, format = DateTime)]
+    pub occurs_at: DateTimeWithTimeZone,
+}
+
+/// The `EndRecurrenceInput` input type
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default)]
+#[schema(as = EndRecurrenceInput)]
+pub struct End {
+    /// When to end the Recurrence, defaults to now. Occurrences after it are left out.
+    #[schema(value_type = Option<String>, format = DateTime)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<DateTimeWithTimeZone>,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_update_apply() -> anyhow::Result<()> {
+        let input: Update = serde_json::from_str(
+            r#"{"title": "Ops checklist", "description": null, "timezone": null, "rule": null}"#,
+        )?;
+
+        let recurrence = Recurrence {
+            title: "Checklist".to_string(),
+            description: Some("Weekly".to_string()),
+            priority: Some(2),
+            rule: "FREQ=WEEKLY".to_string(),
+            timezone: "Europe/Berlin".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            input.apply(recurrence.clone()),
+            Recurrence {
+                title: "Ops checklist".to_string(),
+                description: None,
+                timezone: "UTC".to_string(),
+                ..recurrence
+            }
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use axum::async_trait;
+
+use crate::jobs::handler::{Context, Handler};
+
+/// Generates recurring Tasks, checking every Recurrence each time it runs. Occurrences are
+/// recorded as they're generated, so a retried run or more than one server never generates a
+/// Task twice.
+#[derive(Clone, Debug)]
+pub struct ExpandRecurrences {
+    /// How far ahead Tasks are generated for each Recurrence's occurrences
+    pub horizon: chrono::Duration,
+}
+
+#[async_trait]
+impl Handler for ExpandRecurrences {
+    type Job = ();
+
+    const KIND: &'static str = "expand_recurrences";
+
+    async fn handle(&self, _job: (), context: &Context) -> anyhow::Result<()> {
+        let generated = context.store.expand_recurrences(self.horizon).await?;
+
+        if generated > 0 {
+            log::info!("Generated {generated} recurring Tasks");
+        }
+
+        Ok(())
+    }
+}
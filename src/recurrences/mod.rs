@@ -0,0 +1,26 @@
+/// The Recurrence Model
+pub mod model;
+
+/// The Model recording each occurrence of a Recurrence
+pub mod occurrence;
+
+/// The Recurrence entity general-purpose service
+pub mod service;
+
+/// The Recurrence entity DynamoDB service
+pub mod dynamo_service;
+
+/// The Recurrence error types
+pub mod error;
+
+/// The Recurrence entity input types
+pub mod inputs;
+
+/// iCalendar recurrence rules
+pub mod rrule;
+
+/// Recurrence operations on the Task store for the configured `DataStore`
+pub mod store;
+
+/// The background Job that generates recurring Tasks
+pub mod job;
@@ -0,0 +1,280 @@
+use chrono::Utc;
+use sea_orm::{entity::prelude::*, Set};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use utoipa::ToSchema;
+
+use crate::utils::{
+    dynamo::DynamoItem,
+    timezone::{Timezone, WithTimestamps},
+};
+
+use super::{
+    error::Error,
+    rrule::{Occurrences, Rule},
+};
+
+#[cfg(test)]
+use fake::Dummy;
+
+/// The Recurrence Model, a template for Tasks that repeat on an iCalendar recurrence rule
+#[derive(Clone, Debug, Eq, PartialEq, DeriveEntityModel, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(test, derive(Dummy))]
+#[sea_orm(table_name = "recurrences")]
+#[schema(as = Recurrence)]
+pub struct Model {
+    /// The Recurrence id
+    #[sea_orm(primary_key, column_type = "Text")]
+    pub id: String,
+
+    /// The date the Recurrence was created
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTimeWithTimeZone,
+
+    /// The date the Recurrence was last updated
+    #[schema(value_type = String, format = DateTime)]
+    pub updated_at: DateTimeWithTimeZone,
+
+    /// The title of each generated Task
+    #[sea_orm(column_type = "Text")]
+    pub title: String,
+
+    /// The description of each generated Task
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+
+    /// The priority of each generated Task
+    pub priority: Option<i32>,
+
+    /// The Project each generated Task is created in, if any
+    #[sea_orm(column_type = "Text", nullable)]
+    pub project_id: Option<String>,
+
+    /// The iCalendar recurrence rule, such as `FREQ=WEEKLY;BYDAY=MO`
+    #[sea_orm(column_type = "Text")]
+    pub rule: String,
+
+    /// The first occurrence, whose time of day every occurrence keeps
+    #[schema(value_type = String, format = DateTime)]
+    pub starts_at: DateTimeWithTimeZone,
+
+    /// The IANA timezone or UTC offset the rule is followed in
+    #[sea_orm(column_type = "Text")]
+    pub timezone: String,
+
+    /// When the Recurrence was ended, if it has been. No Tasks are generated after it.
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub ended_at: Option<DateTimeWithTimeZone>,
+
+    /// Tasks have been generated for every occurrence up to this point
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub generated_until: Option<DateTimeWithTimeZone>,
+}
+
+/// The name for a Sea ORM model must be "Model", so this provides a convenient alias
+pub type Recurrence = Model;
+
+/// Recurrence entity relationships
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// The Project the generated Tasks are created in
+    #[sea_orm(
+        belongs_to = "crate::projects::model::Entity",
+        from = "Column::ProjectId",
+        to = "crate::projects::model::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Project,
+
+    /// The occurrences Tasks have been generated or skipped for
+    #[sea_orm(has_many = "super::occurrence::Entity")]
+    Occurrences,
+}
+
+impl Related<super::occurrence::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Occurrences.def()
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    /// Generate a ULID and timestamps for new Recurrences, and bump `updated_at` on every update
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let now = Utc::now().fixed_offset();
+
+        if insert {
+            if self.id.is_not_set() {
+                self.id = Set(Ulid::new().to_string());
+            }
+
+            if self.created_at.is_not_set() {
+                self.created_at = Set(now);
+            }
+
+            if self.updated_at.is_not_set() {
+                self.updated_at = Set(now);
+            }
+        } else {
+            self.updated_at = Set(now);
+        }
+
+        Ok(self)
+    }
+}
+
+impl Model {
+    /// Whether the Recurrence has been ended
+    pub fn is_ended(&self) -> bool {
+        self.ended_at.is_some()
+    }
+
+    /// The Recurrence's occurrences in order, up to when it was ended
+    pub fn occurrences(&self) -> anyhow::Result<impl Iterator<Item = DateTimeUtc>> {
+        let ended_at = self.ended_at;
+
+        Ok(self
+            .schedule()?
+            .take_while(move |occurrence| ended_at.is_none_or(|ended_at| *occurrence <= ended_at)))
+    }
+
+    /// Check that a time is one of the Recurrence's occurrences
+    pub fn check_occurrence(&self, occurs_at: DateTimeWithTimeZone) -> anyhow::Result<()> {
+        let found = self
+            .occurrences()?
+            .take_while(|occurrence| *occurrence <= occurs_at)
+            .any(|occurrence| occurrence == occurs_at);
+
+        if !found {
+            return Err(Error::NotAnOccurrence(occurs_at.to_rfc3339()).into());
+        }
+
+        Ok(())
+    }
+
+    /// Check that the rule and timezone are valid and the rule has at least one occurrence
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.schedule()?.next().is_none() {
+            return Err(
+                Error::Invalid(format!("the rule '{}' has no occurrences", self.rule)).into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Every occurrence of the rule from the start, ignoring when the Recurrence was ended
+    fn schedule(&self) -> anyhow::Result<Occurrences> {
+        let rule: Rule = self.rule.parse()?;
+
+        let timezone: Timezone = self
+            .timezone
+            .parse()
+            .map_err(|err: anyhow::Error| Error::Invalid(err.to_string()))?;
+
+        Ok(rule.occurrences(self.starts_at, timezone))
+    }
+}
+
+impl WithTimestamps for Model {
+    /// Render the timestamps in the given timezone
+    fn in_timezone(self, timezone: &Timezone) -> Self {
+        Self {
+            created_at: timezone.convert(self.created_at),
+            updated_at: timezone.convert(self.updated_at),
+            starts_at: timezone.convert(self.starts_at),
+            ended_at: self.ended_at.map(|ended_at| timezone.convert(ended_at)),
+            generated_until: self
+                .generated_until
+                .map(|generated_until| timezone.convert(generated_until)),
+            ..self
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            id: String::default(),
+            created_at: Utc::now().fixed_offset(),
+            updated_at: Utc::now().fixed_offset(),
+            title: String::default(),
+            description: None,
+            priority: None,
+            project_id: None,
+            rule: String::default(),
+            starts_at: Utc::now().fixed_offset(),
+            timezone: "UTC".to_string(),
+            ended_at: None,
+            generated_until: None,
+        }
+    }
+}
+
+impl DynamoItem for Model {
+    const TIMESTAMPS: &'static [&'static str] = &[
+        "created_at",
+        "updated_at",
+        "starts_at",
+        "ended_at",
+        "generated_until",
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_occurrences_stop_when_ended() -> anyhow::Result<()> {
+        let recurrence = Recurrence {
+            rule: "FREQ=DAILY".to_string(),
+            starts_at: DateTime::parse_from_rfc3339("2026-10-19T09:00:00+02:00")?,
+            timezone: "Europe/Berlin".to_string(),
+            ended_at: Some(DateTime::parse_from_rfc3339("2026-10-21T06:00:00Z")?),
+            ..Default::default()
+        };
+
+        let occurrences: Vec<_> = recurrence
+            .occurrences()?
+            .map(|occurrence| occurrence.to_rfc3339())
+            .collect();
+
+        assert_eq!(
+            occurrences,
+            vec!["2026-10-19T07:00:00+00:00", "2026-10-20T07:00:00+00:00"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() {
+        let recurrence = |rule: &str, timezone: &str| Recurrence {
+            rule: rule.to_string(),
+            timezone: timezone.to_string(),
+            ..Default::default()
+        };
+
+        assert!(recurrence("FREQ=WEEKLY", "America/Chicago")
+            .validate()
+            .is_ok());
+        assert!(recurrence("FREQ=WEEKLY", "+02:00").validate().is_ok());
+
+        for invalid in [
+            recurrence("FREQ=FORTNIGHTLY", "UTC"),
+            recurrence("FREQ=WEEKLY", "Mars/Olympus_Mons"),
+            recurrence("FREQ=DAILY;UNTIL=20000101", "UTC"),
+        ] {
+            let err = invalid.validate().unwrap_err();
+
+            assert!(Error::is_invalid(&err), "{err}");
+        }
+    }
+}
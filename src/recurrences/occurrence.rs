@@ -0,0 +1,58 @@
+use sea_orm::entity::prelude::*;
+
+/// An occurrence of a Recurrence that has been handled, so it's never generated again. The Task
+/// is missing once the occurrence is skipped or the generated Task is deleted.
+#[derive(Clone, Debug, Eq, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "recurrence_occurrences")]
+pub struct Model {
+    /// The Recurrence
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub recurrence_id: String,
+
+    /// When the occurrence falls
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub occurs_at: DateTimeWithTimeZone,
+
+    /// The Task generated for the occurrence, if it still exists
+    #[sea_orm(column_type = "Text", nullable)]
+    pub task_id: Option<String>,
+}
+
+/// The name for a Sea ORM model must be "Model", so this provides a convenient alias
+pub type Occurrence = Model;
+
+/// Occurrence entity relationships
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// The Recurrence
+    #[sea_orm(
+        belongs_to = "super::model::Entity",
+        from = "Column::RecurrenceId",
+        to = "super::model::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Recurrence,
+
+    /// The Task generated for the occurrence
+    #[sea_orm(
+        belongs_to = "crate::tasks::model::Entity",
+        from = "Column::TaskId",
+        to = "crate::tasks::model::Column::Id",
+        on_delete = "SetNull"
+    )]
+    Task,
+}
+
+impl Related<super::model::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Recurrence.def()
+    }
+}
+
+impl Related<crate::tasks::model::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Task.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,570 @@
+//! The subset of iCalendar recurrence rules (RFC 5545) that Recurrences support, such as
+//! `FREQ=WEEKLY;BYDAY=MO,TH` or `RRULE:FREQ=MONTHLY;BYMONTHDAY=-1;COUNT=12`. Rules repeat
+//! `DAILY`, `WEEKLY`, `MONTHLY` or `YEARLY` with an optional `INTERVAL`, narrowed by plain
+//! weekdays in `BYDAY` and days of the month in `BYMONTHDAY`, and bounded by `COUNT` or `UNTIL`.
+//! Weeks start on Monday. Any other part is rejected rather than silently ignored.
+
+use std::{collections::VecDeque, str::FromStr};
+
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Months, NaiveDate, NaiveDateTime, Timelike, Utc, Weekday,
+};
+
+use crate::utils::timezone::Timezone;
+
+use super::error::Error;
+
+/// How many periods in a row can go by without an occurrence before a rule is treated as having
+/// no more, such as a yearly rule for the 30th of February
+const MAX_EMPTY_PERIODS: u32 = 1000;
+
+/// How often a rule repeats
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Frequency {
+    /// Every day
+    Daily,
+
+    /// Every week
+    Weekly,
+
+    /// Every month
+    Monthly,
+
+    /// Every year
+    Yearly,
+}
+
+/// The last occurrence a rule allows
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Until {
+    /// Occurrences on or before a date, in the Recurrence's timezone
+    Date(NaiveDate),
+
+    /// Occurrences at or before an instant
+    Time(DateTime<Utc>),
+}
+
+/// A parsed recurrence rule
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rule {
+    /// How often the rule repeats
+    pub frequency: Frequency,
+
+    /// How many periods of the frequency pass between each repetition
+    pub interval: u32,
+
+    /// The weekdays occurrences fall on
+    pub by_day: Vec<Weekday>,
+
+    /// The days of the month occurrences fall on, where -1 is the last day
+    pub by_month_day: Vec<i32>,
+
+    /// The number of occurrences, if there's a limit
+    pub count: Option<u32>,
+
+    /// The last occurrence allowed, if there's a limit
+    pub until: Option<Until>,
+}
+
+impl Rule {
+    /// The rule's occurrences on or after the start, in order. Each falls at the start's time of
+    /// day in the given timezone, and a time skipped when the clocks go forward is left out.
+    pub fn occurrences(&self, starts_at: DateTime<FixedOffset>, timezone: Timezone) -> Occurrences {
+        let start = timezone
+            .convert(starts_at)
+            .naive_local()
+            .with_nanosecond(0)
+            .unwrap_or_else(|| starts_at.naive_local());
+
+        Occurrences {
+            rule: self.clone(),
+            timezone,
+            start,
+            period: 0,
+            pending: VecDeque::new(),
+            emitted: 0,
+            empty_periods: 0,
+            done: false,
+        }
+    }
+
+    /// Whether a date matches the rule's filters, or the start's place in its period when there
+    /// are none
+    fn matches(&self, start: NaiveDate, date: NaiveDate) -> bool {
+        let by_day = self.by_day.is_empty() || self.by_day.contains(&date.weekday());
+
+        let by_month_day = self.by_month_day.is_empty()
+            || self.by_month_day.iter().any(|day| {
+                let day = if *day < 0 {
+                    days_in_month(date) as i32 + 1 + day
+                } else {
+                    *day
+                };
+
+                day == date.day() as i32
+            });
+
+        let defaults = match self.frequency {
+            Frequency::Daily => true,
+            Frequency::Weekly => !self.by_day.is_empty() || date.weekday() == start.weekday(),
+            Frequency::Monthly => {
+                !self.by_day.is_empty()
+                    || !self.by_month_day.is_empty()
+                    || date.day() == start.day()
+            }
+            Frequency::Yearly => {
+                !self.by_day.is_empty()
+                    || !self.by_month_day.is_empty()
+                    || (date.month(), date.day()) == (start.month(), start.day())
+            }
+        };
+
+        by_day && by_month_day && defaults
+    }
+
+    /// Every date in the rule's nth period from the start, or `None` once the calendar runs out
+    fn period(&self, start: NaiveDate, period: u32) -> Option<Vec<NaiveDate>> {
+        let step = period.checked_mul(self.interval)?;
+
+        let (first, last) = match self.frequency {
+            Frequency::Daily => {
+                let day = start.checked_add_days(Days::new(u64::from(step)))?;
+
+                (day, day)
+            }
+            Frequency::Weekly => {
+                let monday = start.checked_sub_days(Days::new(u64::from(
+                    start.weekday().num_days_from_monday(),
+                )))?;
+                let first = monday.checked_add_days(Days::new(u64::from(step) * 7))?;
+
+                (first, first.checked_add_days(Days::new(6))?)
+            }
+            Frequency::Monthly => {
+                let first = start.with_day(1)?.checked_add_months(Months::new(step))?;
+
+                (first, first.checked_add_months(Months::new(1))?.pred_opt()?)
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(i32::try_from(step).ok()?)?;
+
+                (
+                    NaiveDate::from_ymd_opt(year, 1, 1)?,
+                    NaiveDate::from_ymd_opt(year, 12, 31)?,
+                )
+            }
+        };
+
+        Some(
+            first
+                .iter_days()
+                .take_while(|date| *date <= last)
+                .filter(|date| self.matches(start, *date))
+                .collect(),
+        )
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let value = value.strip_prefix("RRULE:").unwrap_or(value);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut by_day = vec![];
+        let mut by_month_day = vec![];
+        let mut count = None;
+        let mut until = None;
+        let mut seen = vec![];
+
+        for part in value.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part.split_once('=').ok_or_else(|| {
+                Error::InvalidRule(format!("expected NAME=VALUE, found '{part}'"))
+            })?;
+
+            let name = name.to_ascii_uppercase();
+
+            if seen.contains(&name) {
+                return Err(Error::InvalidRule(format!(
+                    "{name} is given more than once"
+                )));
+            }
+
+            match name.as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => {
+                            return Err(Error::InvalidRule(format!(
+                            "unsupported FREQ '{value}', expected DAILY, WEEKLY, MONTHLY or YEARLY"
+                        )))
+                        }
+                    });
+                }
+                "INTERVAL" => interval = positive(&name, value)?,
+                "COUNT" => count = Some(positive(&name, value)?),
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(weekday)
+                        .collect::<Result<Vec<_>, _>>()?;
+                }
+                "BYMONTHDAY" => {
+                    by_month_day = value
+                        .split(',')
+                        .map(month_day)
+                        .collect::<Result<Vec<_>, _>>()?;
+                }
+                "UNTIL" => until = Some(parse_until(value)?),
+                "WKST" if value.eq_ignore_ascii_case("MO") => (),
+                _ => return Err(Error::InvalidRule(format!("{name} is not supported"))),
+            }
+
+            seen.push(name);
+        }
+
+        let frequency =
+            frequency.ok_or_else(|| Error::InvalidRule("FREQ is required".to_string()))?;
+
+        if count.is_some() && until.is_some() {
+            return Err(Error::InvalidRule(
+                "COUNT and UNTIL can't be used together".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            frequency,
+            interval,
+            by_day,
+            by_month_day,
+            count,
+            until,
+        })
+    }
+}
+
+/// The occurrences of a `Rule`, in order
+#[derive(Clone, Debug)]
+pub struct Occurrences {
+    rule: Rule,
+    timezone: Timezone,
+    start: NaiveDateTime,
+    period: u32,
+    pending: VecDeque<NaiveDateTime>,
+    emitted: u32,
+    empty_periods: u32,
+    done: bool,
+}
+
+impl Iterator for Occurrences {
+    type Item = DateTime<Utc>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            if self.rule.count.is_some_and(|count| self.emitted >= count) {
+                break;
+            }
+
+            let Some(local) = self.pending.pop_front() else {
+                let dates = match self.rule.period(self.start.date(), self.period) {
+                    Some(dates) if self.empty_periods < MAX_EMPTY_PERIODS => dates,
+                    _ => break,
+                };
+
+                self.period += 1;
+                self.pending.extend(
+                    dates
+                        .into_iter()
+                        .map(|date| date.and_time(self.start.time()))
+                        .filter(|local| *local >= self.start),
+                );
+
+                if self.pending.is_empty() {
+                    self.empty_periods += 1;
+                } else {
+                    self.empty_periods = 0;
+                }
+
+                continue;
+            };
+
+            if matches!(self.rule.until, Some(Until::Date(until)) if local.date() > until) {
+                break;
+            }
+
+            // The local time doesn't exist on a day the clocks go forward
+            let Some(occurrence) = self.timezone.from_local(local) else {
+                continue;
+            };
+
+            if matches!(self.rule.until, Some(Until::Time(until)) if occurrence > until) {
+                break;
+            }
+
+            self.emitted += 1;
+
+            return Some(occurrence);
+        }
+
+        self.done = true;
+
+        None
+    }
+}
+
+/// The number of days in a date's month
+fn days_in_month(date: NaiveDate) -> u32 {
+    date.with_day(1)
+        .and_then(|first| first.checked_add_months(Months::new(1)))
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+/// Parse a positive count, like an `INTERVAL` or `COUNT`
+fn positive(name: &str, value: &str) -> Result<u32, Error> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|value| *value > 0)
+        .ok_or_else(|| {
+            Error::InvalidRule(format!("{name} must be a positive number, found '{value}'"))
+        })
+}
+
+/// Parse a plain weekday, like `MO`
+fn weekday(value: &str) -> Result<Weekday, Error> {
+    match value.trim().to_ascii_uppercase().as_str() {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(Error::InvalidRule(format!(
+            "unsupported BYDAY '{value}', expected a weekday from MO to SU"
+        ))),
+    }
+}
+
+/// Parse a day of the month from 1 to 31, or from -1 to -31 counting back from the last day
+fn month_day(value: &str) -> Result<i32, Error> {
+    value
+        .trim()
+        .parse::<i32>()
+        .ok()
+        .filter(|day| (1..=31).contains(&day.abs()))
+        .ok_or_else(|| {
+            Error::InvalidRule(format!(
+                "BYMONTHDAY must be from 1 to 31 or -31 to -1, found '{value}'"
+            ))
+        })
+}
+
+/// Parse an `UNTIL` date like `20261231`, or UTC time like `20261231T170000Z`
+fn parse_until(value: &str) -> Result<Until, Error> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Ok(Until::Date(date));
+    }
+
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
+        .map(|until| Until::Time(until.and_utc()))
+        .map_err(|_err| {
+            Error::InvalidRule(format!(
+                "UNTIL must be a date like 20261231 or a UTC time like 20261231T170000Z, found '{value}'"
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Tz;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn occurrences(rule: &str, starts_at: &str, timezone: &str) -> anyhow::Result<Vec<String>> {
+        let rule: Rule = rule.parse()?;
+
+        Ok(rule
+            .occurrences(DateTime::parse_from_rfc3339(starts_at)?, timezone.parse()?)
+            .take(5)
+            .map(|occurrence| occurrence.to_rfc3339())
+            .collect())
+    }
+
+    #[test]
+    fn test_parse() -> anyhow::Result<()> {
+        assert_eq!(
+            "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,th;UNTIL=20261231T170000Z".parse::<Rule>()?,
+            Rule {
+                frequency: Frequency::Weekly,
+                interval: 2,
+                by_day: vec![Weekday::Mon, Weekday::Thu],
+                by_month_day: vec![],
+                count: None,
+                until: Some(Until::Time(
+                    DateTime::parse_from_rfc3339("2026-12-31T17:00:00Z")?.to_utc()
+                )),
+            }
+        );
+
+        for rule in [
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=WEEKLY;BYDAY=1MO",
+            "FREQ=MONTHLY;BYMONTHDAY=0",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;COUNT=2;UNTIL=20261231",
+            "FREQ=DAILY;FREQ=WEEKLY",
+            "FREQ=YEARLY;BYMONTH=3",
+        ] {
+            assert!(
+                matches!(rule.parse::<Rule>(), Err(Error::InvalidRule(_))),
+                "{rule} should be rejected"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_weekly() -> anyhow::Result<()> {
+        // Starting on a Wednesday, so the Monday of the first week has already passed
+        assert_eq!(
+            occurrences(
+                "FREQ=WEEKLY;BYDAY=MO,TH;COUNT=4",
+                "2026-10-21T09:00:00Z",
+                "UTC"
+            )?,
+            vec![
+                "2026-10-22T09:00:00+00:00",
+                "2026-10-26T09:00:00+00:00",
+                "2026-10-29T09:00:00+00:00",
+                "2026-11-02T09:00:00+00:00",
+            ]
+        );
+
+        assert_eq!(
+            occurrences("FREQ=WEEKLY;INTERVAL=2", "2026-10-21T09:00:00Z", "UTC")?[..3],
+            [
+                "2026-10-21T09:00:00+00:00",
+                "2026-11-04T09:00:00+00:00",
+                "2026-11-18T09:00:00+00:00",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_monthly() -> anyhow::Result<()> {
+        // The 31st is skipped in shorter months, while the last day is found in every month
+        assert_eq!(
+            occurrences("FREQ=MONTHLY", "2026-10-31T09:00:00Z", "UTC")?[..3],
+            [
+                "2026-10-31T09:00:00+00:00",
+                "2026-12-31T09:00:00+00:00",
+                "2027-01-31T09:00:00+00:00",
+            ]
+        );
+        assert_eq!(
+            occurrences(
+                "FREQ=MONTHLY;BYMONTHDAY=-1;UNTIL=20270101",
+                "2026-10-31T09:00:00Z",
+                "UTC"
+            )?,
+            vec![
+                "2026-10-31T09:00:00+00:00",
+                "2026-11-30T09:00:00+00:00",
+                "2026-12-31T09:00:00+00:00",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_yearly_and_daily() -> anyhow::Result<()> {
+        assert_eq!(
+            occurrences("FREQ=YEARLY", "2028-02-29T09:00:00Z", "UTC")?[..2],
+            ["2028-02-29T09:00:00+00:00", "2032-02-29T09:00:00+00:00"]
+        );
+        assert_eq!(
+            occurrences(
+                "FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR;COUNT=3",
+                "2026-10-23T09:00:00Z",
+                "UTC"
+            )?,
+            vec![
+                "2026-10-23T09:00:00+00:00",
+                "2026-10-26T09:00:00+00:00",
+                "2026-10-27T09:00:00+00:00",
+            ]
+        );
+        assert_eq!(
+            occurrences("FREQ=YEARLY;BYMONTHDAY=30", "2026-02-01T09:00:00Z", "UTC")?[..2],
+            ["2026-03-30T09:00:00+00:00", "2026-04-30T09:00:00+00:00"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keeps_the_local_time_across_daylight_saving() -> anyhow::Result<()> {
+        let rule: Rule = "FREQ=DAILY;COUNT=3".parse()?;
+
+        let occurrences: Vec<_> = rule
+            .occurrences(
+                DateTime::parse_from_rfc3339("2026-03-07T02:30:00-06:00")?,
+                Timezone::Named(Tz::America__Chicago),
+            )
+            .map(|occurrence| occurrence.to_rfc3339())
+            .collect();
+
+        // 02:30 doesn't exist on the 8th in Chicago, so that day is left out and not counted
+        assert_eq!(
+            occurrences,
+            vec![
+                "2026-03-07T08:30:00+00:00",
+                "2026-03-09T07:30:00+00:00",
+                "2026-03-10T07:30:00+00:00",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ends_without_occurrences() -> anyhow::Result<()> {
+        let rule: Rule = "FREQ=YEARLY;BYMONTHDAY=31;BYDAY=MO".parse()?;
+
+        assert!(rule
+            .occurrences(
+                DateTime::parse_from_rfc3339("2026-10-19T09:00:00Z")?,
+                Timezone::Named(Tz::UTC)
+            )
+            .next()
+            .is_some());
+
+        let rule: Rule = "FREQ=MONTHLY;INTERVAL=12;BYMONTHDAY=30".parse()?;
+
+        assert_eq!(
+            rule.occurrences(
+                DateTime::parse_from_rfc3339("2026-02-01T09:00:00Z")?,
+                Timezone::Named(Tz::UTC)
+            )
+            .next(),
+            None
+        );
+
+        Ok(())
+    }
+}
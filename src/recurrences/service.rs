@@ -0,0 +1,593 @@
+use std::sync::Arc;
+
+use chrono::{Duration, SubsecRound, Utc};
+use sea_orm::{
+    prelude::{DateTimeUtc, DateTimeWithTimeZone},
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    ModelTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set, TransactionTrait,
+};
+
+use crate::{
+    attachments::{
+        self,
+        model::Attachment,
+        storage::{self, Storage},
+    },
+    projects,
+    tasks::{self, workflow::Status},
+};
+
+use super::{
+    error::Error,
+    inputs,
+    model::{self, Recurrence},
+    occurrence,
+};
+
+/// Get an individual `Recurrence` by id
+pub async fn get(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Option<Recurrence>> {
+    let recurrence = model::Entity::find_by_id(id.to_string()).one(&*db).await?;
+
+    Ok(recurrence)
+}
+
+/// List up to `limit` `Recurrence`s in id order, starting after the given cursor
+pub async fn list(
+    db: Arc<DatabaseConnection>,
+    input: &inputs::List,
+    limit: u64,
+) -> anyhow::Result<Vec<Recurrence>> {
+    let mut query = model::Entity::find().order_by_asc(model::Column::Id);
+
+    if let Some(after) = &input.after {
+        query = query.filter(model::Column::Id.gt(after));
+    }
+
+    let recurrences = query.limit(limit).all(&*db).await?;
+
+    Ok(recurrences)
+}
+
+/// Create a `Recurrence` with the given input, then generate its Tasks up to the horizon
+pub async fn create(
+    db: Arc<DatabaseConnection>,
+    horizon: Duration,
+    input: &inputs::Create,
+) -> anyhow::Result<Recurrence> {
+    let recurrence = Recurrence {
+        title: input.title.clone(),
+        description: input.description.clone(),
+        priority: input.priority,
+        project_id: input.project_id.clone(),
+        rule: input.rule.trim().to_string(),
+        starts_at: input.starts_at.trunc_subsecs(0),
+        timezone: input.timezone.clone().unwrap_or_else(|| "UTC".to_string()),
+        ..Default::default()
+    };
+
+    recurrence.validate()?;
+
+    if let Some(project_id) = &recurrence.project_id {
+        projects::service::check_active(&*db, project_id).await?;
+    }
+
+    let recurrence = model::ActiveModel {
+        title: Set(recurrence.title),
+        description: Set(recurrence.description),
+        priority: Set(recurrence.priority),
+        project_id: Set(recurrence.project_id),
+        rule: Set(recurrence.rule),
+        starts_at: Set(recurrence.starts_at),
+        timezone: Set(recurrence.timezone),
+        ended_at: Set(None),
+        generated_until: Set(None),
+        ..Default::default()
+    }
+    .insert(&*db)
+    .await?;
+
+    let (recurrence, _) = expand(db, recurrence, Utc::now() + horizon).await?;
+
+    Ok(recurrence)
+}
+
+/// Update an existing `Recurrence` by id. Upcoming Tasks that haven't been started are deleted
+/// and generated again to match, while skipped occurrences stay skipped.
+pub async fn update(
+    db: Arc<DatabaseConnection>,
+    storage: &dyn Storage,
+    horizon: Duration,
+    id: &str,
+    input: &inputs::Update,
+) -> anyhow::Result<Recurrence> {
+    let existing = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    if existing.is_ended() {
+        return Err(Error::Ended(existing.id).into());
+    }
+
+    let updated = input.apply(existing.clone());
+
+    updated.validate()?;
+
+    if let Some(project_id) = &updated.project_id {
+        if existing.project_id.as_ref() != Some(project_id) {
+            projects::service::check_active(&*db, project_id).await?;
+        }
+    }
+
+    let now = Utc::now().fixed_offset();
+
+    let txn = db.begin().await?;
+
+    let attachments = prune(&txn, id, now).await?;
+
+    let mut recurrence: model::ActiveModel = existing.clone().into();
+    recurrence.title = Set(updated.title);
+    recurrence.description = Set(updated.description);
+    recurrence.priority = Set(updated.priority);
+    recurrence.project_id = Set(updated.project_id);
+    recurrence.rule = Set(updated.rule.trim().to_string());
+    recurrence.starts_at = Set(updated.starts_at.trunc_subsecs(0));
+    recurrence.timezone = Set(updated.timezone);
+    recurrence.generated_until = Set(existing
+        .generated_until
+        .map(|generated_until| generated_until.min(now)));
+
+    let recurrence = recurrence.update(&txn).await?;
+
+    txn.commit().await?;
+
+    storage::purge(storage, &attachments).await;
+
+    let (recurrence, _) = expand(db, recurrence, Utc::now() + horizon).await?;
+
+    Ok(recurrence)
+}
+
+/// Delete an existing `Recurrence`, returning it. The Tasks it generated are kept.
+pub async fn delete(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Recurrence> {
+    let recurrence = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    // The foreign key deletes the Recurrence's occurrences
+    let result = recurrence.clone().delete(&*db).await?;
+
+    // Another request deleted the Recurrence after it was read
+    if result.rows_affected == 0 {
+        return Err(Error::NotFound.into());
+    }
+
+    Ok(recurrence)
+}
+
+/// Skip an occurrence of a `Recurrence`, deleting its Task if one was generated and hasn't been
+/// started. A skipped occurrence is never generated again.
+pub async fn skip(
+    db: Arc<DatabaseConnection>,
+    storage: &dyn Storage,
+    id: &str,
+    input: &inputs::Skip,
+) -> anyhow::Result<Recurrence> {
+    let recurrence = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    if recurrence.is_ended() {
+        return Err(Error::Ended(recurrence.id).into());
+    }
+
+    recurrence.check_occurrence(input.occurs_at)?;
+
+    let txn = db.begin().await?;
+
+    let existing = occurrence::Entity::find_by_id((id.to_string(), input.occurs_at))
+        .one(&txn)
+        .await?;
+
+    let mut attachments = vec![];
+
+    match existing.and_then(|existing| existing.task_id) {
+        Some(task_id) => {
+            if let Some(task) = tasks::model::Entity::find_by_id(task_id).one(&txn).await? {
+                if task.status != Status::Todo {
+                    return Err(Error::AlreadyStarted(task.id).into());
+                }
+
+                attachments = task
+                    .find_related(attachments::model::Entity)
+                    .all(&txn)
+                    .await?;
+
+                // The foreign key clears the occurrence's link to the Task
+                task.delete(&txn).await?;
+            }
+        }
+        None => {
+            occurrence::Entity::insert(occurrence::ActiveModel {
+                recurrence_id: Set(id.to_string()),
+                occurs_at: Set(input.occurs_at),
+                task_id: Set(None),
+            })
+            .on_conflict(
+                OnConflict::columns([
+                    occurrence::Column::RecurrenceId,
+                    occurrence::Column::OccursAt,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&txn)
+            .await?;
+        }
+    }
+
+    txn.commit().await?;
+
+    storage::purge(storage, &attachments).await;
+
+    Ok(recurrence)
+}
+
+/// End a `Recurrence`, deleting the Tasks generated after it ends that haven't been started
+pub async fn end(
+    db: Arc<DatabaseConnection>,
+    storage: &dyn Storage,
+    id: &str,
+    input: &inputs::End,
+) -> anyhow::Result<Recurrence> {
+    let recurrence = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    if recurrence.is_ended() {
+        return Err(Error::Ended(recurrence.id).into());
+    }
+
+    let ended_at = input
+        .ends_at
+        .unwrap_or_else(|| Utc::now().fixed_offset())
+        .trunc_subsecs(0);
+
+    let txn = db.begin().await?;
+
+    let attachments = prune(&txn, id, ended_at).await?;
+
+    let mut recurrence: model::ActiveModel = recurrence.into();
+    recurrence.ended_at = Set(Some(ended_at));
+
+    let recurrence = recurrence.update(&txn).await?;
+
+    txn.commit().await?;
+
+    storage::purge(storage, &attachments).await;
+
+    Ok(recurrence)
+}
+
+/// The Recurrences that may have occurrences left to generate, in id order
+pub async fn due(db: Arc<DatabaseConnection>) -> anyhow::Result<Vec<Recurrence>> {
+    let recurrences = model::Entity::find()
+        .filter(
+            Condition::any()
+                .add(model::Column::EndedAt.is_null())
+                .add(model::Column::GeneratedUntil.is_null())
+                .add(
+                    Expr::col(model::Column::GeneratedUntil).lt(Expr::col(model::Column::EndedAt)),
+                ),
+        )
+        .order_by_asc(model::Column::Id)
+        .all(&*db)
+        .await?;
+
+    Ok(recurrences)
+}
+
+/// Generate a Task for each of a `Recurrence`'s occurrences after the ones already generated, up
+/// to `until`, returning the Recurrence with its new watermark and the number of Tasks generated.
+/// Each occurrence is recorded before its Task is created, so repeating this, or running it from
+/// more than one server at once, never generates an occurrence twice.
+pub async fn expand(
+    db: Arc<DatabaseConnection>,
+    recurrence: Recurrence,
+    until: DateTimeUtc,
+) -> anyhow::Result<(Recurrence, usize)> {
+    if let Some(project_id) = &recurrence.project_id {
+        projects::service::check_active(&*db, project_id).await?;
+    }
+
+    let after = recurrence.generated_until;
+
+    let pending: Vec<_> = recurrence
+        .occurrences()?
+        .skip_while(|occurrence| after.is_some_and(|after| *occurrence <= after))
+        .take_while(|occurrence| *occurrence <= until)
+        .collect();
+
+    let txn = db.begin().await?;
+    let mut generated = 0;
+
+    for occurs_at in pending {
+        let occurs_at = occurs_at.fixed_offset();
+
+        let recorded = occurrence::Entity::insert(occurrence::ActiveModel {
+            recurrence_id: Set(recurrence.id.clone()),
+            occurs_at: Set(occurs_at),
+            task_id: Set(None),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                occurrence::Column::RecurrenceId,
+                occurrence::Column::OccursAt,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+
+        // The occurrence was generated or skipped before
+        if recorded == 0 {
+            continue;
+        }
+
+        let task = tasks::model::ActiveModel {
+            title: Set(recurrence.title.clone()),
+            description: Set(recurrence.description.clone()),
+            due_at: Set(Some(occurs_at)),
+            priority: Set(recurrence.priority),
+            project_id: Set(recurrence.project_id.clone()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        occurrence::Entity::update_many()
+            .col_expr(occurrence::Column::TaskId, Expr::value(task.id))
+            .filter(occurrence::Column::RecurrenceId.eq(&recurrence.id))
+            .filter(occurrence::Column::OccursAt.eq(occurs_at))
+            .exec(&txn)
+            .await?;
+
+        generated += 1;
+    }
+
+    let until = until.fixed_offset();
+
+    if after.is_some_and(|after| after >= until) {
+        txn.commit().await?;
+
+        return Ok((recurrence, generated));
+    }
+
+    // Only move the watermark on from where it was read, so a concurrent edit resetting it wins
+    let watermark = match after {
+        Some(after) => model::Column::GeneratedUntil.eq(after),
+        None => model::Column::GeneratedUntil.is_null(),
+    };
+
+    let result = model::Entity::update_many()
+        .col_expr(model::Column::GeneratedUntil, Expr::value(Some(until)))
+        .filter(model::Column::Id.eq(&recurrence.id))
+        .filter(watermark)
+        .exec(&txn)
+        .await?;
+
+    // The Recurrence was edited or deleted in the meantime, so its Tasks are rolled back to be
+    // generated the next time round
+    if result.rows_affected == 0 {
+        return Ok((recurrence, 0));
+    }
+
+    txn.commit().await?;
+
+    Ok((
+        Recurrence {
+            generated_until: Some(until),
+            ..recurrence
+        },
+        generated,
+    ))
+}
+
+/// Delete the Tasks generated for a Recurrence's occurrences after a point that haven't been
+/// started, along with the occurrences so they can be generated again. Returns the Tasks'
+/// Attachments, whose bytes are purged once the deletes are committed.
+async fn prune<C: ConnectionTrait>(
+    db: &C,
+    id: &str,
+    after: DateTimeWithTimeZone,
+) -> anyhow::Result<Vec<Attachment>> {
+    let subquery = occurrence::Entity::find()
+        .select_only()
+        .column(occurrence::Column::TaskId)
+        .filter(occurrence::Column::RecurrenceId.eq(id))
+        .filter(occurrence::Column::OccursAt.gt(after))
+        .into_query();
+
+    let task_ids: Vec<String> = tasks::model::Entity::find()
+        .filter(tasks::model::Column::Id.in_subquery(subquery))
+        .filter(tasks::model::Column::Status.eq(Status::Todo))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|task| task.id)
+        .collect();
+
+    if task_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Listed first, since the foreign key deletes the Attachments along with the Tasks
+    let attachments = attachments::model::Entity::find()
+        .filter(attachments::model::Column::TaskId.is_in(task_ids.clone()))
+        .all(db)
+        .await?;
+
+    occurrence::Entity::delete_many()
+        .filter(occurrence::Column::RecurrenceId.eq(id))
+        .filter(occurrence::Column::TaskId.is_in(task_ids.clone()))
+        .exec(db)
+        .await?;
+
+    tasks::model::Entity::delete_many()
+        .filter(tasks::model::Column::Id.is_in(task_ids))
+        .exec(db)
+        .await?;
+
+    Ok(attachments)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+
+    use crate::{attachments::storage::LocalStorage, tasks::model::Task};
+
+    use super::*;
+
+    fn unused_storage() -> LocalStorage {
+        LocalStorage::new(std::env::temp_dir().join("unused-attachments"))
+    }
+
+    fn exec(rows_affected: u64) -> MockExecResult {
+        MockExecResult {
+            last_insert_id: 0,
+            rows_affected,
+        }
+    }
+
+    fn weekly() -> anyhow::Result<Recurrence> {
+        Ok(Recurrence {
+            id: "01HR".to_string(),
+            title: "Ops checklist".to_string(),
+            rule: "FREQ=WEEKLY;BYDAY=MO".to_string(),
+            starts_at: DateTime::parse_from_rfc3339("2026-10-19T09:00:00Z")?,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_expand_skips_recorded_occurrences() -> anyhow::Result<()> {
+        let recurrence = weekly()?;
+        let until = DateTime::parse_from_rfc3339("2026-10-27T00:00:00Z")?.to_utc();
+
+        let task = Task {
+            id: "01HT".to_string(),
+            title: recurrence.title.clone(),
+            due_at: Some(recurrence.starts_at),
+            ..Default::default()
+        };
+
+        // The first Monday is new, while the second was already generated by another server
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([exec(1)])
+                .append_query_results([vec![task.clone()]])
+                .append_exec_results([exec(1), exec(0), exec(1)])
+                .into_connection(),
+        );
+
+        let (expanded, generated) = expand(db.clone(), recurrence.clone(), until).await?;
+
+        assert_eq!(generated, 1);
+        assert_eq!(expanded.generated_until, Some(until.fixed_offset()));
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        // The second occurrence is recorded already, so only one Task is inserted
+        let log = format!("{log:?}");
+
+        assert!(log.contains(r#"ON CONFLICT (\"recurrence_id\", \"occurs_at\") DO NOTHING"#));
+        assert_eq!(log.matches(r#"INSERT INTO \"tasks\""#).count(), 1);
+        assert!(log.contains(r#"\"recurrences\".\"generated_until\" IS NULL"#));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_skip() -> anyhow::Result<()> {
+        let recurrence = weekly()?;
+        let started = Task {
+            id: "01HT".to_string(),
+            status: Status::InProgress,
+            ..Default::default()
+        };
+        let recorded = occurrence::Model {
+            recurrence_id: recurrence.id.clone(),
+            occurs_at: recurrence.starts_at,
+            task_id: Some(started.id.clone()),
+        };
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![recurrence.clone()]])
+                .append_query_results([vec![recorded]])
+                .append_query_results([vec![started.clone()]])
+                .into_connection(),
+        );
+
+        let err = skip(
+            db.clone(),
+            &unused_storage(),
+            &recurrence.id,
+            &inputs::Skip {
+                occurs_at: recurrence.starts_at,
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert!(Error::is_already_started(&err));
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![recurrence.clone()]])
+                .into_connection(),
+        );
+
+        // A Tuesday, while the Recurrence is every Monday
+        let err = skip(
+            db,
+            &unused_storage(),
+            &recurrence.id,
+            &inputs::Skip {
+                occurs_at: DateTime::parse_from_rfc3339("2026-10-20T09:00:00Z")?,
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert!(Error::is_not_an_occurrence(&err));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_ended_recurrences() -> anyhow::Result<()> {
+        let recurrence = Recurrence {
+            ended_at: Some(Utc::now().fixed_offset()),
+            ..weekly()?
+        };
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![recurrence.clone()]])
+                .into_connection(),
+        );
+
+        let err = update(
+            db,
+            &unused_storage(),
+            Duration::days(14),
+            &recurrence.id,
+            &inputs::Update::default(),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(Error::is_ended(&err));
+
+        Ok(())
+    }
+}
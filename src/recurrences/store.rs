@@ -0,0 +1,94 @@
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    projects,
+    tasks::{self, store::Store},
+};
+
+use super::{dynamo_service, model::Recurrence, service};
+
+/// A page of `Recurrence`s
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[schema(as = RecurrencePage)]
+pub struct RecurrencePage {
+    /// The Recurrences on this page
+    pub recurrences: Vec<Recurrence>,
+
+    /// The cursor to request the next page with, if there is one
+    pub next_cursor: Option<String>,
+}
+
+impl RecurrencePage {
+    /// Build a page from up to `limit + 1` Recurrences, where the extra Recurrence signals a next
+    /// page
+    pub fn new(mut recurrences: Vec<Recurrence>, limit: usize) -> Self {
+        let has_next_page = recurrences.len() > limit;
+        recurrences.truncate(limit);
+
+        let next_cursor = recurrences
+            .last()
+            .filter(|_| has_next_page)
+            .map(|recurrence| recurrence.id.clone());
+
+        Self {
+            recurrences,
+            next_cursor,
+        }
+    }
+}
+
+/// Recurrences live in the same data store as the Tasks they generate, so the Task store
+/// expands them
+impl Store {
+    /// Generate Tasks for every Recurrence's occurrences up to `horizon` from now, returning the
+    /// number of Tasks generated. A Recurrence that fails is logged and left for the next run.
+    pub async fn expand_recurrences(&self, horizon: Duration) -> anyhow::Result<usize> {
+        let recurrences = match self {
+            Store::Database(db) => service::due(db.clone()).await?,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::due(client.clone(), table_name).await?,
+        };
+
+        let until = Utc::now() + horizon;
+        let mut generated = 0;
+
+        for recurrence in recurrences {
+            let id = recurrence.id.clone();
+
+            let result = match self {
+                Store::Database(db) => service::expand(db.clone(), recurrence, until).await,
+                Store::Dynamo {
+                    client,
+                    table_name,
+                    timestamp_format,
+                } => {
+                    dynamo_service::expand(
+                        client.clone(),
+                        table_name,
+                        *timestamp_format,
+                        recurrence,
+                        until,
+                    )
+                    .await
+                }
+            };
+
+            match result {
+                Ok((_, count)) => generated += count,
+                // Tasks can't be added to an archived Project, so the Recurrence waits for it
+                Err(err)
+                    if projects::error::Error::is_archived(&err)
+                        || tasks::error::Error::is_project_not_found(&err) =>
+                {
+                    log::debug!("Recurrence {id} not expanded: {err}");
+                }
+                Err(err) => log::warn!("Failed to expand Recurrence {id}: {err:#}"),
+            }
+        }
+
+        Ok(generated)
+    }
+}
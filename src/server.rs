@@ -0,0 +1,2661 @@
+use std::sync::Arc;
+
+use aws_sdk_dynamodb::Client;
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{
+        multipart::MultipartError, DefaultBodyLimit, FromRequestParts, Multipart, Path, Query,
+        State,
+    },
+    http::{header, request::Parts, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, patch, post, put},
+    Json, Router,
+};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+
+use crate::{
+    attachments::{
+        self,
+        model::Attachment,
+        storage::{self, Storage},
+    },
+    comments::{self, store::CommentPage},
+    config::{self, ConfigForDB, ConfigForDynamo},
+    dependencies, graphql, openapi,
+    projects::{self, store::ProjectPage},
+    recurrences::{self, store::RecurrencePage},
+    tasks::{
+        self,
+        model::Task,
+        store::{Page, Store},
+        tag::TagCount,
+        tree::Tree,
+        workflow::Transitions,
+    },
+    utils::timezone::{Timezone, WithTimestamps},
+};
+
+/// Room in an upload's request body for the multipart boundaries and headers around the file
+const MULTIPART_OVERHEAD: usize = 64 * 1024;
+
+/// The application state when Tasks are stored in Postgres
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct DatabaseAppState {
+    /// The application config
+    pub config: ConfigForDB,
+
+    /// The Postgres connection
+    pub db: Arc<DatabaseConnection>,
+
+    /// Where the bytes of Attachments are kept
+    pub storage: Arc<dyn Storage>,
+}
+
+/// The application state when Tasks are stored in DynamoDB
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct DynamoAppState {
+    /// The application config
+    pub config: ConfigForDynamo,
+
+    /// The DynamoDB client
+    pub client: Arc<Client>,
+
+    /// The table name to use for Tasks
+    pub tasks_table_name: String,
+
+    /// Where the bytes of Attachments are kept
+    pub storage: Arc<dyn Storage>,
+}
+
+/// Build the HTTP router for Tasks stored in Postgres
+pub fn database_router(state: DatabaseAppState) -> Router {
+    let store = Store::Database(state.db.clone());
+    let upload_limit = state.config.attachments.max_size + MULTIPART_OVERHEAD;
+
+    Router::new()
+        .route("/tasks", get(tasks_list_from_db).post(tasks_create_in_db))
+        .route(
+            "/tasks/:id",
+            get(tasks_get_from_db)
+                .patch(tasks_update_in_db)
+                .delete(tasks_delete_in_db),
+        )
+        .route(
+            "/tasks/:id/transitions",
+            get(tasks_transitions_from_db).post(tasks_transition_in_db),
+        )
+        .route("/tasks/:id/tags", get(tasks_tags_from_db))
+        .route(
+            "/tasks/:id/tags/:name",
+            put(tasks_tag_in_db).delete(tasks_untag_in_db),
+        )
+        .route("/tags", get(tags_list_from_db))
+        .route("/tags/:name/tasks", get(tags_tasks_from_db))
+        .route("/tasks/:id/children", get(tasks_children_from_db))
+        .route("/tasks/:id/subtree", get(tasks_subtree_from_db))
+        .route("/tasks/:id/parent", put(tasks_move_in_db))
+        .route("/tasks/:id/blockers", get(tasks_blockers_from_db))
+        .route(
+            "/tasks/:id/blockers/:blocker_id",
+            put(tasks_block_in_db).delete(tasks_unblock_in_db),
+        )
+        .route("/tasks/:id/dependents", get(tasks_dependents_from_db))
+        .route(
+            "/tasks/:id/comments",
+            get(comments_list_from_db).post(comments_create_in_db),
+        )
+        .route(
+            "/tasks/:id/comments/:comment_id",
+            patch(comments_update_in_db).delete(comments_delete_in_db),
+        )
+        .route(
+            "/tasks/:id/attachments",
+            get(attachments_list_from_db)
+                .post(attachments_create_in_db)
+                .layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route(
+            "/tasks/:id/attachments/:attachment_id",
+            get(attachments_download_from_db).delete(attachments_delete_in_db),
+        )
+        .route(
+            "/projects",
+            get(projects_list_from_db).post(projects_create_in_db),
+        )
+        .route(
+            "/projects/:id",
+            get(projects_get_from_db)
+                .patch(projects_update_in_db)
+                .delete(projects_delete_in_db),
+        )
+        .route("/projects/:id/archive", post(projects_archive_in_db))
+        .route("/projects/:id/restore", post(projects_restore_in_db))
+        .route("/projects/:id/tasks", get(projects_tasks_from_db))
+        .route(
+            "/recurrences",
+            get(recurrences_list_from_db).post(recurrences_create_in_db),
+        )
+        .route(
+            "/recurrences/:id",
+            get(recurrences_get_from_db)
+                .patch(recurrences_update_in_db)
+                .delete(recurrences_delete_in_db),
+        )
+        .route("/recurrences/:id/skip", post(recurrences_skip_in_db))
+        .route("/recurrences/:id/end", post(recurrences_end_in_db))
+        .with_state(state.clone())
+        .merge(graphql::router(
+            store,
+            state.config.workflow,
+            state.storage.clone(),
+        ))
+        .merge(openapi::router())
+}
+
+/// Build the HTTP router for Tasks stored in DynamoDB
+pub fn dynamo_router(state: DynamoAppState) -> Router {
+    let store = Store::Dynamo {
+        client: state.client.clone(),
+        table_name: state.tasks_table_name.clone(),
+        timestamp_format: state.config.dynamo.timestamp_format,
+    };
+    let upload_limit = state.config.attachments.max_size + MULTIPART_OVERHEAD;
+
+    Router::new()
+        .route(
+            "/tasks",
+            get(tasks_list_from_dynamo).post(tasks_create_in_dynamo),
+        )
+        .route(
+            "/tasks/:id",
+            get(tasks_get_from_dynamo)
+                .patch(tasks_update_in_dynamo)
+                .delete(tasks_delete_in_dynamo),
+        )
+        .route(
+            "/tasks/:id/transitions",
+            get(tasks_transitions_from_dynamo).post(tasks_transition_in_dynamo),
+        )
+        .route("/tasks/:id/tags", get(tasks_tags_from_dynamo))
+        .route(
+            "/tasks/:id/tags/:name",
+            put(tasks_tag_in_dynamo).delete(tasks_untag_in_dynamo),
+        )
+        .route("/tags", get(tags_list_from_dynamo))
+        .route("/tags/:name/tasks", get(tags_tasks_from_dynamo))
+        .route("/tasks/:id/children", get(tasks_children_from_dynamo))
+        .route("/tasks/:id/subtree", get(tasks_subtree_from_dynamo))
+        .route("/tasks/:id/parent", put(tasks_move_in_dynamo))
+        .route("/tasks/:id/blockers", get(tasks_blockers_from_dynamo))
+        .route(
+            "/tasks/:id/blockers/:blocker_id",
+            put(tasks_block_in_dynamo).delete(tasks_unblock_in_dynamo),
+        )
+        .route("/tasks/:id/dependents", get(tasks_dependents_from_dynamo))
+        .route(
+            "/tasks/:id/comments",
+            get(comments_list_from_dynamo).post(comments_create_in_dynamo),
+        )
+        .route(
+            "/tasks/:id/comments/:comment_id",
+            patch(comments_update_in_dynamo).delete(comments_delete_in_dynamo),
+        )
+        .route(
+            "/tasks/:id/attachments",
+            get(attachments_list_from_dynamo)
+                .post(attachments_create_in_dynamo)
+                .layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route(
+            "/tasks/:id/attachments/:attachment_id",
+            get(attachments_download_from_dynamo).delete(attachments_delete_in_dynamo),
+        )
+        .route(
+            "/projects",
+            get(projects_list_from_dynamo).post(projects_create_in_dynamo),
+        )
+        .route(
+            "/projects/:id",
+            get(projects_get_from_dynamo)
+                .patch(projects_update_in_dynamo)
+                .delete(projects_delete_in_dynamo),
+        )
+        .route("/projects/:id/archive", post(projects_archive_in_dynamo))
+        .route("/projects/:id/restore", post(projects_restore_in_dynamo))
+        .route("/projects/:id/tasks", get(projects_tasks_from_dynamo))
+        .route(
+            "/recurrences",
+            get(recurrences_list_from_dynamo).post(recurrences_create_in_dynamo),
+        )
+        .route(
+            "/recurrences/:id",
+            get(recurrences_get_from_dynamo)
+                .patch(recurrences_update_in_dynamo)
+                .delete(recurrences_delete_in_dynamo),
+        )
+        .route("/recurrences/:id/skip", post(recurrences_skip_in_dynamo))
+        .route("/recurrences/:id/end", post(recurrences_end_in_dynamo))
+        .with_state(state.clone())
+        .merge(graphql::router(
+            store,
+            state.config.workflow,
+            state.storage.clone(),
+        ))
+        .merge(openapi::router())
+}
+
+/// List a page of Tasks
+#[utoipa::path(
+    get,
+    path = "/tasks",
+    tag = "tasks",
+    params(
+        tasks::inputs::List,
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "A page of Tasks", body = Page),
+        (status = 400, description = "Invalid cursor", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_list_from_db(
+    Query(query): Query<tasks::inputs::List>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    let tasks = match tasks::service::list(state.db.clone(), &query, limit as u64 + 1).await {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(Page::new(
+        timezone.render(tasks),
+        limit,
+        query.order(),
+    )))
+}
+
+async fn tasks_list_from_dynamo(
+    Query(query): Query<tasks::inputs::List>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    let tasks = match tasks::dynamo_service::list(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &query,
+        limit as u64 + 1,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(Page::new(
+        timezone.render(tasks),
+        limit,
+        query.order(),
+    )))
+}
+
+/// Get a Task by id
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}",
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Task", body = tasks::model::Task),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_get_from_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let maybe_task = match tasks::service::get(state.db.clone(), &id).await {
+        Ok(result) => result,
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+
+    if let Some(task) = maybe_task {
+        return Ok(Json(timezone.render(task)));
+    }
+
+    Err((StatusCode::NOT_FOUND, "Task not found".to_string()))
+}
+
+async fn tasks_get_from_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let maybe_task = match tasks::dynamo_service::get(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &id,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+
+    if let Some(task) = maybe_task {
+        return Ok(Json(timezone.render(task)));
+    }
+
+    Err((StatusCode::NOT_FOUND, "Task not found".to_string()))
+}
+
+/// Create a Task
+#[utoipa::path(
+    post,
+    path = "/tasks",
+    tag = "tasks",
+    params(("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),),
+    request_body = tasks::inputs::Create,
+    responses(
+        (status = 200, description = "The created Task", body = tasks::model::Task),
+        (status = 400, description = "Parent Task or Project not found", body = String, content_type = "text/plain"),
+        (status = 409, description = "The Project is archived", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_create_in_db(
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<tasks::inputs::Create>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let task = match tasks::service::create(state.db.clone(), &input).await {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(timezone.render(task)))
+}
+
+async fn tasks_create_in_dynamo(
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<tasks::inputs::Create>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let task = match tasks::dynamo_service::create(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &input,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(timezone.render(task)))
+}
+
+/// Update a Task. Omitted fields are left unchanged and `null` fields are cleared.
+#[utoipa::path(
+    patch,
+    path = "/tasks/{id}",
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    request_body = tasks::inputs::Update,
+    responses(
+        (status = 200, description = "The updated Task", body = tasks::model::Task),
+        (status = 400, description = "Project not found", body = String, content_type = "text/plain"),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 409, description = "The Project is archived", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_update_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<tasks::inputs::Update>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let task = match tasks::service::update(state.db.clone(), &id, &input).await {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(timezone.render(task)))
+}
+
+async fn tasks_update_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<tasks::inputs::Update>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let task = match tasks::dynamo_service::update(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &id,
+        &input,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(timezone.render(task)))
+}
+
+/// Delete a Task with its Comments and Attachments. Send `Prefer: return=representation` to receive
+/// the deleted Task.
+#[utoipa::path(
+    delete,
+    path = "/tasks/{id}",
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("Prefer" = Option<String>, Header, description = "`return=representation` to receive the deleted Task"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Task was deleted, with the deleted Task if requested", body = Option<tasks::model::Task>),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_delete_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let task = match tasks::service::delete(state.db.clone(), state.storage.as_ref(), &id).await {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(deleted_response(timezone.render(task), &headers))
+}
+
+async fn tasks_delete_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let task = match tasks::dynamo_service::delete(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.storage.as_ref(),
+        &id,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(deleted_response(timezone.render(task), &headers))
+}
+
+/// List the statuses a Task can move to from its current status
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/transitions",
+    tag = "tasks",
+    params(("id" = String, Path, description = "The Task id")),
+    responses(
+        (status = 200, description = "The Task's status and the statuses it can move to", body = Transitions),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_transitions_from_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let maybe_task = match tasks::service::get(state.db.clone(), &id).await {
+        Ok(result) => result,
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+
+    if let Some(task) = maybe_task {
+        return Ok(Json(Transitions::new(&state.config.workflow, task.status)));
+    }
+
+    Err((StatusCode::NOT_FOUND, "Task not found".to_string()))
+}
+
+async fn tasks_transitions_from_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let maybe_task = match tasks::dynamo_service::get(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &id,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+
+    if let Some(task) = maybe_task {
+        return Ok(Json(Transitions::new(&state.config.workflow, task.status)));
+    }
+
+    Err((StatusCode::NOT_FOUND, "Task not found".to_string()))
+}
+
+/// Move a Task to another status, if the configured workflow allows it
+#[utoipa::path(
+    post,
+    path = "/tasks/{id}/transitions",
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    request_body = tasks::inputs::Transition,
+    responses(
+        (status = 200, description = "The updated Task", body = tasks::model::Task),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 409, description = "The workflow doesn't allow the transition, or the Task has unfinished blockers", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_transition_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<tasks::inputs::Transition>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let task = match tasks::service::transition(
+        state.db.clone(),
+        &state.config.workflow,
+        &id,
+        input.status,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(timezone.render(task)))
+}
+
+async fn tasks_transition_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<tasks::inputs::Transition>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let task = match tasks::dynamo_service::transition(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &state.config.workflow,
+        &id,
+        input.status,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(timezone.render(task)))
+}
+
+/// List the names of a Task's Tags
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/tags",
+    tag = "tags",
+    params(("id" = String, Path, description = "The Task id")),
+    responses(
+        (status = 200, description = "The Task's Tags, in name order", body = Vec<String>),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_tags_from_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::service::tags(state.db.clone(), &id).await {
+        Ok(tags) => Ok(Json(tags)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tasks_tags_from_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::dynamo_service::tags(state.client.clone(), &state.tasks_table_name, &id).await {
+        Ok(tags) => Ok(Json(tags)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Add a Tag to a Task, creating the Tag the first time it's used
+#[utoipa::path(
+    put,
+    path = "/tasks/{id}/tags/{name}",
+    tag = "tags",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("name" = String, Path, description = "The Tag name, which is trimmed and lowercased"),
+    ),
+    responses(
+        (status = 200, description = "The Task's Tags, in name order", body = Vec<String>),
+        (status = 400, description = "Invalid Tag name", body = String, content_type = "text/plain"),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_tag_in_db(
+    Path((id, name)): Path<(String, String)>,
+    State(state): State<DatabaseAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::service::add_tag(state.db.clone(), &id, &name).await {
+        Ok(tags) => Ok(Json(tags)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tasks_tag_in_dynamo(
+    Path((id, name)): Path<(String, String)>,
+    State(state): State<DynamoAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::dynamo_service::add_tag(state.client.clone(), &state.tasks_table_name, &id, &name)
+        .await
+    {
+        Ok(tags) => Ok(Json(tags)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Remove a Tag from a Task
+#[utoipa::path(
+    delete,
+    path = "/tasks/{id}/tags/{name}",
+    tag = "tags",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("name" = String, Path, description = "The Tag name"),
+    ),
+    responses(
+        (status = 200, description = "The Task's remaining Tags, in name order", body = Vec<String>),
+        (status = 400, description = "Invalid Tag name", body = String, content_type = "text/plain"),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_untag_in_db(
+    Path((id, name)): Path<(String, String)>,
+    State(state): State<DatabaseAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::service::remove_tag(state.db.clone(), &id, &name).await {
+        Ok(tags) => Ok(Json(tags)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tasks_untag_in_dynamo(
+    Path((id, name)): Path<(String, String)>,
+    State(state): State<DynamoAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::dynamo_service::remove_tag(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &id,
+        &name,
+    )
+    .await
+    {
+        Ok(tags) => Ok(Json(tags)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// List every Tag with the number of Tasks it labels
+#[utoipa::path(
+    get,
+    path = "/tags",
+    tag = "tags",
+    responses(
+        (status = 200, description = "Every Tag with its Task count, in name order", body = Vec<TagCount>),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tags_list_from_db(
+    State(state): State<DatabaseAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::service::tag_counts(state.db.clone()).await {
+        Ok(counts) => Ok(Json(counts)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tags_list_from_dynamo(
+    State(state): State<DynamoAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::dynamo_service::tag_counts(state.client.clone(), &state.tasks_table_name).await {
+        Ok(counts) => Ok(Json(counts)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// List a page of the Tasks with a Tag
+#[utoipa::path(
+    get,
+    path = "/tags/{name}/tasks",
+    tag = "tags",
+    params(
+        ("name" = String, Path, description = "The Tag name"),
+        tasks::inputs::ListByTag,
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "A page of Tasks", body = Page),
+        (status = 400, description = "Invalid Tag name", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tags_tasks_from_db(
+    Path(name): Path<String>,
+    Query(query): Query<tasks::inputs::ListByTag>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    let tasks = match tasks::service::list_by_tag(
+        state.db.clone(),
+        &name,
+        query.after.as_deref(),
+        limit as u64 + 1,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(Page::new(
+        timezone.render(tasks),
+        limit,
+        tasks::inputs::Order::Id,
+    )))
+}
+
+async fn tags_tasks_from_dynamo(
+    Path(name): Path<String>,
+    Query(query): Query<tasks::inputs::ListByTag>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    let tasks = match tasks::dynamo_service::list_by_tag(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &name,
+        query.after.as_deref(),
+        limit as u64 + 1,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(Page::new(
+        timezone.render(tasks),
+        limit,
+        tasks::inputs::Order::Id,
+    )))
+}
+
+/// List a Task's direct subtasks, in id order
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/children",
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Task's subtasks", body = Vec<tasks::model::Task>),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_children_from_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::service::children(state.db.clone(), &id).await {
+        Ok(tasks) => Ok(Json(timezone.render(tasks))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tasks_children_from_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::dynamo_service::children(state.client.clone(), &state.tasks_table_name, &id).await
+    {
+        Ok(tasks) => Ok(Json(timezone.render(tasks))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Get a Task with every subtask below it, and how many of them are done
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/subtree",
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Task and its subtasks", body = Tree),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_subtree_from_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::service::subtree(state.db.clone(), &id).await {
+        Ok(tree) => Ok(Json(timezone.render(tree))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tasks_subtree_from_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::dynamo_service::subtree(state.client.clone(), &state.tasks_table_name, &id).await {
+        Ok(tree) => Ok(Json(timezone.render(tree))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Move a Task under another parent, or to the top level with a `null` parent
+#[utoipa::path(
+    put,
+    path = "/tasks/{id}/parent",
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    request_body = tasks::inputs::Move,
+    responses(
+        (status = 200, description = "The moved Task", body = tasks::model::Task),
+        (status = 400, description = "Parent Task not found", body = String, content_type = "text/plain"),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 409, description = "The parent is the Task or one of its subtasks", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_move_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<tasks::inputs::Move>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::service::move_task(state.db.clone(), &id, &input).await {
+        Ok(task) => Ok(Json(timezone.render(task))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tasks_move_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<tasks::inputs::Move>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match tasks::dynamo_service::move_task(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &id,
+        &input,
+    )
+    .await
+    {
+        Ok(task) => Ok(Json(timezone.render(task))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// List the Tasks blocking a Task
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/blockers",
+    tag = "dependencies",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Task's blockers, in id order", body = Vec<tasks::model::Task>),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_blockers_from_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match dependencies::service::blockers(state.db.clone(), &id).await {
+        Ok(tasks) => Ok(Json(timezone.render(tasks))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tasks_blockers_from_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match dependencies::dynamo_service::blockers(state.client.clone(), &state.tasks_table_name, &id)
+        .await
+    {
+        Ok(tasks) => Ok(Json(timezone.render(tasks))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Block a Task by another Task, which must be finished first
+#[utoipa::path(
+    put,
+    path = "/tasks/{id}/blockers/{blocker_id}",
+    tag = "dependencies",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("blocker_id" = String, Path, description = "The id of the Task blocking it"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Task's blockers, in id order", body = Vec<tasks::model::Task>),
+        (status = 404, description = "Task or blocker not found", body = String, content_type = "text/plain"),
+        (status = 409, description = "The blocker already depends on the Task", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_block_in_db(
+    Path((id, blocker_id)): Path<(String, String)>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match dependencies::service::add(state.db.clone(), &id, &blocker_id).await {
+        Ok(tasks) => Ok(Json(timezone.render(tasks))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tasks_block_in_dynamo(
+    Path((id, blocker_id)): Path<(String, String)>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match dependencies::dynamo_service::add(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &id,
+        &blocker_id,
+    )
+    .await
+    {
+        Ok(tasks) => Ok(Json(timezone.render(tasks))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Stop a Task from blocking another Task
+#[utoipa::path(
+    delete,
+    path = "/tasks/{id}/blockers/{blocker_id}",
+    tag = "dependencies",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("blocker_id" = String, Path, description = "The id of the Task blocking it"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Task's remaining blockers, in id order", body = Vec<tasks::model::Task>),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_unblock_in_db(
+    Path((id, blocker_id)): Path<(String, String)>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match dependencies::service::remove(state.db.clone(), &id, &blocker_id).await {
+        Ok(tasks) => Ok(Json(timezone.render(tasks))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tasks_unblock_in_dynamo(
+    Path((id, blocker_id)): Path<(String, String)>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match dependencies::dynamo_service::remove(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &id,
+        &blocker_id,
+    )
+    .await
+    {
+        Ok(tasks) => Ok(Json(timezone.render(tasks))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// List the Tasks blocked by a Task
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/dependents",
+    tag = "dependencies",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Tasks waiting on the Task, in id order", body = Vec<tasks::model::Task>),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn tasks_dependents_from_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match dependencies::service::dependents(state.db.clone(), &id).await {
+        Ok(tasks) => Ok(Json(timezone.render(tasks))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn tasks_dependents_from_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match dependencies::dynamo_service::dependents(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &id,
+    )
+    .await
+    {
+        Ok(tasks) => Ok(Json(timezone.render(tasks))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// List a page of the Comments on a Task, oldest first
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/comments",
+    tag = "comments",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        comments::inputs::List,
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "A page of Comments", body = CommentPage),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn comments_list_from_db(
+    Path(id): Path<String>,
+    Query(query): Query<comments::inputs::List>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    match comments::service::list(
+        state.db.clone(),
+        &id,
+        query.after.as_deref(),
+        limit as u64 + 1,
+    )
+    .await
+    {
+        Ok(comments) => Ok(Json(CommentPage::new(timezone.render(comments), limit))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn comments_list_from_dynamo(
+    Path(id): Path<String>,
+    Query(query): Query<comments::inputs::List>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    match comments::dynamo_service::list(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &id,
+        query.after.as_deref(),
+        limit as u64 + 1,
+    )
+    .await
+    {
+        Ok(comments) => Ok(Json(CommentPage::new(timezone.render(comments), limit))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Post a Comment on a Task
+#[utoipa::path(
+    post,
+    path = "/tasks/{id}/comments",
+    tag = "comments",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    request_body = comments::inputs::Create,
+    responses(
+        (status = 200, description = "The posted Comment", body = comments::model::Comment),
+        (status = 400, description = "Blank author or body, or the body is too long", body = String, content_type = "text/plain"),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn comments_create_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<comments::inputs::Create>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match comments::service::create(state.db.clone(), &id, &input).await {
+        Ok(comment) => Ok(Json(timezone.render(comment))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn comments_create_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<comments::inputs::Create>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match comments::dynamo_service::create(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &id,
+        &input,
+    )
+    .await
+    {
+        Ok(comment) => Ok(Json(timezone.render(comment))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Edit the body of a Comment on a Task
+#[utoipa::path(
+    patch,
+    path = "/tasks/{id}/comments/{comment_id}",
+    tag = "comments",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("comment_id" = String, Path, description = "The Comment id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    request_body = comments::inputs::Update,
+    responses(
+        (status = 200, description = "The edited Comment", body = comments::model::Comment),
+        (status = 400, description = "Blank body, or the body is too long", body = String, content_type = "text/plain"),
+        (status = 404, description = "Comment not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn comments_update_in_db(
+    Path((id, comment_id)): Path<(String, String)>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<comments::inputs::Update>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match comments::service::update(state.db.clone(), &id, &comment_id, &input).await {
+        Ok(comment) => Ok(Json(timezone.render(comment))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn comments_update_in_dynamo(
+    Path((id, comment_id)): Path<(String, String)>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<comments::inputs::Update>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match comments::dynamo_service::update(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &id,
+        &comment_id,
+        &input,
+    )
+    .await
+    {
+        Ok(comment) => Ok(Json(timezone.render(comment))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Delete a Comment on a Task
+#[utoipa::path(
+    delete,
+    path = "/tasks/{id}/comments/{comment_id}",
+    tag = "comments",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("comment_id" = String, Path, description = "The Comment id"),
+    ),
+    responses(
+        (status = 200, description = "The Comment was deleted"),
+        (status = 404, description = "Comment not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn comments_delete_in_db(
+    Path((id, comment_id)): Path<(String, String)>,
+    State(state): State<DatabaseAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match comments::service::delete(state.db.clone(), &id, &comment_id).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn comments_delete_in_dynamo(
+    Path((id, comment_id)): Path<(String, String)>,
+    State(state): State<DynamoAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match comments::dynamo_service::delete(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &id,
+        &comment_id,
+    )
+    .await
+    {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// List the files attached to a Task, oldest first
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/attachments",
+    tag = "attachments",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Task's Attachments", body = Vec<attachments::model::Attachment>),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn attachments_list_from_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match attachments::service::list(state.db.clone(), &id).await {
+        Ok(attachments) => Ok(Json(timezone.render(attachments))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn attachments_list_from_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match attachments::dynamo_service::list(state.client.clone(), &state.tasks_table_name, &id)
+        .await
+    {
+        Ok(attachments) => Ok(Json(timezone.render(attachments))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Attach a file to a Task, uploaded in the `file` field of a multipart form
+#[utoipa::path(
+    post,
+    path = "/tasks/{id}/attachments",
+    tag = "attachments",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    request_body(content = attachments::inputs::UploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "The new Attachment", body = attachments::model::Attachment),
+        (status = 400, description = "No file was uploaded, or the file has no name", body = String, content_type = "text/plain"),
+        (status = 404, description = "Task not found", body = String, content_type = "text/plain"),
+        (status = 413, description = "The file is too large", body = String, content_type = "text/plain"),
+        (status = 415, description = "The file's content type isn't allowed", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn attachments_create_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let upload = match read_upload(multipart, &state.config.attachments).await {
+        Ok(upload) => upload,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    match attachments::service::create(
+        state.db.clone(),
+        state.storage.as_ref(),
+        &state.config.attachments,
+        &id,
+        upload,
+    )
+    .await
+    {
+        Ok(attachment) => Ok(Json(timezone.render(attachment))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn attachments_create_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let upload = match read_upload(multipart, &state.config.attachments).await {
+        Ok(upload) => upload,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    match attachments::dynamo_service::create(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        state.storage.as_ref(),
+        &state.config.attachments,
+        &id,
+        upload,
+    )
+    .await
+    {
+        Ok(attachment) => Ok(Json(timezone.render(attachment))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Download a file attached to a Task
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/attachments/{attachment_id}",
+    tag = "attachments",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("attachment_id" = String, Path, description = "The Attachment id"),
+    ),
+    responses(
+        (status = 200, description = "The file, with its content type and name", body = String, content_type = "application/octet-stream"),
+        (status = 404, description = "Attachment not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn attachments_download_from_db(
+    Path((id, attachment_id)): Path<(String, String)>,
+    State(state): State<DatabaseAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let attachment = match attachments::service::get(state.db.clone(), &id, &attachment_id).await {
+        Ok(attachment) => attachment,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    download_response(state.storage.as_ref(), attachment).await
+}
+
+async fn attachments_download_from_dynamo(
+    Path((id, attachment_id)): Path<(String, String)>,
+    State(state): State<DynamoAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let attachment = match attachments::dynamo_service::get(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &id,
+        &attachment_id,
+    )
+    .await
+    {
+        Ok(attachment) => attachment,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    download_response(state.storage.as_ref(), attachment).await
+}
+
+/// Delete a file attached to a Task
+#[utoipa::path(
+    delete,
+    path = "/tasks/{id}/attachments/{attachment_id}",
+    tag = "attachments",
+    params(
+        ("id" = String, Path, description = "The Task id"),
+        ("attachment_id" = String, Path, description = "The Attachment id"),
+    ),
+    responses(
+        (status = 200, description = "The Attachment was deleted"),
+        (status = 404, description = "Attachment not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn attachments_delete_in_db(
+    Path((id, attachment_id)): Path<(String, String)>,
+    State(state): State<DatabaseAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match attachments::service::delete(
+        state.db.clone(),
+        state.storage.as_ref(),
+        &id,
+        &attachment_id,
+    )
+    .await
+    {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn attachments_delete_in_dynamo(
+    Path((id, attachment_id)): Path<(String, String)>,
+    State(state): State<DynamoAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match attachments::dynamo_service::delete(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.storage.as_ref(),
+        &id,
+        &attachment_id,
+    )
+    .await
+    {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// List a page of Projects, leaving out archived Projects unless they're included
+#[utoipa::path(
+    get,
+    path = "/projects",
+    tag = "projects",
+    params(
+        projects::inputs::List,
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "A page of Projects", body = ProjectPage),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn projects_list_from_db(
+    Query(query): Query<projects::inputs::List>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    match projects::service::list(state.db.clone(), &query, limit as u64 + 1).await {
+        Ok(projects) => Ok(Json(ProjectPage::new(timezone.render(projects), limit))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn projects_list_from_dynamo(
+    Query(query): Query<projects::inputs::List>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    match projects::dynamo_service::list(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &query,
+        limit as u64 + 1,
+    )
+    .await
+    {
+        Ok(projects) => Ok(Json(ProjectPage::new(timezone.render(projects), limit))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Get a Project by id
+#[utoipa::path(
+    get,
+    path = "/projects/{id}",
+    tag = "projects",
+    params(
+        ("id" = String, Path, description = "The Project id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Project", body = projects::model::Project),
+        (status = 404, description = "Project not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn projects_get_from_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::service::get(state.db.clone(), &id).await {
+        Ok(Some(project)) => Ok(Json(timezone.render(project))),
+        Ok(None) => Err(error_response(projects::error::Error::NotFound.into())),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn projects_get_from_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::dynamo_service::get(state.client.clone(), &state.tasks_table_name, &id).await {
+        Ok(Some(project)) => Ok(Json(timezone.render(project))),
+        Ok(None) => Err(error_response(projects::error::Error::NotFound.into())),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Create a Project
+#[utoipa::path(
+    post,
+    path = "/projects",
+    tag = "projects",
+    params(("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),),
+    request_body = projects::inputs::Create,
+    responses(
+        (status = 200, description = "The created Project", body = projects::model::Project),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn projects_create_in_db(
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<projects::inputs::Create>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::service::create(state.db.clone(), &input).await {
+        Ok(project) => Ok(Json(timezone.render(project))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn projects_create_in_dynamo(
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<projects::inputs::Create>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::dynamo_service::create(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &input,
+    )
+    .await
+    {
+        Ok(project) => Ok(Json(timezone.render(project))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Update a Project. Omitted fields are left unchanged and `null` fields are cleared.
+#[utoipa::path(
+    patch,
+    path = "/projects/{id}",
+    tag = "projects",
+    params(
+        ("id" = String, Path, description = "The Project id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    request_body = projects::inputs::Update,
+    responses(
+        (status = 200, description = "The updated Project", body = projects::model::Project),
+        (status = 404, description = "Project not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn projects_update_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<projects::inputs::Update>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::service::update(state.db.clone(), &id, &input).await {
+        Ok(project) => Ok(Json(timezone.render(project))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn projects_update_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<projects::inputs::Update>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::dynamo_service::update(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &id,
+        &input,
+    )
+    .await
+    {
+        Ok(project) => Ok(Json(timezone.render(project))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Delete a Project with all of its Tasks
+#[utoipa::path(
+    delete,
+    path = "/projects/{id}",
+    tag = "projects",
+    params(("id" = String, Path, description = "The Project id")),
+    responses(
+        (status = 200, description = "The Project and its Tasks were deleted"),
+        (status = 404, description = "Project not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn projects_delete_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::service::delete(state.db.clone(), state.storage.as_ref(), &id).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn projects_delete_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::dynamo_service::delete(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.storage.as_ref(),
+        &id,
+    )
+    .await
+    {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Archive a Project, cancelling each of its Tasks the workflow allows to be cancelled. An
+/// archived Project doesn't accept new Tasks.
+#[utoipa::path(
+    post,
+    path = "/projects/{id}/archive",
+    tag = "projects",
+    params(
+        ("id" = String, Path, description = "The Project id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The archived Project", body = projects::model::Project),
+        (status = 404, description = "Project not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn projects_archive_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::service::archive(state.db.clone(), &state.config.workflow, &id).await {
+        Ok(project) => Ok(Json(timezone.render(project))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn projects_archive_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::dynamo_service::archive(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &state.config.workflow,
+        &id,
+    )
+    .await
+    {
+        Ok(project) => Ok(Json(timezone.render(project))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Restore an archived Project. Its cancelled Tasks stay cancelled.
+#[utoipa::path(
+    post,
+    path = "/projects/{id}/restore",
+    tag = "projects",
+    params(
+        ("id" = String, Path, description = "The Project id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The restored Project", body = projects::model::Project),
+        (status = 404, description = "Project not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn projects_restore_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::service::restore(state.db.clone(), &id).await {
+        Ok(project) => Ok(Json(timezone.render(project))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn projects_restore_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match projects::dynamo_service::restore(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        &id,
+    )
+    .await
+    {
+        Ok(project) => Ok(Json(timezone.render(project))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// List a page of a Project's Tasks, in id order
+#[utoipa::path(
+    get,
+    path = "/projects/{id}/tasks",
+    tag = "projects",
+    params(
+        ("id" = String, Path, description = "The Project id"),
+        projects::inputs::ListTasks,
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "A page of Tasks", body = Page),
+        (status = 404, description = "Project not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn projects_tasks_from_db(
+    Path(id): Path<String>,
+    Query(query): Query<projects::inputs::ListTasks>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    let tasks = match projects::service::list_tasks(
+        state.db.clone(),
+        &id,
+        query.after.as_deref(),
+        limit as u64 + 1,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(Page::new(
+        timezone.render(tasks),
+        limit,
+        tasks::inputs::Order::Id,
+    )))
+}
+
+async fn projects_tasks_from_dynamo(
+    Path(id): Path<String>,
+    Query(query): Query<projects::inputs::ListTasks>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    let tasks = match projects::dynamo_service::list_tasks(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &id,
+        query.after.as_deref(),
+        limit as u64 + 1,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return Err(error_response(e)),
+    };
+
+    Ok(Json(Page::new(
+        timezone.render(tasks),
+        limit,
+        tasks::inputs::Order::Id,
+    )))
+}
+
+/// List a page of Recurrences
+#[utoipa::path(
+    get,
+    path = "/recurrences",
+    tag = "recurrences",
+    params(
+        recurrences::inputs::List,
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "A page of Recurrences", body = RecurrencePage),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn recurrences_list_from_db(
+    Query(query): Query<recurrences::inputs::List>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    match recurrences::service::list(state.db.clone(), &query, limit as u64 + 1).await {
+        Ok(recurrences) => Ok(Json(RecurrencePage::new(
+            timezone.render(recurrences),
+            limit,
+        ))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn recurrences_list_from_dynamo(
+    Query(query): Query<recurrences::inputs::List>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let limit = query.page_size();
+
+    match recurrences::dynamo_service::list(
+        state.client.clone(),
+        &state.tasks_table_name,
+        &query,
+        limit as u64 + 1,
+    )
+    .await
+    {
+        Ok(recurrences) => Ok(Json(RecurrencePage::new(
+            timezone.render(recurrences),
+            limit,
+        ))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Get a Recurrence by id
+#[utoipa::path(
+    get,
+    path = "/recurrences/{id}",
+    tag = "recurrences",
+    params(
+        ("id" = String, Path, description = "The Recurrence id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    responses(
+        (status = 200, description = "The Recurrence", body = recurrences::model::Recurrence),
+        (status = 404, description = "Recurrence not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn recurrences_get_from_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::service::get(state.db.clone(), &id).await {
+        Ok(Some(recurrence)) => Ok(Json(timezone.render(recurrence))),
+        Ok(None) => Err(error_response(recurrences::error::Error::NotFound.into())),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn recurrences_get_from_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::dynamo_service::get(state.client.clone(), &state.tasks_table_name, &id).await
+    {
+        Ok(Some(recurrence)) => Ok(Json(timezone.render(recurrence))),
+        Ok(None) => Err(error_response(recurrences::error::Error::NotFound.into())),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Create a Recurrence, generating Tasks for its occurrences within the horizon straight away
+#[utoipa::path(
+    post,
+    path = "/recurrences",
+    tag = "recurrences",
+    params(("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),),
+    request_body = recurrences::inputs::Create,
+    responses(
+        (status = 200, description = "The created Recurrence", body = recurrences::model::Recurrence),
+        (status = 400, description = "Invalid rule or timezone, or Project not found", body = String, content_type = "text/plain"),
+        (status = 409, description = "The Project is archived", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn recurrences_create_in_db(
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<recurrences::inputs::Create>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::service::create(state.db.clone(), state.config.recurrences.horizon, &input)
+        .await
+    {
+        Ok(recurrence) => Ok(Json(timezone.render(recurrence))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn recurrences_create_in_dynamo(
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<recurrences::inputs::Create>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::dynamo_service::create(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.config.dynamo.timestamp_format,
+        state.config.recurrences.horizon,
+        &input,
+    )
+    .await
+    {
+        Ok(recurrence) => Ok(Json(timezone.render(recurrence))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Edit a Recurrence's series. Omitted fields are left unchanged and `null` fields are cleared.
+/// Upcoming Tasks that haven't been started are generated again to match.
+#[utoipa::path(
+    patch,
+    path = "/recurrences/{id}",
+    tag = "recurrences",
+    params(
+        ("id" = String, Path, description = "The Recurrence id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    request_body = recurrences::inputs::Update,
+    responses(
+        (status = 200, description = "The updated Recurrence", body = recurrences::model::Recurrence),
+        (status = 400, description = "Invalid rule or timezone, or Project not found", body = String, content_type = "text/plain"),
+        (status = 404, description = "Recurrence not found", body = String, content_type = "text/plain"),
+        (status = 409, description = "The Recurrence has ended or the Project is archived", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn recurrences_update_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<recurrences::inputs::Update>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::service::update(
+        state.db.clone(),
+        state.storage.as_ref(),
+        state.config.recurrences.horizon,
+        &id,
+        &input,
+    )
+    .await
+    {
+        Ok(recurrence) => Ok(Json(timezone.render(recurrence))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn recurrences_update_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<recurrences::inputs::Update>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::dynamo_service::update(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.storage.as_ref(),
+        state.config.dynamo.timestamp_format,
+        state.config.recurrences.horizon,
+        &id,
+        &input,
+    )
+    .await
+    {
+        Ok(recurrence) => Ok(Json(timezone.render(recurrence))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Delete a Recurrence. The Tasks it generated are kept.
+#[utoipa::path(
+    delete,
+    path = "/recurrences/{id}",
+    tag = "recurrences",
+    params(("id" = String, Path, description = "The Recurrence id")),
+    responses(
+        (status = 200, description = "The Recurrence was deleted"),
+        (status = 404, description = "Recurrence not found", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn recurrences_delete_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::service::delete(state.db.clone(), &id).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn recurrences_delete_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::dynamo_service::delete(state.client.clone(), &state.tasks_table_name, &id)
+        .await
+    {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Skip one occurrence of a Recurrence, deleting its Task if it was generated and hasn't been
+/// started
+#[utoipa::path(
+    post,
+    path = "/recurrences/{id}/skip",
+    tag = "recurrences",
+    params(
+        ("id" = String, Path, description = "The Recurrence id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    request_body = recurrences::inputs::Skip,
+    responses(
+        (status = 200, description = "The Recurrence", body = recurrences::model::Recurrence),
+        (status = 400, description = "Not an occurrence of the Recurrence", body = String, content_type = "text/plain"),
+        (status = 404, description = "Recurrence not found", body = String, content_type = "text/plain"),
+        (status = 409, description = "The Recurrence has ended or the occurrence's Task was started", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn recurrences_skip_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<recurrences::inputs::Skip>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::service::skip(state.db.clone(), state.storage.as_ref(), &id, &input).await {
+        Ok(recurrence) => Ok(Json(timezone.render(recurrence))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn recurrences_skip_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<recurrences::inputs::Skip>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::dynamo_service::skip(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.storage.as_ref(),
+        &id,
+        &input,
+    )
+    .await
+    {
+        Ok(recurrence) => Ok(Json(timezone.render(recurrence))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// End a Recurrence, deleting the Tasks generated after it ends that haven't been started
+#[utoipa::path(
+    post,
+    path = "/recurrences/{id}/end",
+    tag = "recurrences",
+    params(
+        ("id" = String, Path, description = "The Recurrence id"),
+        ("X-Timezone" = Option<String>, Header, description = "An IANA timezone or UTC offset to render timestamps in"),
+    ),
+    request_body = recurrences::inputs::End,
+    responses(
+        (status = 200, description = "The ended Recurrence", body = recurrences::model::Recurrence),
+        (status = 404, description = "Recurrence not found", body = String, content_type = "text/plain"),
+        (status = 409, description = "The Recurrence has already ended", body = String, content_type = "text/plain"),
+        (status = 500, description = "Internal error", body = String, content_type = "text/plain"),
+    )
+)]
+pub(crate) async fn recurrences_end_in_db(
+    Path(id): Path<String>,
+    State(state): State<DatabaseAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<recurrences::inputs::End>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::service::end(state.db.clone(), state.storage.as_ref(), &id, &input).await {
+        Ok(recurrence) => Ok(Json(timezone.render(recurrence))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+async fn recurrences_end_in_dynamo(
+    Path(id): Path<String>,
+    State(state): State<DynamoAppState>,
+    timezone: RequestedTimezone,
+    Json(input): Json<recurrences::inputs::End>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    match recurrences::dynamo_service::end(
+        state.client.clone(),
+        &state.tasks_table_name,
+        state.storage.as_ref(),
+        state.config.dynamo.timestamp_format,
+        &id,
+        &input,
+    )
+    .await
+    {
+        Ok(recurrence) => Ok(Json(timezone.render(recurrence))),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// The timezone requested with the `X-Timezone` header, if any
+#[derive(Clone, Copy, Debug)]
+pub struct RequestedTimezone(Option<Timezone>);
+
+impl RequestedTimezone {
+    /// Render the timestamps of a response in the requested timezone, or leave them in UTC
+    fn render<T: Serialize + WithTimestamps>(&self, value: T) -> T {
+        match &self.0 {
+            Some(timezone) => value.in_timezone(timezone),
+            None => value,
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RequestedTimezone {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get("x-timezone") else {
+            return Ok(Self(None));
+        };
+
+        let timezone = value
+            .to_str()
+            .map_err(|err| err.to_string())
+            .and_then(|value| {
+                value
+                    .trim()
+                    .parse::<Timezone>()
+                    .map_err(|err| err.to_string())
+            })
+            .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+
+        Ok(Self(Some(timezone)))
+    }
+}
+
+/// Respond with the deleted Task if the request prefers `return=representation`, or an empty body
+fn deleted_response(task: Task, headers: &HeaderMap) -> Response {
+    let return_representation = headers
+        .get_all(header::HeaderName::from_static("prefer"))
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|preference| preference.trim() == "return=representation");
+
+    if return_representation {
+        Json(task).into_response()
+    } else {
+        StatusCode::OK.into_response()
+    }
+}
+
+/// Read the `file` field of an upload, giving up as soon as the file is larger than the limit
+async fn read_upload(
+    mut multipart: Multipart,
+    config: &config::Attachments,
+) -> anyhow::Result<attachments::inputs::Upload> {
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|err| upload_error(err, config))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let name = field.file_name().unwrap_or_default().to_string();
+        let content_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+
+        let mut bytes = Vec::new();
+
+        while let Some(chunk) = field
+            .chunk()
+            .await
+            .map_err(|err| upload_error(err, config))?
+        {
+            if bytes.len() + chunk.len() > config.max_size {
+                return Err(attachments::error::Error::TooLarge(config.max_size).into());
+            }
+
+            bytes.extend_from_slice(&chunk);
+        }
+
+        return Ok(attachments::inputs::Upload::new(
+            &name,
+            &content_type,
+            Bytes::from(bytes),
+        ));
+    }
+
+    Err(attachments::error::Error::Invalid("the upload has no file field".to_string()).into())
+}
+
+/// Convert a malformed or oversized multipart body to the matching Attachment error
+fn upload_error(err: MultipartError, config: &config::Attachments) -> anyhow::Error {
+    if err.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        attachments::error::Error::TooLarge(config.max_size).into()
+    } else {
+        attachments::error::Error::Invalid(err.body_text()).into()
+    }
+}
+
+/// Respond with the bytes of an Attachment, named so browsers save it rather than display it
+async fn download_response(
+    storage: &dyn Storage,
+    attachment: Attachment,
+) -> Result<Response, (StatusCode, String)> {
+    let bytes = storage
+        .get(&storage::key(&attachment.task_id, &attachment.id))
+        .await
+        .map_err(error_response)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type),
+            (
+                header::CONTENT_DISPOSITION,
+                content_disposition(&attachment.name),
+            ),
+            (header::ETAG, format!("\"{}\"", attachment.checksum)),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+/// A `Content-Disposition` header for downloading a file, with an ASCII fallback for the name
+/// and the exact name percent-encoded for clients that support RFC 5987
+fn content_disposition(name: &str) -> String {
+    let fallback: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    let encoded: String = name
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect();
+
+    format!("attachment; filename=\"{fallback}\"; filename*=UTF-8''{encoded}")
+}
+
+/// Respond with 404 for a `NotFound` error, 409 for an `InvalidTransition`, `Cycle`, `Archived`,
+/// `Ended` or `AlreadyStarted` error, 400 for an `InvalidCursor`, `InvalidTag`, `ParentNotFound`,
+/// `ProjectNotFound`, `NotAnOccurrence` or invalid Comment, Attachment or Recurrence error, 413 for
+/// a file that's too large, 415 for a content type that isn't allowed, or 500 otherwise
+fn error_response(err: anyhow::Error) -> (StatusCode, String) {
+    if tasks::error::Error::is_not_found(&err)
+        || projects::error::Error::is_not_found(&err)
+        || comments::error::Error::is_not_found(&err)
+        || attachments::error::Error::is_not_found(&err)
+        || dependencies::error::Error::is_blocker_not_found(&err)
+        || recurrences::error::Error::is_not_found(&err)
+    {
+        (StatusCode::NOT_FOUND, err.to_string())
+    } else if attachments::error::Error::is_too_large(&err) {
+        (StatusCode::PAYLOAD_TOO_LARGE, err.to_string())
+    } else if attachments::error::Error::is_unsupported_content_type(&err) {
+        (StatusCode::UNSUPPORTED_MEDIA_TYPE, err.to_string())
+    } else if tasks::error::Error::is_invalid_transition(&err)
+        || tasks::error::Error::is_cycle(&err)
+        || dependencies::error::Error::is_cycle(&err)
+        || dependencies::error::Error::is_open_blockers(&err)
+        || projects::error::Error::is_archived(&err)
+        || recurrences::error::Error::is_ended(&err)
+        || recurrences::error::Error::is_already_started(&err)
+    {
+        (StatusCode::CONFLICT, err.to_string())
+    } else if tasks::error::Error::is_invalid_cursor(&err)
+        || tasks::error::Error::is_invalid_tag(&err)
+        || tasks::error::Error::is_parent_not_found(&err)
+        || tasks::error::Error::is_project_not_found(&err)
+        || comments::error::Error::is_invalid(&err)
+        || attachments::error::Error::is_invalid(&err)
+        || recurrences::error::Error::is_invalid(&err)
+        || recurrences::error::Error::is_not_an_occurrence(&err)
+    {
+        (StatusCode::BAD_REQUEST, err.to_string())
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use chrono::DateTime;
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use crate::{attachments::storage::LocalStorage, utils::fake_dynamo};
+
+    use super::*;
+
+    fn router(db: MockDatabase) -> Router {
+        database_router(DatabaseAppState {
+            config: Default::default(),
+            db: Arc::new(db.into_connection()),
+            storage: Arc::new(LocalStorage::new(
+                std::env::temp_dir().join("unused-attachments"),
+            )),
+        })
+    }
+
+    async fn get(router: &Router, uri: &str, timezone: Option<&str>) -> anyhow::Result<Response> {
+        let mut request = Request::get(uri);
+
+        if let Some(timezone) = timezone {
+            request = request.header("x-timezone", timezone);
+        }
+
+        Ok(router.clone().oneshot(request.body(Body::empty())?).await?)
+    }
+
+    fn dynamo(client: Arc<Client>) -> Router {
+        dynamo_router(DynamoAppState {
+            config: Default::default(),
+            client,
+            tasks_table_name: "tasks".to_string(),
+            storage: Arc::new(LocalStorage::new(
+                std::env::temp_dir().join("unused-attachments"),
+            )),
+        })
+    }
+
+    /// Send a JSON request, returning the response status
+    async fn send(
+        router: &Router,
+        method: &str,
+        uri: &str,
+        body: Value,
+    ) -> anyhow::Result<StatusCode> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))?;
+
+        Ok(router.clone().oneshot(request).await?.status())
+    }
+
+    /// The write routes for a Task, with a valid body for each
+    fn task_writes(id: &str) -> Vec<(&'static str, String, Value)> {
+        vec![
+            (
+                "PATCH",
+                format!("/tasks/{id}"),
+                json!({ "title": "Renamed" }),
+            ),
+            ("DELETE", format!("/tasks/{id}"), json!(null)),
+            (
+                "POST",
+                format!("/tasks/{id}/transitions"),
+                json!({ "status": "in_progress" }),
+            ),
+            (
+                "PUT",
+                format!("/tasks/{id}/parent"),
+                json!({ "parent_id": null }),
+            ),
+        ]
+    }
+
+    async fn json(response: Response) -> anyhow::Result<Value> {
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    #[tokio::test]
+    async fn test_timezone_header() -> anyhow::Result<()> {
+        let timestamp = DateTime::parse_from_rfc3339("2026-10-19T12:00:00Z")?;
+
+        let task = Task {
+            id: "01HX".to_string(),
+            created_at: timestamp,
+            updated_at: timestamp,
+            due_at: Some(timestamp),
+            ..Default::default()
+        };
+
+        let router = router(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![task.clone()], vec![task]]),
+        );
+
+        let response = get(&router, "/tasks/01HX", Some("America/Chicago")).await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = json(response).await?;
+
+        assert_eq!(body["created_at"], "2026-10-19T07:00:00-05:00");
+        assert_eq!(body["updated_at"], "2026-10-19T07:00:00-05:00");
+        assert_eq!(body["due_at"], "2026-10-19T07:00:00-05:00");
+
+        let body = json(get(&router, "/tasks/01HX", None).await?).await?;
+
+        assert_eq!(body["created_at"], "2026-10-19T12:00:00Z");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_timezone_header() -> anyhow::Result<()> {
+        // Rejected before the handler runs, so nothing is queried
+        let router = router(MockDatabase::new(DatabaseBackend::Postgres));
+
+        let response = get(&router, "/tasks/01HX", Some("Mars/Olympus_Mons")).await?;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    /// A Project item, sharing the table with Tasks
+    fn project_item() -> Value {
+        json!({
+            "id": { "S": "01HXPROJECT" },
+            "entity": { "S": "project" },
+            "created_at": { "S": "2026-10-19T12:00:00.000Z" },
+            "updated_at": { "S": "2026-10-19T12:00:00.000Z" },
+            "name": { "S": "Launch" },
+        })
+    }
+
+    #[tokio::test]
+    async fn test_task_routes_ignore_projects() -> anyhow::Result<()> {
+        let router = dynamo(fake_dynamo::client(vec![project_item()]).await?);
+
+        for (method, uri, body) in task_writes("01HXPROJECT") {
+            assert_eq!(
+                send(&router, method, &uri, body).await?,
+                StatusCode::NOT_FOUND,
+                "{method} {uri}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_task_routes_ignore_recurrences() -> anyhow::Result<()> {
+        // Shaped enough like a Task to be read as one if the entity weren't checked
+        let recurrence = json!({
+            "id": { "S": "01HXRECURRENCE" },
+            "entity": { "S": "recurrence" },
+            "created_at": { "S": "2026-10-19T12:00:00.000Z" },
+            "updated_at": { "S": "2026-10-19T12:00:00.000Z" },
+            "title": { "S": "Water the plants" },
+            "rule": { "S": "FREQ=WEEKLY" },
+        });
+
+        let router = dynamo(fake_dynamo::client(vec![recurrence]).await?);
+
+        for (method, uri, body) in task_writes("01HXRECURRENCE") {
+            assert_eq!(
+                send(&router, method, &uri, body).await?,
+                StatusCode::NOT_FOUND,
+                "{method} {uri}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_comment_on_a_project() -> anyhow::Result<()> {
+        let router = dynamo(fake_dynamo::client(vec![project_item()]).await?);
+
+        let status = send(
+            &router,
+            "POST",
+            "/tasks/01HXPROJECT/comments",
+            json!({ "author": "Ada", "body": "Looks good" }),
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+}
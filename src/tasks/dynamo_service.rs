@@ -1,14 +1,51 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use anyhow::anyhow;
-use aws_sdk_dynamodb::{types::AttributeValue, Client};
+use aws_sdk_dynamodb::{
+    operation::{transact_write_items::TransactWriteItemsError, update_item::UpdateItemError},
+    types::{
+        AttributeValue, ConditionCheck, Delete, KeysAndAttributes, Put, ReturnValue,
+        ReturnValuesOnConditionCheckFailure, TransactWriteItem, Update,
+    },
+    Client,
+};
+use chrono::{DateTime, Duration, FixedOffset, SubsecRound};
 use ulid::Ulid;
 
-use crate::utils::update::Update::{Empty, Unchanged, Value};
+use crate::{
+    attachments::{self, storage::Storage},
+    comments,
+    config::TimestampFormat,
+    dependencies, jobs, projects, recurrences,
+    utils::{
+        dynamo::{self, DynamoItem, Item},
+        timestamp,
+        update::Update::{Empty, Unchanged, Value},
+    },
+};
 
-use super::{inputs, model::Task};
+use super::{
+    dynamo_table::{
+        ATTACHMENT_ENTITY, COMMENT_ENTITY, DEPENDENCY_ENTITY, ENTITY_ATTRIBUTE, JOB_ENTITY,
+        OCCURRENCE_ENTITY, PARENT_INDEX, PRIORITY_INDEX, PROJECT_ENTITY, RECURRENCE_ENTITY,
+        TAG_ENTITY, TAG_INDEX, TASK_ENTITY, TASK_TAG_ENTITY, TASK_TAG_INDEX,
+    },
+    error::Error,
+    inputs::{self, Order},
+    model::Task,
+    tag::{self, TagCount},
+    tree::{self, Tree},
+    workflow::{Status, Workflow},
+};
 
-/// Get an individual `Task` by id
+/// The most keys a `BatchGetItem` request can read
+const MAX_BATCH_GET_KEYS: usize = 100;
+
+/// Get an individual `Task` by id. Projects and Tags share the table, so their items aren't
+/// returned as Tasks.
 pub async fn get(client: Arc<Client>, table_name: &str, id: &str) -> anyhow::Result<Option<Task>> {
     let results = client
         .get_item()
@@ -17,112 +54,1831 @@ pub async fn get(client: Arc<Client>, table_name: &str, id: &str) -> anyhow::Res
         .send()
         .await?;
 
-    if let Some(item) = results.item {
-        Ok(Some(item.try_into()?))
-    } else {
-        Ok(None)
+    match results.item {
+        Some(item) if is_task(&item) => Ok(Some(dynamo::from_item(item)?)),
+        _ => Ok(None),
+    }
+}
+
+/// List up to `limit` `Task`s matching the filters, starting after the given cursor. Tasks are in
+/// table scan order by default, or read from the priority index when ordering by priority.
+/// Filters compare against due dates in the configured timestamp format, so Tasks written in
+/// another format should be rewritten first.
+pub async fn list(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    input: &inputs::List,
+    limit: u64,
+) -> anyhow::Result<Vec<Task>> {
+    let order = input.order();
+    let expression = list_expression(input, format, chrono::Utc::now().fixed_offset());
+
+    let mut start_key = input
+        .after
+        .as_deref()
+        .map(|after| start_key(order, after))
+        .transpose()?;
+
+    let mut tasks = vec![];
+
+    // Filters are applied after each request's limit, so keep reading until the page is full
+    loop {
+        let (items, last_key) = match order {
+            Order::Id => {
+                let results = client
+                    .scan()
+                    .table_name(table_name)
+                    .limit(i32::try_from(limit)?)
+                    .set_exclusive_start_key(start_key)
+                    .set_filter_expression(expression.filter.clone())
+                    .set_expression_attribute_names(expression.names())
+                    .set_expression_attribute_values(expression.values())
+                    .send()
+                    .await?;
+
+                (results.items, results.last_evaluated_key)
+            }
+            Order::Priority => {
+                let results = client
+                    .query()
+                    .table_name(table_name)
+                    .index_name(PRIORITY_INDEX)
+                    .key_condition_expression("#entity = :entity")
+                    .scan_index_forward(false)
+                    .limit(i32::try_from(limit)?)
+                    .set_exclusive_start_key(start_key)
+                    .set_filter_expression(expression.filter.clone())
+                    .set_expression_attribute_names(expression.names())
+                    .set_expression_attribute_values(expression.values())
+                    .send()
+                    .await?;
+
+                (results.items, results.last_evaluated_key)
+            }
+        };
+
+        for item in items.unwrap_or_default() {
+            tasks.push(dynamo::from_item(item)?);
+
+            if tasks.len() as u64 >= limit {
+                return Ok(tasks);
+            }
+        }
+
+        match last_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(tasks),
+        }
     }
 }
 
-/// Create a `Task` with the given input
+/// Create a `Task` with the given input, as a subtask of its parent and in its Project if they're
+/// given
 pub async fn create(
     client: Arc<Client>,
     table_name: &str,
+    format: TimestampFormat,
     input: &inputs::Create,
 ) -> anyhow::Result<Task> {
-    let id_av = AttributeValue::S(Ulid::new().to_string());
+    if let Some(parent_id) = &input.parent_id {
+        get(client.clone(), table_name, parent_id)
+            .await?
+            .ok_or_else(|| Error::ParentNotFound(parent_id.clone()))?;
+    }
 
-    let created_at_av = AttributeValue::S(chrono::Utc::now().to_string());
-    let updated_at_av = created_at_av.clone();
+    if let Some(project_id) = &input.project_id {
+        projects::dynamo_service::check_active(client.clone(), table_name, project_id).await?;
+    }
 
-    let title_av = AttributeValue::S(input.title.clone());
+    // Truncated so the returned Task matches what reads return in every format
+    let now = chrono::Utc::now().fixed_offset().trunc_subsecs(3);
 
-    let description_av = match &input.description {
-        Some(description) => AttributeValue::S(description.clone()),
-        None => AttributeValue::Null(true),
+    let task = Task {
+        id: Ulid::new().to_string(),
+        created_at: now,
+        updated_at: now,
+        title: input.title.clone(),
+        description: input.description.clone(),
+        status: Status::default(),
+        due_at: input.due_at,
+        priority: input.priority,
+        parent_id: input.parent_id.clone(),
+        project_id: input.project_id.clone(),
     };
 
-    let result = client
+    client
         .put_item()
         .table_name(table_name)
-        .item("id", id_av)
-        .item("created_at", created_at_av)
-        .item("updated_at", updated_at_av)
-        .item("title", title_av)
-        .item("description", description_av)
+        .set_item(Some(task_item(&task, format)?))
+        .condition_expression("attribute_not_exists(id)")
         .send()
         .await?;
 
-    let task = result
-        .attributes
-        .ok_or(anyhow!("No attributes returned"))?
-        .try_into()?;
-
     Ok(task)
 }
 
-/// Update an existing `Task` by id
+/// Update an existing `Task` by id with a single conditional `UpdateItem`
 pub async fn update(
     client: Arc<Client>,
     table_name: &str,
+    format: TimestampFormat,
     id: &str,
     input: &inputs::Update,
 ) -> anyhow::Result<Task> {
-    let existing = get(client.clone(), table_name, id).await?;
-
-    if existing.is_none() {
-        return Err(anyhow!("Task not found"));
+    if let Value(project_id) = &input.project_id {
+        projects::dynamo_service::check_active(client.clone(), table_name, project_id).await?;
     }
-    let existing = existing.unwrap();
 
-    let id_av = AttributeValue::S(id.to_string());
-    let created_at_av = AttributeValue::S(existing.created_at.to_string());
-    let updated_at_av = AttributeValue::S(chrono::Utc::now().to_string());
+    let updated_at = chrono::Utc::now().fixed_offset().trunc_subsecs(3);
 
-    let title_av = match &input.title {
-        Unchanged | Empty => AttributeValue::S(existing.title.clone()),
-        Value(value) => AttributeValue::S(value.to_string()),
-    };
+    let expression = update_expression(input, format, updated_at);
 
-    let description_av = match &input.description {
-        Unchanged => {
-            if let Some(description) = &existing.description {
-                AttributeValue::S(description.clone())
+    let result = client
+        .update_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(id.to_string()))
+        .update_expression(expression.expression)
+        .condition_expression(TASK_CONDITION)
+        .set_expression_attribute_names(Some(expression.names))
+        .set_expression_attribute_values(Some(expression.values))
+        .return_values(ReturnValue::AllNew)
+        .send()
+        .await
+        .map_err(|err| {
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception())
+            {
+                Error::NotFound.into()
             } else {
-                AttributeValue::Null(true)
+                anyhow::Error::from(err)
             }
+        })?;
+
+    let task = dynamo::from_item(result.attributes.ok_or(anyhow!("No attributes returned"))?)?;
+
+    Ok(task)
+}
+
+/// Move an existing `Task` to the given status with a single `UpdateItem`, guarded by the
+/// statuses the workflow allows it to move from. Completing a Task completes its parent too, once
+/// every sibling is done or cancelled. When the workflow requires it, a Task can't be completed
+/// while its blockers are unfinished.
+pub async fn transition(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    workflow: &Workflow,
+    id: &str,
+    status: Status,
+) -> anyhow::Result<Task> {
+    if status == Status::Done && workflow.requires_unblocked() {
+        dependencies::dynamo_service::check_unblocked(&client, table_name, id).await?;
+    }
+
+    let task = set_status(client.clone(), table_name, format, workflow, id, status).await?;
+
+    if task.status == Status::Done {
+        roll_up(client, table_name, format, workflow, &task).await?;
+    }
+
+    Ok(task)
+}
+
+async fn set_status(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    workflow: &Workflow,
+    id: &str,
+    status: Status,
+) -> anyhow::Result<Task> {
+    let sources = workflow.sources(status);
+
+    // Nothing can move to the status, so there is no condition to send
+    if sources.is_empty() {
+        let task = get(client, table_name, id).await?.ok_or(Error::NotFound)?;
+
+        return Err(Error::InvalidTransition {
+            from: task.status,
+            to: status,
+        }
+        .into());
+    }
+
+    let updated_at = chrono::Utc::now().fixed_offset().trunc_subsecs(3);
+
+    let expression = transition_expression(
+        &sources,
+        status,
+        timestamp::to_attribute(updated_at, format),
+    );
+
+    let result = client
+        .update_item()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(id.to_string()))
+        .update_expression(expression.expression)
+        .condition_expression(expression.condition)
+        .set_expression_attribute_names(Some(expression.names))
+        .set_expression_attribute_values(Some(expression.values))
+        .return_values(ReturnValue::AllNew)
+        .return_values_on_condition_check_failure(ReturnValuesOnConditionCheckFailure::AllOld)
+        .send()
+        .await;
+
+    let result = match result {
+        Ok(result) => result,
+        Err(err) => {
+            let Some(UpdateItemError::ConditionalCheckFailedException(failed)) =
+                err.as_service_error()
+            else {
+                return Err(err.into());
+            };
+
+            // The old item is only returned if something exists under the id, which is either a
+            // Task in a status it can't move from or another entity
+            let item = failed.item.clone().filter(is_task).ok_or(Error::NotFound)?;
+
+            let task: Task = dynamo::from_item(item)?;
+
+            return Err(Error::InvalidTransition {
+                from: task.status,
+                to: status,
+            }
+            .into());
         }
-        Empty => AttributeValue::Null(true),
-        Value(value) => AttributeValue::S(value.to_string()),
     };
 
+    let task = dynamo::from_item(result.attributes.ok_or(anyhow!("No attributes returned"))?)?;
+
+    Ok(task)
+}
+
+/// Complete the ancestors of a completed Task, from its parent up, stopping at the first one with
+/// unfinished subtasks or blockers, or that the workflow doesn't allow to be completed
+async fn roll_up(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    workflow: &Workflow,
+    task: &Task,
+) -> anyhow::Result<()> {
+    let mut visited = HashSet::from([task.id.clone()]);
+    let mut parent_id = task.parent_id.clone();
+
+    while let Some(id) = parent_id.take() {
+        if !visited.insert(id.clone()) {
+            break;
+        }
+
+        let siblings = children_of(&client, table_name, &id).await?;
+
+        if !tree::is_complete(&siblings) {
+            break;
+        }
+
+        if workflow.requires_unblocked() {
+            match dependencies::dynamo_service::check_unblocked(&client, table_name, &id).await {
+                Ok(()) => (),
+                Err(err) if dependencies::error::Error::is_open_blockers(&err) => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        match set_status(
+            client.clone(),
+            table_name,
+            format,
+            workflow,
+            &id,
+            Status::Done,
+        )
+        .await
+        {
+            Ok(parent) => parent_id = parent.parent_id,
+            Err(err) if Error::is_invalid_transition(&err) || Error::is_not_found(&err) => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// List a `Task`'s direct subtasks, in id order
+pub async fn children(
+    client: Arc<Client>,
+    table_name: &str,
+    id: &str,
+) -> anyhow::Result<Vec<Task>> {
+    get(client.clone(), table_name, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    children_of(&client, table_name, id).await
+}
+
+/// Get a `Task` with every subtask below it, querying the parent index for each Task in turn
+pub async fn subtree(client: Arc<Client>, table_name: &str, id: &str) -> anyhow::Result<Tree> {
+    let task = get(client.clone(), table_name, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let mut visited = HashSet::from([task.id.clone()]);
+    let mut level = vec![task.id.clone()];
+    let mut descendants = vec![];
+
+    while !level.is_empty() {
+        let mut next = vec![];
+
+        for parent_id in &level {
+            for child in children_of(&client, table_name, parent_id).await? {
+                if visited.insert(child.id.clone()) {
+                    next.push(child.id.clone());
+                    descendants.push(child);
+                }
+            }
+        }
+
+        level = next;
+    }
+
+    Ok(Tree::new(task, descendants))
+}
+
+/// Move an existing `Task` under another parent, or to the top level when no parent is given. A
+/// Task can't be moved under itself or any of its subtasks.
+pub async fn move_task(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    id: &str,
+    input: &inputs::Move,
+) -> anyhow::Result<Task> {
+    let task = get(client.clone(), table_name, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if let Some(parent_id) = &input.parent_id {
+        let parent = get(client.clone(), table_name, parent_id)
+            .await?
+            .ok_or_else(|| Error::ParentNotFound(parent_id.clone()))?;
+
+        let mut ancestor = Some(parent);
+        let mut visited = HashSet::new();
+
+        while let Some(current) = ancestor.take() {
+            if current.id == task.id {
+                return Err(Error::Cycle {
+                    id: task.id,
+                    parent_id: parent_id.clone(),
+                }
+                .into());
+            }
+
+            if !visited.insert(current.id.clone()) {
+                break;
+            }
+
+            if let Some(next) = &current.parent_id {
+                ancestor = get(client.clone(), table_name, next).await?;
+            }
+        }
+    }
+
+    let updated_at = chrono::Utc::now().fixed_offset().trunc_subsecs(3);
+
+    let expression = move_expression(input.parent_id.as_deref(), format, updated_at);
+
     let result = client
-        .put_item()
+        .update_item()
         .table_name(table_name)
-        .item("id", id_av)
-        .item("created_at", created_at_av)
-        .item("updated_at", updated_at_av)
-        .item("title", title_av)
-        .item("description", description_av)
+        .key("id", AttributeValue::S(id.to_string()))
+        .update_expression(expression.expression)
+        .condition_expression(TASK_CONDITION)
+        .set_expression_attribute_names(Some(expression.names))
+        .set_expression_attribute_values(Some(expression.values))
+        .return_values(ReturnValue::AllNew)
         .send()
-        .await?;
+        .await
+        .map_err(|err| {
+            // Another request deleted the Task after it was read
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception())
+            {
+                Error::NotFound.into()
+            } else {
+                anyhow::Error::from(err)
+            }
+        })?;
 
-    let task = result
-        .attributes
-        .ok_or(anyhow!("No attributes returned"))?
-        .try_into()?;
+    let task = dynamo::from_item(result.attributes.ok_or(anyhow!("No attributes returned"))?)?;
 
     Ok(task)
 }
 
-/// Delete an existing `Task`
-pub async fn delete(client: Arc<Client>, table_name: &str, id: &str) -> anyhow::Result<()> {
-    let _ = client
+/// Insert a full `Task`, preserving its id and timestamps, or replace it if it already exists.
+/// An item of another entity with the same id is left alone, failing with `IdConflict`.
+pub async fn upsert(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+    task: &Task,
+) -> anyhow::Result<()> {
+    client
+        .put_item()
+        .table_name(table_name)
+        .set_item(Some(task_item(task, format)?))
+        .condition_expression(
+            "attribute_not_exists(#id) OR attribute_not_exists(#entity) OR #entity = :task",
+        )
+        .expression_attribute_names("#id", "id")
+        .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+        .expression_attribute_values(":task", AttributeValue::S(TASK_ENTITY.to_string()))
+        .send()
+        .await
+        .map_err(|err| {
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception())
+            {
+                Error::IdConflict(task.id.clone()).into()
+            } else {
+                anyhow::Error::from(err)
+            }
+        })?;
+
+    Ok(())
+}
+
+/// Delete an existing `Task` with its Comments, Attachments and Dependencies, unlink its Tags and
+/// move its subtasks to the top level, returning it
+pub async fn delete(
+    client: Arc<Client>,
+    table_name: &str,
+    storage: &dyn Storage,
+    id: &str,
+) -> anyhow::Result<Task> {
+    let result = client
         .delete_item()
         .table_name(table_name)
         .key("id", AttributeValue::S(id.to_string()))
+        .condition_expression(TASK_CONDITION)
+        .expression_attribute_names("#id", "id")
+        .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+        .expression_attribute_values(":task", AttributeValue::S(TASK_ENTITY.to_string()))
+        .return_values(ReturnValue::AllOld)
+        .send()
+        .await
+        .map_err(|err| {
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_conditional_check_failed_exception())
+            {
+                Error::NotFound.into()
+            } else {
+                anyhow::Error::from(err)
+            }
+        })?;
+
+    let item = result.attributes.ok_or(anyhow!("No attributes returned"))?;
+
+    for name in tag_names(&client, table_name, id).await? {
+        unlink_tag(&client, table_name, id, &name).await?;
+    }
+
+    comments::dynamo_service::delete_all(&client, table_name, id).await?;
+    attachments::dynamo_service::delete_all(&client, table_name, storage, id).await?;
+    dependencies::dynamo_service::delete_all(&client, table_name, id).await?;
+
+    for child in children_of(&client, table_name, id).await? {
+        let result = client
+            .update_item()
+            .table_name(table_name)
+            .key("id", AttributeValue::S(child.id))
+            .update_expression("REMOVE #parent_id")
+            .condition_expression("#parent_id = :parent_id")
+            .expression_attribute_names("#parent_id", "parent_id")
+            .expression_attribute_values(":parent_id", AttributeValue::S(id.to_string()))
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => (),
+            // The subtask was moved or deleted in the meantime
+            Err(err)
+                if err
+                    .as_service_error()
+                    .is_some_and(|err| err.is_conditional_check_failed_exception()) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(dynamo::from_item(item)?)
+}
+
+/// List the names of a `Task`'s Tags, in name order
+pub async fn tags(client: Arc<Client>, table_name: &str, id: &str) -> anyhow::Result<Vec<String>> {
+    get(client.clone(), table_name, id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    tag_names(&client, table_name, id).await
+}
+
+/// Add a Tag to a `Task` and return the Task's Tags. The edge item linking the two and the Tag's
+/// Task count are written in one transaction, on the condition that the Task exists. Adding a Tag
+/// the Task already has changes nothing.
+pub async fn add_tag(
+    client: Arc<Client>,
+    table_name: &str,
+    id: &str,
+    name: &str,
+) -> anyhow::Result<Vec<String>> {
+    let name = tag::normalize(name)?;
+
+    let task_exists = task_exists(table_name, id)?;
+
+    let edge = Put::builder()
+        .table_name(table_name)
+        .set_item(Some(task_tag_item(id, &name)))
+        .condition_expression("attribute_not_exists(#id)")
+        .expression_attribute_names("#id", "id")
+        .build()?;
+
+    let count = Update::builder()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(tag_id(&name)))
+        .update_expression("SET #entity = :entity, #name = :name ADD #task_count :one")
+        .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+        .expression_attribute_names("#name", "name")
+        .expression_attribute_names("#task_count", "task_count")
+        .expression_attribute_values(":entity", AttributeValue::S(TAG_ENTITY.to_string()))
+        .expression_attribute_values(":name", AttributeValue::S(name.clone()))
+        .expression_attribute_values(":one", AttributeValue::N("1".to_string()))
+        .build()?;
+
+    let result = client
+        .transact_write_items()
+        .transact_items(
+            TransactWriteItem::builder()
+                .condition_check(task_exists)
+                .build(),
+        )
+        .transact_items(TransactWriteItem::builder().put(edge).build())
+        .transact_items(TransactWriteItem::builder().update(count).build())
         .send()
+        .await;
+
+    if let Err(err) = result {
+        match failed_conditions(&err).as_deref() {
+            Some([true, ..]) => return Err(Error::NotFound.into()),
+            // The edge already exists, so the Task already has the Tag
+            Some([false, true, ..]) => (),
+            _ => return Err(err.into()),
+        }
+    }
+
+    tag_names(&client, table_name, id).await
+}
+
+/// Remove a Tag from a `Task` and return the Task's remaining Tags. Removing a Tag the Task
+/// doesn't have changes nothing.
+pub async fn remove_tag(
+    client: Arc<Client>,
+    table_name: &str,
+    id: &str,
+    name: &str,
+) -> anyhow::Result<Vec<String>> {
+    let name = tag::normalize(name)?;
+
+    if !unlink_tag(&client, table_name, id, &name).await? {
+        get(client.clone(), table_name, id)
+            .await?
+            .ok_or(Error::NotFound)?;
+    }
+
+    tag_names(&client, table_name, id).await
+}
+
+/// List up to `limit` `Task`s with the given Tag, in id order and starting after the given
+/// cursor. The Task ids are read from the Tag index, then the Tasks are read in batches.
+pub async fn list_by_tag(
+    client: Arc<Client>,
+    table_name: &str,
+    name: &str,
+    after: Option<&str>,
+    limit: u64,
+) -> anyhow::Result<Vec<Task>> {
+    let name = tag::normalize(name)?;
+
+    let mut key_condition = "#tag = :tag".to_string();
+    let mut names = HashMap::from([("#tag".to_string(), "tag".to_string())]);
+    let mut values = HashMap::from([(":tag".to_string(), AttributeValue::S(name))]);
+
+    if let Some(after) = after {
+        key_condition.push_str(" AND #task_id > :after");
+        names.insert("#task_id".to_string(), "task_id".to_string());
+        values.insert(":after".to_string(), AttributeValue::S(after.to_string()));
+    }
+
+    let mut ids = vec![];
+    let mut start_key = None;
+
+    loop {
+        let results = client
+            .query()
+            .table_name(table_name)
+            .index_name(TAG_INDEX)
+            .key_condition_expression(&key_condition)
+            .set_expression_attribute_names(Some(names.clone()))
+            .set_expression_attribute_values(Some(values.clone()))
+            .limit(i32::try_from(limit)?)
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        ids.extend(
+            results
+                .items
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|item| item.get("task_id")?.as_s().ok().cloned()),
+        );
+
+        match results.last_evaluated_key {
+            Some(key) if (ids.len() as u64) < limit => start_key = Some(key),
+            _ => break,
+        }
+    }
+
+    ids.truncate(usize::try_from(limit)?);
+
+    batch_get(&client, table_name, &ids).await
+}
+
+/// List every Tag with the number of Tasks it labels, in name order. Tags share the table with
+/// Tasks, so this scans it.
+pub async fn tag_counts(client: Arc<Client>, table_name: &str) -> anyhow::Result<Vec<TagCount>> {
+    let mut counts = vec![];
+    let mut start_key = None;
+
+    loop {
+        let results = client
+            .scan()
+            .table_name(table_name)
+            .filter_expression("#entity = :entity")
+            .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+            .expression_attribute_values(":entity", AttributeValue::S(TAG_ENTITY.to_string()))
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        for item in results.items.unwrap_or_default() {
+            counts.push(tag_count(&item)?);
+        }
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => break,
+        }
+    }
+
+    counts.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(counts)
+}
+
+/// The direct subtasks of a Task, read from the parent index in id order
+async fn children_of(client: &Client, table_name: &str, id: &str) -> anyhow::Result<Vec<Task>> {
+    let mut children = vec![];
+    let mut start_key = None;
+
+    loop {
+        let results = client
+            .query()
+            .table_name(table_name)
+            .index_name(PARENT_INDEX)
+            .key_condition_expression("#parent_id = :parent_id")
+            .expression_attribute_names("#parent_id", "parent_id")
+            .expression_attribute_values(":parent_id", AttributeValue::S(id.to_string()))
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        for item in results.items.unwrap_or_default() {
+            children.push(dynamo::from_item(item)?);
+        }
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(children),
+        }
+    }
+}
+
+/// The names of the Tags linked to a Task, read from the Task Tag index
+async fn tag_names(client: &Client, table_name: &str, id: &str) -> anyhow::Result<Vec<String>> {
+    let mut names = vec![];
+    let mut start_key = None;
+
+    loop {
+        let results = client
+            .query()
+            .table_name(table_name)
+            .index_name(TASK_TAG_INDEX)
+            .key_condition_expression("#task_id = :task_id")
+            .expression_attribute_names("#task_id", "task_id")
+            .expression_attribute_values(":task_id", AttributeValue::S(id.to_string()))
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        names.extend(
+            results
+                .items
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|item| item.get("tag")?.as_s().ok().cloned()),
+        );
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(names),
+        }
+    }
+}
+
+/// Delete the edge between a Task and a Tag and decrement the Tag's Task count in one
+/// transaction, returning whether there was an edge to delete
+async fn unlink_tag(
+    client: &Client,
+    table_name: &str,
+    id: &str,
+    name: &str,
+) -> anyhow::Result<bool> {
+    let edge = Delete::builder()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(task_tag_id(id, name)))
+        .condition_expression("attribute_exists(#id)")
+        .expression_attribute_names("#id", "id")
+        .build()?;
+
+    let count = Update::builder()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(tag_id(name)))
+        .update_expression("ADD #task_count :minus_one")
+        .expression_attribute_names("#task_count", "task_count")
+        .expression_attribute_values(":minus_one", AttributeValue::N("-1".to_string()))
+        .build()?;
+
+    let result = client
+        .transact_write_items()
+        .transact_items(TransactWriteItem::builder().delete(edge).build())
+        .transact_items(TransactWriteItem::builder().update(count).build())
+        .send()
+        .await;
+
+    match result {
+        Ok(_) => Ok(true),
+        Err(err) => match failed_conditions(&err).as_deref() {
+            Some([true, ..]) => Ok(false),
+            _ => Err(err.into()),
+        },
+    }
+}
+
+/// Which of a cancelled transaction's items failed their condition, or `None` if the request
+/// failed for another reason
+pub(crate) fn failed_conditions<R>(
+    err: &aws_sdk_dynamodb::error::SdkError<TransactWriteItemsError, R>,
+) -> Option<Vec<bool>> {
+    let Some(TransactWriteItemsError::TransactionCanceledException(cancelled)) =
+        err.as_service_error()
+    else {
+        return None;
+    };
+
+    Some(
+        cancelled
+            .cancellation_reasons()
+            .iter()
+            .map(|reason| reason.code() == Some("ConditionalCheckFailed"))
+            .collect(),
+    )
+}
+
+/// Read the Tasks with the given ids, in the same order, skipping any that no longer exist
+pub(crate) async fn batch_get(
+    client: &Client,
+    table_name: &str,
+    ids: &[String],
+) -> anyhow::Result<Vec<Task>> {
+    let mut found: HashMap<String, Task> = HashMap::new();
+
+    for chunk in ids.chunks(MAX_BATCH_GET_KEYS) {
+        let keys = chunk
+            .iter()
+            .map(|id| HashMap::from([("id".to_string(), AttributeValue::S(id.clone()))]))
+            .collect();
+
+        let mut request = Some(KeysAndAttributes::builder().set_keys(Some(keys)).build()?);
+
+        // Keys DynamoDB couldn't read this time are returned to be requested again
+        while let Some(keys) = request.take() {
+            let results = client
+                .batch_get_item()
+                .request_items(table_name, keys)
+                .send()
+                .await?;
+
+            for item in results
+                .responses
+                .and_then(|mut responses| responses.remove(table_name))
+                .unwrap_or_default()
+            {
+                let task: Task = dynamo::from_item(item)?;
+
+                found.insert(task.id.clone(), task);
+            }
+
+            request = results
+                .unprocessed_keys
+                .and_then(|mut unprocessed| unprocessed.remove(table_name));
+        }
+    }
+
+    Ok(ids.iter().filter_map(|id| found.remove(id)).collect())
+}
+
+/// The id of a Tag's item
+fn tag_id(name: &str) -> String {
+    format!("{TAG_ENTITY}#{name}")
+}
+
+/// The id of the edge item linking a Task to a Tag
+fn task_tag_id(id: &str, name: &str) -> String {
+    format!("{TASK_ENTITY}#{id}#{TAG_ENTITY}#{name}")
+}
+
+/// The edge item linking a Task to a Tag, with the keys of both Tag indexes
+fn task_tag_item(id: &str, name: &str) -> Item {
+    HashMap::from([
+        ("id".to_string(), AttributeValue::S(task_tag_id(id, name))),
+        (
+            ENTITY_ATTRIBUTE.to_string(),
+            AttributeValue::S(TASK_TAG_ENTITY.to_string()),
+        ),
+        ("task_id".to_string(), AttributeValue::S(id.to_string())),
+        ("tag".to_string(), AttributeValue::S(name.to_string())),
+    ])
+}
+
+/// Read a Tag item's name and Task count
+fn tag_count(item: &Item) -> anyhow::Result<TagCount> {
+    let name = item
+        .get("name")
+        .and_then(|name| name.as_s().ok())
+        .ok_or_else(|| anyhow!("Found a Tag without a name: {item:?}"))?;
+
+    let tasks = item
+        .get("task_count")
+        .and_then(|count| count.as_n().ok())
+        .map_or(Ok(0), |count| count.parse())?;
+
+    Ok(TagCount {
+        name: name.clone(),
+        tasks,
+    })
+}
+
+/// The condition for writing to an existing Task, so an id belonging to another entity sharing the
+/// table, such as a Project or Recurrence, is treated as missing. Tasks written before items were
+/// marked with their entity still match.
+pub(crate) const TASK_CONDITION: &str =
+    "attribute_exists(#id) AND (attribute_not_exists(#entity) OR #entity = :task)";
+
+/// A transaction check that the id belongs to an existing `Task`, for writes to the items that
+/// hang off it
+pub(crate) fn task_exists(table_name: &str, id: &str) -> anyhow::Result<ConditionCheck> {
+    Ok(ConditionCheck::builder()
+        .table_name(table_name)
+        .key("id", AttributeValue::S(id.to_string()))
+        .condition_expression(TASK_CONDITION)
+        .expression_attribute_names("#id", "id")
+        .expression_attribute_names("#entity", ENTITY_ATTRIBUTE)
+        .expression_attribute_values(":task", AttributeValue::S(TASK_ENTITY.to_string()))
+        .build()?)
+}
+
+/// Whether an item is a Task, including Tasks written before items were marked with their entity
+fn is_task(item: &Item) -> bool {
+    item.get(ENTITY_ATTRIBUTE)
+        .is_none_or(|entity| entity == &AttributeValue::S(TASK_ENTITY.to_string()))
+}
+
+/// Convert a `Task` into an item, marked with its entity so it appears in the indexes
+pub(crate) fn task_item(task: &Task, format: TimestampFormat) -> anyhow::Result<Item> {
+    let mut item = dynamo::to_item(task, format)?;
+
+    item.insert(
+        ENTITY_ATTRIBUTE.to_string(),
+        AttributeValue::S(TASK_ENTITY.to_string()),
+    );
+
+    Ok(item)
+}
+
+/// The key to resume a scan or priority index query after, from a cursor in the given order
+fn start_key(order: Order, after: &str) -> anyhow::Result<Item> {
+    let mut key = HashMap::from([("id".to_string(), AttributeValue::S(after.to_string()))]);
+
+    if order == Order::Priority {
+        let (priority, id) = Order::priority_cursor(after)?;
+
+        key.insert("id".to_string(), AttributeValue::S(id.to_string()));
+        key.insert(
+            ENTITY_ATTRIBUTE.to_string(),
+            AttributeValue::S(TASK_ENTITY.to_string()),
+        );
+        key.insert(
+            "priority".to_string(),
+            AttributeValue::N(priority.to_string()),
+        );
+    }
+
+    Ok(key)
+}
+
+/// The outcome of rewriting timestamps
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rewritten {
+    /// The number of items read
+    pub scanned: usize,
+
+    /// The number of items rewritten in the new format
+    pub rewritten: usize,
+
+    /// The number of items changed by another writer while being rewritten
+    pub skipped: usize,
+}
+
+/// Rewrite the timestamps of every item sharing the table that isn't already stored in the given
+/// format, touching only the timestamps its entity holds
+pub async fn rewrite_timestamps(
+    client: Arc<Client>,
+    table_name: &str,
+    format: TimestampFormat,
+) -> anyhow::Result<Rewritten> {
+    let mut outcome = Rewritten::default();
+    let mut start_key = None;
+
+    loop {
+        let results = client
+            .scan()
+            .table_name(table_name)
+            .set_exclusive_start_key(start_key)
+            .send()
+            .await?;
+
+        for item in results.items.unwrap_or_default() {
+            if timestamps_of(&item)?.is_empty() {
+                continue;
+            }
+
+            outcome.scanned += 1;
+
+            let id = item
+                .get("id")
+                .ok_or_else(|| anyhow!("Found an item without an id: {item:?}"))?;
+
+            let Some(expression) = rewrite_expression(&item, format)? else {
+                continue;
+            };
+
+            // Only rewrite the item if nothing else has changed its timestamps since it was read
+            let result = client
+                .update_item()
+                .table_name(table_name)
+                .key("id", id.clone())
+                .update_expression(expression.expression)
+                .condition_expression(expression.condition)
+                .set_expression_attribute_names(Some(expression.names))
+                .set_expression_attribute_values(Some(expression.values))
+                .send()
+                .await;
+
+            match result {
+                Ok(_) => outcome.rewritten += 1,
+                Err(err)
+                    if err
+                        .as_service_error()
+                        .is_some_and(|err| err.is_conditional_check_failed_exception()) =>
+                {
+                    outcome.skipped += 1;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        match results.last_evaluated_key {
+            Some(key) => start_key = Some(key),
+            None => return Ok(outcome),
+        }
+    }
+}
+
+/// The timestamps held by an item, by its entity. Tags, their edges and the occurrences of
+/// Recurrences have none.
+fn timestamps_of(item: &Item) -> anyhow::Result<&'static [&'static str]> {
+    let entity = match item.get(ENTITY_ATTRIBUTE) {
+        Some(AttributeValue::S(entity)) => entity.as_str(),
+        Some(_) => return Err(anyhow!("Found an item with an invalid entity: {item:?}")),
+        // Tasks written before items were marked with their entity
+        None => TASK_ENTITY,
+    };
+
+    Ok(match entity {
+        TASK_ENTITY => Task::TIMESTAMPS,
+        PROJECT_ENTITY => projects::model::Model::TIMESTAMPS,
+        COMMENT_ENTITY => comments::model::Model::TIMESTAMPS,
+        ATTACHMENT_ENTITY => attachments::model::Model::TIMESTAMPS,
+        DEPENDENCY_ENTITY => dependencies::dynamo_service::TIMESTAMPS,
+        RECURRENCE_ENTITY => recurrences::model::Model::TIMESTAMPS,
+        JOB_ENTITY => jobs::dynamo_service::REWRITABLE_TIMESTAMPS,
+        TAG_ENTITY | TASK_TAG_ENTITY | OCCURRENCE_ENTITY => &[],
+        _ => return Err(anyhow!("Found an item of an unknown entity: {item:?}")),
+    })
+}
+
+/// The expression, condition, names and values for rewriting an item's timestamps
+#[derive(Debug, Default, PartialEq)]
+struct RewriteExpression {
+    expression: String,
+    condition: String,
+    names: HashMap<String, String>,
+    values: HashMap<String, AttributeValue>,
+}
+
+/// Set each of the item's timestamps that isn't already in the given format, on the condition that
+/// it still holds the value that was read. Returns `None` when there is nothing to rewrite.
+fn rewrite_expression(
+    item: &Item,
+    format: TimestampFormat,
+) -> anyhow::Result<Option<RewriteExpression>> {
+    let mut set = vec![];
+    let mut conditions = vec![];
+    let mut expression = RewriteExpression::default();
+
+    for &name in timestamps_of(item)? {
+        // Optional timestamps, like a Task's due date, are left out when unset
+        let Some(previous) = item.get(name) else {
+            continue;
+        };
+
+        let rewritten = timestamp::to_attribute(timestamp::from_attribute(previous)?, format);
+
+        if &rewritten == previous {
+            continue;
+        }
+
+        set.push(format!("#{name} = :{name}"));
+        conditions.push(format!("#{name} = :previous_{name}"));
+
+        expression
+            .names
+            .insert(format!("#{name}"), name.to_string());
+        expression.values.insert(format!(":{name}"), rewritten);
+        expression
+            .values
+            .insert(format!(":previous_{name}"), previous.clone());
+    }
+
+    if set.is_empty() {
+        return Ok(None);
+    }
+
+    expression.expression = format!("SET {}", set.join(", "));
+    expression.condition = conditions.join(" AND ");
+
+    Ok(Some(expression))
+}
+
+/// The expression, names and values for an `UpdateItem` request
+#[derive(Debug, Default, PartialEq)]
+struct UpdateExpression {
+    expression: String,
+    names: HashMap<String, String>,
+    values: HashMap<String, AttributeValue>,
+}
+
+/// Translate an `inputs::Update` into `SET` actions for new values and `REMOVE` actions for
+/// cleared ones, leaving unchanged fields out of the expression. The entity is set too, so Tasks
+/// written before it existed join the indexes once they're updated.
+fn update_expression(
+    input: &inputs::Update,
+    format: TimestampFormat,
+    updated_at: DateTime<FixedOffset>,
+) -> UpdateExpression {
+    let mut set = vec!["#updated_at = :updated_at", "#entity = :entity"];
+    let mut remove = vec![];
+
+    let mut names = HashMap::from([
+        ("#id".to_string(), "id".to_string()),
+        ("#updated_at".to_string(), "updated_at".to_string()),
+        ("#entity".to_string(), ENTITY_ATTRIBUTE.to_string()),
+    ]);
+
+    let mut values = HashMap::from([
+        (
+            ":updated_at".to_string(),
+            timestamp::to_attribute(updated_at, format),
+        ),
+        (
+            ":entity".to_string(),
+            AttributeValue::S(TASK_ENTITY.to_string()),
+        ),
+        (
+            ":task".to_string(),
+            AttributeValue::S(TASK_ENTITY.to_string()),
+        ),
+    ]);
+
+    // The title is required, so clearing it leaves it unchanged
+    if let Value(title) = &input.title {
+        set.push("#title = :title");
+        names.insert("#title".to_string(), "title".to_string());
+        values.insert(":title".to_string(), AttributeValue::S(title.clone()));
+    }
+
+    match &input.description {
+        Unchanged => (),
+        Empty => {
+            remove.push("#description");
+            names.insert("#description".to_string(), "description".to_string());
+        }
+        Value(description) => {
+            set.push("#description = :description");
+            names.insert("#description".to_string(), "description".to_string());
+            values.insert(
+                ":description".to_string(),
+                AttributeValue::S(description.clone()),
+            );
+        }
+    }
+
+    match &input.due_at {
+        Unchanged => (),
+        Empty => {
+            remove.push("#due_at");
+            names.insert("#due_at".to_string(), "due_at".to_string());
+        }
+        Value(due_at) => {
+            set.push("#due_at = :due_at");
+            names.insert("#due_at".to_string(), "due_at".to_string());
+            values.insert(
+                ":due_at".to_string(),
+                timestamp::to_attribute(*due_at, format),
+            );
+        }
+    }
+
+    match &input.priority {
+        Unchanged => (),
+        Empty => {
+            remove.push("#priority");
+            names.insert("#priority".to_string(), "priority".to_string());
+        }
+        Value(priority) => {
+            set.push("#priority = :priority");
+            names.insert("#priority".to_string(), "priority".to_string());
+            values.insert(
+                ":priority".to_string(),
+                AttributeValue::N(priority.to_string()),
+            );
+        }
+    }
+
+    match &input.project_id {
+        Unchanged => (),
+        Empty => {
+            remove.push("#project_id");
+            names.insert("#project_id".to_string(), "project_id".to_string());
+        }
+        Value(project_id) => {
+            set.push("#project_id = :project_id");
+            names.insert("#project_id".to_string(), "project_id".to_string());
+            values.insert(
+                ":project_id".to_string(),
+                AttributeValue::S(project_id.clone()),
+            );
+        }
+    }
+
+    let mut expression = format!("SET {}", set.join(", "));
+
+    if !remove.is_empty() {
+        expression.push_str(&format!(" REMOVE {}", remove.join(", ")));
+    }
+
+    UpdateExpression {
+        expression,
+        names,
+        values,
+    }
+}
+
+/// Set a Task's parent, or remove it to move the Task to the top level
+fn move_expression(
+    parent_id: Option<&str>,
+    format: TimestampFormat,
+    updated_at: DateTime<FixedOffset>,
+) -> UpdateExpression {
+    let names = HashMap::from([
+        ("#id".to_string(), "id".to_string()),
+        ("#updated_at".to_string(), "updated_at".to_string()),
+        ("#parent_id".to_string(), "parent_id".to_string()),
+        ("#entity".to_string(), ENTITY_ATTRIBUTE.to_string()),
+    ]);
+
+    let mut values = HashMap::from([
+        (
+            ":updated_at".to_string(),
+            timestamp::to_attribute(updated_at, format),
+        ),
+        (
+            ":task".to_string(),
+            AttributeValue::S(TASK_ENTITY.to_string()),
+        ),
+    ]);
+
+    let expression = match parent_id {
+        Some(parent_id) => {
+            values.insert(
+                ":parent_id".to_string(),
+                AttributeValue::S(parent_id.to_string()),
+            );
+
+            "SET #updated_at = :updated_at, #parent_id = :parent_id".to_string()
+        }
+        None => "SET #updated_at = :updated_at REMOVE #parent_id".to_string(),
+    };
+
+    UpdateExpression {
+        expression,
+        names,
+        values,
+    }
+}
+
+/// The filter, names and values for a scan or query listing Tasks
+#[derive(Debug, Default, PartialEq)]
+struct ListExpression {
+    filter: Option<String>,
+    names: HashMap<String, String>,
+    values: HashMap<String, AttributeValue>,
+}
+
+impl ListExpression {
+    /// The names, or `None` since DynamoDB rejects an empty map
+    fn names(&self) -> Option<HashMap<String, String>> {
+        Some(self.names.clone()).filter(|names| !names.is_empty())
+    }
+
+    /// The values, or `None` since DynamoDB rejects an empty map
+    fn values(&self) -> Option<HashMap<String, AttributeValue>> {
+        Some(self.values.clone()).filter(|values| !values.is_empty())
+    }
+}
+
+/// Translate the list filters into a filter expression, plus the key condition's names and values
+/// when ordering by priority. Scans skip the Tag items sharing the table, while Tasks written
+/// before items had an entity are still included.
+fn list_expression(
+    input: &inputs::List,
+    format: TimestampFormat,
+    now: DateTime<FixedOffset>,
+) -> ListExpression {
+    let mut conditions = vec![];
+    let mut expression = ListExpression::default();
+
+    expression
+        .names
+        .insert("#entity".to_string(), ENTITY_ATTRIBUTE.to_string());
+    expression.values.insert(
+        ":entity".to_string(),
+        AttributeValue::S(TASK_ENTITY.to_string()),
+    );
+
+    if input.order() == Order::Id {
+        conditions.push("(attribute_not_exists(#entity) OR #entity = :entity)");
+    }
+
+    if input.overdue || input.due_within_days.is_some() {
+        expression
+            .names
+            .insert("#due_at".to_string(), "due_at".to_string());
+        expression
+            .values
+            .insert(":now".to_string(), timestamp::to_attribute(now, format));
+    }
+
+    if input.overdue {
+        conditions.push("#due_at < :now AND NOT (#status IN (:done, :cancelled))");
+
+        expression
+            .names
+            .insert("#status".to_string(), "status".to_string());
+        expression.values.insert(
+            ":done".to_string(),
+            AttributeValue::S(Status::Done.to_string()),
+        );
+        expression.values.insert(
+            ":cancelled".to_string(),
+            AttributeValue::S(Status::Cancelled.to_string()),
+        );
+    }
+
+    if let Some(days) = input.due_within_days {
+        conditions.push("#due_at BETWEEN :now AND :due_before");
+
+        expression.values.insert(
+            ":due_before".to_string(),
+            timestamp::to_attribute(now + Duration::days(i64::from(days)), format),
+        );
+    }
+
+    if !conditions.is_empty() {
+        expression.filter = Some(conditions.join(" AND "));
+    }
+
+    expression
+}
+
+/// The expression, condition, names and values for a transition's `UpdateItem` request
+#[derive(Debug, Default, PartialEq)]
+struct TransitionExpression {
+    expression: String,
+    condition: String,
+    names: HashMap<String, String>,
+    values: HashMap<String, AttributeValue>,
+}
+
+/// Set the status, on the condition that the Task exists and is in one of the given statuses.
+/// Items written before Tasks had a status are read as `todo`, so they match it too.
+fn transition_expression(
+    sources: &[Status],
+    status: Status,
+    updated_at: AttributeValue,
+) -> TransitionExpression {
+    let names = HashMap::from([
+        ("#id".to_string(), "id".to_string()),
+        ("#status".to_string(), "status".to_string()),
+        ("#updated_at".to_string(), "updated_at".to_string()),
+        ("#entity".to_string(), ENTITY_ATTRIBUTE.to_string()),
+    ]);
+
+    let mut values = HashMap::from([
+        (":status".to_string(), AttributeValue::S(status.to_string())),
+        (":updated_at".to_string(), updated_at),
+        (
+            ":task".to_string(),
+            AttributeValue::S(TASK_ENTITY.to_string()),
+        ),
+    ]);
+
+    let mut placeholders = vec![];
+
+    for (index, source) in sources.iter().enumerate() {
+        let placeholder = format!(":from{index}");
+
+        values.insert(placeholder.clone(), AttributeValue::S(source.to_string()));
+        placeholders.push(placeholder);
+    }
+
+    let mut allowed = format!("#status IN ({})", placeholders.join(", "));
+
+    if sources.contains(&Status::default()) {
+        allowed = format!("(attribute_not_exists(#status) OR {allowed})");
+    }
+
+    TransitionExpression {
+        expression: "SET #status = :status, #updated_at = :updated_at".to_string(),
+        condition: format!("{TASK_CONDITION} AND {allowed}"),
+        names,
+        values,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use crate::utils::fake_dynamo::FakeDynamo;
+
+    use super::*;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2026-10-19T12:00:00Z").unwrap()
+    }
+
+    #[test]
+    fn test_update_expression() {
+        let input = inputs::Update {
+            title: Value("Ship it".to_string()),
+            description: Empty,
+            due_at: Value(now()),
+            priority: Empty,
+            project_id: Value("01HP".to_string()),
+        };
+
+        let expression = update_expression(&input, TimestampFormat::EpochMillis, now());
+
+        assert_eq!(
+            expression.expression,
+            "SET #updated_at = :updated_at, #entity = :entity, #title = :title, #due_at = :due_at, \
+             #project_id = :project_id REMOVE #description, #priority"
+        );
+        assert_eq!(
+            expression.values.get(":due_at"),
+            Some(&AttributeValue::N("1792411200000".to_string()))
+        );
+        assert_eq!(
+            expression.values.get(":title"),
+            Some(&AttributeValue::S("Ship it".to_string()))
+        );
+        assert!(!expression.values.contains_key(":description"));
+        assert_eq!(
+            expression.names.get("#description"),
+            Some(&"description".to_string())
+        );
+    }
+
+    #[test]
+    fn test_update_expression_unchanged() {
+        let expression =
+            update_expression(&inputs::Update::default(), TimestampFormat::Rfc3339, now());
+
+        assert_eq!(
+            expression.expression,
+            "SET #updated_at = :updated_at, #entity = :entity"
+        );
+        assert_eq!(expression.names.len(), 3);
+        assert_eq!(expression.values.len(), 3);
+    }
+
+    #[test]
+    fn test_move_expression() {
+        let expression = move_expression(Some("01HX"), TimestampFormat::Rfc3339, now());
+
+        assert_eq!(
+            expression.expression,
+            "SET #updated_at = :updated_at, #parent_id = :parent_id"
+        );
+        assert_eq!(
+            expression.values.get(":parent_id"),
+            Some(&AttributeValue::S("01HX".to_string()))
+        );
+
+        let expression = move_expression(None, TimestampFormat::Rfc3339, now());
+
+        assert_eq!(
+            expression.expression,
+            "SET #updated_at = :updated_at REMOVE #parent_id"
+        );
+        assert!(!expression.values.contains_key(":parent_id"));
+        assert_eq!(
+            expression.values.get(":task"),
+            Some(&AttributeValue::S("task".to_string()))
+        );
+        assert_eq!(expression.names.get("#entity"), Some(&"entity".to_string()));
+    }
+
+    #[test]
+    fn test_list_expression() {
+        let expression = list_expression(&inputs::List::default(), TimestampFormat::Rfc3339, now());
+
+        assert_eq!(
+            expression.filter.as_deref(),
+            Some("(attribute_not_exists(#entity) OR #entity = :entity)")
+        );
+        assert_eq!(
+            expression.values(),
+            Some(HashMap::from([(
+                ":entity".to_string(),
+                AttributeValue::S(TASK_ENTITY.to_string())
+            )]))
+        );
+
+        let input = inputs::List {
+            overdue: true,
+            due_within_days: Some(7),
+            order: Some(Order::Priority),
+            ..Default::default()
+        };
+
+        let expression = list_expression(&input, TimestampFormat::Rfc3339, now());
+
+        assert_eq!(
+            expression.filter.as_deref(),
+            Some(
+                "#due_at < :now AND NOT (#status IN (:done, :cancelled)) AND \
+                 #due_at BETWEEN :now AND :due_before"
+            )
+        );
+        assert_eq!(
+            expression.values.get(":due_before"),
+            Some(&AttributeValue::S("2026-10-26T12:00:00.000Z".to_string()))
+        );
+        assert_eq!(
+            expression.names.get("#entity"),
+            Some(&ENTITY_ATTRIBUTE.to_string())
+        );
+    }
+
+    #[test]
+    fn test_due_filters_with_fractional_due_dates() {
+        let input = inputs::List {
+            overdue: true,
+            due_within_days: Some(7),
+            ..Default::default()
+        };
+
+        let expression = list_expression(&input, TimestampFormat::Rfc3339, now());
+
+        let Some(AttributeValue::S(now_value)) = expression.values.get(":now") else {
+            panic!("Expected a string :now value");
+        };
+
+        // DynamoDB compares the strings, so they must sort the same way as the timestamps
+        for due_at in [
+            "2026-10-19T11:59:59Z",
+            "2026-10-19T11:59:59.5Z",
+            "2026-10-19T12:00:00Z",
+            "2026-10-19T12:00:00.5Z",
+            "2026-10-19T12:00:01Z",
+        ] {
+            let due_at = DateTime::parse_from_rfc3339(due_at).unwrap();
+
+            let AttributeValue::S(due_value) =
+                timestamp::to_attribute(due_at, TimestampFormat::Rfc3339)
+            else {
+                panic!("Expected a string due_at value");
+            };
+
+            assert_eq!(
+                due_value.as_str() < now_value.as_str(),
+                due_at < now(),
+                "{due_value} against {now_value}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_task_tag_item() {
+        let item = task_tag_item("01HX", "urgent");
+
+        assert_eq!(
+            item.get("id"),
+            Some(&AttributeValue::S("task#01HX#tag#urgent".to_string()))
+        );
+        assert_eq!(
+            item.get("task_id"),
+            Some(&AttributeValue::S("01HX".to_string()))
+        );
+        assert_eq!(
+            item.get("tag"),
+            Some(&AttributeValue::S("urgent".to_string()))
+        );
+        assert_eq!(tag_id("urgent"), "tag#urgent");
+    }
+
+    #[test]
+    fn test_tag_count() -> anyhow::Result<()> {
+        let item = HashMap::from([
+            ("id".to_string(), AttributeValue::S(tag_id("urgent"))),
+            ("name".to_string(), AttributeValue::S("urgent".to_string())),
+            ("task_count".to_string(), AttributeValue::N("2".to_string())),
+        ]);
+
+        assert_eq!(
+            tag_count(&item)?,
+            TagCount {
+                name: "urgent".to_string(),
+                tasks: 2
+            }
+        );
+        assert!(tag_count(&HashMap::new()).is_err());
+
+        Ok(())
+    }
+
+    /// An item of the given entity with its timestamps in epoch milliseconds
+    fn legacy_item(entity: &str, timestamps: &[&str]) -> Item {
+        let mut item = HashMap::from([
+            ("id".to_string(), AttributeValue::S("01HX".to_string())),
+            (
+                ENTITY_ATTRIBUTE.to_string(),
+                AttributeValue::S(entity.to_string()),
+            ),
+        ]);
+
+        for name in timestamps {
+            item.insert(
+                name.to_string(),
+                AttributeValue::N("1792411200000".to_string()),
+            );
+        }
+
+        item
+    }
+
+    #[test]
+    fn test_rewrite_expression() -> anyhow::Result<()> {
+        let rewritten = AttributeValue::S("2026-10-19T12:00:00.000Z".to_string());
+
+        let expression = rewrite_expression(
+            &legacy_item(TASK_ENTITY, &["created_at", "updated_at"]),
+            TimestampFormat::Rfc3339,
+        )?
+        .ok_or(anyhow!("Expected an expression"))?;
+
+        assert!(expression.expression.starts_with("SET "));
+        assert_eq!(expression.names.len(), 2);
+        assert_eq!(expression.values.get(":updated_at"), Some(&rewritten));
+        assert!(!expression.names.contains_key("#due_at"));
+
+        // Dependencies only have a creation date, so nothing else is set or checked
+        let expression = rewrite_expression(
+            &legacy_item(DEPENDENCY_ENTITY, &["created_at"]),
+            TimestampFormat::Rfc3339,
+        )?
+        .ok_or(anyhow!("Expected an expression"))?;
+
+        assert_eq!(expression.expression, "SET #created_at = :created_at");
+        assert_eq!(expression.condition, "#created_at = :previous_created_at");
+        assert_eq!(expression.values.get(":created_at"), Some(&rewritten));
+        assert_eq!(
+            expression.values.get(":previous_created_at"),
+            Some(&AttributeValue::N("1792411200000".to_string()))
+        );
+
+        // Attachments likewise
+        let expression = rewrite_expression(
+            &legacy_item(ATTACHMENT_ENTITY, &["created_at"]),
+            TimestampFormat::Rfc3339,
+        )?
+        .ok_or(anyhow!("Expected an expression"))?;
+
+        assert_eq!(expression.expression, "SET #created_at = :created_at");
+        assert_eq!(expression.condition, "#created_at = :previous_created_at");
+
+        // A Job's run_at stays a fixed-width string, so the Job index still sorts by it
+        let mut job = legacy_item(JOB_ENTITY, &["created_at", "updated_at"]);
+
+        job.insert(
+            "run_at".to_string(),
+            AttributeValue::S("2026-10-19T12:00:00.000Z".to_string()),
+        );
+
+        let expression = rewrite_expression(&job, TimestampFormat::EpochMillis)?;
+
+        assert_eq!(expression, None);
+
+        let expression = rewrite_expression(&job, TimestampFormat::Rfc3339)?
+            .ok_or(anyhow!("Expected an expression"))?;
+
+        assert!(!expression.names.contains_key("#run_at"));
+        assert_eq!(expression.values.get(":created_at"), Some(&rewritten));
+
+        // Already in the format
+        assert_eq!(
+            rewrite_expression(
+                &legacy_item(DEPENDENCY_ENTITY, &["created_at"]),
+                TimestampFormat::EpochMillis
+            )?,
+            None
+        );
+
+        assert!(timestamps_of(&legacy_item(TAG_ENTITY, &[]))?.is_empty());
+        assert!(timestamps_of(&legacy_item("unknown", &[])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_start_key() -> anyhow::Result<()> {
+        assert_eq!(
+            start_key(Order::Id, "01HX")?,
+            HashMap::from([("id".to_string(), AttributeValue::S("01HX".to_string()))])
+        );
+
+        let key = start_key(Order::Priority, "3:01HX")?;
+
+        assert_eq!(key.get("id"), Some(&AttributeValue::S("01HX".to_string())));
+        assert_eq!(
+            key.get("priority"),
+            Some(&AttributeValue::N("3".to_string()))
+        );
+        assert!(start_key(Order::Priority, "01HX").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transition_expression() {
+        let updated_at = AttributeValue::N("0".to_string());
+
+        let expression = transition_expression(
+            &[Status::Todo, Status::InProgress],
+            Status::Done,
+            updated_at.clone(),
+        );
+
+        assert_eq!(
+            expression.condition,
+            format!(
+                "{TASK_CONDITION} AND (attribute_not_exists(#status) OR #status IN (:from0, :from1))"
+            )
+        );
+        assert_eq!(
+            expression.values.get(":from1"),
+            Some(&AttributeValue::S("in_progress".to_string()))
+        );
+        assert_eq!(
+            expression.values.get(":status"),
+            Some(&AttributeValue::S("done".to_string()))
+        );
+
+        let expression = transition_expression(&[Status::Blocked], Status::InProgress, updated_at);
+
+        assert_eq!(
+            expression.condition,
+            format!("{TASK_CONDITION} AND #status IN (:from0)")
+        );
+        assert_eq!(
+            expression.values.get(":task"),
+            Some(&AttributeValue::S("task".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upsert_keeps_other_entities() -> anyhow::Result<()> {
+        let project = json!({
+            "id": { "S": "01HXPROJECT" },
+            "entity": { "S": "project" },
+            "name": { "S": "Launch" },
+        });
+
+        let table = FakeDynamo::serve(vec![project.clone()]).await?;
+
+        let task = Task {
+            id: "01HXPROJECT".to_string(),
+            title: "Imported".to_string(),
+            ..Default::default()
+        };
+
+        let err = upsert(
+            table.client.clone(),
+            "tasks",
+            TimestampFormat::Rfc3339,
+            &task,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(Error::is_id_conflict(&err), "{err:#}");
+        assert_eq!(table.get("01HXPROJECT"), Some(project));
+
+        // New Tasks and Tasks written before items had an entity are still written
+        let task = Task {
+            id: "01HXTASK".to_string(),
+            ..task
+        };
+
+        upsert(
+            table.client.clone(),
+            "tasks",
+            TimestampFormat::Rfc3339,
+            &task,
+        )
+        .await?;
+        upsert(
+            table.client.clone(),
+            "tasks",
+            TimestampFormat::Rfc3339,
+            &task,
+        )
         .await?;
 
-    Ok(())
+        assert_eq!(
+            table.get("01HXTASK").map(|item| item["title"].clone()),
+            Some(json!({ "S": "Imported" }))
+        );
+
+        Ok(())
+    }
 }
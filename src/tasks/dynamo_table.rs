@@ -0,0 +1,643 @@
+use std::time::Duration;
+
+use aws_sdk_dynamodb::{
+    types::{
+        AttributeDefinition, BillingMode as DynamoBillingMode, CreateGlobalSecondaryIndexAction,
+        GlobalSecondaryIndex, GlobalSecondaryIndexUpdate, IndexStatus, KeySchemaElement, KeyType,
+        Projection, ProjectionType, ProvisionedThroughput, ScalarAttributeType, TableDescription,
+        TableStatus, TimeToLiveSpecification, TimeToLiveStatus,
+    },
+    Client,
+};
+
+use crate::config::{self, BillingMode};
+
+/// The partition key of the Tasks table
+pub const PARTITION_KEY: &str = "id";
+
+/// An attribute identifying the kind of each item, so every Task shares a partition in the
+/// indexes that order all Tasks
+pub const ENTITY_ATTRIBUTE: &str = "entity";
+
+/// The `ENTITY_ATTRIBUTE` value for Tasks
+pub const TASK_ENTITY: &str = "task";
+
+/// The `ENTITY_ATTRIBUTE` value for Tags, which count the Tasks they label
+pub const TAG_ENTITY: &str = "tag";
+
+/// The `ENTITY_ATTRIBUTE` value for the edges linking a Task to a Tag
+pub const TASK_TAG_ENTITY: &str = "task_tag";
+
+/// The `ENTITY_ATTRIBUTE` value for Projects
+pub const PROJECT_ENTITY: &str = "project";
+
+/// The `ENTITY_ATTRIBUTE` value for Comments on Tasks
+pub const COMMENT_ENTITY: &str = "comment";
+
+/// The `ENTITY_ATTRIBUTE` value for the metadata of files attached to Tasks
+pub const ATTACHMENT_ENTITY: &str = "attachment";
+
+/// The `ENTITY_ATTRIBUTE` value for the edges from a Task to the Tasks blocking it
+pub const DEPENDENCY_ENTITY: &str = "dependency";
+
+/// The `ENTITY_ATTRIBUTE` value for Recurrences, the templates of repeating Tasks
+pub const RECURRENCE_ENTITY: &str = "recurrence";
+
+/// The `ENTITY_ATTRIBUTE` value for the occurrences of a Recurrence that have been handled
+pub const OCCURRENCE_ENTITY: &str = "occurrence";
+
+/// The `ENTITY_ATTRIBUTE` value for queued background Jobs
+pub const JOB_ENTITY: &str = "job";
+
+/// The index ordering Tasks with a priority by that priority
+pub const PRIORITY_INDEX: &str = "priority-index";
+
+/// The index listing the Task ids linked to each Tag, in id order
+pub const TAG_INDEX: &str = "tag-index";
+
+/// The index listing the Tags linked to each Task, in name order
+pub const TASK_TAG_INDEX: &str = "task-tag-index";
+
+/// The index listing each Task's subtasks, in id order
+pub const PARENT_INDEX: &str = "parent-index";
+
+/// The index listing each Project's Tasks, in id order
+pub const PROJECT_INDEX: &str = "project-index";
+
+/// The index listing each Task's Comments, oldest first
+pub const COMMENT_INDEX: &str = "comment-index";
+
+/// The index listing the files attached to each Task, oldest first
+pub const ATTACHMENT_INDEX: &str = "attachment-index";
+
+/// The index listing the Tasks blocking each Task, in id order
+pub const BLOCKER_INDEX: &str = "blocker-index";
+
+/// The index listing the Tasks blocked by each Task, in id order
+pub const DEPENDENT_INDEX: &str = "dependent-index";
+
+/// The index listing the handled occurrences of each Recurrence, in time order
+pub const OCCURRENCE_INDEX: &str = "occurrence-index";
+
+/// The index listing the queued Jobs of each kind by when they're due, leaving out failed Jobs
+pub const JOB_INDEX: &str = "job-index";
+
+/// How long to wait for the table and its indexes to become active after creating one, which
+/// includes backfilling a new index from the existing items
+const ACTIVE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How often to check whether the table and its indexes are active
+const ACTIVE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A key attribute with its scalar type
+pub type KeyAttribute = (&'static str, ScalarAttributeType);
+
+/// A global secondary index on the Tasks table
+#[derive(Clone, Debug)]
+pub struct Index {
+    /// The index name
+    pub name: &'static str,
+
+    /// The partition key of the index
+    pub partition_key: KeyAttribute,
+
+    /// The optional sort key of the index
+    pub sort_key: Option<KeyAttribute>,
+}
+
+/// The global secondary indexes the Tasks table needs
+pub fn indexes() -> Vec<Index> {
+    vec![
+        Index {
+            name: PRIORITY_INDEX,
+            partition_key: (ENTITY_ATTRIBUTE, ScalarAttributeType::S),
+            sort_key: Some(("priority", ScalarAttributeType::N)),
+        },
+        Index {
+            name: TAG_INDEX,
+            partition_key: ("tag", ScalarAttributeType::S),
+            sort_key: Some(("task_id", ScalarAttributeType::S)),
+        },
+        Index {
+            name: TASK_TAG_INDEX,
+            partition_key: ("task_id", ScalarAttributeType::S),
+            sort_key: Some(("tag", ScalarAttributeType::S)),
+        },
+        Index {
+            name: PARENT_INDEX,
+            partition_key: ("parent_id", ScalarAttributeType::S),
+            sort_key: Some(("id", ScalarAttributeType::S)),
+        },
+        Index {
+            name: PROJECT_INDEX,
+            partition_key: ("project_id", ScalarAttributeType::S),
+            sort_key: Some(("id", ScalarAttributeType::S)),
+        },
+        Index {
+            name: COMMENT_INDEX,
+            partition_key: ("task_id", ScalarAttributeType::S),
+            sort_key: Some(("comment_id", ScalarAttributeType::S)),
+        },
+        Index {
+            name: ATTACHMENT_INDEX,
+            partition_key: ("task_id", ScalarAttributeType::S),
+            sort_key: Some(("attachment_id", ScalarAttributeType::S)),
+        },
+        Index {
+            name: BLOCKER_INDEX,
+            partition_key: ("task_id", ScalarAttributeType::S),
+            sort_key: Some(("blocker_id", ScalarAttributeType::S)),
+        },
+        Index {
+            name: DEPENDENT_INDEX,
+            partition_key: ("blocker_id", ScalarAttributeType::S),
+            sort_key: Some(("task_id", ScalarAttributeType::S)),
+        },
+        Index {
+            name: OCCURRENCE_INDEX,
+            partition_key: ("recurrence_id", ScalarAttributeType::S),
+            sort_key: Some(("occurrence", ScalarAttributeType::S)),
+        },
+        Index {
+            name: JOB_INDEX,
+            partition_key: ("queued_kind", ScalarAttributeType::S),
+            sort_key: Some(("run_at", ScalarAttributeType::S)),
+        },
+    ]
+}
+
+/// Create the Tasks table if it doesn't exist, add any missing indexes and enable TTL, returning
+/// a description of each change made
+pub async fn provision(client: &Client, config: &config::Dynamo) -> anyhow::Result<Vec<String>> {
+    let mut changes = vec![];
+
+    let table = if let Some(table) = describe(client, &config.tasks_table_name).await? {
+        table
+    } else {
+        create_table(client, config).await?;
+
+        changes.push(format!("Created table '{}'", config.tasks_table_name));
+
+        wait_until_active(client, &config.tasks_table_name).await?
+    };
+
+    let problems = key_schema_problems(&table);
+
+    if !problems.is_empty() {
+        return Err(mismatch(&config.tasks_table_name, &problems));
+    }
+
+    for index in missing_indexes(&table) {
+        create_index(client, config, &index).await?;
+
+        changes.push(format!("Created index '{}'", index.name));
+
+        wait_until_active(client, &config.tasks_table_name).await?;
+    }
+
+    if let Some(attribute) = &config.ttl_attribute {
+        if ttl_attribute(client, &config.tasks_table_name)
+            .await?
+            .as_ref()
+            != Some(attribute)
+        {
+            client
+                .update_time_to_live()
+                .table_name(&config.tasks_table_name)
+                .time_to_live_specification(
+                    TimeToLiveSpecification::builder()
+                        .attribute_name(attribute)
+                        .enabled(true)
+                        .build()?,
+                )
+                .send()
+                .await?;
+
+            changes.push(format!("Enabled TTL on '{attribute}'"));
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Check that the Tasks table exists and matches the expected key schema, indexes and TTL,
+/// explaining every mismatch otherwise
+pub async fn check(client: &Client, config: &config::Dynamo) -> anyhow::Result<()> {
+    let Some(table) = describe(client, &config.tasks_table_name).await? else {
+        return Err(anyhow::anyhow!(
+            "The DynamoDB table '{}' does not exist, run the `provision` command to create it",
+            config.tasks_table_name
+        ));
+    };
+
+    let mut problems = key_schema_problems(&table);
+
+    problems.extend(
+        missing_indexes(&table)
+            .iter()
+            .map(|index| format!("the index '{}' is missing", index.name)),
+    );
+
+    if let Some(attribute) = &config.ttl_attribute {
+        if ttl_attribute(client, &config.tasks_table_name)
+            .await?
+            .as_ref()
+            != Some(attribute)
+        {
+            problems.push(format!("TTL is not enabled on '{attribute}'"));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatch(&config.tasks_table_name, &problems))
+    }
+}
+
+/// Describe the table, or return `None` if it doesn't exist
+async fn describe(client: &Client, table_name: &str) -> anyhow::Result<Option<TableDescription>> {
+    match client.describe_table().table_name(table_name).send().await {
+        Ok(output) => Ok(output.table),
+        Err(err)
+            if err
+                .as_service_error()
+                .is_some_and(|err| err.is_resource_not_found_exception()) =>
+        {
+            Ok(None)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+async fn create_table(client: &Client, config: &config::Dynamo) -> anyhow::Result<()> {
+    let indexes = indexes();
+
+    let mut request = client
+        .create_table()
+        .table_name(&config.tasks_table_name)
+        .set_attribute_definitions(Some(attribute_definitions(&indexes)?))
+        .set_key_schema(Some(key_schema(
+            &(PARTITION_KEY, ScalarAttributeType::S),
+            None,
+        )?))
+        .billing_mode(billing_mode(config.billing_mode));
+
+    if let Some(throughput) = throughput(config.billing_mode)? {
+        request = request.provisioned_throughput(throughput);
+    }
+
+    for index in &indexes {
+        request = request.global_secondary_indexes(global_secondary_index(config, index)?);
+    }
+
+    request.send().await?;
+
+    Ok(())
+}
+
+async fn create_index(
+    client: &Client,
+    config: &config::Dynamo,
+    index: &Index,
+) -> anyhow::Result<()> {
+    let mut create = CreateGlobalSecondaryIndexAction::builder()
+        .index_name(index.name)
+        .set_key_schema(Some(key_schema(
+            &index.partition_key,
+            index.sort_key.as_ref(),
+        )?))
+        .projection(projection());
+
+    if let Some(throughput) = throughput(config.billing_mode)? {
+        create = create.provisioned_throughput(throughput);
+    }
+
+    client
+        .update_table()
+        .table_name(&config.tasks_table_name)
+        .set_attribute_definitions(Some(attribute_definitions(std::slice::from_ref(index))?))
+        .global_secondary_index_updates(
+            GlobalSecondaryIndexUpdate::builder()
+                .create(create.build()?)
+                .build(),
+        )
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+async fn wait_until_active(client: &Client, table_name: &str) -> anyhow::Result<TableDescription> {
+    let deadline = tokio::time::Instant::now() + ACTIVE_TIMEOUT;
+
+    // Indexes are created in the background, so the table can be active before they are.
+    loop {
+        let table = describe(client, table_name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("The DynamoDB table '{table_name}' disappeared"))?;
+
+        let pending = not_active(&table);
+
+        if pending.is_empty() {
+            return Ok(table);
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            return Err(anyhow::anyhow!(
+                "Timed out after {}s waiting for the DynamoDB table '{table_name}' to become \
+                 active, still waiting on {}",
+                ACTIVE_TIMEOUT.as_secs(),
+                pending.join(", ")
+            ));
+        }
+
+        tokio::time::sleep(ACTIVE_POLL_INTERVAL).await;
+    }
+}
+
+async fn ttl_attribute(client: &Client, table_name: &str) -> anyhow::Result<Option<String>> {
+    let output = client
+        .describe_time_to_live()
+        .table_name(table_name)
+        .send()
+        .await?;
+
+    Ok(output
+        .time_to_live_description
+        .filter(|ttl| {
+            matches!(
+                ttl.time_to_live_status(),
+                Some(TimeToLiveStatus::Enabled | TimeToLiveStatus::Enabling)
+            )
+        })
+        .and_then(|ttl| ttl.attribute_name))
+}
+
+/// Describe how the table's primary key differs from the expected `id` string partition key
+fn key_schema_problems(table: &TableDescription) -> Vec<String> {
+    let mut problems = vec![];
+
+    let key_schema: Vec<(&str, &KeyType)> = table
+        .key_schema()
+        .iter()
+        .map(|key| (key.attribute_name(), key.key_type()))
+        .collect();
+
+    if key_schema != [(PARTITION_KEY, &KeyType::Hash)] {
+        problems.push(format!(
+            "the primary key must be a single '{PARTITION_KEY}' partition key, found {key_schema:?}"
+        ));
+    }
+
+    let id_type = table
+        .attribute_definitions()
+        .iter()
+        .find(|definition| definition.attribute_name() == PARTITION_KEY)
+        .map(AttributeDefinition::attribute_type);
+
+    if id_type.is_some_and(|id_type| id_type != &ScalarAttributeType::S) {
+        problems.push(format!(
+            "the '{PARTITION_KEY}' attribute must be a string, found {id_type:?}"
+        ));
+    }
+
+    problems
+}
+
+/// Describe the table and each of its indexes that isn't active yet, with its current status
+fn not_active(table: &TableDescription) -> Vec<String> {
+    let mut pending = vec![];
+
+    if table.table_status() != Some(&TableStatus::Active) {
+        pending.push(format!(
+            "the table ({})",
+            table.table_status().map_or("UNKNOWN", TableStatus::as_str)
+        ));
+    }
+
+    pending.extend(
+        table
+            .global_secondary_indexes()
+            .iter()
+            .filter(|index| index.index_status() != Some(&IndexStatus::Active))
+            .map(|index| {
+                format!(
+                    "the index '{}' ({})",
+                    index.index_name().unwrap_or_default(),
+                    index.index_status().map_or("UNKNOWN", IndexStatus::as_str)
+                )
+            }),
+    );
+
+    pending
+}
+
+/// The expected indexes that the table doesn't have
+fn missing_indexes(table: &TableDescription) -> Vec<Index> {
+    indexes()
+        .into_iter()
+        .filter(|index| {
+            !table
+                .global_secondary_indexes()
+                .iter()
+                .any(|existing| existing.index_name() == Some(index.name))
+        })
+        .collect()
+}
+
+fn mismatch(table_name: &str, problems: &[String]) -> anyhow::Error {
+    anyhow::anyhow!(
+        "The DynamoDB table '{}' doesn't match what the Tasks service expects: {}",
+        table_name,
+        problems.join("; ")
+    )
+}
+
+fn key_schema(
+    partition_key: &KeyAttribute,
+    sort_key: Option<&KeyAttribute>,
+) -> anyhow::Result<Vec<KeySchemaElement>> {
+    let mut key_schema = vec![KeySchemaElement::builder()
+        .attribute_name(partition_key.0)
+        .key_type(KeyType::Hash)
+        .build()?];
+
+    if let Some(sort_key) = sort_key {
+        key_schema.push(
+            KeySchemaElement::builder()
+                .attribute_name(sort_key.0)
+                .key_type(KeyType::Range)
+                .build()?,
+        );
+    }
+
+    Ok(key_schema)
+}
+
+/// Definitions for the table's partition key and every key attribute of the given indexes
+fn attribute_definitions(indexes: &[Index]) -> anyhow::Result<Vec<AttributeDefinition>> {
+    let mut attributes = vec![(PARTITION_KEY, ScalarAttributeType::S)];
+
+    for index in indexes {
+        attributes.push(index.partition_key.clone());
+        attributes.extend(index.sort_key.clone());
+    }
+
+    attributes.sort_by_key(|(name, _)| *name);
+    attributes.dedup_by_key(|(name, _)| *name);
+
+    attributes
+        .into_iter()
+        .map(|(name, attribute_type)| {
+            Ok(AttributeDefinition::builder()
+                .attribute_name(name)
+                .attribute_type(attribute_type)
+                .build()?)
+        })
+        .collect()
+}
+
+fn global_secondary_index(
+    config: &config::Dynamo,
+    index: &Index,
+) -> anyhow::Result<GlobalSecondaryIndex> {
+    Ok(GlobalSecondaryIndex::builder()
+        .index_name(index.name)
+        .set_key_schema(Some(key_schema(
+            &index.partition_key,
+            index.sort_key.as_ref(),
+        )?))
+        .projection(projection())
+        .set_provisioned_throughput(throughput(config.billing_mode)?)
+        .build()?)
+}
+
+fn projection() -> Projection {
+    Projection::builder()
+        .projection_type(ProjectionType::All)
+        .build()
+}
+
+fn billing_mode(billing_mode: BillingMode) -> DynamoBillingMode {
+    match billing_mode {
+        BillingMode::PayPerRequest => DynamoBillingMode::PayPerRequest,
+        BillingMode::Provisioned { .. } => DynamoBillingMode::Provisioned,
+    }
+}
+
+fn throughput(billing_mode: BillingMode) -> anyhow::Result<Option<ProvisionedThroughput>> {
+    match billing_mode {
+        BillingMode::PayPerRequest => Ok(None),
+        BillingMode::Provisioned {
+            read_capacity_units,
+            write_capacity_units,
+        } => Ok(Some(
+            ProvisionedThroughput::builder()
+                .read_capacity_units(read_capacity_units)
+                .write_capacity_units(write_capacity_units)
+                .build()?,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::types::GlobalSecondaryIndexDescription;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn table(key_schema: Vec<KeySchemaElement>, id_type: ScalarAttributeType) -> TableDescription {
+        TableDescription::builder()
+            .set_key_schema(Some(key_schema))
+            .attribute_definitions(
+                AttributeDefinition::builder()
+                    .attribute_name(PARTITION_KEY)
+                    .attribute_type(id_type)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_key_schema_problems() -> anyhow::Result<()> {
+        let expected = key_schema(&(PARTITION_KEY, ScalarAttributeType::S), None)?;
+
+        assert_eq!(
+            key_schema_problems(&table(expected.clone(), ScalarAttributeType::S)),
+            Vec::<String>::new()
+        );
+
+        assert_eq!(
+            key_schema_problems(&table(expected, ScalarAttributeType::N)),
+            vec!["the 'id' attribute must be a string, found Some(N)".to_string()]
+        );
+
+        let composite = key_schema(
+            &(PARTITION_KEY, ScalarAttributeType::S),
+            Some(&("created_at", ScalarAttributeType::S)),
+        )?;
+
+        assert_eq!(
+            key_schema_problems(&table(composite, ScalarAttributeType::S)),
+            vec![
+                "the primary key must be a single 'id' partition key, found [(\"id\", Hash), \
+                 (\"created_at\", Range)]"
+                    .to_string()
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_attribute_definitions() -> anyhow::Result<()> {
+        let index = Index {
+            name: "by-status",
+            partition_key: ("status", ScalarAttributeType::S),
+            sort_key: Some(("id", ScalarAttributeType::S)),
+        };
+
+        let names: Vec<String> = attribute_definitions(&[index])?
+            .iter()
+            .map(|definition| definition.attribute_name().to_string())
+            .collect();
+
+        assert_eq!(names, vec!["id", "status"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_active() {
+        let index = |name: &str, status: IndexStatus| {
+            GlobalSecondaryIndexDescription::builder()
+                .index_name(name)
+                .index_status(status)
+                .build()
+        };
+
+        let active = TableDescription::builder()
+            .table_status(TableStatus::Active)
+            .global_secondary_indexes(index(TAG_INDEX, IndexStatus::Active))
+            .build();
+
+        assert_eq!(not_active(&active), Vec::<String>::new());
+
+        let backfilling = TableDescription::builder()
+            .table_status(TableStatus::Updating)
+            .global_secondary_indexes(index(TAG_INDEX, IndexStatus::Active))
+            .global_secondary_indexes(index(JOB_INDEX, IndexStatus::Creating))
+            .build();
+
+        assert_eq!(
+            not_active(&backfilling),
+            vec![
+                "the table (UPDATING)".to_string(),
+                "the index 'job-index' (CREATING)".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use super::workflow::Status;
+
+/// Task errors that callers respond to differently, carried inside `anyhow::Error`
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The Task does not exist
+    #[error("Task not found")]
+    NotFound,
+
+    /// The workflow doesn't allow the Task to move to the requested status
+    #[error("A Task can't move from {from} to {to}")]
+    InvalidTransition {
+        /// The Task's current status
+        from: Status,
+
+        /// The requested status
+        to: Status,
+    },
+
+    /// The cursor wasn't returned for the requested order
+    #[error("Invalid cursor: {0}")]
+    InvalidCursor(String),
+
+    /// The parent given for a Task doesn't exist
+    #[error("Parent Task not found: {0}")]
+    ParentNotFound(String),
+
+    /// The Project given for a Task doesn't exist
+    #[error("Project not found: {0}")]
+    ProjectNotFound(String),
+
+    /// Moving the Task under the parent would make the Task its own ancestor
+    #[error(
+        "Task {id} can't be moved under {parent_id}, which is the Task or one of its subtasks"
+    )]
+    Cycle {
+        /// The Task being moved
+        id: String,
+
+        /// The requested parent
+        parent_id: String,
+    },
+
+    /// The Tag name is empty or too long
+    #[error("Invalid tag '{0}', tags must be 1 to 64 characters")]
+    InvalidTag(String),
+
+    /// The id given for a Task already belongs to another kind of item, such as a Project
+    #[error("The id {0} already belongs to an item that isn't a Task")]
+    IdConflict(String),
+}
+
+impl Error {
+    /// Check whether the error is, or wraps, a `NotFound` error
+    pub fn is_not_found(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::NotFound))
+    }
+
+    /// Check whether the error is, or wraps, an `InvalidCursor` error
+    pub fn is_invalid_cursor(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::InvalidCursor(_)))
+    }
+
+    /// Check whether the error is, or wraps, a `ParentNotFound` error
+    pub fn is_parent_not_found(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::ParentNotFound(_)))
+    }
+
+    /// Check whether the error is, or wraps, a `ProjectNotFound` error
+    pub fn is_project_not_found(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::ProjectNotFound(_)))
+    }
+
+    /// Check whether the error is, or wraps, a `Cycle` error
+    pub fn is_cycle(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::Cycle { .. }))
+    }
+
+    /// Check whether the error is, or wraps, an `InvalidTag` error
+    pub fn is_invalid_tag(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::InvalidTag(_)))
+    }
+
+    /// Check whether the error is, or wraps, an `IdConflict` error
+    pub fn is_id_conflict(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<Error>(), Some(Error::IdConflict(_)))
+    }
+
+    /// Check whether the error is, or wraps, an `InvalidTransition` error
+    pub fn is_invalid_transition(err: &anyhow::Error) -> bool {
+        matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::InvalidTransition { .. })
+        )
+    }
+}
@@ -0,0 +1,700 @@
+use std::sync::Arc;
+
+use chrono::DateTime;
+use derive_new::new;
+use prost_types::{FieldMask, Timestamp};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use tonic::{Request, Response, Status};
+
+use crate::{
+    attachments::storage::Storage,
+    comments::{self, model::Comment},
+    dependencies, projects,
+    utils::Update,
+};
+
+use super::{
+    error::Error,
+    inputs::{self, Order},
+    model::Task,
+    store::{Store, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+    tree::Tree,
+    workflow::{self, Workflow},
+};
+
+/// Generated types and service traits for the `tasks.v1` protobuf package
+#[allow(missing_docs, clippy::all)]
+pub mod proto {
+    tonic::include_proto!("tasks.v1");
+}
+
+use proto::task_service_server::{TaskService, TaskServiceServer};
+
+/// The gRPC `TaskService`, backed by the configured Task store
+#[derive(Clone, Debug, new)]
+pub struct TasksService {
+    store: Store,
+    workflow: Workflow,
+    storage: Arc<dyn Storage>,
+}
+
+impl TasksService {
+    /// Wrap the service in a tonic server
+    pub fn into_server(self) -> TaskServiceServer<Self> {
+        TaskServiceServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl TaskService for TasksService {
+    async fn get_task(
+        &self,
+        request: Request<proto::GetTaskRequest>,
+    ) -> Result<Response<proto::Task>, Status> {
+        let request = request.into_inner();
+
+        let task = self
+            .store
+            .get(&request.id)
+            .await
+            .map_err(to_status)?
+            .ok_or_else(|| Status::not_found("Task not found"))?;
+
+        Ok(Response::new(task.into()))
+    }
+
+    async fn list_tasks(
+        &self,
+        request: Request<proto::ListTasksRequest>,
+    ) -> Result<Response<proto::ListTasksResponse>, Status> {
+        let request = request.into_inner();
+
+        let limit = match usize::try_from(request.page_size) {
+            Ok(0) | Err(_) => DEFAULT_PAGE_SIZE,
+            Ok(page_size) => page_size.min(MAX_PAGE_SIZE),
+        };
+
+        let order = match proto::TaskOrder::try_from(request.order) {
+            Ok(proto::TaskOrder::Unspecified | proto::TaskOrder::Id) => Order::Id,
+            Ok(proto::TaskOrder::Priority) => Order::Priority,
+            Err(_) => return Err(Status::invalid_argument("Unknown order")),
+        };
+
+        let input = inputs::List {
+            after: Some(request.page_token).filter(|token| !token.is_empty()),
+            limit: Some(limit),
+            overdue: request.overdue,
+            due_within_days: request.due_within_days,
+            order: Some(order),
+        };
+
+        let page = self.store.page(&input).await.map_err(to_status)?;
+
+        Ok(Response::new(proto::ListTasksResponse {
+            tasks: page.tasks.into_iter().map(Into::into).collect(),
+            next_page_token: page.next_cursor.unwrap_or_default(),
+        }))
+    }
+
+    async fn create_task(
+        &self,
+        request: Request<proto::CreateTaskRequest>,
+    ) -> Result<Response<proto::Task>, Status> {
+        let request = request.into_inner();
+
+        let input = inputs::Create {
+            title: request.title,
+            description: request.description,
+            due_at: request.due_at.map(from_timestamp).transpose()?,
+            priority: request.priority,
+            parent_id: request.parent_id,
+            project_id: request.project_id,
+        };
+
+        let task = self.store.create(&input).await.map_err(to_status)?;
+
+        Ok(Response::new(task.into()))
+    }
+
+    async fn update_task(
+        &self,
+        request: Request<proto::UpdateTaskRequest>,
+    ) -> Result<Response<proto::Task>, Status> {
+        let request = request.into_inner();
+
+        let input = masked_update(request.task.unwrap_or_default(), request.update_mask)?;
+
+        let task = self
+            .store
+            .update(&request.id, &input)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(task.into()))
+    }
+
+    async fn delete_task(
+        &self,
+        request: Request<proto::DeleteTaskRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        self.store
+            .delete(self.storage.as_ref(), &request.id)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(()))
+    }
+
+    async fn transition_task(
+        &self,
+        request: Request<proto::TransitionTaskRequest>,
+    ) -> Result<Response<proto::Task>, Status> {
+        let request = request.into_inner();
+
+        let status = match proto::TaskStatus::try_from(request.status) {
+            Ok(proto::TaskStatus::Todo) => workflow::Status::Todo,
+            Ok(proto::TaskStatus::InProgress) => workflow::Status::InProgress,
+            Ok(proto::TaskStatus::Blocked) => workflow::Status::Blocked,
+            Ok(proto::TaskStatus::Done) => workflow::Status::Done,
+            Ok(proto::TaskStatus::Cancelled) => workflow::Status::Cancelled,
+            Ok(proto::TaskStatus::Unspecified) | Err(_) => {
+                return Err(Status::invalid_argument("A status is required"))
+            }
+        };
+
+        let task = self
+            .store
+            .transition(&self.workflow, &request.id, status)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(task.into()))
+    }
+
+    async fn list_task_tags(
+        &self,
+        request: Request<proto::ListTaskTagsRequest>,
+    ) -> Result<Response<proto::TaskTags>, Status> {
+        let request = request.into_inner();
+
+        let tags = self.store.tags(&request.id).await.map_err(to_status)?;
+
+        Ok(Response::new(proto::TaskTags { tags }))
+    }
+
+    async fn add_task_tag(
+        &self,
+        request: Request<proto::TaskTagRequest>,
+    ) -> Result<Response<proto::TaskTags>, Status> {
+        let request = request.into_inner();
+
+        let tags = self
+            .store
+            .add_tag(&request.id, &request.name)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(proto::TaskTags { tags }))
+    }
+
+    async fn remove_task_tag(
+        &self,
+        request: Request<proto::TaskTagRequest>,
+    ) -> Result<Response<proto::TaskTags>, Status> {
+        let request = request.into_inner();
+
+        let tags = self
+            .store
+            .remove_tag(&request.id, &request.name)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(proto::TaskTags { tags }))
+    }
+
+    async fn list_tags(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::ListTagsResponse>, Status> {
+        let counts = self.store.tag_counts().await.map_err(to_status)?;
+
+        Ok(Response::new(proto::ListTagsResponse {
+            tags: counts
+                .into_iter()
+                .map(|count| proto::TagCount {
+                    name: count.name,
+                    tasks: count.tasks,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn list_tasks_by_tag(
+        &self,
+        request: Request<proto::ListTasksByTagRequest>,
+    ) -> Result<Response<proto::ListTasksResponse>, Status> {
+        let request = request.into_inner();
+
+        let input = inputs::ListByTag {
+            after: Some(request.page_token).filter(|token| !token.is_empty()),
+            limit: usize::try_from(request.page_size).ok(),
+        };
+
+        let page = self
+            .store
+            .page_by_tag(&request.name, &input)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(proto::ListTasksResponse {
+            tasks: page.tasks.into_iter().map(Into::into).collect(),
+            next_page_token: page.next_cursor.unwrap_or_default(),
+        }))
+    }
+
+    async fn list_child_tasks(
+        &self,
+        request: Request<proto::ListChildTasksRequest>,
+    ) -> Result<Response<proto::ListChildTasksResponse>, Status> {
+        let request = request.into_inner();
+
+        let tasks = self.store.children(&request.id).await.map_err(to_status)?;
+
+        Ok(Response::new(proto::ListChildTasksResponse {
+            tasks: tasks.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_task_tree(
+        &self,
+        request: Request<proto::GetTaskTreeRequest>,
+    ) -> Result<Response<proto::TaskTree>, Status> {
+        let request = request.into_inner();
+
+        let tree = self.store.subtree(&request.id).await.map_err(to_status)?;
+
+        Ok(Response::new(tree.into()))
+    }
+
+    async fn move_task(
+        &self,
+        request: Request<proto::MoveTaskRequest>,
+    ) -> Result<Response<proto::Task>, Status> {
+        let request = request.into_inner();
+
+        let input = inputs::Move {
+            parent_id: request.parent_id,
+        };
+
+        let task = self
+            .store
+            .move_task(&request.id, &input)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(task.into()))
+    }
+
+    async fn list_comments(
+        &self,
+        request: Request<proto::ListCommentsRequest>,
+    ) -> Result<Response<proto::ListCommentsResponse>, Status> {
+        let request = request.into_inner();
+
+        let input = comments::inputs::List {
+            after: Some(request.page_token).filter(|token| !token.is_empty()),
+            limit: usize::try_from(request.page_size).ok(),
+        };
+
+        let page = self
+            .store
+            .page_comments(&request.task_id, &input)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(proto::ListCommentsResponse {
+            comments: page.comments.into_iter().map(Into::into).collect(),
+            next_page_token: page.next_cursor.unwrap_or_default(),
+        }))
+    }
+
+    async fn add_comment(
+        &self,
+        request: Request<proto::AddCommentRequest>,
+    ) -> Result<Response<proto::Comment>, Status> {
+        let request = request.into_inner();
+
+        let input = comments::inputs::Create {
+            author: request.author,
+            body: request.body,
+        };
+
+        let comment = self
+            .store
+            .create_comment(&request.task_id, &input)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(comment.into()))
+    }
+
+    async fn update_comment(
+        &self,
+        request: Request<proto::UpdateCommentRequest>,
+    ) -> Result<Response<proto::Comment>, Status> {
+        let request = request.into_inner();
+
+        let input = comments::inputs::Update { body: request.body };
+
+        let comment = self
+            .store
+            .update_comment(&request.task_id, &request.id, &input)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(comment.into()))
+    }
+
+    async fn delete_comment(
+        &self,
+        request: Request<proto::DeleteCommentRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+
+        self.store
+            .delete_comment(&request.task_id, &request.id)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(()))
+    }
+}
+
+impl From<Comment> for proto::Comment {
+    fn from(comment: Comment) -> Self {
+        Self {
+            id: comment.id,
+            task_id: comment.task_id,
+            created_at: Some(timestamp(comment.created_at)),
+            updated_at: Some(timestamp(comment.updated_at)),
+            author: comment.author,
+            body: comment.body,
+        }
+    }
+}
+
+impl From<Task> for proto::Task {
+    fn from(task: Task) -> Self {
+        Self {
+            id: task.id,
+            created_at: Some(timestamp(task.created_at)),
+            updated_at: Some(timestamp(task.updated_at)),
+            title: task.title,
+            description: task.description,
+            status: proto::TaskStatus::from(task.status).into(),
+            due_at: task.due_at.map(timestamp),
+            priority: task.priority,
+            parent_id: task.parent_id,
+            project_id: task.project_id,
+        }
+    }
+}
+
+impl From<Tree> for proto::TaskTree {
+    fn from(tree: Tree) -> Self {
+        Self {
+            task: Some(tree.task.into()),
+            progress: Some(proto::TaskProgress {
+                done: tree.progress.done,
+                total: tree.progress.total,
+            }),
+            children: tree.children.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<workflow::Status> for proto::TaskStatus {
+    fn from(status: workflow::Status) -> Self {
+        match status {
+            workflow::Status::Todo => Self::Todo,
+            workflow::Status::InProgress => Self::InProgress,
+            workflow::Status::Blocked => Self::Blocked,
+            workflow::Status::Done => Self::Done,
+            workflow::Status::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
+/// Convert a `TaskUpdate` and its field mask into an update input. Fields named in the mask are
+/// set to their value, or cleared if unset, and fields left out of the mask are unchanged. An
+/// empty mask updates every field that is set.
+#[allow(clippy::result_large_err)]
+fn masked_update(
+    task: proto::TaskUpdate,
+    mask: Option<FieldMask>,
+) -> Result<inputs::Update, Status> {
+    let paths = match mask {
+        Some(mask) if !mask.paths.is_empty() => mask.paths,
+        _ => [
+            ("title", task.title.is_some()),
+            ("description", task.description.is_some()),
+            ("due_at", task.due_at.is_some()),
+            ("priority", task.priority.is_some()),
+            ("project_id", task.project_id.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, is_set)| *is_set)
+        .map(|(path, _)| path.to_string())
+        .collect(),
+    };
+
+    let mut input = inputs::Update::default();
+
+    for path in paths {
+        match path.as_str() {
+            "title" => input.title = Some(task.title.clone()).into(),
+            "description" => input.description = Some(task.description.clone()).into(),
+            "due_at" => input.due_at = Some(task.due_at.map(from_timestamp).transpose()?).into(),
+            "priority" => input.priority = Some(task.priority).into(),
+            "project_id" => input.project_id = Some(task.project_id.clone()).into(),
+            _ => {
+                return Err(Status::invalid_argument(format!(
+                    "Unknown update_mask path: {path}"
+                )))
+            }
+        }
+    }
+
+    if input.title == Update::Empty {
+        return Err(Status::invalid_argument("A Task title cannot be cleared"));
+    }
+
+    Ok(input)
+}
+
+fn timestamp(value: DateTimeWithTimeZone) -> Timestamp {
+    Timestamp {
+        seconds: value.timestamp(),
+        nanos: value.timestamp_subsec_nanos() as i32,
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn from_timestamp(value: Timestamp) -> Result<DateTimeWithTimeZone, Status> {
+    DateTime::from_timestamp(value.seconds, value.nanos.try_into().unwrap_or(u32::MAX))
+        .map(|timestamp| timestamp.fixed_offset())
+        .ok_or_else(|| Status::invalid_argument("Timestamp out of range"))
+}
+
+fn to_status(err: anyhow::Error) -> Status {
+    if Error::is_not_found(&err)
+        || projects::error::Error::is_not_found(&err)
+        || comments::error::Error::is_not_found(&err)
+    {
+        Status::not_found(err.to_string())
+    } else if Error::is_invalid_transition(&err)
+        || Error::is_cycle(&err)
+        || dependencies::error::Error::is_open_blockers(&err)
+        || projects::error::Error::is_archived(&err)
+    {
+        Status::failed_precondition(err.to_string())
+    } else if Error::is_invalid_cursor(&err)
+        || Error::is_invalid_tag(&err)
+        || Error::is_parent_not_found(&err)
+        || Error::is_project_not_found(&err)
+        || comments::error::Error::is_invalid(&err)
+    {
+        Status::invalid_argument(err.to_string())
+    } else {
+        Status::internal(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+
+    use crate::attachments::{
+        model::Attachment,
+        storage::{self, LocalStorage},
+    };
+
+    use super::*;
+
+    fn unused_storage() -> Arc<dyn Storage> {
+        Arc::new(LocalStorage::new(
+            std::env::temp_dir().join("unused-attachments"),
+        ))
+    }
+
+    fn mask(paths: &[&str]) -> Option<FieldMask> {
+        Some(FieldMask {
+            paths: paths.iter().map(ToString::to_string).collect(),
+        })
+    }
+
+    #[test]
+    fn test_masked_update() {
+        let task = proto::TaskUpdate {
+            title: Some("Title".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            masked_update(task.clone(), mask(&["description"])).unwrap(),
+            inputs::Update {
+                description: Update::Empty,
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(
+            masked_update(task.clone(), None).unwrap(),
+            inputs::Update {
+                title: Update::Value("Title".to_string()),
+                ..Default::default()
+            }
+        );
+
+        assert!(masked_update(task, mask(&["unknown"])).is_err());
+    }
+
+    #[test]
+    fn test_masked_update_title_cannot_be_cleared() {
+        let result = masked_update(proto::TaskUpdate::default(), mask(&["title"]));
+
+        assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_get_task() -> anyhow::Result<()> {
+        let task: Task = Faker.fake();
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![task.clone()], vec![]])
+            .into_connection();
+
+        let service = TasksService::new(
+            Store::Database(Arc::new(db)),
+            Workflow::default(),
+            unused_storage(),
+        );
+
+        let response = service
+            .get_task(Request::new(proto::GetTaskRequest {
+                id: task.id.clone(),
+            }))
+            .await?;
+
+        assert_eq!(response.into_inner(), task.clone().into());
+
+        let result = service
+            .get_task(Request::new(proto::GetTaskRequest { id: task.id }))
+            .await;
+
+        assert_eq!(result.unwrap_err().code(), tonic::Code::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_task_purges_attachments() -> anyhow::Result<()> {
+        let task = Task {
+            id: "01HX".to_string(),
+            ..Faker.fake()
+        };
+        let attachment = Attachment {
+            id: "01HY".to_string(),
+            task_id: task.id.clone(),
+            ..Default::default()
+        };
+
+        let root = tempfile::tempdir()?;
+        let storage = Arc::new(LocalStorage::new(root.path().to_path_buf()));
+        let key = storage::key(&task.id, &attachment.id);
+
+        storage.put(&key, "text/plain", "hello".into()).await?;
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![task.clone()]])
+            .append_query_results([vec![attachment]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            .into_connection();
+
+        let service = TasksService::new(
+            Store::Database(Arc::new(db)),
+            Workflow::default(),
+            storage.clone(),
+        );
+
+        service
+            .delete_task(Request::new(proto::DeleteTaskRequest { id: task.id }))
+            .await?;
+
+        assert!(storage.get(&key).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_missing_task() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<Task>::new()])
+            .into_connection();
+
+        let service = TasksService::new(
+            Store::Database(Arc::new(db)),
+            Workflow::default(),
+            unused_storage(),
+        );
+
+        let result = service
+            .update_task(Request::new(proto::UpdateTaskRequest {
+                id: "missing".to_string(),
+                task: Some(proto::TaskUpdate {
+                    title: Some("Title".to_string()),
+                    ..Default::default()
+                }),
+                update_mask: None,
+            }))
+            .await;
+
+        assert_eq!(result.unwrap_err().code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_transition_not_allowed() {
+        let task = Task {
+            status: workflow::Status::Cancelled,
+            ..Faker.fake()
+        };
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![], vec![task.clone()]])
+            .into_connection();
+
+        let service = TasksService::new(
+            Store::Database(Arc::new(db)),
+            Workflow::default(),
+            unused_storage(),
+        );
+
+        let result = service
+            .transition_task(Request::new(proto::TransitionTaskRequest {
+                id: task.id,
+                status: proto::TaskStatus::Done.into(),
+            }))
+            .await;
+
+        assert_eq!(result.unwrap_err().code(), tonic::Code::FailedPrecondition);
+    }
+}
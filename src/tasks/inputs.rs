@@ -1,31 +1,205 @@
-use crate::utils;
+use std::str::FromStr;
 
+use async_graphql::{Enum, InputObject};
 #[cfg(test)]
 use fake::{Dummy, Faker, Rng};
-use serde::Deserialize;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::utils;
+
+use super::{
+    error::Error,
+    model::Task,
+    store::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+    workflow::Status,
+};
 
 #[cfg(test)]
 use crate::utils::update::dummy_update;
 
 /// The `CreateInput` input type
-#[derive(Clone, Default, Eq, PartialEq, Deserialize)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, InputObject, ToSchema)]
 #[cfg_attr(test, derive(Dummy))]
+#[graphql(name = "CreateTaskInput")]
+#[schema(as = CreateTaskInput)]
 pub struct Create {
     /// The Task's title
     pub title: String,
 
     /// The Task's description
     pub description: Option<String>,
+
+    /// When the Task is due
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub due_at: Option<DateTimeWithTimeZone>,
+
+    /// The Task's priority, where higher numbers are more urgent
+    pub priority: Option<i32>,
+
+    /// The Task to create this as a subtask of
+    pub parent_id: Option<String>,
+
+    /// The Project to create the Task in
+    pub project_id: Option<String>,
 }
 
-/// The `UpdateInput` input type
-#[derive(Clone, Default, Eq, PartialEq, Deserialize)]
+/// The `UpdateInput` input type. An omitted field is left unchanged, an explicit `null` clears it,
+/// and any other value replaces it.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default)]
+#[schema(as = UpdateTaskInput)]
 pub struct Update {
-    /// The Task's title
+    /// The Task's title, which cannot be cleared
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
     pub title: utils::Update<String>,
 
     /// The Task's description
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
     pub description: utils::Update<String>,
+
+    /// When the Task is due
+    #[schema(value_type = Option<String>, format = DateTime)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub due_at: utils::Update<DateTimeWithTimeZone>,
+
+    /// The Task's priority, where higher numbers are more urgent
+    #[schema(value_type = Option<i32>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub priority: utils::Update<i32>,
+
+    /// The Project the Task belongs to
+    #[schema(value_type = Option<String>)]
+    #[serde(skip_serializing_if = "utils::Update::is_unchanged")]
+    pub project_id: utils::Update<String>,
+}
+
+/// The `TransitionInput` input type
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[schema(as = TransitionTaskInput)]
+pub struct Transition {
+    /// The status to move the Task to
+    pub status: Status,
+}
+
+/// The `MoveInput` input type
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[schema(as = MoveTaskInput)]
+pub struct Move {
+    /// The Task to move the Task under, or `null` to move it to the top level
+    pub parent_id: Option<String>,
+}
+
+/// The `ListInput` query parameters for a page of Tasks
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct List {
+    /// The cursor returned with the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+
+    /// The maximum number of Tasks to return, defaults to 20 and is capped at 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+
+    /// Only include Tasks past their due date that aren't done or cancelled
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub overdue: bool,
+
+    /// Only include Tasks due between now and this many days from now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_within_days: Option<u32>,
+
+    /// The order to list Tasks in, defaults to `id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[param(inline)]
+    pub order: Option<Order>,
+}
+
+/// The order to list Tasks in
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize, Enum, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[graphql(name = "TaskOrder")]
+pub enum Order {
+    /// By id, which for ULIDs is roughly the order Tasks were created in
+    #[default]
+    Id,
+
+    /// Most urgent first, leaving out Tasks without a priority
+    Priority,
+}
+
+impl Order {
+    /// The cursor to resume listing after the given Task
+    pub fn cursor(&self, task: &Task) -> String {
+        match self {
+            Order::Id => task.id.clone(),
+            Order::Priority => format!("{}:{}", task.priority.unwrap_or_default(), task.id),
+        }
+    }
+
+    /// Split a cursor returned when ordering by priority into the priority and id
+    pub fn priority_cursor(cursor: &str) -> Result<(i32, &str), Error> {
+        cursor
+            .split_once(':')
+            .and_then(|(priority, id)| Some((priority.parse().ok()?, id)))
+            .ok_or_else(|| Error::InvalidCursor(cursor.to_string()))
+    }
+}
+
+impl FromStr for Order {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "id" => Ok(Order::Id),
+            "priority" => Ok(Order::Priority),
+            _ => Err(anyhow::anyhow!("Invalid order: {}", value)),
+        }
+    }
+}
+
+impl List {
+    /// The requested order, or the default
+    pub fn order(&self) -> Order {
+        self.order.unwrap_or_default()
+    }
+
+    /// The requested page size, with the default and maximum applied
+    pub fn page_size(&self) -> usize {
+        page_size(self.limit)
+    }
+}
+
+/// The `ListByTagInput` query parameters for a page of the Tasks with a Tag, in id order
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListByTag {
+    /// The cursor returned with the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+
+    /// The maximum number of Tasks to return, defaults to 20 and is capped at 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl ListByTag {
+    /// The requested page size, with the default and maximum applied
+    pub fn page_size(&self) -> usize {
+        page_size(self.limit)
+    }
+}
+
+/// The page size for a requested limit, with the default and maximum applied
+pub(crate) fn page_size(limit: Option<usize>) -> usize {
+    match limit {
+        Some(0) | None => DEFAULT_PAGE_SIZE,
+        Some(limit) => limit.min(MAX_PAGE_SIZE),
+    }
 }
 
 #[cfg(test)]
@@ -34,6 +208,69 @@ impl Dummy<Faker> for Update {
         Update {
             title: dummy_update(config, rng),
             description: dummy_update(config, rng),
+            due_at: dummy_update(config, rng),
+            priority: dummy_update(config, rng),
+            project_id: dummy_update(config, rng),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_update_omitted_and_null_fields() -> anyhow::Result<()> {
+        let input: Update = serde_json::from_str(r#"{"description": null}"#)?;
+
+        assert_eq!(
+            input,
+            Update {
+                title: utils::Update::Unchanged,
+                description: utils::Update::Empty,
+                ..Default::default()
+            }
+        );
+
+        let input: Update = serde_json::from_str(r#"{"title": "Title"}"#)?;
+
+        assert_eq!(
+            input,
+            Update {
+                title: utils::Update::Value("Title".to_string()),
+                ..Default::default()
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_omits_unchanged_fields() -> anyhow::Result<()> {
+        let input = Update {
+            description: utils::Update::Empty,
+            ..Default::default()
+        };
+
+        assert_eq!(serde_json::to_string(&input)?, r#"{"description":null}"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_priority_cursor() {
+        let task = Task {
+            id: "01HX".to_string(),
+            priority: Some(3),
+            ..Default::default()
+        };
+
+        let cursor = Order::Priority.cursor(&task);
+
+        assert_eq!(cursor, "3:01HX");
+        assert_eq!(Order::priority_cursor(&cursor).unwrap(), (3, "01HX"));
+        assert!(Order::priority_cursor("01HX").is_err());
+    }
+}
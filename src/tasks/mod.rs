@@ -1,11 +1,38 @@
 /// The Task Model
 pub mod model;
 
+/// The Tag Model
+pub mod tag;
+
+/// The join Model between Tasks and Tags
+pub mod task_tag;
+
 /// The Task entity general-purpose service
 pub mod service;
 
 /// The Task entity DynamoDB service
 pub mod dynamo_service;
 
+/// The Task DynamoDB table definition and provisioning
+pub mod dynamo_table;
+
+/// The Task error types
+pub mod error;
+
+/// The Task status workflow
+pub mod workflow;
+
+/// Task hierarchies and their completion
+pub mod tree;
+
 /// The Task entity input types
 pub mod inputs;
+
+/// The Task store for the configured `DataStore`
+pub mod store;
+
+/// The Task GraphQL resolvers
+pub mod resolvers;
+
+/// The Task gRPC service
+pub mod grpc;
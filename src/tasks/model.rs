@@ -1,28 +1,40 @@
-use std::{collections::HashMap, convert::TryFrom};
-
-use anyhow::anyhow;
-use aws_sdk_dynamodb::types::AttributeValue;
+use async_graphql::SimpleObject;
 use chrono::Utc;
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, Set};
 use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use utoipa::ToSchema;
+
+use crate::utils::{
+    dynamo::DynamoItem,
+    timezone::{Timezone, WithTimestamps},
+};
+
+use super::workflow::Status;
 
 #[cfg(test)]
 use fake::Dummy;
 
 /// The Task  Model
-#[derive(Clone, Debug, Eq, PartialEq, DeriveEntityModel, Deserialize, Serialize)]
+#[derive(
+    Clone, Debug, Eq, PartialEq, DeriveEntityModel, Deserialize, Serialize, SimpleObject, ToSchema,
+)]
 #[cfg_attr(test, derive(Dummy))]
 #[sea_orm(table_name = "tasks")]
+#[graphql(name = "Task", complex)]
+#[schema(as = Task)]
 pub struct Model {
     /// The Task id
     #[sea_orm(primary_key, column_type = "Text")]
     pub id: String,
 
     /// The date the Task was created
-    pub created_at: DateTime,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTimeWithTimeZone,
 
     /// The date the Task was last updated
-    pub updated_at: DateTime,
+    #[schema(value_type = String, format = DateTime)]
+    pub updated_at: DateTimeWithTimeZone,
 
     /// The Task title
     #[sea_orm(column_type = "Text")]
@@ -31,6 +43,25 @@ pub struct Model {
     /// An optional Task description
     #[sea_orm(column_type = "Text", nullable)]
     pub description: Option<String>,
+
+    /// The Task's status, which only changes through the workflow's transitions
+    #[serde(default)]
+    pub status: Status,
+
+    /// When the Task is due
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub due_at: Option<DateTimeWithTimeZone>,
+
+    /// The Task's priority, where higher numbers are more urgent
+    pub priority: Option<i32>,
+
+    /// The Task this is a subtask of, if any
+    #[sea_orm(column_type = "Text", nullable)]
+    pub parent_id: Option<String>,
+
+    /// The Project the Task belongs to, if any
+    #[sea_orm(column_type = "Text", nullable)]
+    pub project_id: Option<String>,
 }
 
 /// The name for a Sea ORM model must be "Model", so this provides a convenient alias
@@ -38,73 +69,206 @@ pub type Task = Model;
 
 /// Show entity relationships
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    /// The join rows linking the Task to its Tags
+    #[sea_orm(has_many = "super::task_tag::Entity")]
+    TaskTags,
+
+    /// The Task this is a subtask of
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::ParentId",
+        to = "Column::Id",
+        on_delete = "SetNull"
+    )]
+    Parent,
+
+    /// The Project the Task belongs to
+    #[sea_orm(
+        belongs_to = "crate::projects::model::Entity",
+        from = "Column::ProjectId",
+        to = "crate::projects::model::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Project,
+
+    /// The Comments on the Task
+    #[sea_orm(has_many = "crate::comments::model::Entity")]
+    Comments,
+
+    /// The files attached to the Task
+    #[sea_orm(has_many = "crate::attachments::model::Entity")]
+    Attachments,
+}
+
+impl Related<crate::projects::model::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
 
-impl ActiveModelBehavior for ActiveModel {}
+impl Related<crate::comments::model::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Comments.def()
+    }
+}
+
+impl Related<crate::attachments::model::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Attachments.def()
+    }
+}
+
+impl Related<super::task_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaskTags.def()
+    }
+}
+
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::task_tag::Relation::Tag.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::task_tag::Relation::Task.def().rev())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    /// Generate a ULID, timestamps and the initial status for new Tasks, and bump `updated_at` on
+    /// every update
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let now = Utc::now().fixed_offset();
+
+        if insert {
+            if self.id.is_not_set() {
+                self.id = Set(Ulid::new().to_string());
+            }
+
+            if self.created_at.is_not_set() {
+                self.created_at = Set(now);
+            }
+
+            if self.updated_at.is_not_set() {
+                self.updated_at = Set(now);
+            }
+
+            if self.status.is_not_set() {
+                self.status = Set(Status::default());
+            }
+        } else {
+            self.updated_at = Set(now);
+        }
+
+        Ok(self)
+    }
+}
+
+impl WithTimestamps for Model {
+    /// Render the timestamps in the given timezone
+    fn in_timezone(self, timezone: &Timezone) -> Self {
+        Self {
+            created_at: timezone.convert(self.created_at),
+            updated_at: timezone.convert(self.updated_at),
+            due_at: self.due_at.map(|due_at| timezone.convert(due_at)),
+            ..self
+        }
+    }
+}
 
 impl Default for Model {
     fn default() -> Self {
         Self {
             id: String::default(),
-            created_at: Utc::now().naive_utc(),
-            updated_at: Utc::now().naive_utc(),
+            created_at: Utc::now().fixed_offset(),
+            updated_at: Utc::now().fixed_offset(),
             title: String::default(),
             description: Option::default(),
+            status: Status::default(),
+            due_at: None,
+            priority: None,
+            parent_id: None,
+            project_id: None,
         }
     }
 }
 
-impl TryFrom<HashMap<String, AttributeValue>> for Model {
-    type Error = anyhow::Error;
-
-    fn try_from(item: HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
-        let id = item
-            .get("id")
-            .ok_or(anyhow!("Unable to find id property"))?
-            .as_s()
-            .map_err(|_err| anyhow!("Unable to convert id to string"))?
-            .to_string();
-
-        let created_at = item
-            .get("created_at")
-            .ok_or(anyhow!("Unable to find created_at property"))?
-            .as_s()
-            .map_err(|_err| anyhow!("Unable to convert created_at to String"))?
-            .to_string()
-            .parse()
-            .map_err(|_err| anyhow!("Unable to parse created_at to NaiveDateTime"))?;
-
-        let updated_at = item
-            .get("updated_at")
-            .ok_or(anyhow!("Unable to find updated_at property"))?
-            .as_s()
-            .map_err(|_err| anyhow!("Unable to convert updated_at to String"))?
-            .to_string()
-            .parse()
-            .map_err(|_err| anyhow!("Unable to parse updated_at to NaiveDateTime"))?;
-
-        let title = item
-            .get("title")
-            .ok_or(anyhow!("Unable to find title property"))?
-            .as_s()
-            .map_err(|_err| anyhow!("Unable to convert title to string"))?
-            .to_string();
-
-        let description = if let Some(desc) = item.get("description").map(|v| v.as_s()) {
-            Some(
-                desc.map_err(|_err| anyhow!("Unable to parse description to String"))?
-                    .to_string(),
-            )
-        } else {
-            None
-        };
-
-        Ok(Self {
-            id,
-            created_at,
-            updated_at,
-            title,
-            description,
-        })
+impl DynamoItem for Model {
+    const TIMESTAMPS: &'static [&'static str] = &["created_at", "updated_at", "due_at"];
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::SubsecRound;
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    use crate::{config::TimestampFormat, utils::dynamo};
+
+    use super::*;
+
+    #[test]
+    fn test_item_round_trip() -> anyhow::Result<()> {
+        let task: Task = Faker.fake();
+
+        // Both formats keep timestamps to the millisecond
+        let mut expected = task.clone();
+
+        expected.created_at = expected.created_at.trunc_subsecs(3);
+        expected.updated_at = expected.updated_at.trunc_subsecs(3);
+        expected.due_at = expected.due_at.map(|due_at| due_at.trunc_subsecs(3));
+
+        for format in [TimestampFormat::Rfc3339, TimestampFormat::EpochMillis] {
+            assert_eq!(
+                dynamo::from_item::<Task>(dynamo::to_item(&task, format)?)?,
+                expected
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_before_save_insert() -> anyhow::Result<()> {
+        let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+        let task = ActiveModel {
+            title: Set("Title".to_string()),
+            ..Default::default()
+        }
+        .before_save(&db, true)
+        .await?;
+
+        let id = task.id.unwrap();
+
+        assert!(Ulid::from_string(&id).is_ok(), "{id} is not a ULID");
+        assert_eq!(task.created_at.unwrap(), task.updated_at.unwrap());
+        assert_eq!(task.status.unwrap(), Status::Todo);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_before_save_update() -> anyhow::Result<()> {
+        let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+        let existing: Task = Faker.fake();
+
+        let task = ActiveModel::from(existing.clone())
+            .before_save(&db, false)
+            .await?;
+
+        assert_eq!(task.id.unwrap(), existing.id);
+        assert_eq!(task.created_at.unwrap(), existing.created_at);
+        assert!(task.updated_at.is_set());
+        assert_ne!(task.updated_at.unwrap(), existing.updated_at);
+
+        Ok(())
     }
 }
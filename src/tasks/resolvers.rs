@@ -0,0 +1,389 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    connection::{query, Connection, Edge},
+    ComplexObject, Context, Error, InputObject, MaybeUndefined, Object, Result,
+};
+
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+use crate::{
+    attachments::{model::Attachment, storage::Storage},
+    comments::{self, model::Comment},
+    projects::model::Project,
+};
+
+use super::{
+    inputs::{self, Order},
+    model::Task,
+    store::{Store, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+    tag::TagCount,
+    tree::Tree,
+    workflow::{Status, Workflow},
+};
+
+/// The `UpdateTaskInput` input type, where an omitted field is left unchanged and an explicit
+/// `null` clears it
+#[derive(Clone, Default, Eq, PartialEq, InputObject)]
+pub struct UpdateTaskInput {
+    /// The Task's title
+    pub title: MaybeUndefined<String>,
+
+    /// The Task's description
+    pub description: MaybeUndefined<String>,
+
+    /// When the Task is due
+    pub due_at: MaybeUndefined<DateTimeWithTimeZone>,
+
+    /// The Task's priority, where higher numbers are more urgent
+    pub priority: MaybeUndefined<i32>,
+
+    /// The Project the Task belongs to
+    pub project_id: MaybeUndefined<String>,
+}
+
+impl From<UpdateTaskInput> for inputs::Update {
+    fn from(input: UpdateTaskInput) -> Self {
+        Self {
+            title: input.title.into(),
+            description: input.description.into(),
+            due_at: input.due_at.into(),
+            priority: input.priority.into(),
+            project_id: input.project_id.into(),
+        }
+    }
+}
+
+/// Fields resolved from other entities
+#[ComplexObject]
+impl Task {
+    /// The names of the Task's Tags, in name order
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.tags(&self.id).await?)
+    }
+
+    /// The Task this is a subtask of, if any
+    async fn parent(&self, ctx: &Context<'_>) -> Result<Option<Task>> {
+        let Some(parent_id) = &self.parent_id else {
+            return Ok(None);
+        };
+
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.get(parent_id).await?)
+    }
+
+    /// The Project the Task belongs to, if any
+    async fn project(&self, ctx: &Context<'_>) -> Result<Option<Project>> {
+        let Some(project_id) = &self.project_id else {
+            return Ok(None);
+        };
+
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.get_project(project_id).await?)
+    }
+
+    /// Page through the Comments on the Task, oldest first, using a cursor-based connection
+    async fn comments(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<String, Comment>> {
+        let store = ctx.data::<Store>()?;
+
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _before: Option<String>, first, _last| async move {
+                let input = comments::inputs::List {
+                    after: after.clone(),
+                    limit: Some(first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)),
+                };
+
+                let page = store.page_comments(&self.id, &input).await?;
+
+                let mut connection = Connection::new(after.is_some(), page.next_cursor.is_some());
+                connection.edges.extend(
+                    page.comments
+                        .into_iter()
+                        .map(|comment| Edge::new(comment.id.clone(), comment)),
+                );
+
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// The files attached to the Task, oldest first. The files themselves are downloaded over REST.
+    async fn attachments(&self, ctx: &Context<'_>) -> Result<Vec<Attachment>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.attachments(&self.id).await?)
+    }
+
+    /// The Tasks that must be finished before this one, in id order
+    async fn blockers(&self, ctx: &Context<'_>) -> Result<Vec<Task>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.blockers(&self.id).await?)
+    }
+
+    /// The Tasks waiting on this one, in id order
+    async fn dependents(&self, ctx: &Context<'_>) -> Result<Vec<Task>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.dependents(&self.id).await?)
+    }
+
+    /// The Task's direct subtasks, in id order
+    async fn children(&self, ctx: &Context<'_>) -> Result<Vec<Task>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.children(&self.id).await?)
+    }
+}
+
+/// The Query segment for Tasks
+#[derive(Default)]
+pub struct TasksQuery;
+
+/// Queries for the `Task` model
+#[Object]
+impl TasksQuery {
+    /// Get a single Task by id
+    async fn task(&self, ctx: &Context<'_>, id: String) -> Result<Option<Task>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.get(&id).await?)
+    }
+
+    /// Page through Tasks using a cursor-based connection, optionally filtered by due date and
+    /// ordered by priority
+    #[allow(clippy::too_many_arguments)]
+    async fn tasks(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+        #[graphql(default)] overdue: bool,
+        due_within_days: Option<u32>,
+        order: Option<Order>,
+    ) -> Result<Connection<String, Task>> {
+        let store = ctx.data::<Store>()?;
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<String>, before: Option<String>, first, last| async move {
+                if before.is_some() || last.is_some() {
+                    return Err(Error::new("Backward pagination is not supported"));
+                }
+
+                let limit = first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+
+                let input = inputs::List {
+                    after: after.clone(),
+                    limit: Some(limit),
+                    overdue,
+                    due_within_days,
+                    order,
+                };
+
+                let page = store.page(&input).await?;
+
+                let mut connection = Connection::new(after.is_some(), page.next_cursor.is_some());
+                connection.edges.extend(
+                    page.tasks
+                        .into_iter()
+                        .map(|task| Edge::new(input.order().cursor(&task), task)),
+                );
+
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Page through the Tasks with a Tag using a cursor-based connection
+    async fn tasks_by_tag(
+        &self,
+        ctx: &Context<'_>,
+        name: String,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<String, Task>> {
+        let store = ctx.data::<Store>()?;
+
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _before: Option<String>, first, _last| async move {
+                let input = inputs::ListByTag {
+                    after: after.clone(),
+                    limit: Some(first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)),
+                };
+
+                let page = store.page_by_tag(&name, &input).await?;
+
+                let mut connection = Connection::new(after.is_some(), page.next_cursor.is_some());
+                connection.edges.extend(
+                    page.tasks
+                        .into_iter()
+                        .map(|task| Edge::new(task.id.clone(), task)),
+                );
+
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// List every Tag with the number of Tasks it labels
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<TagCount>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.tag_counts().await?)
+    }
+
+    /// Get a Task with every subtask below it, and how many of them are done
+    async fn task_tree(&self, ctx: &Context<'_>, id: String) -> Result<Tree> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.subtree(&id).await?)
+    }
+}
+
+/// The Mutation segment for Tasks
+#[derive(Default)]
+pub struct TasksMutation;
+
+/// Mutations for the `Task` model
+#[Object]
+impl TasksMutation {
+    /// Create a new Task
+    async fn create_task(&self, ctx: &Context<'_>, input: inputs::Create) -> Result<Task> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.create(&input).await?)
+    }
+
+    /// Update an existing Task
+    async fn update_task(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        input: UpdateTaskInput,
+    ) -> Result<Task> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.update(&id, &input.into()).await?)
+    }
+
+    /// Move an existing Task to another status, if the workflow allows it
+    async fn transition_task(&self, ctx: &Context<'_>, id: String, status: Status) -> Result<Task> {
+        let store = ctx.data::<Store>()?;
+        let workflow = ctx.data::<Workflow>()?;
+
+        Ok(store.transition(workflow, &id, status).await?)
+    }
+
+    /// Add a Tag to a Task, returning the Task's Tags
+    async fn add_task_tag(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        name: String,
+    ) -> Result<Vec<String>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.add_tag(&id, &name).await?)
+    }
+
+    /// Remove a Tag from a Task, returning the Task's remaining Tags
+    async fn remove_task_tag(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        name: String,
+    ) -> Result<Vec<String>> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.remove_tag(&id, &name).await?)
+    }
+
+    /// Move an existing Task under another parent, or to the top level when no parent is given
+    async fn move_task(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        parent_id: Option<String>,
+    ) -> Result<Task> {
+        let store = ctx.data::<Store>()?;
+
+        Ok(store.move_task(&id, &inputs::Move { parent_id }).await?)
+    }
+
+    /// Remove an existing Task with the files attached to it
+    async fn delete_task(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let store = ctx.data::<Store>()?;
+        let storage = ctx.data::<Arc<dyn Storage>>()?;
+
+        store.delete(storage.as_ref(), &id).await?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_graphql::{EmptySubscription, Schema};
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tasks_connection() -> anyhow::Result<()> {
+        let tasks: Vec<Task> = (0..3).map(|_| Faker.fake()).collect();
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([tasks.clone()])
+            .into_connection();
+
+        let schema = Schema::build(TasksQuery, TasksMutation, EmptySubscription)
+            .data(Store::Database(Arc::new(db)))
+            .finish();
+
+        let response = schema
+            .execute("{ tasks(first: 2) { pageInfo { hasNextPage } edges { cursor } } }")
+            .await;
+
+        assert_eq!(response.errors, vec![]);
+
+        let data = response.data.into_json()?;
+
+        assert_eq!(data["tasks"]["pageInfo"]["hasNextPage"], true);
+        assert_eq!(
+            data["tasks"]["edges"],
+            serde_json::json!([{ "cursor": tasks[0].id }, { "cursor": tasks[1].id }])
+        );
+
+        Ok(())
+    }
+}
@@ -1,13 +1,27 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
-use anyhow::anyhow;
-use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, ModelTrait, Set};
+use chrono::{Duration, Utc};
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    ModelTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use ulid::Ulid;
 
-use crate::utils::Update::{Empty, Unchanged, Value};
+use crate::{
+    attachments::{self, storage::Storage},
+    dependencies, projects,
+    utils::Update::{Empty, Unchanged, Value},
+};
 
 use super::{
-    inputs,
+    error::Error,
+    inputs::{self, Order},
     model::{self, Task},
+    tag::{self, TagCount},
+    task_tag,
+    tree::{self, Tree},
+    workflow::{Status, Workflow},
 };
 
 /// Get an individual `Task` by id
@@ -19,11 +33,83 @@ pub async fn get(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Option
     Ok(task)
 }
 
-/// Create a `Task` with the given input
+/// List up to `limit` `Task`s matching the filters, in the requested order and starting after the
+/// given cursor
+pub async fn list(
+    db: Arc<DatabaseConnection>,
+    input: &inputs::List,
+    limit: u64,
+) -> anyhow::Result<Vec<Task>> {
+    let mut query = model::Entity::find();
+
+    match input.order() {
+        Order::Id => {
+            query = query.order_by_asc(model::Column::Id);
+
+            if let Some(after) = &input.after {
+                query = query.filter(model::Column::Id.gt(after));
+            }
+        }
+        Order::Priority => {
+            query = query
+                .filter(model::Column::Priority.is_not_null())
+                .order_by_desc(model::Column::Priority)
+                .order_by_asc(model::Column::Id);
+
+            if let Some(after) = &input.after {
+                let (priority, id) = Order::priority_cursor(after)?;
+
+                query = query.filter(
+                    Condition::any()
+                        .add(model::Column::Priority.lt(priority))
+                        .add(
+                            Condition::all()
+                                .add(model::Column::Priority.eq(priority))
+                                .add(model::Column::Id.gt(id)),
+                        ),
+                );
+            }
+        }
+    }
+
+    let now = Utc::now().fixed_offset();
+
+    if input.overdue {
+        query = query
+            .filter(model::Column::DueAt.lt(now))
+            .filter(model::Column::Status.is_not_in([Status::Done, Status::Cancelled]));
+    }
+
+    if let Some(days) = input.due_within_days {
+        query =
+            query.filter(model::Column::DueAt.between(now, now + Duration::days(i64::from(days))));
+    }
+
+    let tasks = query.limit(limit).all(&*db).await?;
+
+    Ok(tasks)
+}
+
+/// Create a `Task` with the given input, as a subtask of its parent and in its Project if they're
+/// given
 pub async fn create(db: Arc<DatabaseConnection>, input: &inputs::Create) -> anyhow::Result<Task> {
+    if let Some(parent_id) = &input.parent_id {
+        get(db.clone(), parent_id)
+            .await?
+            .ok_or_else(|| Error::ParentNotFound(parent_id.clone()))?;
+    }
+
+    if let Some(project_id) = &input.project_id {
+        projects::service::check_active(&*db, project_id).await?;
+    }
+
     let task = model::ActiveModel {
         title: Set(input.title.clone()),
         description: Set(input.description.clone()),
+        due_at: Set(input.due_at),
+        priority: Set(input.priority),
+        parent_id: Set(input.parent_id.clone()),
+        project_id: Set(input.project_id.clone()),
         ..Default::default()
     }
     .insert(&*db)
@@ -41,10 +127,7 @@ pub async fn update(
     let query = model::Entity::find_by_id(id.to_owned());
 
     // Retrieve the existing Show
-    let show = query
-        .one(&*db)
-        .await?
-        .ok_or_else(|| anyhow!("Unable to find Show with id: {}", id))?;
+    let show = query.one(&*db).await?.ok_or(Error::NotFound)?;
 
     let mut show: model::ActiveModel = show.into();
 
@@ -59,19 +142,688 @@ pub async fn update(
         Value(value) => show.description = Set(Some(value.clone())),
     }
 
+    match &input.due_at {
+        Unchanged => (),
+        Empty => show.due_at = Set(None),
+        Value(value) => show.due_at = Set(Some(*value)),
+    }
+
+    match &input.priority {
+        Unchanged => (),
+        Empty => show.priority = Set(None),
+        Value(value) => show.priority = Set(Some(*value)),
+    }
+
+    match &input.project_id {
+        Unchanged => (),
+        Empty => show.project_id = Set(None),
+        Value(value) => {
+            projects::service::check_active(&*db, value).await?;
+            show.project_id = Set(Some(value.clone()));
+        }
+    }
+
     let updated: Task = show.update(&*db).await?;
 
     Ok(updated)
 }
 
-/// Delete an existing `Task`
-pub async fn delete(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<()> {
+/// Move an existing `Task` to the given status, guarded by the statuses the workflow allows it to
+/// move from. Completing a Task completes its parent too, once every sibling is done or cancelled.
+/// When the workflow requires it, a Task can't be completed while its blockers are unfinished.
+pub async fn transition(
+    db: Arc<DatabaseConnection>,
+    workflow: &Workflow,
+    id: &str,
+    status: Status,
+) -> anyhow::Result<Task> {
+    if status == Status::Done && workflow.requires_unblocked() {
+        dependencies::service::check_unblocked(&*db, id).await?;
+    }
+
+    let task = set_status(db.clone(), workflow, id, status).await?;
+
+    if task.status == Status::Done {
+        roll_up(db, workflow, &task).await?;
+    }
+
+    Ok(task)
+}
+
+async fn set_status(
+    db: Arc<DatabaseConnection>,
+    workflow: &Workflow,
+    id: &str,
+    status: Status,
+) -> anyhow::Result<Task> {
+    let updated = model::Entity::update_many()
+        .col_expr(model::Column::Status, Expr::value(status))
+        .col_expr(
+            model::Column::UpdatedAt,
+            Expr::value(Utc::now().fixed_offset()),
+        )
+        .filter(model::Column::Id.eq(id))
+        .filter(model::Column::Status.is_in(workflow.sources(status)))
+        .exec_with_returning(&*db)
+        .await?;
+
+    if let Some(task) = updated.into_iter().next() {
+        return Ok(task);
+    }
+
+    // Nothing was updated, so read the Task to find out why
+    let task = get(db, id).await?.ok_or(Error::NotFound)?;
+
+    Err(Error::InvalidTransition {
+        from: task.status,
+        to: status,
+    }
+    .into())
+}
+
+/// Complete the ancestors of a completed Task, from its parent up, stopping at the first one with
+/// unfinished subtasks or blockers, or that the workflow doesn't allow to be completed
+async fn roll_up(
+    db: Arc<DatabaseConnection>,
+    workflow: &Workflow,
+    task: &Task,
+) -> anyhow::Result<()> {
+    let mut visited = HashSet::from([task.id.clone()]);
+    let mut parent_id = task.parent_id.clone();
+
+    while let Some(id) = parent_id.take() {
+        if !visited.insert(id.clone()) {
+            break;
+        }
+
+        let siblings = children_of(&*db, &id).await?;
+
+        if !tree::is_complete(&siblings) {
+            break;
+        }
+
+        if workflow.requires_unblocked() {
+            match dependencies::service::check_unblocked(&*db, &id).await {
+                Ok(()) => (),
+                Err(err) if dependencies::error::Error::is_open_blockers(&err) => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        match set_status(db.clone(), workflow, &id, Status::Done).await {
+            Ok(parent) => parent_id = parent.parent_id,
+            Err(err) if Error::is_invalid_transition(&err) || Error::is_not_found(&err) => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// List a `Task`'s direct subtasks, in id order
+pub async fn children(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Vec<Task>> {
+    let task = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    children_of(&*db, &task.id).await
+}
+
+/// Get a `Task` with every subtask below it, read one level at a time
+pub async fn subtree(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Tree> {
+    let task = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    let mut visited = HashSet::from([task.id.clone()]);
+    let mut level = vec![task.id.clone()];
+    let mut descendants = vec![];
+
+    while !level.is_empty() {
+        let children = model::Entity::find()
+            .filter(model::Column::ParentId.is_in(level))
+            .order_by_asc(model::Column::Id)
+            .all(&*db)
+            .await?;
+
+        level = children
+            .iter()
+            .filter(|child| visited.insert(child.id.clone()))
+            .map(|child| child.id.clone())
+            .collect();
+
+        descendants.extend(children);
+    }
+
+    Ok(Tree::new(task, descendants))
+}
+
+/// Move an existing `Task` under another parent, or to the top level when no parent is given. A
+/// Task can't be moved under itself or any of its subtasks.
+pub async fn move_task(
+    db: Arc<DatabaseConnection>,
+    id: &str,
+    input: &inputs::Move,
+) -> anyhow::Result<Task> {
+    let task = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    if let Some(parent_id) = &input.parent_id {
+        let parent = get(db.clone(), parent_id)
+            .await?
+            .ok_or_else(|| Error::ParentNotFound(parent_id.clone()))?;
+
+        let mut ancestor = Some(parent);
+        let mut visited = HashSet::new();
+
+        while let Some(current) = ancestor.take() {
+            if current.id == task.id {
+                return Err(Error::Cycle {
+                    id: task.id,
+                    parent_id: parent_id.clone(),
+                }
+                .into());
+            }
+
+            if !visited.insert(current.id.clone()) {
+                break;
+            }
+
+            if let Some(next) = &current.parent_id {
+                ancestor = get(db.clone(), next).await?;
+            }
+        }
+    }
+
+    let updated = model::Entity::update_many()
+        .col_expr(
+            model::Column::ParentId,
+            Expr::value(input.parent_id.clone()),
+        )
+        .col_expr(
+            model::Column::UpdatedAt,
+            Expr::value(Utc::now().fixed_offset()),
+        )
+        .filter(model::Column::Id.eq(id))
+        .exec_with_returning(&*db)
+        .await?;
+
+    // Another request deleted the Task after it was read
+    let task = updated.into_iter().next().ok_or(Error::NotFound)?;
+
+    Ok(task)
+}
+
+/// Insert a full `Task`, preserving its id and timestamps, or replace it if it already exists
+pub async fn upsert(db: Arc<DatabaseConnection>, task: &Task) -> anyhow::Result<()> {
+    let task = model::ActiveModel::from(task.clone()).reset_all();
+
+    model::Entity::insert(task)
+        .on_conflict(
+            OnConflict::column(model::Column::Id)
+                .update_columns([
+                    model::Column::CreatedAt,
+                    model::Column::UpdatedAt,
+                    model::Column::Title,
+                    model::Column::Description,
+                    model::Column::Status,
+                    model::Column::DueAt,
+                    model::Column::Priority,
+                    model::Column::ParentId,
+                    model::Column::ProjectId,
+                ])
+                .to_owned(),
+        )
+        .exec_without_returning(&*db)
+        .await?;
+
+    Ok(())
+}
+
+/// Delete an existing `Task` with the bytes of its Attachments, returning it
+pub async fn delete(
+    db: Arc<DatabaseConnection>,
+    storage: &dyn Storage,
+    id: &str,
+) -> anyhow::Result<Task> {
     let show = model::Entity::find_by_id(id.to_owned())
         .one(&*db)
         .await?
-        .ok_or_else(|| anyhow!("Unable to find Show with id: {}", id))?;
+        .ok_or(Error::NotFound)?;
 
-    let _result = show.delete(&*db).await?;
+    // Listed first, since the foreign key deletes the Attachments along with the Task
+    let attachments = show
+        .find_related(attachments::model::Entity)
+        .all(&*db)
+        .await?;
 
-    Ok(())
+    let result = show.clone().delete(&*db).await?;
+
+    // Another request deleted the Task after it was read
+    if result.rows_affected == 0 {
+        return Err(Error::NotFound.into());
+    }
+
+    attachments::storage::purge(storage, &attachments).await;
+
+    Ok(show)
+}
+
+/// List the names of a `Task`'s Tags, in name order
+pub async fn tags(db: Arc<DatabaseConnection>, id: &str) -> anyhow::Result<Vec<String>> {
+    let task = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    tag_names(&*db, &task).await
+}
+
+/// Add a Tag to a `Task`, creating the Tag the first time it's used, and return the Task's Tags.
+/// Adding a Tag the Task already has changes nothing.
+pub async fn add_tag(
+    db: Arc<DatabaseConnection>,
+    id: &str,
+    name: &str,
+) -> anyhow::Result<Vec<String>> {
+    let name = tag::normalize(name)?;
+
+    let txn = db.begin().await?;
+
+    let task = model::Entity::find_by_id(id.to_string())
+        .one(&txn)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    tag::Entity::insert(tag::ActiveModel {
+        id: Set(Ulid::new().to_string()),
+        name: Set(name.clone()),
+        created_at: Set(Utc::now().fixed_offset()),
+    })
+    .on_conflict(
+        OnConflict::column(tag::Column::Name)
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(&txn)
+    .await?;
+
+    let tag = tag::Entity::find()
+        .filter(tag::Column::Name.eq(&name))
+        .one(&txn)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("The Tag '{name}' wasn't saved"))?;
+
+    task_tag::Entity::insert(task_tag::ActiveModel {
+        task_id: Set(task.id.clone()),
+        tag_id: Set(tag.id),
+    })
+    .on_conflict(
+        OnConflict::columns([task_tag::Column::TaskId, task_tag::Column::TagId])
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(&txn)
+    .await?;
+
+    let names = tag_names(&txn, &task).await?;
+
+    txn.commit().await?;
+
+    Ok(names)
+}
+
+/// Remove a Tag from a `Task` and return the Task's remaining Tags. Removing a Tag the Task
+/// doesn't have changes nothing.
+pub async fn remove_tag(
+    db: Arc<DatabaseConnection>,
+    id: &str,
+    name: &str,
+) -> anyhow::Result<Vec<String>> {
+    let name = tag::normalize(name)?;
+
+    let task = get(db.clone(), id).await?.ok_or(Error::NotFound)?;
+
+    let tag = tag::Entity::find()
+        .filter(tag::Column::Name.eq(&name))
+        .one(&*db)
+        .await?;
+
+    if let Some(tag) = tag {
+        task_tag::Entity::delete_by_id((task.id.clone(), tag.id))
+            .exec(&*db)
+            .await?;
+    }
+
+    tag_names(&*db, &task).await
+}
+
+/// List up to `limit` `Task`s with the given Tag, in id order and starting after the given cursor
+pub async fn list_by_tag(
+    db: Arc<DatabaseConnection>,
+    name: &str,
+    after: Option<&str>,
+    limit: u64,
+) -> anyhow::Result<Vec<Task>> {
+    let name = tag::normalize(name)?;
+
+    let mut query = model::Entity::find()
+        .inner_join(tag::Entity)
+        .filter(tag::Column::Name.eq(name))
+        .order_by_asc(model::Column::Id);
+
+    if let Some(after) = after {
+        query = query.filter(model::Column::Id.gt(after));
+    }
+
+    let tasks = query.limit(limit).all(&*db).await?;
+
+    Ok(tasks)
+}
+
+/// List every Tag with the number of Tasks it labels, in name order
+pub async fn tag_counts(db: Arc<DatabaseConnection>) -> anyhow::Result<Vec<TagCount>> {
+    let counts = tag::Entity::find()
+        .select_only()
+        .column(tag::Column::Name)
+        .column_as(
+            Expr::col((task_tag::Entity, task_tag::Column::TaskId)).count(),
+            "tasks",
+        )
+        .left_join(task_tag::Entity)
+        .group_by(tag::Column::Name)
+        .order_by_asc(tag::Column::Name)
+        .into_model::<TagCount>()
+        .all(&*db)
+        .await?;
+
+    Ok(counts)
+}
+
+async fn children_of<C: ConnectionTrait>(db: &C, id: &str) -> anyhow::Result<Vec<Task>> {
+    let children = model::Entity::find()
+        .filter(model::Column::ParentId.eq(id))
+        .order_by_asc(model::Column::Id)
+        .all(db)
+        .await?;
+
+    Ok(children)
+}
+
+async fn tag_names<C: ConnectionTrait>(db: &C, task: &Task) -> anyhow::Result<Vec<String>> {
+    let tags = task
+        .find_related(tag::Entity)
+        .order_by_asc(tag::Column::Name)
+        .all(db)
+        .await?;
+
+    Ok(tags.into_iter().map(|tag| tag.name).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use fake::{Fake, Faker};
+    use pretty_assertions::assert_eq;
+
+    use sea_orm::{DatabaseBackend, MockDatabase, Transaction, Value};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_by_priority() -> anyhow::Result<()> {
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<Task>::new()])
+                .into_connection(),
+        );
+
+        let input = inputs::List {
+            after: Some("3:01HX".to_string()),
+            order: Some(Order::Priority),
+            ..Default::default()
+        };
+
+        list(db.clone(), &input, 11).await?;
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        assert_eq!(
+            log,
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "tasks"."id", "tasks"."created_at", "tasks"."updated_at", "tasks"."title", "tasks"."description", "tasks"."status", "tasks"."due_at", "tasks"."priority", "tasks"."parent_id", "tasks"."project_id" FROM "tasks" WHERE "tasks"."priority" IS NOT NULL AND ("tasks"."priority" < $1 OR ("tasks"."priority" = $2 AND "tasks"."id" > $3)) ORDER BY "tasks"."priority" DESC, "tasks"."id" ASC LIMIT $4"#,
+                [3.into(), 3.into(), "01HX".into(), 11u64.into()],
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_invalid_cursor() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+        let input = inputs::List {
+            after: Some("01HX".to_string()),
+            order: Some(Order::Priority),
+            ..Default::default()
+        };
+
+        let err = list(Arc::new(db), &input, 11).await.unwrap_err();
+
+        assert!(Error::is_invalid_cursor(&err));
+    }
+
+    #[tokio::test]
+    async fn test_transition() -> anyhow::Result<()> {
+        let task = Task {
+            status: Status::Done,
+            parent_id: None,
+            ..Faker.fake()
+        };
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![task.clone()]])
+            .into_connection();
+
+        let updated =
+            transition(Arc::new(db), &Workflow::default(), &task.id, Status::Done).await?;
+
+        assert_eq!(updated, task);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transition_not_allowed() -> anyhow::Result<()> {
+        let task = Task {
+            status: Status::Done,
+            parent_id: None,
+            ..Faker.fake()
+        };
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![], vec![task.clone()], vec![], vec![]])
+                .into_connection(),
+        );
+
+        let err = transition(
+            db.clone(),
+            &Workflow::default(),
+            &task.id,
+            Status::Cancelled,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(Error::is_invalid_transition(&err));
+        assert_eq!(err.to_string(), "A Task can't move from done to cancelled");
+
+        let err = transition(db, &Workflow::default(), "missing", Status::Cancelled)
+            .await
+            .unwrap_err();
+
+        assert!(Error::is_not_found(&err));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transition_rolls_up() -> anyhow::Result<()> {
+        let parent = Task {
+            status: Status::Done,
+            parent_id: None,
+            ..Faker.fake()
+        };
+
+        let task = Task {
+            status: Status::Done,
+            parent_id: Some(parent.id.clone()),
+            ..Faker.fake()
+        };
+
+        let sibling = Task {
+            status: Status::Cancelled,
+            parent_id: Some(parent.id.clone()),
+            ..Faker.fake()
+        };
+
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([
+                    vec![task.clone()],
+                    vec![task.clone(), sibling],
+                    vec![parent.clone()],
+                ])
+                .into_connection(),
+        );
+
+        let updated = transition(db.clone(), &Workflow::default(), &task.id, Status::Done).await?;
+
+        assert_eq!(updated, task);
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        // The Task, its siblings, then the parent
+        assert_eq!(log.len(), 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_move_task_cycle() {
+        let task = Task {
+            parent_id: None,
+            ..Faker.fake()
+        };
+
+        let child = Task {
+            parent_id: Some(task.id.clone()),
+            ..Faker.fake()
+        };
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![task.clone()], vec![child.clone()], vec![task.clone()]])
+            .into_connection();
+
+        let input = inputs::Move {
+            parent_id: Some(child.id),
+        };
+
+        let err = move_task(Arc::new(db), &task.id, &input).await.unwrap_err();
+
+        assert!(Error::is_cycle(&err));
+    }
+
+    #[tokio::test]
+    async fn test_create_missing_parent() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<Task>::new()])
+            .into_connection();
+
+        let input = inputs::Create {
+            title: "Subtask".to_string(),
+            parent_id: Some("missing".to_string()),
+            ..Default::default()
+        };
+
+        let err = create(Arc::new(db), &input).await.unwrap_err();
+
+        assert!(Error::is_parent_not_found(&err));
+    }
+
+    #[tokio::test]
+    async fn test_list_by_tag() -> anyhow::Result<()> {
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<Task>::new()])
+                .into_connection(),
+        );
+
+        list_by_tag(db.clone(), " Urgent", Some("01HX"), 21).await?;
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        assert_eq!(
+            log,
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "tasks"."id", "tasks"."created_at", "tasks"."updated_at", "tasks"."title", "tasks"."description", "tasks"."status", "tasks"."due_at", "tasks"."priority", "tasks"."parent_id", "tasks"."project_id" FROM "tasks" INNER JOIN "task_tags" ON "tasks"."id" = "task_tags"."task_id" INNER JOIN "tags" ON "task_tags"."tag_id" = "tags"."id" WHERE "tags"."name" = $1 AND "tasks"."id" > $2 ORDER BY "tasks"."id" ASC LIMIT $3"#,
+                ["urgent".into(), "01HX".into(), 21u64.into()],
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tag_counts() -> anyhow::Result<()> {
+        let db = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![BTreeMap::from([
+                    ("name", Value::from("urgent")),
+                    ("tasks", Value::from(2i64)),
+                ])]])
+                .into_connection(),
+        );
+
+        let counts = tag_counts(db.clone()).await?;
+
+        assert_eq!(
+            counts,
+            vec![TagCount {
+                name: "urgent".to_string(),
+                tasks: 2
+            }]
+        );
+
+        let log = Arc::try_unwrap(db)
+            .map_err(|_err| anyhow::anyhow!("The connection is still shared"))?
+            .into_transaction_log();
+
+        assert_eq!(
+            log,
+            vec![Transaction::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT "tags"."name", COUNT("task_tags"."task_id") AS "tasks" FROM "tags" LEFT JOIN "task_tags" ON "tags"."id" = "task_tags"."tag_id" GROUP BY "tags"."name" ORDER BY "tags"."name" ASC"#,
+                [],
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_add_tag_missing_task() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([Vec::<Task>::new()])
+            .into_connection();
+
+        let err = add_tag(Arc::new(db), "missing", "urgent")
+            .await
+            .unwrap_err();
+
+        assert!(Error::is_not_found(&err));
+    }
 }
@@ -0,0 +1,331 @@
+use std::sync::Arc;
+
+use aws_sdk_dynamodb::Client;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    args::DataStore,
+    attachments::storage::Storage,
+    config::{self, TimestampFormat},
+};
+
+use super::{
+    dynamo_service,
+    inputs::{self, Order},
+    model::Task,
+    service,
+    tag::TagCount,
+    tree::Tree,
+    workflow::{Status, Workflow},
+};
+
+/// The default number of Tasks returned per page
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// The maximum number of Tasks that can be requested per page
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// A page of `Task`s
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[schema(as = TaskPage)]
+pub struct Page {
+    /// The Tasks on this page
+    pub tasks: Vec<Task>,
+
+    /// The cursor to request the next page with, if there is one
+    pub next_cursor: Option<String>,
+}
+
+impl Page {
+    /// Build a page from up to `limit + 1` Tasks in the given order, where the extra Task signals
+    /// a next page
+    pub fn new(mut tasks: Vec<Task>, limit: usize, order: Order) -> Self {
+        let has_next_page = tasks.len() > limit;
+        tasks.truncate(limit);
+
+        let next_cursor = tasks
+            .last()
+            .filter(|_| has_next_page)
+            .map(|task| order.cursor(task));
+
+        Self { tasks, next_cursor }
+    }
+}
+
+/// The configured Task backend, dispatching to the service for the selected `DataStore`
+#[derive(Clone, Debug)]
+pub enum Store {
+    /// Tasks are stored in Postgres
+    Database(Arc<DatabaseConnection>),
+
+    /// Tasks are stored in DynamoDB
+    Dynamo {
+        /// The DynamoDB client
+        client: Arc<Client>,
+
+        /// The table name to use for Tasks
+        table_name: String,
+
+        /// The format timestamps are written in
+        timestamp_format: TimestampFormat,
+    },
+}
+
+impl Store {
+    /// Connect to the Task store for the given `DataStore`
+    pub async fn connect(
+        data_store: DataStore,
+        db: &config::Database,
+        dynamo: &config::Dynamo,
+    ) -> anyhow::Result<Self> {
+        match data_store {
+            DataStore::Postgres => {
+                let db = sea_orm::Database::connect(&db.url).await?;
+
+                Ok(Store::Database(Arc::new(db)))
+            }
+            DataStore::DynamoDB => {
+                let client = Client::from_conf(aws_sdk_dynamodb::Config::builder().build());
+
+                Ok(Store::Dynamo {
+                    client: Arc::new(client),
+                    table_name: dynamo.tasks_table_name.clone(),
+                    timestamp_format: dynamo.timestamp_format,
+                })
+            }
+        }
+    }
+
+    /// Check that the data store can be reached
+    pub async fn ping(&self) -> anyhow::Result<()> {
+        match self {
+            Store::Database(db) => db.ping().await?,
+            Store::Dynamo {
+                client, table_name, ..
+            } => {
+                client
+                    .describe_table()
+                    .table_name(table_name)
+                    .send()
+                    .await?;
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Get an individual `Task` by id
+    pub async fn get(&self, id: &str) -> anyhow::Result<Option<Task>> {
+        match self {
+            Store::Database(db) => service::get(db.clone(), id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::get(client.clone(), table_name, id).await,
+        }
+    }
+
+    /// List up to `limit` `Task`s matching the filters, starting after the given cursor
+    pub async fn list(&self, input: &inputs::List, limit: u64) -> anyhow::Result<Vec<Task>> {
+        match self {
+            Store::Database(db) => service::list(db.clone(), input, limit).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => {
+                dynamo_service::list(client.clone(), table_name, *timestamp_format, input, limit)
+                    .await
+            }
+        }
+    }
+
+    /// Get a page of `Task`s matching the filters, starting after the given cursor
+    pub async fn page(&self, input: &inputs::List) -> anyhow::Result<Page> {
+        let limit = input.page_size();
+
+        // Fetch one extra Task to find out if there is another page
+        let tasks = self.list(input, limit as u64 + 1).await?;
+
+        Ok(Page::new(tasks, limit, input.order()))
+    }
+
+    /// Create a `Task` with the given input
+    pub async fn create(&self, input: &inputs::Create) -> anyhow::Result<Task> {
+        match self {
+            Store::Database(db) => service::create(db.clone(), input).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => dynamo_service::create(client.clone(), table_name, *timestamp_format, input).await,
+        }
+    }
+
+    /// Update an existing `Task` by id
+    pub async fn update(&self, id: &str, input: &inputs::Update) -> anyhow::Result<Task> {
+        match self {
+            Store::Database(db) => service::update(db.clone(), id, input).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => {
+                dynamo_service::update(client.clone(), table_name, *timestamp_format, id, input)
+                    .await
+            }
+        }
+    }
+
+    /// Move an existing `Task` to the given status, if the workflow allows it
+    pub async fn transition(
+        &self,
+        workflow: &Workflow,
+        id: &str,
+        status: Status,
+    ) -> anyhow::Result<Task> {
+        match self {
+            Store::Database(db) => service::transition(db.clone(), workflow, id, status).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => {
+                dynamo_service::transition(
+                    client.clone(),
+                    table_name,
+                    *timestamp_format,
+                    workflow,
+                    id,
+                    status,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Insert a full `Task`, preserving its id and timestamps, or replace it if it already exists
+    pub async fn upsert(&self, task: &Task) -> anyhow::Result<()> {
+        match self {
+            Store::Database(db) => service::upsert(db.clone(), task).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => dynamo_service::upsert(client.clone(), table_name, *timestamp_format, task).await,
+        }
+    }
+
+    /// List a `Task`'s direct subtasks
+    pub async fn children(&self, id: &str) -> anyhow::Result<Vec<Task>> {
+        match self {
+            Store::Database(db) => service::children(db.clone(), id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::children(client.clone(), table_name, id).await,
+        }
+    }
+
+    /// Get a `Task` with every subtask below it
+    pub async fn subtree(&self, id: &str) -> anyhow::Result<Tree> {
+        match self {
+            Store::Database(db) => service::subtree(db.clone(), id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::subtree(client.clone(), table_name, id).await,
+        }
+    }
+
+    /// Move an existing `Task` under another parent, or to the top level
+    pub async fn move_task(&self, id: &str, input: &inputs::Move) -> anyhow::Result<Task> {
+        match self {
+            Store::Database(db) => service::move_task(db.clone(), id, input).await,
+            Store::Dynamo {
+                client,
+                table_name,
+                timestamp_format,
+            } => {
+                dynamo_service::move_task(client.clone(), table_name, *timestamp_format, id, input)
+                    .await
+            }
+        }
+    }
+
+    /// Delete an existing `Task` with the bytes of its Attachments, returning it
+    pub async fn delete(&self, storage: &dyn Storage, id: &str) -> anyhow::Result<Task> {
+        match self {
+            Store::Database(db) => service::delete(db.clone(), storage, id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::delete(client.clone(), table_name, storage, id).await,
+        }
+    }
+
+    /// List the names of a `Task`'s Tags
+    pub async fn tags(&self, id: &str) -> anyhow::Result<Vec<String>> {
+        match self {
+            Store::Database(db) => service::tags(db.clone(), id).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::tags(client.clone(), table_name, id).await,
+        }
+    }
+
+    /// Add a Tag to a `Task`, returning the Task's Tags
+    pub async fn add_tag(&self, id: &str, name: &str) -> anyhow::Result<Vec<String>> {
+        match self {
+            Store::Database(db) => service::add_tag(db.clone(), id, name).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::add_tag(client.clone(), table_name, id, name).await,
+        }
+    }
+
+    /// Remove a Tag from a `Task`, returning the Task's remaining Tags
+    pub async fn remove_tag(&self, id: &str, name: &str) -> anyhow::Result<Vec<String>> {
+        match self {
+            Store::Database(db) => service::remove_tag(db.clone(), id, name).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::remove_tag(client.clone(), table_name, id, name).await,
+        }
+    }
+
+    /// List up to `limit` `Task`s with the given Tag, starting after the given cursor
+    pub async fn list_by_tag(
+        &self,
+        name: &str,
+        after: Option<&str>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<Task>> {
+        match self {
+            Store::Database(db) => service::list_by_tag(db.clone(), name, after, limit).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::list_by_tag(client.clone(), table_name, name, after, limit).await,
+        }
+    }
+
+    /// Get a page of the `Task`s with the given Tag, starting after the given cursor
+    pub async fn page_by_tag(&self, name: &str, input: &inputs::ListByTag) -> anyhow::Result<Page> {
+        let limit = input.page_size();
+
+        // Fetch one extra Task to find out if there is another page
+        let tasks = self
+            .list_by_tag(name, input.after.as_deref(), limit as u64 + 1)
+            .await?;
+
+        Ok(Page::new(tasks, limit, Order::Id))
+    }
+
+    /// List every Tag with the number of Tasks it labels
+    pub async fn tag_counts(&self) -> anyhow::Result<Vec<TagCount>> {
+        match self {
+            Store::Database(db) => service::tag_counts(db.clone()).await,
+            Store::Dynamo {
+                client, table_name, ..
+            } => dynamo_service::tag_counts(client.clone(), table_name).await,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use async_graphql::SimpleObject;
+use sea_orm::{entity::prelude::*, FromQueryResult};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::error::Error;
+
+/// The longest tag name allowed
+const MAX_NAME_LENGTH: usize = 64;
+
+/// The Tag Model
+#[derive(Clone, Debug, Eq, PartialEq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "tags")]
+pub struct Model {
+    /// The Tag id
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub id: String,
+
+    /// The Tag name, unique across Tags
+    #[sea_orm(column_type = "Text", unique)]
+    pub name: String,
+
+    /// The date the Tag was first used
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// The name for a Sea ORM model must be "Model", so this provides a convenient alias
+pub type Tag = Model;
+
+/// Show entity relationships
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// The join rows linking the Tag to Tasks
+    #[sea_orm(has_many = "super::task_tag::Entity")]
+    TaskTags,
+}
+
+impl Related<super::task_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaskTags.def()
+    }
+}
+
+impl Related<super::model::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::task_tag::Relation::Task.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::task_tag::Relation::Tag.def().rev())
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// A Tag name with the number of Tasks it labels
+#[derive(
+    Clone, Debug, Eq, PartialEq, Deserialize, Serialize, FromQueryResult, SimpleObject, ToSchema,
+)]
+#[graphql(name = "TagCount")]
+#[schema(as = TagCount)]
+pub struct TagCount {
+    /// The Tag name
+    pub name: String,
+
+    /// The number of Tasks with the Tag
+    pub tasks: i64,
+}
+
+/// Trim and lowercase a Tag name, so labels match however they're typed
+pub fn normalize(name: &str) -> Result<String, Error> {
+    let normalized = name.trim().to_lowercase();
+
+    if normalized.is_empty() || normalized.chars().count() > MAX_NAME_LENGTH {
+        return Err(Error::InvalidTag(name.to_string()));
+    }
+
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(" Urgent ").unwrap(), "urgent");
+        assert!(matches!(normalize("  "), Err(Error::InvalidTag(_))));
+        assert!(normalize(&"x".repeat(65)).is_err());
+    }
+}
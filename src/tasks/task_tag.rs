@@ -0,0 +1,50 @@
+use sea_orm::entity::prelude::*;
+
+/// The join between Tasks and the Tags that label them
+#[derive(Clone, Debug, Eq, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "task_tags")]
+pub struct Model {
+    /// The Task id
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub task_id: String,
+
+    /// The Tag id
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub tag_id: String,
+}
+
+/// Show entity relationships
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    /// The labelled Task
+    #[sea_orm(
+        belongs_to = "super::model::Entity",
+        from = "Column::TaskId",
+        to = "super::model::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Task,
+
+    /// The Tag labelling the Task
+    #[sea_orm(
+        belongs_to = "super::tag::Entity",
+        from = "Column::TagId",
+        to = "super::tag::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Tag,
+}
+
+impl Related<super::model::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Task.def()
+    }
+}
+
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tag.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use async_graphql::SimpleObject;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::timezone::{Timezone, WithTimestamps};
+
+use super::{model::Task, workflow::Status};
+
+/// How many of a Task's subtasks, at every level below it, are done. Cancelled subtasks aren't
+/// counted.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize, SimpleObject, ToSchema,
+)]
+#[graphql(name = "TaskProgress")]
+#[schema(as = TaskProgress)]
+pub struct Progress {
+    /// The number of subtasks that are done
+    pub done: u64,
+
+    /// The number of subtasks that aren't cancelled
+    pub total: u64,
+}
+
+/// A Task with its subtasks, in id order
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize, SimpleObject, ToSchema)]
+#[graphql(name = "TaskTree")]
+#[schema(as = TaskTree)]
+pub struct Tree {
+    /// The Task
+    pub task: Task,
+
+    /// The completion of the Task's subtasks
+    pub progress: Progress,
+
+    /// The Task's direct subtasks, with their own subtasks
+    #[schema(no_recursion)]
+    pub children: Vec<Tree>,
+}
+
+impl Tree {
+    /// Build the tree under `root` from its descendants, given in any order
+    pub fn new(root: Task, descendants: Vec<Task>) -> Self {
+        let mut by_parent: HashMap<String, Vec<Task>> = HashMap::new();
+
+        for task in descendants {
+            if let Some(parent_id) = task.parent_id.clone() {
+                by_parent.entry(parent_id).or_default().push(task);
+            }
+        }
+
+        Self::build(root, &mut by_parent)
+    }
+
+    // Each Task's children are removed from the map as they're used, so nothing is visited twice
+    fn build(task: Task, by_parent: &mut HashMap<String, Vec<Task>>) -> Self {
+        let mut children = by_parent.remove(&task.id).unwrap_or_default();
+        children.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let children: Vec<Tree> = children
+            .into_iter()
+            .map(|child| Self::build(child, by_parent))
+            .collect();
+
+        let mut progress = Progress::default();
+
+        for child in &children {
+            progress.done += child.progress.done;
+            progress.total += child.progress.total;
+
+            match child.task.status {
+                Status::Done => {
+                    progress.done += 1;
+                    progress.total += 1;
+                }
+                Status::Cancelled => (),
+                _ => progress.total += 1,
+            }
+        }
+
+        Self {
+            task,
+            progress,
+            children,
+        }
+    }
+}
+
+impl WithTimestamps for Tree {
+    /// Render every Task's timestamps in the given timezone
+    fn in_timezone(self, timezone: &Timezone) -> Self {
+        Self {
+            task: self.task.in_timezone(timezone),
+            progress: self.progress,
+            children: self
+                .children
+                .into_iter()
+                .map(|child| child.in_timezone(timezone))
+                .collect(),
+        }
+    }
+}
+
+/// Whether a parent's completion rolls up from its children, which is when at least one child is
+/// done and the rest are done or cancelled
+pub fn is_complete(children: &[Task]) -> bool {
+    children.iter().any(|child| child.status == Status::Done)
+        && children
+            .iter()
+            .all(|child| matches!(child.status, Status::Done | Status::Cancelled))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn task(id: &str, parent_id: Option<&str>, status: Status) -> Task {
+        Task {
+            id: id.to_string(),
+            parent_id: parent_id.map(ToString::to_string),
+            status,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let root = task("1", None, Status::InProgress);
+
+        let tree = Tree::new(
+            root.clone(),
+            vec![
+                task("4", Some("2"), Status::Todo),
+                task("3", Some("1"), Status::Cancelled),
+                task("2", Some("1"), Status::InProgress),
+                task("5", Some("2"), Status::Done),
+            ],
+        );
+
+        assert_eq!(tree.task, root);
+        assert_eq!(tree.progress, Progress { done: 1, total: 3 });
+
+        let ids: Vec<&str> = tree
+            .children
+            .iter()
+            .map(|child| child.task.id.as_str())
+            .collect();
+
+        assert_eq!(ids, vec!["2", "3"]);
+        assert_eq!(tree.children[0].progress, Progress { done: 1, total: 2 });
+        assert_eq!(tree.children[0].children.len(), 2);
+    }
+
+    #[test]
+    fn test_is_complete() {
+        assert!(is_complete(&[
+            task("2", Some("1"), Status::Done),
+            task("3", Some("1"), Status::Cancelled),
+        ]));
+        assert!(!is_complete(&[
+            task("2", Some("1"), Status::Done),
+            task("3", Some("1"), Status::Todo),
+        ]));
+        assert!(!is_complete(&[task("2", Some("1"), Status::Cancelled)]));
+        assert!(!is_complete(&[]));
+    }
+}
@@ -0,0 +1,287 @@
+//! The Task status workflow. A `Workflow` is a directed graph of the statuses a Task can move
+//! between, which the services enforce on every transition.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::Context;
+use sea_orm::{DeriveActiveEnum, EnumIter};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[cfg(test)]
+use fake::{Dummy, Faker, Rng};
+
+use super::error::Error;
+
+/// The status of a Task
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    EnumIter,
+    DeriveActiveEnum,
+    Deserialize,
+    Serialize,
+    async_graphql::Enum,
+    ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+#[graphql(name = "TaskStatus")]
+#[schema(as = TaskStatus)]
+pub enum Status {
+    /// Not started yet
+    #[default]
+    #[sea_orm(string_value = "todo")]
+    Todo,
+
+    /// Being worked on
+    #[sea_orm(string_value = "in_progress")]
+    InProgress,
+
+    /// Waiting on something else
+    #[sea_orm(string_value = "blocked")]
+    Blocked,
+
+    /// Finished
+    #[sea_orm(string_value = "done")]
+    Done,
+
+    /// Abandoned
+    #[sea_orm(string_value = "cancelled")]
+    Cancelled,
+}
+
+impl Status {
+    /// The name used for the status in JSON, the database and on the command line
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Todo => "todo",
+            Status::InProgress => "in_progress",
+            Status::Blocked => "blocked",
+            Status::Done => "done",
+            Status::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Status {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "todo" => Ok(Status::Todo),
+            "in_progress" => Ok(Status::InProgress),
+            "blocked" => Ok(Status::Blocked),
+            "done" => Ok(Status::Done),
+            "cancelled" => Ok(Status::Cancelled),
+            _ => Err(anyhow::anyhow!("Invalid status: {}", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Dummy<Faker> for Status {
+    fn dummy_with_rng<R: Rng + ?Sized>(_config: &Faker, rng: &mut R) -> Self {
+        [
+            Status::Todo,
+            Status::InProgress,
+            Status::Blocked,
+            Status::Done,
+            Status::Cancelled,
+        ][rng.gen_range(0..5)]
+    }
+}
+
+/// The transitions allowed between statuses, read from JSON as a map from each status to the
+/// statuses it can move to, such as `{"todo": ["in_progress"], "in_progress": ["done"]}`
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Workflow {
+    transitions: BTreeMap<Status, BTreeSet<Status>>,
+
+    /// Whether a Task must wait for its blockers before it can be done, set apart from the file
+    #[serde(skip)]
+    require_unblocked: bool,
+}
+
+impl Default for Workflow {
+    fn default() -> Self {
+        use Status::{Blocked, Cancelled, Done, InProgress, Todo};
+
+        Self::new([
+            (Todo, InProgress),
+            (Todo, Blocked),
+            (Todo, Done),
+            (Todo, Cancelled),
+            (InProgress, Todo),
+            (InProgress, Blocked),
+            (InProgress, Done),
+            (InProgress, Cancelled),
+            (Blocked, Todo),
+            (Blocked, InProgress),
+            (Blocked, Cancelled),
+            (Done, Todo),
+            (Cancelled, Todo),
+        ])
+    }
+}
+
+impl Workflow {
+    /// Build a workflow from `(from, to)` transitions
+    pub fn new(transitions: impl IntoIterator<Item = (Status, Status)>) -> Self {
+        let mut graph: BTreeMap<Status, BTreeSet<Status>> = BTreeMap::new();
+
+        for (from, to) in transitions {
+            graph.entry(from).or_default().insert(to);
+        }
+
+        Self {
+            transitions: graph,
+            require_unblocked: false,
+        }
+    }
+
+    /// Refuse to complete a Task while any of the Tasks blocking it are neither done nor cancelled
+    pub fn require_unblocked(self, require_unblocked: bool) -> Self {
+        Self {
+            require_unblocked,
+            ..self
+        }
+    }
+
+    /// Whether a Task can only be completed once its blockers are finished
+    pub fn requires_unblocked(&self) -> bool {
+        self.require_unblocked
+    }
+
+    /// Read a workflow from a JSON file
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Unable to read the workflow '{}'", path.display()))?;
+
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Unable to parse the workflow '{}'", path.display()))
+    }
+
+    /// The statuses a Task can move to from the given status
+    pub fn next(&self, from: Status) -> Vec<Status> {
+        self.transitions
+            .get(&from)
+            .map(|to| to.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// The statuses a Task can move to the given status from
+    pub fn sources(&self, to: Status) -> Vec<Status> {
+        self.transitions
+            .iter()
+            .filter(|(_, next)| next.contains(&to))
+            .map(|(from, _)| *from)
+            .collect()
+    }
+
+    /// Check that a Task can move between the given statuses
+    pub fn check(&self, from: Status, to: Status) -> Result<(), Error> {
+        let allowed = self
+            .transitions
+            .get(&from)
+            .is_some_and(|next| next.contains(&to));
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(Error::InvalidTransition { from, to })
+        }
+    }
+}
+
+/// A Task's status and the statuses it can move to next
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[schema(as = TaskTransitions)]
+pub struct Transitions {
+    /// The Task's current status
+    pub status: Status,
+
+    /// The statuses the Task can move to
+    pub allowed: Vec<Status>,
+}
+
+impl Transitions {
+    /// The transitions available from the given status
+    pub fn new(workflow: &Workflow, status: Status) -> Self {
+        Self {
+            status,
+            allowed: workflow.next(status),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_default_workflow() {
+        let workflow = Workflow::default();
+
+        assert!(workflow.check(Status::Todo, Status::InProgress).is_ok());
+        assert!(workflow.check(Status::Done, Status::Todo).is_ok());
+        assert!(matches!(
+            workflow.check(Status::Done, Status::Cancelled),
+            Err(Error::InvalidTransition {
+                from: Status::Done,
+                to: Status::Cancelled
+            })
+        ));
+        assert_eq!(
+            workflow.sources(Status::Done),
+            vec![Status::Todo, Status::InProgress]
+        );
+    }
+
+    #[test]
+    fn test_deserialize() -> anyhow::Result<()> {
+        let workflow: Workflow =
+            serde_json::from_str(r#"{"todo": ["in_progress"], "in_progress": ["done", "todo"]}"#)?;
+
+        assert_eq!(
+            workflow,
+            Workflow::new([
+                (Status::Todo, Status::InProgress),
+                (Status::InProgress, Status::Done),
+                (Status::InProgress, Status::Todo),
+            ])
+        );
+        assert_eq!(
+            workflow.next(Status::InProgress),
+            vec![Status::Todo, Status::Done]
+        );
+        assert_eq!(workflow.next(Status::Done), vec![]);
+        assert!(!workflow.requires_unblocked());
+        assert!(workflow.require_unblocked(true).requires_unblocked());
+
+        assert!(serde_json::from_str::<Workflow>(r#"{"todo": ["someday"]}"#).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+//! Generic conversions between serde types and DynamoDB items. Options, numbers, nested maps and
+//! lists map to their natural attribute types, timestamps are written in the configured format,
+//! and conversion errors report the path to the attribute that failed.
+
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{config::TimestampFormat, utils::timestamp};
+
+/// A DynamoDB item
+pub type Item = HashMap<String, AttributeValue>;
+
+/// A failure to convert between a type and a DynamoDB item
+#[derive(Debug, thiserror::Error)]
+#[error("Unable to convert the DynamoDB attribute '{path}': {message}")]
+pub struct Error {
+    /// The path to the attribute, such as `tags[0].name`, or `.` for the item itself
+    pub path: String,
+
+    /// What went wrong
+    pub message: String,
+}
+
+/// A type stored as a DynamoDB item
+pub trait DynamoItem: Serialize + DeserializeOwned {
+    /// The top-level attributes holding timestamps, which are written in the configured
+    /// `TimestampFormat` and read back from any supported format
+    const TIMESTAMPS: &'static [&'static str] = &[];
+}
+
+/// Convert a value into a DynamoDB item, leaving out `None` attributes
+pub fn to_item<T: DynamoItem>(value: &T, format: TimestampFormat) -> Result<Item, Error> {
+    let attribute =
+        serde_path_to_error::serialize(value, serde_dynamo::Serializer).map_err(|err| Error {
+            path: err.path().to_string(),
+            message: err.inner().to_string(),
+        })?;
+
+    let AttributeValue::M(mut item) = AttributeValue::from(attribute) else {
+        return Err(Error {
+            path: ".".to_string(),
+            message: "expected a struct or map".to_string(),
+        });
+    };
+
+    item.retain(|_, attribute| !matches!(attribute, AttributeValue::Null(true)));
+
+    for name in T::TIMESTAMPS {
+        if let Some(attribute) = item.get_mut(*name) {
+            let value = timestamp::from_attribute(attribute).map_err(|err| Error {
+                path: name.to_string(),
+                message: err.to_string(),
+            })?;
+
+            *attribute = timestamp::to_attribute(value, format);
+        }
+    }
+
+    Ok(item)
+}
+
+/// Convert a DynamoDB item into a value
+pub fn from_item<T: DynamoItem>(mut item: Item) -> Result<T, Error> {
+    for name in T::TIMESTAMPS {
+        if let Some(attribute) = item.get_mut(*name) {
+            let value = timestamp::from_attribute(attribute).map_err(|err| Error {
+                path: name.to_string(),
+                message: err.to_string(),
+            })?;
+
+            // The RFC 3339 form that `chrono::DateTime` is deserialized from by serde
+            *attribute = AttributeValue::S(value.to_rfc3339());
+        }
+    }
+
+    let deserializer =
+        serde_dynamo::Deserializer::from_attribute_value(AttributeValue::M(item).into());
+
+    serde_path_to_error::deserialize(deserializer).map_err(|err| Error {
+        path: err.path().to_string(),
+        message: err.inner().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, NaiveDate};
+    use pretty_assertions::assert_eq;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Label {
+        name: String,
+        weight: f64,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        id: String,
+        count: u32,
+        note: Option<String>,
+        labels: Vec<Label>,
+        attributes: HashMap<String, i64>,
+        created_at: DateTime<FixedOffset>,
+    }
+
+    impl DynamoItem for Record {
+        const TIMESTAMPS: &'static [&'static str] = &["created_at"];
+    }
+
+    fn record() -> Record {
+        Record {
+            id: "01HX".to_string(),
+            count: 3,
+            note: None,
+            labels: vec![Label {
+                name: "urgent".to_string(),
+                weight: 1.5,
+            }],
+            attributes: HashMap::from([("points".to_string(), 8)]),
+            created_at: NaiveDate::from_ymd_opt(2026, 10, 18)
+                .and_then(|date| date.and_hms_milli_opt(10, 0, 0, 123))
+                .unwrap()
+                .and_utc()
+                .fixed_offset(),
+        }
+    }
+
+    #[test]
+    fn test_to_item() -> anyhow::Result<()> {
+        let item = to_item(&record(), TimestampFormat::Rfc3339)?;
+
+        assert_eq!(item.get("count"), Some(&AttributeValue::N("3".to_string())));
+        assert_eq!(item.get("note"), None);
+        assert_eq!(
+            item.get("created_at"),
+            Some(&AttributeValue::S("2026-10-18T10:00:00.123Z".to_string()))
+        );
+        assert_eq!(
+            item.get("labels"),
+            Some(&AttributeValue::L(vec![AttributeValue::M(HashMap::from(
+                [
+                    ("name".to_string(), AttributeValue::S("urgent".to_string())),
+                    ("weight".to_string(), AttributeValue::N("1.5".to_string())),
+                ]
+            ))]))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> anyhow::Result<()> {
+        for format in [TimestampFormat::Rfc3339, TimestampFormat::EpochMillis] {
+            let item = to_item(&record(), format)?;
+
+            assert_eq!(from_item::<Record>(item)?, record());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_null_is_none() -> anyhow::Result<()> {
+        let mut item = to_item(&record(), TimestampFormat::Rfc3339)?;
+        item.insert("note".to_string(), AttributeValue::Null(true));
+
+        assert_eq!(from_item::<Record>(item)?.note, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_paths() -> anyhow::Result<()> {
+        let mut item = to_item(&record(), TimestampFormat::Rfc3339)?;
+        item.insert(
+            "labels".to_string(),
+            AttributeValue::L(vec![AttributeValue::M(HashMap::from([(
+                "name".to_string(),
+                AttributeValue::N("1".to_string()),
+            )]))]),
+        );
+
+        let err = from_item::<Record>(item).unwrap_err();
+        assert_eq!(err.path, "labels[0].name");
+
+        let mut item = to_item(&record(), TimestampFormat::Rfc3339)?;
+        item.insert(
+            "created_at".to_string(),
+            AttributeValue::S("yesterday".to_string()),
+        );
+
+        let err = from_item::<Record>(item.clone()).unwrap_err();
+        assert_eq!(err.path, "created_at");
+
+        item.remove("created_at");
+
+        let err = from_item::<Record>(item).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unable to convert the DynamoDB attribute '.': missing field `created_at`"
+        );
+
+        Ok(())
+    }
+}
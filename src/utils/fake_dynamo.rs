@@ -0,0 +1,406 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use aws_sdk_dynamodb::{
+    config::{BehaviorVersion, Credentials, Region},
+    Client,
+};
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+/// The prefix DynamoDB puts on the operation in the `X-Amz-Target` header
+const TARGET_PREFIX: &str = "DynamoDB_20120810.";
+
+/// The items in the fake table, in DynamoDB's JSON format, by id
+type Items = Arc<Mutex<HashMap<String, Value>>>;
+
+/// A stand-in for the DynamoDB API over a table of items given in DynamoDB's JSON format. It
+/// answers `GetItem`, `PutItem`, `DeleteItem`, `UpdateItem` and `TransactWriteItems`, evaluating
+/// condition expressions built from `attribute_exists`, `attribute_not_exists`, `=`, `IN`, `AND`,
+/// `OR` and `NOT`. Puts and deletes are applied, while updates that pass their condition are
+/// rejected, since it's only meant to show which writes the conditions stop. Queries find
+/// nothing, like an index that hasn't caught up with the table yet.
+pub struct FakeDynamo {
+    items: Items,
+
+    /// A client for the fake table
+    pub client: Arc<Client>,
+}
+
+impl FakeDynamo {
+    /// Serve the fake table on a local port
+    pub async fn serve(items: Vec<Value>) -> anyhow::Result<Self> {
+        let items: Items = Arc::new(Mutex::new(
+            items
+                .into_iter()
+                .filter_map(|item| Some((item["id"]["S"].as_str()?.to_string(), item)))
+                .collect(),
+        ));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+
+        let router = Router::new()
+            .route("/", post(handle))
+            .with_state(items.clone());
+
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let config = aws_sdk_dynamodb::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("us-east-1"))
+            .credentials_provider(Credentials::new("test", "test", None, None, "test"))
+            .endpoint_url(format!("http://{address}"))
+            .build();
+
+        Ok(Self {
+            items,
+            client: Arc::new(Client::from_conf(config)),
+        })
+    }
+
+    /// The item with the given id, if the table has one
+    pub fn get(&self, id: &str) -> Option<Value> {
+        self.items.lock().unwrap().get(id).cloned()
+    }
+}
+
+/// Serve a fake table over the given items and return a client for it
+pub async fn client(items: Vec<Value>) -> anyhow::Result<Arc<Client>> {
+    Ok(FakeDynamo::serve(items).await?.client)
+}
+
+async fn handle(
+    State(items): State<Items>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, Json<Value>) {
+    // Sent as `application/x-amz-json-1.0`, which the `Json` extractor doesn't accept
+    let request: Value = serde_json::from_slice(&body).unwrap_or_default();
+
+    let operation = headers
+        .get("x-amz-target")
+        .and_then(|target| target.to_str().ok())
+        .and_then(|target| target.strip_prefix(TARGET_PREFIX))
+        .unwrap_or_default();
+
+    let mut items = items.lock().unwrap();
+
+    let result = match operation {
+        "GetItem" => Ok(json!({ "Item": items.get(key(operation, &request)) })),
+        "Query" => Ok(json!({ "Items": [], "Count": 0, "ScannedCount": 0 })),
+        "PutItem" | "DeleteItem" | "UpdateItem" => write(&mut items, operation, &request),
+        "TransactWriteItems" => transact(&mut items, &request),
+        _ => Err(error("UnknownOperationException", operation)),
+    };
+
+    match result {
+        Ok(body) => (StatusCode::OK, Json(body)),
+        Err(body) => (StatusCode::BAD_REQUEST, Json(body)),
+    }
+}
+
+/// The id of the item a request is for
+fn key<'a>(operation: &str, request: &'a Value) -> &'a str {
+    let key = if operation == "PutItem" || operation == "Put" {
+        &request["Item"]["id"]
+    } else {
+        &request["Key"]["id"]
+    };
+
+    key["S"].as_str().unwrap_or_default()
+}
+
+fn write(
+    items: &mut HashMap<String, Value>,
+    operation: &str,
+    request: &Value,
+) -> Result<Value, Value> {
+    let id = key(operation, request);
+    let old = items.get(id);
+
+    match check(request, old) {
+        Ok(true) => (),
+        Ok(false) => {
+            let mut body = error(
+                "ConditionalCheckFailedException",
+                "The conditional request failed",
+            );
+
+            if request["ReturnValuesOnConditionCheckFailure"] == "ALL_OLD" {
+                if let Some(old) = old {
+                    body["Item"] = old.clone();
+                }
+            }
+
+            return Err(body);
+        }
+        Err(message) => return Err(error("ValidationException", &message)),
+    }
+
+    let old = match operation {
+        "PutItem" => items.insert(id.to_string(), request["Item"].clone()),
+        "DeleteItem" => items.remove(id),
+        _ => {
+            return Err(error(
+                "ValidationException",
+                "The fake table doesn't apply updates",
+            ))
+        }
+    };
+
+    if request["ReturnValues"] == "ALL_OLD" {
+        Ok(json!({ "Attributes": old }))
+    } else {
+        Ok(json!({}))
+    }
+}
+
+/// Check every condition in a transaction, then apply its puts and deletes
+fn transact(items: &mut HashMap<String, Value>, request: &Value) -> Result<Value, Value> {
+    let actions: Vec<(&str, &Value)> = request["TransactItems"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|action| action.as_object()?.iter().next())
+        .map(|(operation, request)| (operation.as_str(), request))
+        .collect();
+
+    let mut reasons = vec![];
+
+    for (operation, request) in &actions {
+        match check(request, items.get(key(operation, request))) {
+            Ok(true) => reasons.push(json!({ "Code": "None" })),
+            Ok(false) => reasons.push(json!({ "Code": "ConditionalCheckFailed" })),
+            Err(message) => return Err(error("ValidationException", &message)),
+        }
+    }
+
+    if reasons.iter().any(|reason| reason["Code"] != "None") {
+        let mut body = error(
+            "TransactionCanceledException",
+            "Transaction cancelled, please refer cancellation reasons for specific reasons",
+        );
+
+        body["CancellationReasons"] = Value::Array(reasons);
+
+        return Err(body);
+    }
+
+    for (operation, request) in actions {
+        match operation {
+            "Put" => {
+                items.insert(key(operation, request).to_string(), request["Item"].clone());
+            }
+            "Delete" => {
+                items.remove(key(operation, request));
+            }
+            "ConditionCheck" => (),
+            _ => {
+                return Err(error(
+                    "ValidationException",
+                    "The fake table doesn't apply updates",
+                ))
+            }
+        }
+    }
+
+    Ok(json!({}))
+}
+
+/// Whether the item passes the request's condition, which holds when there is none
+fn check(request: &Value, item: Option<&Value>) -> Result<bool, String> {
+    let Some(condition) = request["ConditionExpression"].as_str() else {
+        return Ok(true);
+    };
+
+    let tokens = tokenize(condition);
+
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        request,
+        item,
+    };
+
+    let result = parser.or()?;
+
+    match parser.tokens.get(parser.position) {
+        None => Ok(result),
+        Some(token) => Err(format!("Unexpected '{token}' in {condition}")),
+    }
+}
+
+/// Split a condition into names, placeholders, keywords and symbols
+fn tokenize(condition: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+
+    for char in condition.chars() {
+        if char.is_alphanumeric() || "#:_".contains(char) {
+            current.push(char);
+            continue;
+        }
+
+        if !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+
+        if !char.is_whitespace() {
+            tokens.push(char.to_string());
+        }
+    }
+
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+/// A recursive descent parser evaluating a condition against an item as it goes
+struct Parser<'a> {
+    tokens: &'a [String],
+    position: usize,
+    request: &'a Value,
+    item: Option<&'a Value>,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Result<&str, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .ok_or("Unexpected end of condition")?;
+
+        self.position += 1;
+
+        Ok(token)
+    }
+
+    fn peek_is(&self, expected: &str) -> bool {
+        self.tokens.get(self.position).map(String::as_str) == Some(expected)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(format!("Expected '{expected}' but found '{token}'")),
+        }
+    }
+
+    fn or(&mut self) -> Result<bool, String> {
+        let mut result = self.and()?;
+
+        while self.peek_is("OR") {
+            self.position += 1;
+            result = self.and()? || result;
+        }
+
+        Ok(result)
+    }
+
+    fn and(&mut self) -> Result<bool, String> {
+        let mut result = self.not()?;
+
+        while self.peek_is("AND") {
+            self.position += 1;
+            result = self.not()? && result;
+        }
+
+        Ok(result)
+    }
+
+    fn not(&mut self) -> Result<bool, String> {
+        if self.peek_is("NOT") {
+            self.position += 1;
+
+            return Ok(!self.not()?);
+        }
+
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<bool, String> {
+        let token = self.next()?.to_string();
+
+        match token.as_str() {
+            "(" => {
+                let result = self.or()?;
+                self.expect(")")?;
+
+                Ok(result)
+            }
+            "attribute_exists" | "attribute_not_exists" => {
+                self.expect("(")?;
+                let name = self.next()?.to_string();
+                let exists = self.operand(&name)?.is_some();
+                self.expect(")")?;
+
+                Ok(exists == (token == "attribute_exists"))
+            }
+            _ => {
+                let left = self.operand(&token)?;
+
+                match self.next()? {
+                    "=" => {
+                        let right = self.next()?.to_string();
+
+                        Ok(left.is_some() && left == self.operand(&right)?)
+                    }
+                    "IN" => {
+                        self.expect("(")?;
+                        let mut found = false;
+
+                        loop {
+                            let right = self.next()?.to_string();
+                            found |= left.is_some() && left == self.operand(&right)?;
+
+                            if self.next()? == ")" {
+                                break;
+                            }
+                        }
+
+                        Ok(found)
+                    }
+                    operator => Err(format!("Unsupported operator '{operator}'")),
+                }
+            }
+        }
+    }
+
+    /// Resolve a `#name` to the item's attribute, or a `:placeholder` to its value
+    fn operand(&self, token: &str) -> Result<Option<Value>, String> {
+        if token.starts_with('#') {
+            let name = self.request["ExpressionAttributeNames"][token]
+                .as_str()
+                .ok_or(format!("Missing name {token}"))?;
+
+            Ok(self.item.and_then(|item| item.get(name)).cloned())
+        } else if token.starts_with(':') {
+            match &self.request["ExpressionAttributeValues"][token] {
+                Value::Null => Err(format!("Missing value {token}")),
+                value => Ok(Some(value.clone())),
+            }
+        } else {
+            // Conditions may use attribute names directly
+            Ok(self.item.and_then(|item| item.get(token)).cloned())
+        }
+    }
+}
+
+fn error(kind: &str, message: &str) -> Value {
+    json!({
+        "__type": format!("com.amazonaws.dynamodb.v20120810#{kind}"),
+        "message": message,
+    })
+}
@@ -1,4 +1,17 @@
 /// Utilities for partial updates
 pub mod update;
 
+/// Utilities for storing timestamps in DynamoDB
+pub mod timestamp;
+
+/// Utilities for converting types to and from DynamoDB items
+pub mod dynamo;
+
+/// Utilities for rendering timestamps in a requested timezone
+pub mod timezone;
+
+/// A stand-in for the DynamoDB API, for testing requests against items already in the table
+#[cfg(test)]
+pub mod fake_dynamo;
+
 pub use update::Update;
@@ -0,0 +1,122 @@
+//! Conversions between timestamps and DynamoDB attributes. Timestamps are written in the
+//! configured `TimestampFormat` and read back from any format this service has ever written.
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, SecondsFormat, Utc};
+
+use crate::config::TimestampFormat;
+
+/// Formats that earlier versions wrote timestamps in, without an offset, which are read as UTC
+const LEGACY_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f UTC", "%Y-%m-%dT%H:%M:%S%.f"];
+
+/// Convert a timestamp into an attribute in the given format, normalized to UTC. RFC 3339 strings
+/// always carry milliseconds, so they sort the same lexically as chronologically and filters can
+/// compare them as strings.
+pub fn to_attribute(value: DateTime<FixedOffset>, format: TimestampFormat) -> AttributeValue {
+    match format {
+        TimestampFormat::Rfc3339 => AttributeValue::S(
+            value
+                .with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::Millis, true),
+        ),
+        TimestampFormat::EpochMillis => AttributeValue::N(value.timestamp_millis().to_string()),
+    }
+}
+
+/// Read a timestamp from an attribute, accepting RFC 3339, epoch milliseconds and the legacy
+/// formats
+pub fn from_attribute(value: &AttributeValue) -> anyhow::Result<DateTime<FixedOffset>> {
+    match value {
+        AttributeValue::N(millis) => from_millis(millis),
+        AttributeValue::S(value) => from_str(value),
+        _ => Err(anyhow::anyhow!(
+            "Expected a string or number timestamp, found {value:?}"
+        )),
+    }
+}
+
+fn from_str(value: &str) -> anyhow::Result<DateTime<FixedOffset>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp);
+    }
+
+    for format in LEGACY_FORMATS {
+        if let Ok(timestamp) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(timestamp.and_utc().fixed_offset());
+        }
+    }
+
+    if value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return from_millis(value);
+    }
+
+    Err(anyhow::anyhow!("Unable to parse the timestamp '{value}'"))
+}
+
+fn from_millis(value: &str) -> anyhow::Result<DateTime<FixedOffset>> {
+    let millis: i64 = value
+        .parse()
+        .map_err(|_err| anyhow::anyhow!("Unable to parse the epoch milliseconds '{value}'"))?;
+
+    DateTime::<Utc>::from_timestamp_millis(millis)
+        .map(|timestamp| timestamp.fixed_offset())
+        .ok_or_else(|| anyhow::anyhow!("The epoch milliseconds '{value}' are out of range"))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, SubsecRound};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn timestamp() -> DateTime<FixedOffset> {
+        NaiveDate::from_ymd_opt(2026, 10, 18)
+            .and_then(|date| date.and_hms_milli_opt(10, 0, 0, 123))
+            .unwrap()
+            .and_utc()
+            .fixed_offset()
+    }
+
+    #[test]
+    fn test_to_attribute() {
+        assert_eq!(
+            to_attribute(timestamp(), TimestampFormat::Rfc3339),
+            AttributeValue::S("2026-10-18T10:00:00.123Z".to_string())
+        );
+        assert_eq!(
+            to_attribute(
+                timestamp().with_timezone(&FixedOffset::east_opt(7200).unwrap()),
+                TimestampFormat::Rfc3339
+            ),
+            AttributeValue::S("2026-10-18T10:00:00.123Z".to_string())
+        );
+        assert_eq!(
+            to_attribute(timestamp().trunc_subsecs(0), TimestampFormat::Rfc3339),
+            AttributeValue::S("2026-10-18T10:00:00.000Z".to_string())
+        );
+        assert_eq!(
+            to_attribute(timestamp(), TimestampFormat::EpochMillis),
+            AttributeValue::N("1792317600123".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_attribute() -> anyhow::Result<()> {
+        for value in [
+            AttributeValue::S("2026-10-18T10:00:00.123Z".to_string()),
+            AttributeValue::S("2026-10-18T12:00:00.123+02:00".to_string()),
+            AttributeValue::S("2026-10-18 10:00:00.123 UTC".to_string()),
+            AttributeValue::S("2026-10-18T10:00:00.123".to_string()),
+            AttributeValue::S("1792317600123".to_string()),
+            AttributeValue::N("1792317600123".to_string()),
+        ] {
+            assert_eq!(from_attribute(&value)?, timestamp(), "{value:?}");
+        }
+
+        assert!(from_attribute(&AttributeValue::S("yesterday".to_string())).is_err());
+        assert!(from_attribute(&AttributeValue::Bool(true)).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+//! Timezones that clients can request timestamps to be rendered in, given as an IANA name like
+//! `America/Chicago` or a fixed offset like `+02:00`.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// A timezone to render timestamps in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Timezone {
+    /// An IANA timezone, which accounts for daylight saving time
+    Named(Tz),
+
+    /// A fixed offset from UTC
+    Fixed(FixedOffset),
+}
+
+impl Timezone {
+    /// Convert a timestamp to the same instant in this timezone
+    pub fn convert(&self, value: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self {
+            Timezone::Named(tz) => value.with_timezone(tz).fixed_offset(),
+            Timezone::Fixed(offset) => value.with_timezone(offset),
+        }
+    }
+
+    /// The instant a local date and time in this timezone refers to, taking the earlier one when
+    /// the clocks go back, or `None` when the clocks go forward past it
+    pub fn from_local(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Timezone::Named(tz) => tz
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|value| value.with_timezone(&Utc)),
+            Timezone::Fixed(offset) => offset
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|value| value.with_timezone(&Utc)),
+        }
+    }
+}
+
+/// A value whose timestamps can be rendered in a requested timezone
+pub trait WithTimestamps {
+    /// Render the timestamps in the given timezone
+    fn in_timezone(self, timezone: &Timezone) -> Self;
+}
+
+impl<T: WithTimestamps> WithTimestamps for Vec<T> {
+    fn in_timezone(self, timezone: &Timezone) -> Self {
+        self.into_iter()
+            .map(|value| value.in_timezone(timezone))
+            .collect()
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Ok(tz) = value.parse::<Tz>() {
+            return Ok(Timezone::Named(tz));
+        }
+
+        if let Ok(offset) = value.parse::<FixedOffset>() {
+            return Ok(Timezone::Fixed(offset));
+        }
+
+        Err(anyhow::anyhow!(
+            "Invalid timezone '{value}', expected an IANA name like 'America/Chicago' or an offset like '+02:00'"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_from_str() -> anyhow::Result<()> {
+        assert_eq!(
+            "America/Chicago".parse::<Timezone>()?,
+            Timezone::Named(Tz::America__Chicago)
+        );
+        assert_eq!("UTC".parse::<Timezone>()?, Timezone::Named(Tz::UTC));
+        assert_eq!(
+            "+02:00".parse::<Timezone>()?,
+            Timezone::Fixed(FixedOffset::east_opt(7200).unwrap())
+        );
+        assert!("Mars/Olympus_Mons".parse::<Timezone>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert() -> anyhow::Result<()> {
+        let value = DateTime::parse_from_rfc3339("2026-10-19T12:00:00Z")?;
+
+        let chicago = Timezone::Named(Tz::America__Chicago).convert(value);
+        let fixed = "+05:30".parse::<Timezone>()?.convert(value);
+
+        assert_eq!(chicago.to_rfc3339(), "2026-10-19T07:00:00-05:00");
+        assert_eq!(fixed.to_rfc3339(), "2026-10-19T17:30:00+05:30");
+
+        // The same instant, just rendered differently
+        assert_eq!(chicago, value);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_local() -> anyhow::Result<()> {
+        let chicago = Timezone::Named(Tz::America__Chicago);
+        let local = |value: &str| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M");
+
+        assert_eq!(
+            chicago.from_local(local("2026-10-19 07:00")?),
+            Some(DateTime::parse_from_rfc3339("2026-10-19T12:00:00Z")?.to_utc())
+        );
+
+        // 02:30 is skipped when daylight saving time starts, and happens twice when it ends
+        assert_eq!(chicago.from_local(local("2026-03-08 02:30")?), None);
+        assert_eq!(
+            chicago.from_local(local("2026-11-01 01:30")?),
+            Some(DateTime::parse_from_rfc3339("2026-11-01T06:30:00Z")?.to_utc())
+        );
+
+        Ok(())
+    }
+}
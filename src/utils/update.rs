@@ -3,6 +3,7 @@
 
 use std::ops::Deref;
 
+use async_graphql::MaybeUndefined;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[cfg(test)]
@@ -10,19 +11,14 @@ use fake::{Dummy, Fake, Faker, Rng};
 
 /// Similar to `Option`, but it has three states, `unchanged`, `empty` and `value`.
 #[allow(missing_docs)]
-#[derive(Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
+#[derive(Copy, Clone, Default, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
 pub enum Update<T> {
+    #[default]
     Unchanged,
     Empty,
     Value(T),
 }
 
-impl<T> Default for Update<T> {
-    fn default() -> Self {
-        Self::Unchanged
-    }
-}
-
 impl<T> Update<T> {
     /// Returns true if the `Update<T>` is unchanged.
     #[inline]
@@ -152,7 +148,7 @@ impl<T> Update<T> {
     /// # Example
     ///
     /// ```rust
-    /// use crate::utils::Update;
+    /// use rust_demo_dependency_injection::utils::Update;
     ///
     /// let mut value = None;
     ///
@@ -239,6 +235,16 @@ impl<T> From<Option<Option<T>>> for Update<T> {
     }
 }
 
+impl<T> From<MaybeUndefined<T>> for Update<T> {
+    fn from(value: MaybeUndefined<T>) -> Self {
+        match value {
+            MaybeUndefined::Undefined => Self::Unchanged,
+            MaybeUndefined::Null => Self::Empty,
+            MaybeUndefined::Value(value) => Self::Value(value),
+        }
+    }
+}
+
 #[cfg(test)]
 /// Randomly generate the `Update` type for testing
 pub fn dummy_update<T, R: Rng + ?Sized>(config: &Faker, rng: &mut R) -> Update<T>
@@ -268,6 +274,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_maybe_undefined() {
+        assert_eq!(
+            Update::<i32>::from(MaybeUndefined::Undefined),
+            Update::Unchanged
+        );
+
+        assert_eq!(Update::<i32>::from(MaybeUndefined::Null), Update::Empty);
+
+        assert_eq!(
+            Update::<i32>::from(MaybeUndefined::Value(42)),
+            Update::Value(42)
+        );
+    }
+
     #[test]
     fn test_as_opt_ref() {
         let value = Update::<String>::Unchanged;